@@ -0,0 +1,36 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use ratata::command::{self, Command};
+
+fn quit() -> Command {
+    black_box(Command::Quit)
+}
+
+fn batch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("batch");
+
+    group.bench_function("empty", |b| b.iter(|| command::batch![]));
+    group.bench_function("pair", |b| b.iter(|| command::batch![quit(), quit()]));
+    group.bench_function("pair (vec)", |b| b.iter(|| Command::Batch(vec![quit(), quit()])));
+    group.bench_function("eight", |b| b.iter(|| {
+        command::batch![quit(), quit(), quit(), quit(), quit(), quit(), quit(), quit()]
+    }));
+
+    group.finish();
+}
+
+fn then(c: &mut Criterion) {
+    let mut group = c.benchmark_group("then");
+
+    group.bench_function("chained", |b| b.iter(|| {
+        (0..8).fold(command::batch![], |command, _| command.then(quit()))
+    }));
+    group.bench_function("nested", |b| b.iter(|| {
+        (0..8).fold(command::batch![], |command, _| Command::Batch(vec![command, quit()]))
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, batch, then);
+criterion_main!(benches);
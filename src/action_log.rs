@@ -0,0 +1,104 @@
+use std::{fmt, fs, io, time};
+use std::io::Write;
+use std::path::Path;
+use std::collections::VecDeque;
+
+// Only what the user did in terms the application chose to name, never raw keys or message contents.
+pub(crate) enum Action {
+    Visit(&'static str),
+    Named(String),
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | Action::Visit(screen) => write!(f, "visit   {screen}"),
+            | Action::Named(action) => write!(f, "action  {action}"),
+        }
+    }
+}
+
+pub(crate) struct ActionLog {
+    capacity: usize,
+    started: time::Instant,
+    entries: VecDeque<(time::Duration, Action)>,
+}
+
+impl ActionLog {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            started: time::Instant::now(),
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn record(&mut self, action: Action) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back((self.started.elapsed(), action));
+    }
+
+    pub(crate) fn export(&self, path: &Path) -> io::Result<()> {
+        let mut file = io::BufWriter::new(fs::File::create(path)?);
+
+        writeln!(file, "ratata action log")?;
+        writeln!(file, "last {} actions:", self.entries.len())?;
+
+        for (at, action) in &self.entries {
+            writeln!(file, "[{:>10.3}s] {action}", at.as_secs_f64())?;
+        }
+
+        file.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    fn exported(log: &ActionLog, name: &str) -> Vec<String> {
+        let path = env::temp_dir().join(format!("ratata-{name}-{}.log", std::process::id()));
+
+        log.export(&path).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        // The timestamps vary between runs.
+        contents.lines().map(|line| line.split_once("] ").map_or(line, |(_, action)| action).to_owned()).collect()
+    }
+
+    #[test]
+    fn only_the_latest_actions_are_kept() {
+        let mut log = ActionLog::new(2);
+
+        log.record(Action::Visit("Home"));
+        log.record(Action::Named(String::from("save")));
+        log.record(Action::Visit("Settings"));
+
+        assert_eq!(exported(&log, "action-log"), [
+            "ratata action log",
+            "last 2 actions:",
+            "action  save",
+            "visit   Settings",
+        ]);
+    }
+
+    #[test]
+    fn nothing_is_kept_without_a_capacity() {
+        let mut log = ActionLog::new(0);
+
+        log.record(Action::Visit("Home"));
+
+        assert_eq!(exported(&log, "action-log-empty"), ["ratata action log", "last 0 actions:"]);
+    }
+}
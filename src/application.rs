@@ -1,57 +1,419 @@
 use std::io::Write;
-use std::any::TypeId;
-use std::collections::HashMap;
-use std::{io, time, mem, thread};
-use std::sync::atomic::Ordering;
+use std::any::{self, Any, TypeId};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::{fmt, fs, io, time, mem, panic, thread};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver};
+use std::path::PathBuf;
+use std::process;
+use std::ops::RangeInclusive;
+use std::marker::PhantomData;
 
-use crossterm::event::Event;
+use crossterm::terminal::{supports_keyboard_enhancement, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::event::{KeyboardEnhancementFlags, PushKeyboardEnhancementFlags};
+#[cfg(feature = "paste")]
+use crossterm::event::{EnableBracketedPaste, DisableBracketedPaste};
 
+use ratatui::Frame;
 use ratatui::backend::Backend;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
 
 use thiserror::Error;
 
+#[cfg(feature = "futures")]
+use futures::executor::ThreadPool;
+#[cfg(feature = "tokio")]
+use futures::{FutureExt, StreamExt};
+#[cfg(feature = "tokio")]
+use crossterm::event::EventStream;
+#[cfg(feature = "tokio")]
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
 use crate::{
-    events,
-    message::Message,
+    workers::{Cancelled, Scope, Workers},
+    events::{self, BurstDetector, EventListenerError, KeyEventFilter, ListenerPause, PasteBursts, TimedEvent},
+    motion,
+    input::InputState,
+    keymap::{KeyMap, KeymapConflictError, Keymaps},
+    bell::{Bel, Bell},
+    ready,
+    crash::{CrashLog, Entry},
+    action_log::{ActionLog, Action},
+    autosave::Autosave,
+    onboarding::Onboarding,
+    format::Locale,
+    scheduler::Scheduler,
+    subscription::Subscription,
+    pane::PinnedPane,
+    compositor::{Compositor, Dim, Layer},
+    clock::Clock,
+    damage::FlushDeadline,
+    message::{Message, KeyMsg, Payload, Routing, TickMsg},
     command::Command,
-    screen::Screen,
+    screen::{BackgroundUpdate, Screen, ScreenError, Shared},
+    terminal::{self, TerminalGuard, TerminalState},
+    panic_hook::PanicHook,
 };
 
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use std::{future::Future, pin::Pin};
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use crate::command::Task;
+
+#[cfg(feature = "update-check")]
+use crate::update;
+
+// Releases are reported with the event types, disambiguating keeps keys like escape from being reported late.
+const GAME_KEYBOARD_FLAGS: KeyboardEnhancementFlags = KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+    .union(KeyboardEnhancementFlags::REPORT_EVENT_TYPES);
+
+// Without key releases, how long a key counts as held after it was last pressed or repeated. Has to outlast the
+// delay before terminals start repeating a held key, which is usually around half a second.
+const KEY_REPEAT_TIMEOUT: time::Duration = time::Duration::from_millis(700);
+
+// The most fixed steps a single frame catches up on in game mode.
+const MAX_FIXED_STEPS: u32 = 5;
+
 #[derive(Debug, Error)]
 #[error("the event source was disconnected")]
 pub struct EventSourceDisconnectedError;
 
 #[derive(Debug, Error)]
-#[error("could not find a registered screen for: {:?}", 0)]
+#[error("could not find a registered screen for: {0:?}")]
 pub struct MissingScreenError(TypeId);
 
+#[derive(Debug, Error)]
+#[error("the screen is already active, stacked or open as an overlay: {0:?}")]
+pub struct ScreenInUseError(TypeId);
+
+#[derive(Debug, Error)]
+#[error("there is no screen to return to")]
+pub struct EmptyScreenStackError;
+
+#[derive(Debug, Error)]
+#[error("there is no previously active screen")]
+pub struct NoPreviousScreenError;
+
+#[derive(Debug, Error)]
+#[error("there is no overlay to close")]
+pub struct NoOverlayError;
+
 #[derive(Debug, Error)]
 pub enum RuntimeError {
     #[error(transparent)]
     EventSourceDisconnected(#[from] EventSourceDisconnectedError),
     #[error(transparent)]
     MissingScreen(#[from] MissingScreenError),
+    #[error(transparent)]
+    ScreenInUse(#[from] ScreenInUseError),
+    #[error(transparent)]
+    EmptyScreenStack(#[from] EmptyScreenStackError),
+    #[error(transparent)]
+    NoPreviousScreen(#[from] NoPreviousScreenError),
+    #[error(transparent)]
+    NoOverlay(#[from] NoOverlayError),
     #[error("failed to execute a crossterm command: {0}")]
     CrosstermCommandExecution(io::Error),
     #[error("failed to enable or disable raw mode: {0}")]
     RawMode(io::Error),
+    #[error("failed to restore the terminal to its previous state: {0}")]
+    TerminalRestore(io::Error),
+    #[error("failed to set up the terminal: {0}")]
+    Terminal(io::Error),
+    #[error("background work did not finish within the shutdown grace period of {0:?}")]
+    ShutdownTimeout(time::Duration),
+    #[error("failed to render a frame: {0}")]
+    Render(io::Error),
+    #[error("failed to autosave: {0}")]
+    Autosave(io::Error),
+    #[error("failed to export the action log: {0}")]
+    ActionLogExport(io::Error),
+    #[error("failed to export: {0}")]
+    Export(io::Error),
+    #[error("failed to update the onboarding state: {0}")]
+    Onboarding(io::Error),
+    #[error("a screen failed to handle a message: {0}")]
+    Screen(ScreenError),
+    #[error("failed to suspend the process: {0}")]
+    Suspend(io::Error),
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    #[error("failed to start the background executor: {0}")]
+    Executor(io::Error),
+}
+
+fn is_disconnect(error: &io::Error) -> bool {
+    // EIO is what reads and writes fail with once the controlling terminal has hung up.
+    const EIO: i32 = 5;
+
+    matches!(error.kind(), io::ErrorKind::BrokenPipe | io::ErrorKind::UnexpectedEof)
+        || (cfg!(unix) && error.raw_os_error() == Some(EIO))
+}
+
+// Once signal-hook's handler is installed it stays, so a hangup after the application stopped would be
+// swallowed. Whenever no application is running, it's handled the default way again (terminating the process).
+#[cfg(unix)]
+fn hangup_default() -> &'static AtomicBool {
+    static DEFAULT: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+
+    DEFAULT.get_or_init(|| {
+        let default = Arc::new(AtomicBool::new(true));
+        let _ = signal_hook::flag::register_conditional_default(signal_hook::consts::SIGHUP, Arc::clone(&default));
+        default
+    })
+}
+
+fn fingerprint<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn ansi<C: crossterm::Command>(command: C) -> String {
+    let mut ansi = String::new();
+    let _ = command.write_ansi(&mut ansi);
+    ansi
+}
+
+// How a command shows up in the dry run trace, terminal commands as the sequences they would write.
+fn describe(command: &Command, names: &HashMap<TypeId, &'static str>) -> String {
+    let name = |ident: &TypeId| names.get(ident).copied().unwrap_or("<unknown>");
+
+    match command {
+        | Command::Screen(ident) => format!("switch to {}", name(ident)),
+        | Command::PushScreen(ident) => format!("push {}", name(ident)),
+        | Command::PopScreen => String::from("pop screen"),
+        | Command::PreviousScreen => String::from("switch to previous screen"),
+        | Command::OpenOverlay(ident) => format!("open overlay {}", name(ident)),
+        | Command::CloseOverlay => String::from("close overlay"),
+        | Command::SendTo(ident, message) => format!("send {message:?} to {}", name(ident)),
+        | Command::EnableRawMode => String::from("enable raw mode"),
+        | Command::DisableRawMode => String::from("disable raw mode"),
+        | Command::EnterAlternateScreen => format!("write {:?}", ansi(EnterAlternateScreen)),
+        | Command::LeaveAlternateScreen => format!("write {:?}", ansi(LeaveAlternateScreen)),
+        #[cfg(feature = "paste")]
+        | Command::EnableBracketedPaste => format!("write {:?}", ansi(EnableBracketedPaste)),
+        #[cfg(feature = "paste")]
+        | Command::DisableBracketedPaste => format!("write {:?}", ansi(DisableBracketedPaste)),
+        | Command::Crossterm(command) => format!("write {:?}", ansi(command)),
+        | Command::ExportActionLog(path) => format!("export action log to {}", path.display()),
+        | Command::Export(path, _) => format!("export to {}", path.display()),
+        | Command::Exec(process) => format!("run {process:?}"),
+        | Command::Suspend => String::from("suspend"),
+        | Command::Bell => String::from("bell"),
+        | Command::Quit => String::from("quit"),
+        | command => format!("{command:?}"),
+    }
 }
 
-type ScreenEntry = (TypeId, Box<dyn Screen>);
+type ScreenEntry<Ctx> = (TypeId, Box<dyn Screen<Ctx>>);
+
+// A message waiting to be processed and the screen it's for, the ones without go wherever input would.
+type Queued = (Option<TypeId>, Message);
+
+// A result of work in the background, dropped if the screen it's for was popped or replaced before it arrived.
+type Outcome = (Queued, Option<Cancelled>);
+
+fn delivered((queued, cancelled): Outcome) -> Option<Queued> {
+    match cancelled {
+        | Some(cancelled) if cancelled.is_set() => None,
+        | _ => Some(queued),
+    }
+}
+
+// Where the result of work started now goes, and whether it's still wanted by the time it's done.
+struct Delivery {
+    to: Option<TypeId>,
+    routing: Option<Routing>,
+    cancelled: Option<Cancelled>,
+}
+
+impl Delivery {
+    #[inline(always)]
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.as_ref().is_some_and(Cancelled::is_set)
+    }
+
+    fn outcome(self, message: Message) -> Outcome {
+        let message = match self.routing {
+            | Some(routing) => Message::Routed(routing, Box::new(message)),
+            | None => message,
+        };
+
+        ((self.to, message), self.cancelled)
+    }
+}
+
+// Resolves to nothing once it was cancelled.
+#[cfg(any(feature = "futures", feature = "tokio"))]
+type ScopedTask = Pin<Box<dyn Future<Output = Option<Message>> + Send>>;
+
+// A screen along with its type, if it can be told apart by it.
+type TaggedScreen<'a, Ctx> = (Option<TypeId>, &'a mut Box<dyn Screen<Ctx>>);
+
+// Messages queued while processing others, e.g. by publishing or sending to a screen, are handled in the same
+// cycle. Only this many rounds deep, so screens messaging each other back and forth can't stall it.
+const MAX_QUEUED_ROUNDS: usize = 8;
+
+type CommandHandler<Ctx> = Box<dyn FnMut(Box<dyn Any + Send>, &mut Ctx) -> Option<Command>>;
+
+// Exec and suspend under `run_async`, carried out by the loop once it dropped its event stream.
+#[cfg(feature = "tokio")]
+enum Handover {
+    Exec(Option<TypeId>, Box<process::Command>),
+    #[cfg(unix)]
+    Suspend,
+}
+
+// The messages that aren't input, as seen by screens that aren't focused.
+fn unfocused_copy(message: &Message) -> Option<Message> {
+    match message {
+        | Message::Tick(tick) => Some(Message::Tick(*tick)),
+        | Message::Resize(width, height) => Some(Message::Resize(*width, *height)),
+        | Message::FocusGained => Some(Message::FocusGained),
+        | Message::FocusLost => Some(Message::FocusLost),
+        | _ => None,
+    }
+}
+
+#[inline(always)]
+fn is_input(message: &Message) -> bool {
+    match message {
+        | Message::Key(_) | Message::Mouse(_) | Message::PasteBurst(_) => true,
+        #[cfg(feature = "paste")]
+        | Message::Paste(_) => true,
+        | _ => false,
+    }
+}
+
+fn render<Ctx>(
+    frame: &mut Frame<'_>,
+    screen: &dyn Screen<Ctx>,
+    overlays: &[ScreenEntry<Ctx>],
+    compositor: &mut Compositor<Ctx>,
+    context: &Ctx,
+) {
+    screen.render(frame, context);
+    compositor.compose_below(frame.buffer_mut(), context);
+
+    overlays.iter().for_each(|(_, overlay)| overlay.render(frame, context));
+
+    compositor.compose_above(frame.buffer_mut());
+}
+
+// Held for as long as the application runs.
+struct Running {
+    // Uninstalled again in reverse order.
+    crash_hook: Option<PanicHook>,
+    restore_hook: Option<PanicHook>,
+    #[cfg(unix)]
+    hangup: Option<signal_hook::SigId>,
+}
 
-pub struct Application<B: Backend> {
+// `Ctx` is state shared by every screen, handed to every `Screen` method that takes a `ctx`.
+pub struct Application<B: Backend, Ctx = ()> {
+    context: Ctx,
     startup_callback: Option<fn() -> Command>,
     shutdown_callback: Option<fn() -> Command>,
+    command_handler: Option<CommandHandler<Ctx>>,
     terminal: ratatui::Terminal<B>,
-    sink: Box<dyn Write>,
+    terminal_guard: TerminalGuard,
+    terminal_state: Option<TerminalState>,
     tick_rate: time::Duration,
+    align_ticks: bool,
+    paste_bursts: BurstDetector,
+    key_event_filter: KeyEventFilter,
+    skip_unchanged_frames: bool,
+    always_redraw: bool,
+    flush_deadline: Option<FlushDeadline>,
+    game_mode: bool,
+    // Where the held keys are kept in the context, see `Builder::input_state`.
+    input_state: Option<fn(&mut Ctx) -> &mut InputState>,
+    // Set when the terminal doesn't report key releases, see `KEY_REPEAT_TIMEOUT`.
+    key_repeat_timeout: Option<time::Duration>,
+    bell: Bell,
+    // Time not yet covered by a fixed step, in game mode.
+    accumulator: time::Duration,
+    next_frame: Option<time::Instant>,
+    // Whether anything happened since the last frame was drawn that could change it.
+    dirty: bool,
+    // When what was drawn last changes by itself, see `Screen::next_change`.
+    redraw_at: Option<time::Instant>,
+    last_frame: Option<u64>,
     last_tick: Option<time::Instant>,
-    event_poll_rate: time::Duration,
-    screens: HashMap<TypeId, Box<dyn Screen>>,
-    active_screen_entry: Option<ScreenEntry>,
-    previous_screen_entry: Option<ScreenEntry>,
+    frame: u64,
+    event_poll_rate: RangeInclusive<time::Duration>,
+    shutdown_grace_period: time::Duration,
+    resize_debounce: Option<time::Duration>,
+    resizing_until: Option<time::Instant>,
+    screens: HashMap<TypeId, Box<dyn Screen<Ctx>>>,
+    screen_names: HashMap<TypeId, &'static str>,
+    initialized: HashSet<TypeId>,
+    active_screen_entry: Option<ScreenEntry<Ctx>>,
+    previous_screen_entry: Option<ScreenEntry<Ctx>>,
+    // The screens pushed over, the active screen is on top of these.
+    screen_stack: Vec<ScreenEntry<Ctx>>,
+    // Rendered over the active screen bottom to top, the top one receives the input.
+    overlays: Vec<ScreenEntry<Ctx>>,
+    main_screen: Option<TypeId>,
+    onboarding: Option<Onboarding>,
+    locale: Option<Locale>,
+    reduced_motion: Option<bool>,
+    scheduler: Scheduler,
+    clock: Clock,
+    subscriptions: Vec<Subscription>,
+    screen_subscriptions: Vec<Subscription>,
+    subscribed_screen: Option<TypeId>,
+    #[cfg(feature = "futures")]
+    executor: Option<ThreadPool>,
+    workers: Workers,
+    keymaps: Keymaps,
+    // Screens updated on worker threads while inactive, and their updates since the last frame.
+    background_screens: Vec<(TypeId, Arc<Mutex<dyn BackgroundUpdate>>)>,
+    background_updates: Vec<thread::JoinHandle<()>>,
+    // Messages produced by work running in the background.
+    inbox: (mpsc::Sender<Outcome>, Receiver<Outcome>),
+    // The screen whose commands are being handled, results of the work they start are delivered back to it.
+    issuer: Option<TypeId>,
+    // Set while handling `Command::Detached`, the work isn't tied to the issuer's scope.
+    detached: bool,
+    // Set while handling `Command::Correlated` and `Command::RouteTo`.
+    correlation: Option<u64>,
+    target: Option<TypeId>,
+    // The work of every screen still around, cancelled once it's popped or replaced.
+    scopes: HashMap<TypeId, Scope>,
+    // Where performed tasks resolve to under `run_async`.
+    #[cfg(feature = "tokio")]
+    task_results: Option<UnboundedSender<Outcome>>,
+    compositor: Compositor<Ctx>,
     exiting: bool,
+    hangup: Arc<AtomicBool>,
+    disconnected: bool,
+    queued_messages: VecDeque<Queued>,
+    listener_pause: ListenerPause,
+    #[cfg(feature = "tokio")]
+    handovers: VecDeque<Handover>,
+    recording_macro: Option<(String, Vec<KeyMsg>)>,
+    ready_signalled: bool,
+    // Commands touching the terminal, the filesystem, the network or other processes are skipped, and custom ones
+    // aren't handed to the handler. Every command ends up in the trace, whether it ran or not.
+    dry_run: bool,
+    trace: Option<Vec<String>>,
+    trace_writer: Option<Box<dyn Write>>,
+    // Set while secrets are entered, keeps input out of crash reports and macros.
+    recording_suppressed: bool,
+    macros: HashMap<String, Vec<KeyMsg>>,
+    crash_log: Option<CrashLog>,
+    action_log: Option<ActionLog>,
+    autosave: Option<Autosave>,
+    manage_raw_mode: bool,
+    manage_alternate_screen: bool,
+    #[cfg(feature = "paste")]
+    manage_bracketed_paste: bool,
+    #[cfg(feature = "update-check")]
+    update_toast: bool,
 }
 
 impl<B: Backend> Application<B> {
@@ -59,8 +421,25 @@ impl<B: Backend> Application<B> {
     pub fn builder() -> Builder {
         Builder::new()
     }
+}
+
+impl<B: Backend, Ctx> Application<B, Ctx> {
+    #[inline(always)]
+    pub fn context(&self) -> &Ctx {
+        &self.context
+    }
+
+    fn record(&self, entry: impl FnOnce() -> Entry) {
+        if self.recording_suppressed {
+            return;
+        }
+
+        if let Some(log) = &self.crash_log {
+            log.record(entry());
+        }
+    }
 
-    fn try_read_events(&self, events: &Receiver<Event>) -> Result<Vec<Event>, EventSourceDisconnectedError> {
+    fn try_read_events(&self, events: &Receiver<TimedEvent>) -> Result<Vec<TimedEvent>, EventSourceDisconnectedError> {
         // Allocate some default capacity.
         let mut buffer = Vec::with_capacity(5);
 
@@ -75,20 +454,243 @@ impl<B: Backend> Application<B> {
         Ok(buffer)
     }
 
+    // Waits for input for up to `timeout`, and reads whatever arrived along with it. Game mode only reads input
+    // on its steps, waking up in between would throw off their schedule.
+    fn wait_for_events(
+        &self,
+        events: &Receiver<TimedEvent>,
+        timeout: time::Duration,
+    ) -> Result<Vec<TimedEvent>, EventSourceDisconnectedError> {
+        let first = match self.game_mode {
+            | true => {
+                thread::sleep(timeout);
+                None
+            },
+            | false => match events.recv_timeout(timeout) {
+                | Ok(event) => Some(event),
+                | Err(mpsc::RecvTimeoutError::Timeout) => None,
+                | Err(mpsc::RecvTimeoutError::Disconnected) => return Err(EventSourceDisconnectedError),
+            },
+        };
+
+        let mut buffer = self.try_read_events(events)?;
+
+        buffer.splice(0..0, first);
+
+        Ok(buffer)
+    }
+
+    // Every screen, registered, active, stacked or pinned (which can't be told apart by their type).
+    // Handed out alongside the context, which the screens are updated with.
+    fn all_screens_mut(&mut self) -> (impl Iterator<Item = TaggedScreen<'_, Ctx>>, &mut Ctx) {
+        let active = self.active_screen_entry.iter_mut()
+            .chain(&mut self.previous_screen_entry)
+            .chain(&mut self.screen_stack)
+            .chain(&mut self.overlays)
+            .map(|(ident, s)| (Some(*ident), s));
+
+        let screens = self.screens.iter_mut().map(|(ident, s)| (Some(*ident), s)).chain(active);
+        let panes = self.compositor.panes.iter_mut().map(|p| (None, &mut p.screen));
+
+        (screens.chain(panes), &mut self.context)
+    }
+
+    // Screens that can be told apart by their type, pinned panes can't.
+    fn identified_screens_mut(&mut self) -> (impl Iterator<Item = (TypeId, &mut Box<dyn Screen<Ctx>>)>, &mut Ctx) {
+        let active = self.active_screen_entry.iter_mut()
+            .chain(&mut self.previous_screen_entry)
+            .chain(&mut self.screen_stack)
+            .chain(&mut self.overlays)
+            .map(|(ident, s)| (*ident, s));
+
+        (self.screens.iter_mut().map(|(ident, s)| (*ident, s)).chain(active), &mut self.context)
+    }
+
+    fn restore_autosaves(&mut self) -> Result<(), RuntimeError> {
+        let Some(autosave) = self.autosave.take() else {
+            return Ok(());
+        };
+
+        let (names, context) = (&self.screen_names, &mut self.context);
+
+        let commands = self.screens.iter_mut()
+            .filter_map(|(ident, screen)| Some((*ident, screen, autosave.load(names.get(ident)?)?)))
+            .map(|(ident, screen, state)| Ok((ident, screen.update(Message::AutosaveAvailable(state), context)?)))
+            .collect::<Result<Vec<_>, _>>();
+
+        self.autosave = Some(autosave);
+
+        commands.map_err(RuntimeError::Screen)?
+            .into_iter()
+            .filter_map(|(ident, command)| Some((ident, command?)))
+            .try_for_each(|(ident, command)| self.handle_command_of(Some(ident), command))
+    }
+
+    fn save_screens(&mut self, force: bool) -> io::Result<()> {
+        let Some(mut autosave) = self.autosave.take().filter(|_| !self.dry_run) else {
+            return Ok(());
+        };
+
+        let mut result = Ok(());
+
+        if force || autosave.due() {
+            let names = self.screen_names.clone();
+
+            result = self.identified_screens_mut().0
+                .filter_map(|(ident, screen)| Some((names.get(&ident)?, screen.autosave()?)))
+                .try_for_each(|(name, state)| autosave.save(name, &state));
+        }
+
+        self.autosave = Some(autosave);
+
+        result
+    }
+
+    // Nothing is left to restore after a clean exit.
+    fn discard_autosaves(&self) -> io::Result<()> {
+        match &self.autosave {
+            | Some(_) if self.dry_run => Ok(()),
+            | Some(autosave) => self.screen_names.values().try_for_each(|name| autosave.discard(name)),
+            | None => Ok(()),
+        }
+    }
+
     fn shutdown_screens(&mut self) {
-        self.screens.values_mut().for_each(|s| {
-            let _ = s.update(Message::Shutdown);
+        let (screens, context) = self.all_screens_mut();
+
+        screens.for_each(|(_, s)| {
+            let _ = s.update(Message::Shutdown, context);
         });
     }
 
-    fn get_screen(&mut self, screen: TypeId) -> Result<ScreenEntry, MissingScreenError> {
-        self.screens.remove_entry(&screen).map_or_else(|| Err(MissingScreenError(screen)), Ok)
+    fn shutdown(&mut self, listener: events::JoinHandle, quit: Arc<AtomicBool>) -> Result<(), RuntimeError> {
+        // Stop producing new messages before the screens are told to shut down.
+        quit.store(true, Ordering::Relaxed);
+
+        let callback = self.stop();
+
+        let deadline = time::Instant::now() + self.shutdown_grace_period;
+
+        while !listener.is_finished() {
+            if time::Instant::now() >= deadline {
+                return Err(RuntimeError::ShutdownTimeout(self.shutdown_grace_period));
+            }
+
+            thread::sleep(time::Duration::from_millis(1));
+        }
+
+        let _ = listener.join();
+
+        self.join_workers(deadline)?;
+
+        callback
+    }
+
+    // Whatever was started in the background gets until the deadline to finish, there is no cancelling it.
+    fn join_workers(&mut self, deadline: time::Instant) -> Result<(), RuntimeError> {
+        if !self.workers.join(deadline) {
+            return Err(RuntimeError::ShutdownTimeout(self.shutdown_grace_period));
+        }
+
+        // Every task on it is done, its threads go with it.
+        #[cfg(feature = "futures")]
+        drop(self.executor.take());
+
+        Ok(())
+    }
+
+    fn get_screen(&mut self, screen: TypeId) -> Result<ScreenEntry<Ctx>, RuntimeError> {
+        // Kept aside for going back, but it can still be switched to directly.
+        if let Some(previous) = self.previous_screen_entry.take_if(|(ident, _)| *ident == screen) {
+            return Ok(previous);
+        }
+
+        // Only one of each screen exists, it can't be in two places at once.
+        if self.is_around(screen) {
+            return Err(ScreenInUseError(screen).into());
+        }
+
+        self.screens.remove_entry(&screen).ok_or_else(|| MissingScreenError(screen).into())
+    }
+
+    fn record_switch(&mut self, screen: TypeId) {
+        let name = self.screen_names.get(&screen).copied().unwrap_or("<unknown>");
+
+        self.record(|| Entry::ScreenSwitch(name));
+
+        if let Some(log) = &mut self.action_log {
+            log.record(Action::Visit(name));
+        }
+    }
+
+    // Every kind of switch goes through here. The screen that was left hears about it first, then the new one
+    // is initialized if it never was active before, and told it's active.
+    fn switch<E, F>(&mut self, switch: F) -> Result<(), RuntimeError>
+        where E: Into<RuntimeError>, F: FnOnce(&mut Self) -> Result<(), E>,
+    {
+        let left = self.active_screen_entry.as_ref().map(|(ident, _)| *ident);
+
+        switch(self).map_err(Into::into)?;
+
+        let Some(entered) = self.active_screen_entry.as_ref().map(|(ident, _)| *ident) else {
+            return Ok(());
+        };
+
+        // Nothing changed, e.g. on switching to the screen that's already active.
+        if left == Some(entered) {
+            return Ok(());
+        }
+
+        // Before it hears about it, what it starts on the way out is still delivered.
+        if let Some(left) = left.filter(|left| !self.is_around(*left)) {
+            self.cancel_work_of(left);
+        }
+
+        let exit = left.and_then(|left| {
+            let (mut screens, context) = self.identified_screens_mut();
+
+            screens.find(|(ident, _)| *ident == left).and_then(|(_, screen)| screen.on_exit(context))
+        });
+
+        if let Some(command) = exit {
+            self.handle_command_of(left, command)?;
+        }
+
+        // A command of the screen that was left may have switched again already.
+        let Some((ident, screen)) = self.active_screen_entry.as_mut().filter(|(ident, _)| *ident == entered) else {
+            return Ok(());
+        };
+
+        let init = match self.initialized.insert(*ident) {
+            | true => screen.init(&mut self.context),
+            | false => None,
+        };
+
+        if let Some(command) = init {
+            self.handle_command_of(Some(entered), command)?;
+        }
+
+        let enter = self.active_screen_entry.as_mut()
+            .filter(|(ident, _)| *ident == entered)
+            .and_then(|(_, screen)| screen.on_enter(&mut self.context));
+
+        match enter {
+            | Some(command) => self.handle_command_of(Some(entered), command),
+            | None => Ok(()),
+        }
     }
 
-    fn activate_screen(&mut self, screen: TypeId) -> Result<(), MissingScreenError> {
+    fn activate_screen(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        // Switching to the screen that's already active leaves everything as it is.
+        if self.active_screen_entry.as_ref().is_some_and(|(ident, _)| *ident == screen) {
+            return Ok(());
+        }
+
         let new = self.get_screen(screen)?;
 
-        let previous = mem::replace(&mut self.active_screen_entry, Some(new));
+        self.record_switch(screen);
+
+        let previous = self.active_screen_entry.replace(new);
 
         let replaced = mem::replace(&mut self.previous_screen_entry, previous);
 
@@ -99,144 +701,2885 @@ impl<B: Backend> Application<B> {
         Ok(())
     }
 
-    fn handle_command(&mut self, command: Command) -> Result<(), RuntimeError> {
+    fn publish(&mut self, topic: String, payload: Payload) -> Result<(), RuntimeError> {
+        // Subscribers all see the publication before any of their commands run.
+        let (screens, context) = self.all_screens_mut();
+
+        let commands = screens
+            .filter(|(_, s)| s.subscribed(&topic))
+            .map(|(ident, s)| Ok((ident, s.update(Message::Published(topic.clone(), Arc::clone(&payload)), context)?)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RuntimeError::Screen)?;
+
+        commands.into_iter()
+            .filter_map(|(ident, command)| Some((ident, command?)))
+            .try_for_each(|(ident, command)| self.handle_command_of(ident, command))
+    }
+
+    // Every screen, active or not, may hold on to something that went stale while the process was stopped.
+    fn resume_screens(&mut self) -> Result<(), RuntimeError> {
+        let (screens, context) = self.all_screens_mut();
+
+        let commands = screens
+            .map(|(ident, s)| Ok((ident, s.update(Message::Resumed, context)?)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(RuntimeError::Screen)?;
+
+        commands.into_iter()
+            .filter_map(|(ident, command)| Some((ident, command?)))
+            .try_for_each(|(ident, command)| self.handle_command_of(ident, command))
+    }
+
+    fn send_to(&mut self, screen: TypeId, message: Message) -> Result<(), RuntimeError> {
+        let command = {
+            let (mut screens, context) = self.identified_screens_mut();
+
+            let (_, target) = screens
+                .find(|(ident, _)| *ident == screen)
+                .ok_or(MissingScreenError(screen))?;
+
+            target.update(message, context).map_err(RuntimeError::Screen)?
+        };
+
         match command {
-            | Command::Batch(commands) => {
-                for command in commands {
-                    self.handle_command(command)?;
-                }
-                Ok(())
-            },
-            | Command::EnableRawMode => crossterm::terminal::enable_raw_mode().map_err(RuntimeError::RawMode),
-            | Command::DisableRawMode => crossterm::terminal::disable_raw_mode().map_err(RuntimeError::RawMode),
-            | Command::Screen(ident) => Ok(self.activate_screen(ident)?),
-            | Command::Crossterm(command) =>
-                crossterm::execute!(self.sink, command).map_err(RuntimeError::CrosstermCommandExecution),
-            | Command::Quit => {
-                self.exiting = true;
-                Ok(())
-            },
+            | Some(command) => self.handle_command_of(Some(screen), command),
+            | None => Ok(()),
         }
     }
 
-    pub fn run<S: Screen + 'static>(mut self) -> Result<(), RuntimeError> {
-        let screen = TypeId::of::<S>();
-
-        if let Some(callback) = self.startup_callback {
-            self.handle_command(callback())?;
+    // Where work started now belongs, nowhere if it's detached or wasn't started by a screen.
+    fn scope(&mut self) -> Option<&mut Scope> {
+        match self.detached {
+            | true => None,
+            | false => self.target.or(self.issuer).map(|screen| self.scopes.entry(screen).or_default()),
         }
+    }
 
-        self.activate_screen(screen)?;
+    fn delivery(&mut self) -> Delivery {
+        let to = self.target.or(self.issuer);
 
-        let (_, events, event_quit_handle) = events::listen(self.event_poll_rate);
+        let routing = (self.correlation.is_some() || self.target.is_some())
+            .then_some(Routing { origin: self.issuer, target: to, correlation: self.correlation });
 
-        loop {
-            if self.exiting {
-                break self.shutdown_screens();
-            }
+        let cancelled = self.scope().map(|scope| scope.cancelled());
 
-            if let Some(last_tick) = &self.last_tick {
-                thread::sleep(self.tick_rate.saturating_sub(last_tick.elapsed()));
-            }
+        Delivery { to, routing, cancelled }
+    }
 
-            self.last_tick = Some(time::Instant::now());
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    fn scoped(&mut self, task: Task) -> (Delivery, ScopedTask) {
+        let delivery = self.delivery();
 
-            let mut messages = self.try_read_events(&events)?
-                .into_iter()
-                .map(Message::from)
-                .collect::<Vec<_>>();
+        let task: ScopedTask = match self.scope() {
+            | Some(scope) => Box::pin(scope.abortable(task.0)),
+            | None => Box::pin(async move { Some(task.0.await) }),
+        };
 
-            messages.push(Message::Tick);
+        (delivery, task)
+    }
 
-            for message in messages {
-                let screen = &mut self.active_screen_entry.as_mut().unwrap().1;
+    // Whether the screen is active, stacked or an overlay, i.e. not popped or replaced.
+    fn is_around(&self, screen: TypeId) -> bool {
+        self.active_screen_entry.iter()
+            .chain(&self.screen_stack)
+            .chain(&self.overlays)
+            .any(|(ident, _)| *ident == screen)
+    }
 
-                if let Some(command) = screen.update(message) {
-                    self.handle_command(command)?;
+    #[inline(always)]
+    fn cancel_work_of(&mut self, screen: TypeId) {
+        if let Some(scope) = self.scopes.remove(&screen) {
+            scope.cancel();
+        }
+    }
+
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    fn perform(&mut self, task: Task) -> Result<(), RuntimeError> {
+        // Under `run_async` it runs on the same runtime, and the result wakes the loop up right away.
+        #[cfg(feature = "tokio")]
+        if let Some(results) = self.task_results.clone() {
+            let token = self.workers.token();
+            let (delivery, task) = self.scoped(task);
+
+            tokio::spawn(async move {
+                if let Some(message) = task.await {
+                    let _ = results.send(delivery.outcome(message));
                 }
-            }
 
-            let screen = &mut self.active_screen_entry.as_mut().unwrap().1;
+                drop(token);
+            });
 
-            let _ = self.terminal.draw(|f| screen.render(f)).unwrap();
+            return Ok(());
         }
 
-        if let Some(callback) = self.shutdown_callback {
-            self.handle_command(callback())?;
-        }
+        self.perform_in_background(task)
+    }
 
-        event_quit_handle.store(true, Ordering::Relaxed);
+    #[cfg(feature = "futures")]
+    fn perform_in_background(&mut self, task: Task) -> Result<(), RuntimeError> {
+        let (sender, token) = (self.inbox.0.clone(), self.workers.token());
+        let (delivery, task) = self.scoped(task);
+
+        // The pool is only started once something is actually performed.
+        let executor = match &mut self.executor {
+            | Some(executor) => executor,
+            | None => {
+                let executor = ThreadPool::builder()
+                    .name_prefix("ratata-perform-")
+                    .create()
+                    .map_err(RuntimeError::Executor)?;
+
+                self.executor.insert(executor)
+            },
+        };
+
+        executor.spawn_ok(async move {
+            if let Some(message) = task.await {
+                let _ = sender.send(delivery.outcome(message));
+            }
+
+            drop(token);
+        });
 
         Ok(())
     }
-}
 
-#[derive(Default)]
-pub struct Builder {
-    event_poll_rate: Option<time::Duration>,
-    screens: HashMap<TypeId, Box<dyn Screen>>,
-    tick_rate: Option<time::Duration>,
-    startup_callback: Option<fn() -> Command>,
-    shutdown_callback: Option<fn() -> Command>,
-}
+    // Without the executor of `futures`, every task gets a thread with a runtime of its own.
+    #[cfg(all(feature = "tokio", not(feature = "futures")))]
+    fn perform_in_background(&mut self, task: Task) -> Result<(), RuntimeError> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(RuntimeError::Executor)?;
 
-impl Builder {
-    #[inline(always)]
-    pub fn new() -> Self {
-        Self::default()
-    }
+        let sender = self.inbox.0.clone();
+        let (delivery, task) = self.scoped(task);
 
-    pub fn event_polling_rate(mut self, rate: time::Duration) -> Self {
-        self.event_poll_rate = Some(rate);
-        self
-    }
+        self.workers.spawn(move || {
+            if let Some(message) = runtime.block_on(task) {
+                let _ = sender.send(delivery.outcome(message));
+            }
+        });
 
-    pub fn screen<S: Screen + 'static>(mut self, screen: S) -> Self {
-        self.screens.insert(TypeId::of::<S>(), Box::new(screen));
-        self
+        Ok(())
     }
 
-    pub fn tick_rate(mut self, rate: time::Duration) -> Self {
-        self.tick_rate = Some(rate);
-        self
-    }
+    // Best effort, a failed check is indistinguishable from there being no update.
+    #[cfg(feature = "update-check")]
+    fn check_for_updates(&mut self, url: String, current: String) {
+        let sender = self.inbox.0.clone();
 
-    pub fn frames_per_second(mut self, fps: u8) -> Self {
-        self.tick_rate = Some(time::Duration::from_secs_f32(1. / fps as f32));
-        self
+        self.workers.spawn(move || {
+            if let Ok(Some(version)) = update::check(&url, &current) {
+                let _ = sender.send(((None, Message::UpdateAvailable(version)), None));
+            }
+        });
     }
 
-    pub fn on_startup(mut self, callback: fn() -> Command) -> Self {
-        self.startup_callback = Some(callback);
-        self
+    fn continue_onboarding(&mut self) -> Result<(), RuntimeError> {
+        let Some(onboarding) = self.onboarding.as_mut().filter(|o| o.in_progress()) else {
+            return Ok(());
+        };
+
+        let next = match onboarding.advance() {
+            | Some(step) => step,
+            | None => {
+                if !self.dry_run {
+                    onboarding.complete().map_err(RuntimeError::Onboarding)?;
+                }
+
+                self.main_screen.expect("onboarding only runs once a main screen is known")
+            },
+        };
+
+        self.switch(|app| app.activate_screen(next))
     }
 
-    pub fn on_shutdown(mut self, callback: fn() -> Command) -> Self {
-        self.shutdown_callback = Some(callback);
-        self
+    fn reset_onboarding(&mut self) -> Result<(), RuntimeError> {
+        let Some(onboarding) = &mut self.onboarding else {
+            return Ok(());
+        };
+
+        if !self.dry_run {
+            onboarding.reset().map_err(RuntimeError::Onboarding)?;
+        }
+
+        match onboarding.start() {
+            | Some(step) => self.switch(|app| app.activate_screen(step)),
+            | None => Ok(()),
+        }
     }
 
-    pub fn build<W, B>(self, sink: W, backend: B) -> Result<Application<B>, io::Error>
-        where W: Write + 'static, B: Backend,
-    {
-        let tick_rate = self.tick_rate.unwrap_or(time::Duration::from_secs_f32(1. / 30.));
+    // Swaps the active and previous screen, so going back twice returns to where it started.
+    fn previous_screen(&mut self) -> Result<(), NoPreviousScreenError> {
+        let previous = self.previous_screen_entry.take().ok_or(NoPreviousScreenError)?;
 
-        let event_poll_rate = self.event_poll_rate.unwrap_or(tick_rate / 2);
+        self.record_switch(previous.0);
 
-        let terminal = ratatui::Terminal::new(backend)?;
+        self.previous_screen_entry = self.active_screen_entry.replace(previous);
 
-        Ok(Application {
-            shutdown_callback: self.shutdown_callback,
-            startup_callback: self.startup_callback,
+        Ok(())
+    }
+
+    fn push_screen(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        let new = self.get_screen(screen)?;
+
+        self.record_switch(screen);
+
+        if let Some(current) = self.active_screen_entry.replace(new) {
+            self.screen_stack.push(current);
+        }
+
+        Ok(())
+    }
+
+    // Overlays get the same hooks as screens that are switched to.
+    fn open_overlay(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        let entry = self.get_screen(screen)?;
+
+        self.record_switch(screen);
+
+        self.overlays.push(entry);
+
+        let init = match self.initialized.insert(screen) {
+            | true => self.overlays.last_mut().and_then(|(_, overlay)| overlay.init(&mut self.context)),
+            | false => None,
+        };
+
+        if let Some(command) = init {
+            self.handle_command_of(Some(screen), command)?;
+        }
+
+        let enter = self.overlays.last_mut()
+            .filter(|(ident, _)| *ident == screen)
+            .and_then(|(_, overlay)| overlay.on_enter(&mut self.context));
+
+        match enter {
+            | Some(command) => self.handle_command_of(Some(screen), command),
+            | None => Ok(()),
+        }
+    }
+
+    fn close_overlay(&mut self) -> Result<(), RuntimeError> {
+        let (ident, mut overlay) = self.overlays.pop().ok_or(NoOverlayError)?;
+
+        self.cancel_work_of(ident);
+
+        let exit = overlay.on_exit(&mut self.context);
+
+        assert!(self.screens.insert(ident, overlay).is_none());
+
+        match exit {
+            | Some(command) => self.handle_command_of(Some(ident), command),
+            | None => Ok(()),
+        }
+    }
+
+    fn pop_screen(&mut self) -> Result<(), EmptyScreenStackError> {
+        let below = self.screen_stack.pop().ok_or(EmptyScreenStackError)?;
+
+        self.record_switch(below.0);
+
+        if let Some((ident, screen)) = self.active_screen_entry.replace(below) {
+            assert!(self.screens.insert(ident, screen).is_none());
+        }
+
+        Ok(())
+    }
+
+    fn enter_terminal_modes(&mut self) -> Result<(), RuntimeError> {
+        if self.dry_run {
+            if self.manage_raw_mode {
+                self.traced(String::from("enable raw mode"));
+            }
+
+            if self.manage_alternate_screen {
+                self.traced(format!("write {:?}", ansi(EnterAlternateScreen)));
+            }
+
+            #[cfg(feature = "paste")]
+            if self.manage_bracketed_paste {
+                self.traced(format!("write {:?}", ansi(EnableBracketedPaste)));
+            }
+
+            if self.game_mode {
+                self.traced(format!("write {:?}", ansi(PushKeyboardEnhancementFlags(GAME_KEYBOARD_FLAGS))));
+            }
+
+            return Ok(());
+        }
+
+        let state = match self.terminal_state.clone() {
+            | Some(state) => state,
+            | None => TerminalState::detect().map_err(RuntimeError::RawMode)?,
+        };
+
+        self.terminal_guard.reset(state);
+
+        if self.manage_raw_mode {
+            self.terminal_guard.enable_raw_mode().map_err(RuntimeError::RawMode)?;
+        }
+
+        if self.manage_alternate_screen {
+            self.terminal_guard.execute(EnterAlternateScreen).map_err(RuntimeError::CrosstermCommandExecution)?;
+        }
+
+        #[cfg(feature = "paste")]
+        if self.manage_bracketed_paste {
+            self.terminal_guard.execute(EnableBracketedPaste).map_err(RuntimeError::CrosstermCommandExecution)?;
+        }
+
+        // Terminals without the kitty keyboard protocol ignore it, and where it can't even be written (like the
+        // legacy Windows console) the game just never sees a release.
+        if self.game_mode {
+            let _ = self.terminal_guard.execute(PushKeyboardEnhancementFlags(GAME_KEYBOARD_FLAGS));
+
+            // Asked once the flags are pushed, and before anything else reads from the terminal.
+            let releases = supports_keyboard_enhancement().unwrap_or(false);
+
+            self.key_repeat_timeout = (!releases).then_some(KEY_REPEAT_TIMEOUT);
+        }
+
+        Ok(())
+    }
+
+    fn track_input(&mut self, message: &Message) {
+        if let (true, Some(input)) = (self.game_mode, self.input_state) {
+            input(&mut self.context).apply(message, self.clock.now());
+        }
+    }
+
+    fn restore_terminal_modes(&mut self) -> Result<(), RuntimeError> {
+        if self.disconnected || self.dry_run {
+            return Ok(());
+        }
+
+        self.terminal_guard.restore().map_err(RuntimeError::TerminalRestore)
+    }
+
+    fn disconnect(&mut self) {
+        self.disconnected = true;
+        self.exiting = true;
+
+        // There is nothing left to restore on a terminal that's gone.
+        self.terminal_guard.disarm();
+    }
+
+    fn terminal_io(&mut self, result: io::Result<()>, error: fn(io::Error) -> RuntimeError) -> Result<(), RuntimeError> {
+        match result {
+            | Err(e) if is_disconnect(&e) => {
+                self.disconnect();
+                Ok(())
+            },
+            | result => result.map_err(error),
+        }
+    }
+
+    // Left on exit like any other mode, whichever way it was entered.
+    fn switch_screen_buffer(&mut self, alternate: bool) -> Result<(), RuntimeError> {
+        let result = match alternate {
+            | true => self.terminal_guard.execute(EnterAlternateScreen),
+            | false => self.terminal_guard.execute(LeaveAlternateScreen),
+        };
+
+        self.terminal_io(result, RuntimeError::CrosstermCommandExecution)?;
+
+        // The other buffer has different contents, so the next frame is drawn in full.
+        self.last_frame = None;
+
+        let result = self.terminal.clear();
+        self.terminal_io(result, RuntimeError::Render)
+    }
+
+    // Hands the terminal back as it was before the application started (without the event listener reading
+    // from it) for as long as `f` runs, the whole screen is drawn again after.
+    fn with_terminal_released<T>(&mut self, f: impl FnOnce() -> T) -> Result<T, RuntimeError> {
+        let state = self.terminal_guard.state();
+
+        self.listener_pause.pause();
+
+        let released = self.restore_terminal_modes().map(|_| f());
+
+        self.listener_pause.resume();
+
+        let released = released?;
+
+        self.take_terminal_back(state)?;
+
+        Ok(released)
+    }
+
+    fn take_terminal_back(&mut self, state: TerminalState) -> Result<(), RuntimeError> {
+        let result = self.terminal_guard.reenter(state);
+        self.terminal_io(result, RuntimeError::CrosstermCommandExecution)?;
+
+        self.last_frame = None;
+
+        let result = self.terminal.clear();
+        self.terminal_io(result, RuntimeError::Render)
+    }
+
+    // Blocks until the child exits.
+    fn exec(&mut self, mut process: process::Command) -> Result<(), RuntimeError> {
+        #[cfg(feature = "tokio")]
+        if self.task_results.is_some() {
+            self.handovers.push_back(Handover::Exec(self.issuer, Box::new(process)));
+            return Ok(());
+        }
+
+        let finished = Message::ExecFinished(self.with_terminal_released(|| process.status())?);
+
+        // Only the screen that ran it sees the message, but keys were let go of for everyone.
+        self.track_input(&finished);
+        self.queued_messages.push_back((self.issuer, finished));
+
+        Ok(())
+    }
+
+    // Under `run_async` the event stream reads from the terminal on a thread of its own, which the child or the
+    // shell would be competing with, so it's dropped for as long as they have the terminal.
+    #[cfg(feature = "tokio")]
+    async fn hand_over(&mut self) -> Result<(), RuntimeError> {
+        while let Some(handover) = self.handovers.pop_front() {
+            let state = self.terminal_guard.state();
+
+            self.restore_terminal_modes()?;
+
+            match handover {
+                | Handover::Exec(issuer, mut process) => {
+                    // Waited for off the runtime, so tasks on it keep going in the meantime.
+                    let status = tokio::task::spawn_blocking(move || process.status()).await;
+
+                    self.take_terminal_back(state)?;
+
+                    let finished = Message::ExecFinished(status.unwrap_or_else(|error| Err(io::Error::other(error))));
+
+                    self.track_input(&finished);
+                    self.queued_messages.push_back((issuer, finished));
+                },
+                #[cfg(unix)]
+                | Handover::Suspend => {
+                    let result = signal_hook::low_level::raise(signal_hook::consts::SIGTSTP);
+
+                    self.take_terminal_back(state)?;
+                    self.terminal_io(result, RuntimeError::Suspend)?;
+
+                    self.queued_messages.push_back((None, Message::Resumed));
+                },
+            }
+        }
+
+        Ok(())
+    }
+
+    // Blocks until the process is continued.
+    #[cfg(unix)]
+    fn suspend(&mut self) -> Result<(), RuntimeError> {
+        #[cfg(feature = "tokio")]
+        if self.task_results.is_some() && !self.disconnected && !self.dry_run {
+            self.handovers.push_back(Handover::Suspend);
+            return Ok(());
+        }
+
+        if !self.disconnected && !self.dry_run {
+            let result = self.with_terminal_released(|| signal_hook::low_level::raise(signal_hook::consts::SIGTSTP))?;
+            self.terminal_io(result, RuntimeError::Suspend)?;
+        }
+
+        self.queued_messages.push_back((None, Message::Resumed));
+
+        Ok(())
+    }
+
+    // Written to the caller's writer as well, if there is one.
+    fn traced(&mut self, line: String) {
+        if let Some(writer) = &mut self.trace_writer {
+            if let Err(error) = writeln!(writer, "{line}") {
+                self.record(|| Entry::Warning(format!("trace: {error}")));
+            }
+        }
+
+        self.trace.get_or_insert_with(Vec::new).push(line);
+    }
+
+    fn handle_command_of(&mut self, screen: Option<TypeId>, command: Command) -> Result<(), RuntimeError> {
+        let outer = mem::replace(&mut self.issuer, screen);
+        let result = self.handle_command(command);
+
+        self.issuer = outer;
+
+        result
+    }
+
+    fn handle_command(&mut self, command: Command) -> Result<(), RuntimeError> {
+        let batch = matches!(
+            command,
+            Command::Batch(_) | Command::Detached(_) | Command::Correlated(..) | Command::RouteTo(..)
+        );
+
+        self.dirty = true;
+
+        if !batch {
+            self.record(|| Entry::Command(format!("{command:?}")));
+
+            if self.trace.is_some() {
+                self.traced(describe(&command, &self.screen_names));
+            }
+        }
+
+        let result = match command {
+            | Command::Batch(commands) => {
+                for command in commands {
+                    self.handle_command(command)?;
+                }
+                Ok(())
+            },
+            | Command::Detached(command) => {
+                let outer = mem::replace(&mut self.detached, true);
+                let result = self.handle_command(*command);
+
+                self.detached = outer;
+
+                result
+            },
+            | Command::Correlated(correlation, command) => {
+                let outer = self.correlation.replace(correlation);
+                let result = self.handle_command(*command);
+
+                self.correlation = outer;
+
+                result
+            },
+            | Command::RouteTo(screen, command) => {
+                let outer = self.target.replace(screen);
+                let result = self.handle_command(*command);
+
+                self.target = outer;
+
+                result
+            },
+            // Shutdown still runs after a disconnect, but anything touching the terminal is skipped.
+            | Command::EnableRawMode | Command::DisableRawMode | Command::Crossterm(_)
+            | Command::EnterAlternateScreen | Command::LeaveAlternateScreen
+                if self.disconnected || self.dry_run => Ok(()),
+            #[cfg(feature = "paste")]
+            | Command::EnableBracketedPaste | Command::DisableBracketedPaste
+                if self.disconnected || self.dry_run => Ok(()),
+            | Command::EnableRawMode => {
+                let result = self.terminal_guard.enable_raw_mode();
+                self.terminal_io(result, RuntimeError::RawMode)
+            },
+            | Command::DisableRawMode => {
+                let result = self.terminal_guard.disable_raw_mode();
+                self.terminal_io(result, RuntimeError::RawMode)
+            },
+            | Command::EnterAlternateScreen => self.switch_screen_buffer(true),
+            | Command::LeaveAlternateScreen => self.switch_screen_buffer(false),
+            // Turned off again on exit, like every other mode.
+            #[cfg(feature = "paste")]
+            | Command::EnableBracketedPaste => {
+                let result = self.terminal_guard.execute(EnableBracketedPaste);
+                self.terminal_io(result, RuntimeError::CrosstermCommandExecution)
+            },
+            #[cfg(feature = "paste")]
+            | Command::DisableBracketedPaste => {
+                let result = self.terminal_guard.execute(DisableBracketedPaste);
+                self.terminal_io(result, RuntimeError::CrosstermCommandExecution)
+            },
+            | Command::Screen(ident) => self.switch(|app| app.activate_screen(ident)),
+            | Command::PushScreen(ident) => self.switch(|app| app.push_screen(ident)),
+            | Command::PopScreen => self.switch(|app| app.pop_screen()),
+            | Command::PreviousScreen => self.switch(|app| app.previous_screen()),
+            | Command::Render => Ok(()),
+            | Command::OpenOverlay(ident) => self.open_overlay(ident),
+            | Command::CloseOverlay => self.close_overlay(),
+            | Command::Crossterm(command) => {
+                let result = self.terminal_guard.execute_erased(command);
+                self.terminal_io(result, RuntimeError::CrosstermCommandExecution)
+            },
+            | Command::StartMacro(name) => {
+                self.recording_macro = Some((name, Vec::new()));
+                Ok(())
+            },
+            | Command::StopMacro => {
+                if let Some((name, keys)) = self.recording_macro.take() {
+                    self.macros.insert(name, keys);
+                }
+                Ok(())
+            },
+            | Command::PlayMacro(name) => {
+                // Replayed keys are queued rather than delivered right away, so a macro can't recurse into itself.
+                if let Some(keys) = self.macros.get(&name) {
+                    self.queued_messages.extend(keys.iter().cloned().map(|key| (None, Message::Key(key))));
+                }
+                Ok(())
+            },
+            | Command::SuppressRecording(suppress) => {
+                self.recording_suppressed = suppress;
+                Ok(())
+            },
+            | Command::LogAction(action) => {
+                if let Some(log) = &mut self.action_log {
+                    log.record(Action::Named(action));
+                }
+                Ok(())
+            },
+            | Command::ExportActionLog(_) if self.dry_run => Ok(()),
+            | Command::ExportActionLog(path) => match &self.action_log {
+                | Some(log) => log.export(&path).map_err(RuntimeError::ActionLogExport),
+                | None => Ok(()),
+            },
+            | Command::Export(..) if self.dry_run => Ok(()),
+            | Command::Export(path, contents) => fs::write(path, contents).map_err(RuntimeError::Export),
+            | Command::PushFilter(filter) => {
+                self.compositor.push_filter(filter);
+                Ok(())
+            },
+            | Command::PopFilter => {
+                self.compositor.pop_filter();
+                Ok(())
+            },
+            | Command::Publish(topic, payload) => {
+                self.queued_messages.push_back((None, Message::Published(topic, payload)));
+                Ok(())
+            },
+            | Command::SendTo(ident, message) => {
+                self.queued_messages.push_back((Some(ident), *message));
+                Ok(())
+            },
+            | Command::Spawn(work) => {
+                let (sender, delivery) = (self.inbox.0.clone(), self.delivery());
+
+                self.workers.spawn(move || {
+                    let message = (work.0)();
+
+                    if !delivery.is_cancelled() {
+                        let _ = sender.send(delivery.outcome(message));
+                    }
+                });
+
+                Ok(())
+            },
+            #[cfg(any(feature = "futures", feature = "tokio"))]
+            | Command::Perform(task) => self.perform(task),
+            #[cfg(feature = "update-check")]
+            | Command::CheckForUpdates { .. } if self.dry_run => Ok(()),
+            #[cfg(feature = "update-check")]
+            | Command::CheckForUpdates { url, current } => {
+                self.check_for_updates(url, current);
+                Ok(())
+            },
+            | Command::Bell => {
+                let ring = self.bell.ring(motion::reduced());
+
+                if ring.flash {
+                    self.compositor.visual_bell.flash(self.clock.now());
+                }
+
+                if ring.toast {
+                    self.compositor.toasts.show(String::from("Bell"), self.clock.now());
+                }
+
+                match ring.audible && !self.disconnected && !self.dry_run {
+                    | true => {
+                        let result = self.terminal_guard.execute(Bel);
+                        self.terminal_io(result, RuntimeError::CrosstermCommandExecution)
+                    },
+                    | false => Ok(()),
+                }
+            },
+            | Command::ShowTooltip(tooltip) => {
+                self.compositor.tooltips.show(tooltip, self.clock.now());
+                Ok(())
+            },
+            | Command::HideTooltip => {
+                self.compositor.tooltips.hide();
+                Ok(())
+            },
+            | Command::Toast(text) => {
+                self.compositor.toasts.show(text, self.clock.now());
+                Ok(())
+            },
+            | Command::ContinueOnboarding => self.continue_onboarding(),
+            | Command::ResetOnboarding => self.reset_onboarding(),
+            // The handler is the application's own code, which could do anything.
+            | Command::Custom(_) if self.dry_run => Ok(()),
+            // Dropped when there's no handler, like a message no screen cares about.
+            | Command::Custom(value) => {
+                let next = self.command_handler.as_mut().and_then(|handler| handler(value, &mut self.context));
+
+                match next {
+                    | Some(command) => self.handle_command(command),
+                    | None => Ok(()),
+                }
+            },
+            | Command::Exec(_) if self.disconnected || self.dry_run => Ok(()),
+            | Command::Exec(process) => self.exec(process),
+            #[cfg(unix)]
+            | Command::Suspend => self.suspend(),
+            #[cfg(not(unix))]
+            | Command::Suspend => Ok(()),
+            | Command::Quit => {
+                self.exiting = true;
+                Ok(())
+            },
+        };
+
+        // A failure inside a batch is counted once, where it happened.
+        #[cfg(feature = "metrics")]
+        if result.is_err() && !batch {
+            metrics::counter!("ratata_command_failures").increment(1);
+        }
+
+        result
+    }
+
+    pub fn run<S: Screen<Ctx> + 'static>(mut self) -> Result<(), RuntimeError> {
+        let running = self.begin();
+
+        let result = self.enter_terminal_modes()
+            .and_then(|_| self.run_loop(TypeId::of::<S>()))
+            .and(self.restore_terminal_modes());
+
+        self.end(running, result)
+    }
+
+    // Like `run`, on the tokio runtime it's awaited on. Input comes from crossterm's `EventStream` instead of a
+    // thread of its own, and `Command::perform` spawns onto the same runtime, waking the loop up as soon as it
+    // resolves. Everything else still happens between awaits, screens update and draw like they always do.
+    #[cfg(feature = "tokio")]
+    pub async fn run_async<S: Screen<Ctx> + 'static>(mut self) -> Result<(), RuntimeError> {
+        let running = self.begin();
+
+        let result = match self.enter_terminal_modes() {
+            | Ok(()) => self.run_loop_async(TypeId::of::<S>()).await,
+            | Err(error) => Err(error),
+        };
+
+        let result = result.and(self.restore_terminal_modes());
+
+        self.end(running, result)
+    }
+
+    fn begin(&mut self) -> Running {
+        // A dry run doesn't write crash reports.
+        let crash_hook = self.crash_log.as_ref().filter(|_| !self.dry_run).map(CrashLog::install_panic_hook);
+
+        if let Some(locale) = self.locale {
+            Locale::set(locale);
+        }
+
+        if let Some(reduced) = self.reduced_motion {
+            motion::set_reduced(reduced);
+        }
+
+        // Installed last, so it runs first and the panic message is printed to a usable terminal.
+        let restore_hook = (!self.dry_run).then(|| terminal::restore_on_panic(self.terminal_guard.shared()));
+
+        #[cfg(unix)]
+        hangup_default().store(false, Ordering::SeqCst);
+
+        Running {
+            crash_hook,
+            restore_hook,
+            #[cfg(unix)]
+            hangup: signal_hook::flag::register(signal_hook::consts::SIGHUP, Arc::clone(&self.hangup)).ok(),
+        }
+    }
+
+    fn end(&mut self, running: Running, result: Result<(), RuntimeError>) -> Result<(), RuntimeError> {
+        #[cfg(unix)]
+        if let Some(id) = running.hangup {
+            signal_hook::low_level::unregister(id);
+            hangup_default().store(true, Ordering::SeqCst);
+        }
+
+        if let (Err(error), Some(log), false) = (&result, &self.crash_log, self.dry_run) {
+            let _ = log.dump(error);
+        }
+
+        running.restore_hook.into_iter().chain(running.crash_hook).for_each(PanicHook::uninstall);
+
+        result
+    }
+
+    fn start(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        if let Some(callback) = self.startup_callback {
+            self.handle_command(callback())?;
+        }
+
+        self.restore_autosaves()?;
+
+        self.main_screen = Some(screen);
+
+        // A first run starts on the onboarding steps, the last one continues to the main screen.
+        let first = match &mut self.onboarding {
+            | Some(onboarding) if onboarding.is_first_run() => onboarding.start(),
+            | _ => None,
+        };
+
+        self.switch(|app| app.activate_screen(first.unwrap_or(screen)))
+    }
+
+    // Drives the application without a terminal or event loop, for `testing::TestApplication`. Nothing is
+    // autosaved or restored, every test starts from the same state.
+    pub(crate) fn start_headless(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        self.dry_run = true;
+        self.autosave = None;
+        self.trace.get_or_insert_with(Vec::new);
+
+        self.start(screen)?;
+        self.render_frame()
+    }
+
+    // One time around the loop, with the message as the input and a tick unless `elapsed` is `None`.
+    pub(crate) fn step(&mut self, message: Option<Message>, elapsed: Option<time::Duration>) -> Result<(), RuntimeError> {
+        self.advance(message.into_iter().collect(), elapsed)?;
+        self.render_frame()
+    }
+
+    // Whether the work in the background finished before the deadline.
+    #[inline(always)]
+    pub(crate) fn wait_for_workers(&mut self, deadline: time::Instant) -> bool {
+        self.workers.join(deadline)
+    }
+
+    pub(crate) fn stop(&mut self) -> Result<(), RuntimeError> {
+        self.shutdown_screens();
+
+        match self.shutdown_callback {
+            | Some(callback) => self.handle_command(callback()),
+            | None => Ok(()),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    #[inline(always)]
+    pub(crate) fn trace(&self) -> &[String] {
+        self.trace.as_deref().unwrap_or_default()
+    }
+
+    #[inline(always)]
+    pub(crate) fn terminal(&self) -> &ratatui::Terminal<B> {
+        &self.terminal
+    }
+
+    // How many frames were drawn so far.
+    #[inline(always)]
+    pub(crate) fn frames(&mut self) -> usize {
+        self.terminal.get_frame().count()
+    }
+
+    #[inline(always)]
+    pub(crate) fn is_exiting(&self) -> bool {
+        self.exiting
+    }
+
+    fn run_loop(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        self.start(screen)?;
+
+        let (listener, events, event_quit_handle) = events::listen_pausable(
+            self.event_poll_rate.clone(),
+            self.key_event_filter,
+            self.listener_pause.clone(),
+        );
+
+        let error = match self.event_loop(&events) {
+            | Ok(()) => {
+                let result = self.shutdown(listener, event_quit_handle);
+
+                return result.and(self.persist().map_err(RuntimeError::Autosave));
+            },
+            // The listener stops on the read failing once the terminal is gone, which may come before SIGHUP does.
+            | Err(RuntimeError::EventSourceDisconnected(error)) => match listener.join() {
+                | Ok(Err(EventListenerError::ReadError(e))) if is_disconnect(&e) => {
+                    self.disconnect();
+
+                    let deadline = time::Instant::now() + self.shutdown_grace_period;
+                    let result = self.stop().and_then(|()| self.join_workers(deadline));
+
+                    return result.and(self.persist().map_err(RuntimeError::Autosave));
+                },
+                | _ => error.into(),
+            },
+            | Err(error) => error,
+        };
+
+        event_quit_handle.store(true, Ordering::Relaxed);
+        let _ = self.save_screens(true);
+        Err(error)
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn run_loop_async(&mut self, screen: TypeId) -> Result<(), RuntimeError> {
+        self.start(screen)?;
+
+        let (sender, mut results) = unbounded_channel();
+
+        self.task_results = Some(sender);
+
+        let result = self.event_loop_async(&mut results).await;
+
+        self.task_results = None;
+
+        match result {
+            | Ok(()) => {
+                let callback = self.stop();
+
+                // Tasks on this runtime only make progress while this one waits for them.
+                let deadline = tokio::time::Instant::now() + self.shutdown_grace_period;
+
+                while !self.workers.is_finished() && tokio::time::Instant::now() < deadline {
+                    tokio::time::sleep(time::Duration::from_millis(1)).await;
+                }
+
+                let result = callback.and(self.join_workers(deadline.into_std()));
+
+                result.and(self.persist().map_err(RuntimeError::Autosave))
+            },
+            | Err(error) => {
+                let _ = self.save_screens(true);
+                Err(error)
+            },
+        }
+    }
+
+    // A hangup isn't a clean exit, whatever the user was doing should still be there next time.
+    fn persist(&mut self) -> io::Result<()> {
+        match self.disconnected {
+            | true => self.save_screens(true),
+            | false => self.discard_autosaves(),
+        }
+    }
+
+    // Runs alongside the messages of this time around, the screens are locked until their update is done.
+    fn update_in_background(&mut self, elapsed: time::Duration) {
+        self.finish_background_updates();
+
+        let active = self.active_screen_entry.iter().chain(&self.overlays).map(|(ident, _)| *ident).collect::<Vec<_>>();
+
+        for (_, screen) in self.background_screens.iter().filter(|(ident, _)| !active.contains(ident)) {
+            let screen = Arc::clone(screen);
+
+            self.background_updates.push(thread::spawn(move || {
+                screen.lock().unwrap_or_else(PoisonError::into_inner).background_update(elapsed);
+            }));
+        }
+    }
+
+    // A panic in an update carries on on the thread running the application.
+    fn finish_background_updates(&mut self) {
+        for update in self.background_updates.drain(..) {
+            if let Err(panic) = update.join() {
+                panic::resume_unwind(panic);
+            }
+        }
+    }
+
+    fn next_tick(&mut self, elapsed: time::Duration) -> Message {
+        let tick = TickMsg { elapsed, frame: self.frame };
+
+        self.frame += 1;
+
+        Message::Tick(tick)
+    }
+
+    #[inline(always)]
+    pub(crate) fn tick_rate(&self) -> time::Duration {
+        self.tick_rate
+    }
+
+    fn until_next_tick(&self) -> time::Duration {
+        match (self.align_ticks, &self.last_tick) {
+            // Ticks land on multiples of the tick rate since the epoch, a late tick skips to the next boundary.
+            | (true, _) => {
+                let now = self.clock.system_now().duration_since(time::UNIX_EPOCH).unwrap_or_default();
+                let rate = self.tick_rate.as_nanos().max(1);
+
+                time::Duration::from_nanos((rate - now.as_nanos() % rate) as u64)
+            },
+            | (false, Some(last_tick)) => self.tick_rate.saturating_sub(last_tick.elapsed()),
+            | (false, None) => time::Duration::ZERO,
+        }
+    }
+
+    // Until something is due in between ticks: a redraw, a flush or the end of a paste burst. Game mode draws
+    // at the step rate anyway, waking up in between would throw off its schedule.
+    fn until_due(&self) -> time::Duration {
+        let flush_due = self.flush_deadline.as_ref().and_then(FlushDeadline::get);
+        let due = self.redraw_at.into_iter().chain(flush_due).chain(self.paste_bursts.flush_at()).min();
+
+        match (self.game_mode, due) {
+            | (false, Some(at)) => at.saturating_duration_since(self.clock.now()),
+            | _ => time::Duration::MAX,
+        }
+    }
+
+    fn next_change(&self) -> Option<time::Instant> {
+        let active = self.active_screen_entry.iter().chain(&self.overlays);
+
+        active.filter_map(|(_, screen)| screen.next_change(&self.context))
+            .chain(self.compositor.next_change(&self.context))
+            .min()
+    }
+
+    fn until_next_wakeup(&mut self) -> time::Duration {
+        match self.game_mode {
+            | true => self.until_next_frame(),
+            | false => self.until_next_tick(),
+        }
+    }
+
+    // Frames are due on a fixed schedule from the first one instead of a tick after the one before, so they
+    // don't drift. A late frame is drawn right away and the ones missed in the meantime are skipped.
+    fn until_next_frame(&mut self) -> time::Duration {
+        let now = time::Instant::now();
+        let due = *self.next_frame.get_or_insert(now);
+        let rate = self.tick_rate.max(time::Duration::from_nanos(1));
+
+        let missed = now.saturating_duration_since(due).as_nanos() / rate.as_nanos();
+
+        self.next_frame = Some(due + rate * (missed as u32 + 1));
+
+        due.saturating_duration_since(now)
+    }
+
+    // As many ticks of exactly the tick rate as fit in the time since the last frame, the rest carries over.
+    fn fixed_steps(&mut self, elapsed: time::Duration, messages: &mut Vec<Message>) {
+        // Catching up on a long stall all at once would only stall it further.
+        self.accumulator = (self.accumulator + elapsed).min(self.tick_rate * MAX_FIXED_STEPS);
+
+        while !self.tick_rate.is_zero() && self.accumulator >= self.tick_rate {
+            self.accumulator -= self.tick_rate;
+            messages.push(self.next_tick(self.tick_rate));
+        }
+    }
+
+    fn poll_subscriptions(&mut self, messages: &mut Vec<Message>) {
+        let active = self.active_screen_entry.as_ref().map(|(ident, _)| *ident);
+
+        if active != self.subscribed_screen {
+            self.subscribed_screen = active;
+            self.screen_subscriptions = self.active_screen_entry.as_ref()
+                .map(|(_, screen)| screen.subscriptions(&self.context))
+                .unwrap_or_default();
+        }
+
+        let now = self.clock.now();
+
+        self.subscriptions.iter_mut()
+            .chain(&mut self.screen_subscriptions)
+            .for_each(|subscription| subscription.poll(now, messages));
+    }
+
+    fn process_message(&mut self, message: Message) -> Result<(), RuntimeError> {
+        if let (Message::Resize(..), Some(debounce)) = (&message, self.resize_debounce) {
+            self.resizing_until = Some(self.clock.now() + debounce);
+        }
+
+        self.track_input(&message);
+
+        #[cfg(feature = "update-check")]
+        if let (Message::UpdateAvailable(version), true) = (&message, self.update_toast) {
+            self.compositor.toasts.show(format!("Update available: {version}"), self.clock.now());
+        }
+
+        // Ticks only cause a redraw through the commands they produce, see `Command::Render`.
+        if !matches!(message, Message::Tick(_)) {
+            self.dirty = true;
+            self.record(|| Entry::Message(format!("{message:?}")));
+        }
+
+        // Published to the subscribers only, whether they're active or not.
+        if let Message::Published(topic, payload) = message {
+            return self.publish(topic, payload);
+        }
+
+        if let Message::Resumed = message {
+            return self.resume_screens();
+        }
+
+        // Keys that start or stop the recording are not part of the macro.
+        let key = match (&message, &self.recording_macro) {
+            | (Message::Key(key), Some(_)) if !self.recording_suppressed => Some(key.clone()),
+            | _ => None,
+        };
+
+        // Pinned panes aren't focused, so they only see messages that aren't input.
+        for index in 0..self.compositor.panes.len() {
+            let Some(copy) = unfocused_copy(&message) else {
+                break;
+            };
+
+            let pane = &mut self.compositor.panes[index].screen;
+
+            if let Some(command) = pane.update(copy, &mut self.context).map_err(RuntimeError::Screen)? {
+                self.handle_command_of(None, command)?;
+            }
+        }
+
+        // Neither are the overlays below the top one.
+        for index in 0..self.overlays.len().saturating_sub(1) {
+            let Some(copy) = unfocused_copy(&message) else {
+                break;
+            };
+
+            // Commands of the ones before may have closed it already.
+            let Some((ident, overlay)) = self.overlays.get_mut(index) else {
+                break;
+            };
+
+            if let Some(command) = overlay.update(copy, &mut self.context).map_err(RuntimeError::Screen)? {
+                let ident = *ident;
+                self.handle_command_of(Some(ident), command)?;
+            }
+        }
+
+        let top = self.overlays.last_mut();
+
+        // Input only goes to the top overlay, the active screen still sees everything else.
+        let (ident, screen) = match (top, is_input(&message)) {
+            | (Some(top), true) => top,
+            | (Some((ident, top)), false) => {
+                let command = match unfocused_copy(&message) {
+                    | Some(copy) => top.update(copy, &mut self.context).map_err(RuntimeError::Screen)?,
+                    | None => None,
+                };
+
+                if let Some(command) = command {
+                    let ident = *ident;
+                    self.handle_command_of(Some(ident), command)?;
+                }
+
+                self.active_screen_entry.as_mut().unwrap()
+            },
+            | (None, _) => self.active_screen_entry.as_mut().unwrap(),
+        };
+
+        let message = self.keymaps.translate(*ident, message);
+
+        if let Some(command) = screen.update(message, &mut self.context).map_err(RuntimeError::Screen)? {
+            let ident = *ident;
+            self.handle_command_of(Some(ident), command)?;
+        }
+
+        if let (Some(key), Some((_, keys))) = (key, &mut self.recording_macro) {
+            keys.push(key);
+        }
+
+        Ok(())
+    }
+
+    fn process_messages(&mut self, messages: Vec<Message>) -> Result<(), RuntimeError> {
+        #[cfg(feature = "metrics")]
+        metrics::counter!("ratata_messages_processed").increment(messages.len() as u64);
+
+        messages.into_iter().try_for_each(|message| self.process_message(message))
+    }
+
+    fn process_queued(&mut self) -> Result<(), RuntimeError> {
+        for _ in 0..MAX_QUEUED_ROUNDS {
+            if self.queued_messages.is_empty() {
+                break;
+            }
+
+            let queued = mem::take(&mut self.queued_messages);
+
+            #[cfg(feature = "metrics")]
+            metrics::counter!("ratata_messages_processed").increment(queued.len() as u64);
+
+            for (target, message) in queued {
+                match target {
+                    | Some(screen) => {
+                        self.dirty = true;
+                        self.record(|| Entry::Message(format!("{message:?}")));
+                        self.send_to(screen, message)?;
+                    },
+                    | None => self.process_message(message)?,
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Renders into the terminal's buffer up front and only draws when the contents differ from the last frame,
+    // returns whether anything was drawn.
+    fn draw_if_changed(&mut self) -> io::Result<bool> {
+        let screen = &mut self.active_screen_entry.as_mut().unwrap().1;
+        let (overlays, compositor, context) = (&self.overlays, &mut self.compositor, &self.context);
+
+        self.terminal.autoresize()?;
+
+        let mut frame = self.terminal.get_frame();
+
+        render(&mut frame, screen.as_ref(), overlays, compositor, context);
+
+        let (contents, rendered) = (fingerprint(frame.buffer_mut()), fingerprint(&frame));
+
+        // A fresh frame on the same buffer only hashes differently if the screen placed the cursor,
+        // whose position can't be read back. Those frames are always drawn, and rendered again for it.
+        let cursor = rendered != fingerprint(&self.terminal.get_frame());
+
+        if !cursor && self.last_frame == Some(contents) {
+            self.terminal.current_buffer_mut().reset();
+            return Ok(false);
+        }
+
+        self.last_frame = (!cursor).then_some(contents);
+
+        match cursor {
+            | false => self.terminal.draw(|_| ()).map(|_| true),
+            | true => {
+                self.terminal.current_buffer_mut().reset();
+
+                self.terminal.draw(|f| render(f, screen.as_ref(), overlays, compositor, context)).map(|_| true)
+            },
+        }
+    }
+
+    fn render_frame(&mut self) -> Result<(), RuntimeError> {
+        self.finish_background_updates();
+
+        self.save_screens(false).map_err(RuntimeError::Autosave)?;
+
+        #[cfg(feature = "metrics")]
+        let frame_start = self.clock.now();
+
+        let placeholder = self.resizing_until.is_some_and(|until| until > self.clock.now());
+
+        // The first frame after the resize events settled replaces the placeholder.
+        if !placeholder && self.resizing_until.take().is_some() {
+            self.dirty = true;
+        }
+
+        if self.redraw_at.is_some_and(|at| at <= self.clock.now()) {
+            self.dirty = true;
+        }
+
+        let flush_due = self.flush_deadline.as_ref().and_then(FlushDeadline::get);
+
+        if !self.dirty && !self.always_redraw && flush_due.is_some_and(|at| at <= self.clock.now()) {
+            let flushed = self.terminal.backend_mut().flush();
+
+            self.terminal_io(flushed, RuntimeError::Render)?;
+        }
+
+        if !self.dirty && !self.always_redraw {
+            #[cfg(feature = "metrics")]
+            metrics::counter!("ratata_frames_skipped").increment(1);
+
+            return Ok(());
+        }
+
+        // Only draw an empty placeholder frame until the resize events settle.
+        let drawn = match placeholder {
+            | true => {
+                self.last_frame = None;
+                self.terminal.draw(|_| ()).map(|_| true)
+            },
+            | false if self.skip_unchanged_frames => self.draw_if_changed(),
+            | false => {
+                let screen = &mut self.active_screen_entry.as_mut().unwrap().1;
+                let (overlays, compositor, context) = (&self.overlays, &mut self.compositor, &self.context);
+
+                self.terminal.draw(|f| render(f, screen.as_ref(), overlays, compositor, context)).map(|_| true)
+            },
+        };
+
+        #[cfg(feature = "metrics")]
+        match drawn {
+            | Ok(true) => {
+                metrics::counter!("ratata_frames_rendered").increment(1);
+                metrics::histogram!("ratata_frame_time_seconds").record(self.clock.elapsed(frame_start).as_secs_f64());
+            },
+            | Ok(false) => metrics::counter!("ratata_frames_skipped").increment(1),
+            | Err(_) => (),
+        }
+
+        self.terminal_io(drawn.map(drop), RuntimeError::Render)?;
+
+        // A flash or a toast needs another frame to go away.
+        self.dirty = self.compositor.toasts.is_pending() || self.compositor.visual_bell.is_pending();
+        self.redraw_at = self.next_change();
+
+        if !self.ready_signalled && !placeholder && !self.dry_run && !self.disconnected {
+            self.ready_signalled = true;
+
+            let mut failures = Vec::new();
+
+            ready::signal(&mut *self.terminal_guard.sink(), |error| failures.push(error));
+
+            for error in failures {
+                self.record(|| Entry::Warning(format!("ready signal: {error}")));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn event_loop(&mut self, events: &Receiver<TimedEvent>) -> Result<(), RuntimeError> {
+        loop {
+            if self.hangup.load(Ordering::Relaxed) {
+                self.disconnect();
+            }
+
+            if self.exiting {
+                break Ok(());
+            }
+
+            let (tick, due) = (self.until_next_wakeup(), self.until_due());
+            let tick_at = time::Instant::now() + tick;
+
+            let events = match self.wait_for_events(events, tick.min(due)) {
+                // The listener dies with the terminal, which is handled at the top of the loop.
+                | Err(_) if self.hangup.load(Ordering::Relaxed) => continue,
+                | result => result?,
+            };
+
+            let now = time::Instant::now();
+
+            // Woken up early, by input or for something due in between, which isn't a tick.
+            let elapsed = match now < tick_at {
+                | true => None,
+                | false => {
+                    let elapsed = self.last_tick.map(|last_tick| now - last_tick).unwrap_or_default();
+
+                    self.last_tick = Some(now);
+
+                    Some(elapsed)
+                },
+            };
+
+            self.cycle(events, elapsed)?;
+        }
+    }
+
+    // Everything that's due gets handled and drawn in one go: scheduled commands, the events, queued and
+    // background messages, and a tick unless `elapsed` is `None`.
+    fn cycle(&mut self, events: Vec<TimedEvent>, elapsed: Option<time::Duration>) -> Result<(), RuntimeError> {
+        #[cfg(feature = "metrics")]
+        let received = events.iter().map(|(received, _)| *received).collect::<Vec<_>>();
+
+        let messages = self.paste_bursts.messages(events, time::Instant::now());
+
+        self.advance(messages, elapsed)?;
+
+        #[cfg(feature = "metrics")]
+        for received in &received {
+            metrics::histogram!("ratata_update_latency_seconds").record(self.clock.elapsed(*received).as_secs_f64());
+        }
+
+        self.render_frame()?;
+
+        // From reading the event to the frame reflecting it being written.
+        #[cfg(feature = "metrics")]
+        for received in received {
+            metrics::histogram!("ratata_input_latency_seconds").record(self.clock.elapsed(received).as_secs_f64());
+        }
+
+        Ok(())
+    }
+
+    // Everything but drawing, shared with `step`.
+    fn advance(&mut self, mut messages: Vec<Message>, elapsed: Option<time::Duration>) -> Result<(), RuntimeError> {
+        for command in self.scheduler.due(self.clock.system_now()) {
+            self.handle_command(command)?;
+        }
+
+        // Results of work in the background, which go to the screen that started it.
+        self.queued_messages.extend(self.inbox.1.try_iter().filter_map(delivered));
+
+        self.poll_subscriptions(&mut messages);
+
+        if let Some(elapsed) = elapsed {
+            self.update_in_background(elapsed);
+        }
+
+        if let (Some(timeout), Some(input)) = (self.key_repeat_timeout, self.input_state) {
+            input(&mut self.context).expire(self.clock.now(), timeout);
+        }
+
+        match (elapsed, self.game_mode) {
+            | (Some(elapsed), true) => self.fixed_steps(elapsed, &mut messages),
+            | (Some(elapsed), false) => messages.push(self.next_tick(elapsed)),
+            | (None, _) => (),
+        }
+
+        self.process_messages(messages)?;
+        self.process_queued()
+    }
+
+    #[cfg(feature = "tokio")]
+    async fn event_loop_async(&mut self, results: &mut UnboundedReceiver<Outcome>) -> Result<(), RuntimeError> {
+        // Opened once there's something to wait for, quitting right away never touches the terminal's input.
+        let mut events = None;
+        let mut next_tick = None;
+
+        loop {
+            if self.hangup.load(Ordering::Relaxed) {
+                self.disconnect();
+            }
+
+            if self.exiting {
+                break Ok(());
+            }
+
+            if !self.handovers.is_empty() {
+                events = None;
+
+                self.hand_over().await?;
+                self.cycle(Vec::new(), None)?;
+            }
+
+            let events = events.get_or_insert_with(EventStream::new);
+
+            let wait = self.until_next_wakeup();
+            let tick_at = *next_tick.get_or_insert_with(|| tokio::time::Instant::now() + wait);
+            let due_at = tokio::time::Instant::now() + self.until_due().min(wait);
+
+            tokio::select! {
+                _ = tokio::time::sleep_until(tick_at) => {
+                    next_tick = None;
+
+                    let now = time::Instant::now();
+                    let elapsed = self.last_tick.map(|last_tick| now - last_tick).unwrap_or_default();
+
+                    self.last_tick = Some(now);
+
+                    self.cycle(Vec::new(), Some(elapsed))?;
+                },
+                _ = tokio::time::sleep_until(due_at), if due_at < tick_at => {
+                    self.cycle(Vec::new(), None)?;
+                },
+                event = events.next() => {
+                    let event = match event {
+                        | Some(Ok(event)) => event,
+                        // Reads fail once the terminal is gone, which may come before SIGHUP does.
+                        | Some(Err(e)) if is_disconnect(&e) => {
+                            self.disconnect();
+                            continue;
+                        },
+                        // The stream ends with the terminal, which is handled at the top of the loop.
+                        | _ if self.hangup.load(Ordering::Relaxed) => continue,
+                        | _ => break Err(EventSourceDisconnectedError.into()),
+                    };
+
+                    let mut batch = vec![(time::Instant::now(), event)];
+
+                    // Whatever arrived along with it, so pasted bursts are seen whole.
+                    while let Some(Some(Ok(event))) = events.next().now_or_never() {
+                        batch.push((time::Instant::now(), event));
+                    }
+
+                    let keys = self.key_event_filter;
+
+                    batch.retain(|(_, event)| events::is_delivered(event, keys));
+
+                    self.cycle(batch, None)?;
+                },
+                Some(outcome) = results.recv() => {
+                    self.queued_messages.extend(delivered(outcome));
+                    self.cycle(Vec::new(), None)?;
+                },
+            }
+        }
+    }
+}
+
+// Tracks whether `Builder::initial_screen` was called, only then can the builder run the application itself.
+pub struct NoInitialScreen;
+
+pub struct InitialScreen<S>(PhantomData<fn() -> S>);
+
+pub struct Builder<Ctx = (), I = NoInitialScreen> {
+    context: Ctx,
+    event_poll_rate: Option<RangeInclusive<time::Duration>>,
+    shutdown_grace_period: Option<time::Duration>,
+    resize_debounce: Option<time::Duration>,
+    screens: HashMap<TypeId, Box<dyn Screen<Ctx>>>,
+    compositor: Compositor<Ctx>,
+    tick_rate: Option<time::Duration>,
+    align_ticks: bool,
+    paste_bursts: PasteBursts,
+    key_event_filter: KeyEventFilter,
+    skip_unchanged_frames: bool,
+    always_redraw: bool,
+    flush_deadline: Option<FlushDeadline>,
+    game_mode: bool,
+    input_state: Option<fn(&mut Ctx) -> &mut InputState>,
+    bell: Bell,
+    startup_callback: Option<fn() -> Command>,
+    shutdown_callback: Option<fn() -> Command>,
+    command_handler: Option<CommandHandler<Ctx>>,
+    screen_names: HashMap<TypeId, &'static str>,
+    background_screens: Vec<(TypeId, Arc<Mutex<dyn BackgroundUpdate>>)>,
+    keymaps: Keymaps,
+    crash_report: Option<(PathBuf, usize)>,
+    action_log: Option<usize>,
+    autosave: Option<(PathBuf, time::Duration)>,
+    onboarding: Option<Onboarding>,
+    locale: Option<Locale>,
+    reduced_motion: Option<bool>,
+    scheduler: Scheduler,
+    clock: Clock,
+    subscriptions: Vec<Subscription>,
+    manage_raw_mode: bool,
+    manage_alternate_screen: bool,
+    #[cfg(feature = "paste")]
+    manage_bracketed_paste: bool,
+    #[cfg(feature = "update-check")]
+    update_toast: bool,
+    dry_run: bool,
+    trace_writer: Option<Box<dyn Write>>,
+    terminal_state: Option<TerminalState>,
+    initial: PhantomData<fn() -> I>,
+}
+
+impl Default for Builder {
+    fn default() -> Self {
+        Self::with_context(())
+    }
+}
+
+impl Builder {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Ctx> Builder<Ctx> {
+    pub fn with_context(context: Ctx) -> Self {
+        Self {
+            context,
+            event_poll_rate: None,
+            shutdown_grace_period: None,
+            resize_debounce: None,
+            screens: HashMap::new(),
+            compositor: Compositor::default(),
+            tick_rate: None,
+            align_ticks: false,
+            paste_bursts: PasteBursts::default(),
+            key_event_filter: KeyEventFilter::default(),
+            skip_unchanged_frames: false,
+            always_redraw: false,
+            flush_deadline: None,
+            game_mode: false,
+            input_state: None,
+            bell: Bell::default(),
+            startup_callback: None,
+            shutdown_callback: None,
+            command_handler: None,
+            screen_names: HashMap::new(),
+            background_screens: Vec::new(),
+            keymaps: Keymaps::default(),
+            crash_report: None,
+            action_log: None,
+            autosave: None,
+            onboarding: None,
+            locale: None,
+            reduced_motion: None,
+            scheduler: Scheduler::default(),
+            clock: Clock::system(),
+            subscriptions: Vec::new(),
+            manage_raw_mode: false,
+            manage_alternate_screen: false,
+            #[cfg(feature = "paste")]
+            manage_bracketed_paste: false,
+            #[cfg(feature = "update-check")]
+            update_toast: true,
+            dry_run: false,
+            trace_writer: None,
+            terminal_state: None,
+            initial: PhantomData,
+        }
+    }
+
+    // Registers the screen the application starts on, see `Builder::run`.
+    pub fn initial_screen<S: Screen<Ctx> + 'static>(self, screen: S) -> Builder<Ctx, InitialScreen<S>> {
+        self.screen(screen).with_initial()
+    }
+}
+
+impl<Ctx, I> Builder<Ctx, I> {
+    fn with_initial<J>(self) -> Builder<Ctx, J> {
+        Builder {
+            context: self.context,
+            event_poll_rate: self.event_poll_rate,
+            shutdown_grace_period: self.shutdown_grace_period,
+            resize_debounce: self.resize_debounce,
+            screens: self.screens,
+            compositor: self.compositor,
+            tick_rate: self.tick_rate,
+            align_ticks: self.align_ticks,
+            paste_bursts: self.paste_bursts,
+            key_event_filter: self.key_event_filter,
+            skip_unchanged_frames: self.skip_unchanged_frames,
+            always_redraw: self.always_redraw,
+            flush_deadline: self.flush_deadline,
+            game_mode: self.game_mode,
+            input_state: self.input_state,
+            bell: self.bell,
+            startup_callback: self.startup_callback,
+            shutdown_callback: self.shutdown_callback,
+            command_handler: self.command_handler,
+            screen_names: self.screen_names,
+            background_screens: self.background_screens,
+            keymaps: self.keymaps,
+            crash_report: self.crash_report,
+            action_log: self.action_log,
+            autosave: self.autosave,
+            onboarding: self.onboarding,
+            locale: self.locale,
+            reduced_motion: self.reduced_motion,
+            scheduler: self.scheduler,
+            clock: self.clock,
+            subscriptions: self.subscriptions,
+            manage_raw_mode: self.manage_raw_mode,
+            manage_alternate_screen: self.manage_alternate_screen,
+            #[cfg(feature = "paste")]
+            manage_bracketed_paste: self.manage_bracketed_paste,
+            #[cfg(feature = "update-check")]
+            update_toast: self.update_toast,
+            dry_run: self.dry_run,
+            trace_writer: self.trace_writer,
+            terminal_state: self.terminal_state,
+            initial: PhantomData,
+        }
+    }
+
+    // A fixed rate, by default it adapts to the input between an eighth and half of the tick rate.
+    pub fn event_polling_rate(mut self, rate: time::Duration) -> Self {
+        self.event_poll_rate = Some(rate..=rate);
+        self
+    }
+
+    pub fn adaptive_event_polling(mut self, fastest: time::Duration, slowest: time::Duration) -> Self {
+        self.event_poll_rate = Some(fastest..=slowest);
+        self
+    }
+
+    pub fn shutdown_grace_period(mut self, period: time::Duration) -> Self {
+        self.shutdown_grace_period = Some(period);
+        self
+    }
+
+    pub fn resize_debounce(mut self, debounce: time::Duration) -> Self {
+        self.resize_debounce = Some(debounce);
+        self
+    }
+
+    pub fn screen<S: Screen<Ctx> + 'static>(mut self, screen: S) -> Self {
+        self.screens.insert(TypeId::of::<S>(), Box::new(screen));
+        self.screen_names.insert(TypeId::of::<S>(), any::type_name::<S>());
+        self
+    }
+
+    // Updated on worker threads while it isn't active, see `BackgroundUpdate`.
+    pub fn background_screen<S>(mut self, screen: S) -> Self
+        where S: Screen<Ctx> + BackgroundUpdate + 'static,
+    {
+        let shared = Arc::new(Mutex::new(screen));
+
+        self.background_screens.push((TypeId::of::<S>(), Arc::clone(&shared) as _));
+        self.screens.insert(TypeId::of::<S>(), Box::new(Shared(shared)));
+        self.screen_names.insert(TypeId::of::<S>(), any::type_name::<S>());
+        self
+    }
+
+    // The keys it binds reach the active screen as `Message::Custom` holding the action, see `KeyMap`.
+    pub fn keymap<A>(mut self, keymap: KeyMap<A>) -> Result<Self, KeymapConflictError>
+        where A: fmt::Debug + Clone + Send + 'static,
+    {
+        self.keymaps.register(None, keymap)?;
+        Ok(self)
+    }
+
+    // Only while the screen is active, shadowing the global keymaps.
+    pub fn screen_keymap<S, A>(mut self, keymap: KeyMap<A>) -> Result<Self, KeymapConflictError>
+        where S: Screen<Ctx> + 'static, A: fmt::Debug + Clone + Send + 'static,
+    {
+        self.keymaps.register(Some(TypeId::of::<S>()), keymap)?;
+        Ok(self)
+    }
+
+    pub fn pinned_pane<S: Screen<Ctx> + 'static>(mut self, screen: S, placement: fn(Rect) -> Rect) -> Self {
+        self.compositor.panes.push(PinnedPane::new(Box::new(screen), placement));
+        self
+    }
+
+    pub fn dim_background(mut self, dim: Dim) -> Self {
+        self.compositor.dim = dim;
+        self
+    }
+
+    pub fn post_process(mut self, filter: fn(&mut Buffer)) -> Self {
+        self.compositor.add_filter(filter);
+        self
+    }
+
+    pub fn layer<L: Layer + 'static>(mut self, z: i32, layer: L) -> Self {
+        self.compositor.insert(z, Box::new(layer));
+        self
+    }
+
+    pub fn tick_rate(mut self, rate: time::Duration) -> Self {
+        self.tick_rate = Some(rate);
+        self
+    }
+
+    pub fn frames_per_second(mut self, fps: u8) -> Self {
+        self.tick_rate = Some(time::Duration::from_secs_f32(1. / fps as f32));
+        self
+    }
+
+    pub fn align_ticks(mut self, align: bool) -> Self {
+        self.align_ticks = align;
+        self
+    }
+
+    // For screens that can't tell whether they changed, a frame identical to the last one isn't written at all.
+    // Costs a hash of the buffer per frame.
+    pub fn skip_unchanged_frames(mut self, skip: bool) -> Self {
+        self.skip_unchanged_frames = skip;
+        self
+    }
+
+    // By default frames are only drawn after input, commands or other messages than ticks. Screens that
+    // animate on ticks return `Command::Render`, or draw every frame with this.
+    pub fn always_redraw(mut self, always: bool) -> Self {
+        self.always_redraw = always;
+        self
+    }
+
+    // Wakes the loop up to flush a `DamageBackend` once its rate cap allows, see `DamageBackend::flush_deadline`.
+    // Otherwise what it held back is only transmitted with the next frame.
+    pub fn flush_deadline(mut self, deadline: FlushDeadline) -> Self {
+        self.flush_deadline = Some(deadline);
+        self
+    }
+
+    // A preset for games. Updates happen in fixed steps of the tick rate, every `TickMsg` is exactly one step
+    // however long the frame took, and frames are drawn on a steady schedule. Key releases are reported as
+    // well, so the `InputState` of `Builder::input_state` knows which keys are held down.
+    pub fn game_mode(mut self) -> Self {
+        self.game_mode = true;
+        self.always_redraw = true;
+        self.key_event_filter = KeyEventFilter::ALL;
+        self
+    }
+
+    // Where the context keeps the keys held down, which is up to date with every message a screen is updated with
+    // in game mode, e.g. `.input_state(|game: &mut Game| &mut game.input)`.
+    pub fn input_state(mut self, input: fn(&mut Ctx) -> &mut InputState) -> Self {
+        self.input_state = Some(input);
+        self
+    }
+
+    pub fn bell(mut self, bell: Bell) -> Self {
+        self.bell = bell;
+        self
+    }
+
+    pub fn paste_bursts(mut self, bursts: PasteBursts) -> Self {
+        self.paste_bursts = bursts;
+        self
+    }
+
+    // Only presses by default, games usually want releases as well.
+    pub fn key_event_filter(mut self, filter: KeyEventFilter) -> Self {
+        self.key_event_filter = filter;
+        self
+    }
+
+    pub fn on_startup(mut self, callback: fn() -> Command) -> Self {
+        self.startup_callback = Some(callback);
+        self
+    }
+
+    pub fn on_shutdown(mut self, callback: fn() -> Command) -> Self {
+        self.shutdown_callback = Some(callback);
+        self
+    }
+
+    // Receives every `Command::Custom`, the command it returns is handled right after.
+    pub fn command_handler<F>(mut self, handler: F) -> Self
+        where F: FnMut(Box<dyn Any + Send>, &mut Ctx) -> Option<Command> + 'static,
+    {
+        self.command_handler = Some(Box::new(handler));
+        self
+    }
+
+    pub fn subscription(mut self, subscription: Subscription) -> Self {
+        self.subscriptions.push(subscription);
+        self
+    }
+
+    pub fn scheduler(mut self, scheduler: Scheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    // What the scheduler, subscriptions, resize debouncing, tooltips, toasts and the visual bell go by. Share it
+    // with components that go by the time too, e.g. `LiveText::clock`.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // A `TestApplication` only moves time forward when it ticks, keeping the manual clock it may have been given.
+    pub(crate) fn manual_clock(mut self) -> Self {
+        if !self.clock.is_manual() {
+            self.clock = Clock::manual();
+        }
+
+        self
+    }
+
+    pub fn reduced_motion(mut self, reduced: bool) -> Self {
+        self.reduced_motion = Some(reduced);
+        self
+    }
+
+    pub fn locale(mut self, locale: Locale) -> Self {
+        self.locale = Some(locale);
+        self
+    }
+
+    pub fn onboarding(mut self, onboarding: Onboarding) -> Self {
+        self.onboarding = Some(onboarding);
+        self
+    }
+
+    pub fn autosave<P: Into<PathBuf>>(mut self, directory: P, interval: time::Duration) -> Self {
+        self.autosave = Some((directory.into(), interval));
+        self
+    }
+
+    // Opt-in, keeps the last `capacity` screen visits and actions logged with `Command::LogAction`.
+    pub fn action_log(mut self, capacity: usize) -> Self {
+        self.action_log = Some(capacity);
+        self
+    }
+
+    pub fn crash_report<P: Into<PathBuf>>(mut self, path: P, capacity: usize) -> Self {
+        self.crash_report = Some((path.into(), capacity));
+        self
+    }
+
+    pub fn manage_raw_mode(mut self, manage: bool) -> Self {
+        self.manage_raw_mode = manage;
+        self
+    }
+
+    // Commands are traced rather than executed as far as they would touch the terminal, the filesystem, the network
+    // or other processes, custom commands aren't handled at all. Screens still switch and messages are still
+    // delivered.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    // Where the dry run trace is written as it goes, a line per command.
+    pub fn trace_to<W: Write + 'static>(mut self, writer: W) -> Self {
+        self.trace_writer = Some(Box::new(writer));
+        self
+    }
+
+    pub fn manage_alternate_screen(mut self, manage: bool) -> Self {
+        self.manage_alternate_screen = manage;
+        self
+    }
+
+    // Pasted text arrives as a single `Message::Paste` instead of key by key.
+    #[cfg(feature = "paste")]
+    pub fn bracketed_paste(mut self, enable: bool) -> Self {
+        self.manage_bracketed_paste = enable;
+        self
+    }
+
+    // Whether `Message::UpdateAvailable` is announced with a toast as well, it's delivered to the screens either
+    // way.
+    #[cfg(feature = "update-check")]
+    pub fn update_toast(mut self, enable: bool) -> Self {
+        self.update_toast = enable;
+        self
+    }
+
+    pub fn terminal_state(mut self, state: TerminalState) -> Self {
+        self.terminal_state = Some(state);
+        self
+    }
+
+    pub fn build<W, B>(mut self, sink: W, backend: B) -> Result<Application<B, Ctx>, io::Error>
+        where W: Write + Send + 'static, B: Backend,
+    {
+        self.compositor.clock = self.clock.clone();
+
+        let tick_rate = self.tick_rate.unwrap_or(time::Duration::from_secs_f32(1. / 30.));
+
+        let event_poll_rate = self.event_poll_rate.unwrap_or(tick_rate / 8..=tick_rate / 2);
+
+        let terminal = ratatui::Terminal::new(backend)?;
+
+        Ok(Application {
+            context: self.context,
+            shutdown_callback: self.shutdown_callback,
+            startup_callback: self.startup_callback,
+            command_handler: self.command_handler,
             terminal,
-            sink: Box::new(sink),
+            terminal_guard: TerminalGuard::with_state(sink, TerminalState::default()),
+            terminal_state: self.terminal_state,
             last_tick: None,
+            frame: 0,
             tick_rate,
+            align_ticks: self.align_ticks,
+            paste_bursts: BurstDetector::new(self.paste_bursts),
+            key_event_filter: self.key_event_filter,
+            skip_unchanged_frames: self.skip_unchanged_frames,
+            always_redraw: self.always_redraw,
+            flush_deadline: self.flush_deadline,
+            game_mode: self.game_mode,
+            input_state: self.input_state,
+            key_repeat_timeout: None,
+            bell: self.bell,
+            accumulator: time::Duration::ZERO,
+            next_frame: None,
+            dirty: true,
+            redraw_at: None,
+            last_frame: None,
             event_poll_rate,
+            shutdown_grace_period: self.shutdown_grace_period.unwrap_or(time::Duration::from_secs(1)),
+            resize_debounce: self.resize_debounce,
+            resizing_until: None,
             screens: self.screens,
+            screen_names: self.screen_names,
+            initialized: HashSet::new(),
             exiting: false,
+            hangup: Arc::new(AtomicBool::new(false)),
+            disconnected: false,
+            queued_messages: VecDeque::new(),
+            listener_pause: ListenerPause::default(),
+            #[cfg(feature = "tokio")]
+            handovers: VecDeque::new(),
+            recording_macro: None,
+            recording_suppressed: false,
+            ready_signalled: false,
+            dry_run: self.dry_run,
+            trace: self.dry_run.then(Vec::new),
+            trace_writer: self.trace_writer,
+            macros: HashMap::new(),
+            crash_log: self.crash_report.map(|(path, capacity)| CrashLog::new(path, capacity)),
+            action_log: self.action_log.map(ActionLog::new),
+            autosave: self.autosave.map(|(directory, interval)| Autosave::new(directory, interval)),
+            manage_raw_mode: self.manage_raw_mode,
+            manage_alternate_screen: self.manage_alternate_screen,
+            #[cfg(feature = "paste")]
+            manage_bracketed_paste: self.manage_bracketed_paste,
+            #[cfg(feature = "update-check")]
+            update_toast: self.update_toast,
             previous_screen_entry: None,
+            screen_stack: Vec::new(),
+            overlays: Vec::new(),
             active_screen_entry: None,
+            main_screen: None,
+            onboarding: self.onboarding,
+            locale: self.locale,
+            reduced_motion: self.reduced_motion,
+            scheduler: self.scheduler,
+            clock: self.clock,
+            subscriptions: self.subscriptions,
+            screen_subscriptions: Vec::new(),
+            subscribed_screen: None,
+            #[cfg(feature = "futures")]
+            executor: None,
+            workers: Workers::default(),
+            background_screens: self.background_screens,
+            keymaps: self.keymaps,
+            background_updates: Vec::new(),
+            inbox: mpsc::channel(),
+            #[cfg(feature = "tokio")]
+            task_results: None,
+            issuer: None,
+            detached: false,
+            correlation: None,
+            target: None,
+            scopes: HashMap::new(),
+            compositor: self.compositor,
         })
     }
+}
+
+impl<Ctx, S: Screen<Ctx> + 'static> Builder<Ctx, InitialScreen<S>> {
+    // Builds the application and runs it from the initial screen, which can't be missing like it can with
+    // `Application::run`.
+    pub fn run<W, B>(self, sink: W, backend: B) -> Result<(), RuntimeError>
+        where W: Write + Send + 'static, B: Backend,
+    {
+        self.build(sink, backend).map_err(RuntimeError::Terminal)?.run::<S>()
+    }
+
+    #[cfg(feature = "tokio")]
+    pub async fn run_async<W, B>(self, sink: W, backend: B) -> Result<(), RuntimeError>
+        where W: Write + Send + 'static, B: Backend,
+    {
+        self.build(sink, backend).map_err(RuntimeError::Terminal)?.run_async::<S>().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crossterm::event::KeyCode;
+
+    use ratatui::backend::TestBackend;
+
+    use crate::command;
+    use crate::panic_hook;
+    use crate::keymap::KeyBinding;
+    use crate::screen::ScreenError;
+    use crate::testing::TestApplication;
+
+    // Every screen notes what it got in the context.
+    type Log = Vec<String>;
+
+    struct Talker;
+    struct Listener;
+
+    impl Screen<Log> for Talker {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            Ok(match message {
+                | Message::Key(key) if key.code == KeyCode::Char('p') => Some(Command::publish("news", 1u8)),
+                | Message::Key(key) if key.code == KeyCode::Char('o') => Some(Command::open_overlay::<Popup, _>()),
+                | Message::Key(key) if key.code == KeyCode::Char('c') => Some(Command::custom(1u8)),
+                | Message::Key(key) if key.code == KeyCode::Char('s') => {
+                    Some(Command::send_to::<Listener, _>(Message::FocusGained))
+                },
+                | Message::FocusGained => {
+                    log.push(String::from("talker"));
+                    Some(Command::send_to::<Listener, _>(Message::FocusGained))
+                },
+                | Message::Resumed => {
+                    log.push(String::from("talker resumed"));
+                    None
+                },
+                | Message::Custom(action) if action.is::<&str>() => {
+                    log.push(format!("talker did {}", action.downcast_ref::<&str>().unwrap()));
+                    None
+                },
+                | Message::Routed(routing, message) => {
+                    let value = message.downcast_ref::<u8>().copied().unwrap_or_default();
+                    let correlation = routing.correlation.unwrap_or_default();
+
+                    log.push(format!("talker got {value} for request {correlation}"));
+                    None
+                },
+                | _ => None,
+            })
+        }
+    }
+
+    impl Screen<Log> for Listener {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            Ok(match message {
+                | Message::Published(topic, _) => {
+                    log.push(format!("published {topic}"));
+                    None
+                },
+                | Message::FocusGained => {
+                    log.push(String::from("listener"));
+                    Some(Command::send_to::<Talker, _>(Message::FocusGained))
+                },
+                | Message::Resumed => {
+                    log.push(String::from("listener resumed"));
+                    None
+                },
+                | _ => None,
+            })
+        }
+
+        fn subscribed(&self, topic: &str) -> bool {
+            topic == "news"
+        }
+    }
+
+    fn application() -> TestApplication<Log> {
+        let builder = Builder::with_context(Log::new()).screen(Talker).screen(Listener).screen(Popup);
+
+        TestApplication::new::<Talker>(builder, 10, 2).unwrap()
+    }
+
+    // Opened over the talker, starts work in the background.
+    struct Popup;
+
+    impl Screen<Log> for Popup {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            Ok(match message {
+                | Message::Key(key) if key.code == KeyCode::Char('w') => {
+                    Some(command::spawn(|| Message::custom(42u8)))
+                },
+                | Message::Key(key) if key.code == KeyCode::Char('d') => {
+                    Some(command::spawn(|| Message::custom(42u8)).detached())
+                },
+                | Message::Key(key) if key.code == KeyCode::Char('r') => {
+                    Some(command::spawn(|| Message::custom(42u8)).correlated(7).route_to::<Talker, _>())
+                },
+                #[cfg(any(feature = "futures", feature = "tokio"))]
+                | Message::Key(key) if key.code == KeyCode::Char('f') => {
+                    Some(Command::perform(async { Message::custom(43u8) }).detached())
+                },
+                | Message::Key(key) if key.code == KeyCode::Esc => Some(Command::CloseOverlay),
+                | message => {
+                    if let Some(value) = message.downcast_ref::<u8>() {
+                        log.push(format!("popup got {value}"));
+                    }
+
+                    None
+                },
+            })
+        }
+    }
+
+    #[test]
+    fn publications_reach_inactive_subscribers() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('p')).unwrap();
+
+        assert_eq!(app.context(), &["published news"]);
+    }
+
+    #[test]
+    fn screens_messaging_each_other_are_queued_instead_of_recursing() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('s')).unwrap();
+
+        assert_eq!(app.context().len(), MAX_QUEUED_ROUNDS);
+
+        // The rest is picked up next time around.
+        app.tick().unwrap();
+
+        assert_eq!(app.context().len(), 2 * MAX_QUEUED_ROUNDS);
+        assert_eq!(app.context()[..2], ["listener", "talker"]);
+    }
+
+    #[test]
+    fn results_go_back_to_the_screen_that_started_the_work() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Char('d')).unwrap().key(KeyCode::Esc).unwrap();
+
+        app.settle(time::Duration::from_secs(5)).unwrap();
+
+        assert_eq!(app.context(), &["popup got 42"]);
+    }
+
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    #[test]
+    fn performed_results_go_back_to_the_screen_that_returned_the_task() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Char('f')).unwrap().key(KeyCode::Esc).unwrap();
+
+        app.settle(time::Duration::from_secs(5)).unwrap();
+
+        assert_eq!(app.context(), &["popup got 43"]);
+    }
+
+    #[test]
+    fn work_of_a_closed_overlay_is_cancelled() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Char('w')).unwrap().key(KeyCode::Esc).unwrap();
+
+        app.settle(time::Duration::from_secs(5)).unwrap();
+
+        assert!(app.context().is_empty());
+    }
+
+    #[test]
+    fn routed_results_reach_their_target_after_the_origin_is_gone() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Char('r')).unwrap().key(KeyCode::Esc).unwrap();
+
+        app.settle(time::Duration::from_secs(5)).unwrap();
+
+        assert_eq!(app.context(), &["talker got 42 for request 7"]);
+    }
+
+    #[test]
+    fn work_of_an_open_overlay_is_delivered() {
+        let mut app = application();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Char('w')).unwrap();
+
+        app.settle(time::Duration::from_secs(5)).unwrap();
+
+        assert_eq!(app.context(), &["popup got 42"]);
+    }
+
+    // What the trace or the terminal's output is written to, read back by the test.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Shared {
+        fn take(&self) -> String {
+            String::from_utf8(mem::take(&mut *self.0.lock().unwrap())).unwrap()
+        }
+    }
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn the_trace_goes_to_the_writer() {
+        let writer = Shared::default();
+        let builder = Builder::with_context(Log::new()).screen(Talker).screen(Popup).trace_to(writer.clone());
+        let mut app = TestApplication::new::<Talker>(builder, 10, 2).unwrap();
+
+        app.key(KeyCode::Char('o')).unwrap().key(KeyCode::Esc).unwrap();
+
+        let written = writer.take();
+
+        assert_eq!(written.lines().collect::<Vec<_>>(), app.commands());
+        assert_eq!(app.commands().len(), 2);
+    }
+
+    #[test]
+    fn custom_commands_are_not_handled_in_a_dry_run() {
+        let builder = Builder::with_context(Log::new())
+            .screen(Talker)
+            .command_handler(|_, log: &mut Log| {
+                log.push(String::from("handled"));
+                None
+            });
+
+        let mut app = TestApplication::new::<Talker>(builder, 10, 2).unwrap();
+
+        app.key(KeyCode::Char('c')).unwrap();
+
+        assert!(app.context().is_empty());
+        assert_eq!(app.commands().len(), 1);
+    }
+
+    #[test]
+    fn reads_and_writes_failing_on_a_hung_up_terminal_are_disconnects() {
+        assert!(is_disconnect(&io::Error::from(io::ErrorKind::BrokenPipe)));
+        assert!(is_disconnect(&io::Error::from(io::ErrorKind::UnexpectedEof)));
+        assert_eq!(is_disconnect(&io::Error::from_raw_os_error(5)), cfg!(unix));
+        assert!(!is_disconnect(&io::Error::from(io::ErrorKind::WouldBlock)));
+    }
+
+    #[test]
+    fn inactive_screens_are_resumed_too() {
+        let mut app = application();
+
+        app.send(Message::Resumed).unwrap();
+
+        let mut log = app.context().clone();
+        log.sort();
+
+        assert_eq!(log, ["listener resumed", "talker resumed"]);
+    }
+
+    // Counts its updates in the background, tells about them when there's news.
+    #[derive(Default)]
+    struct Indexer {
+        updated_on: Vec<thread::ThreadId>,
+    }
+
+    impl Screen<Log> for Indexer {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            if let Message::Published(..) = message {
+                let here = self.updated_on.contains(&thread::current().id());
+
+                log.push(format!("indexed {} times, here: {here}", self.updated_on.len()));
+            }
+
+            Ok(None)
+        }
+
+        fn subscribed(&self, _topic: &str) -> bool {
+            true
+        }
+    }
+
+    impl BackgroundUpdate for Indexer {
+        fn background_update(&mut self, _: time::Duration) {
+            self.updated_on.push(thread::current().id());
+        }
+    }
+
+    #[test]
+    fn inactive_background_screens_are_updated_on_other_threads() {
+        let builder = Builder::with_context(Log::new()).screen(Talker).background_screen(Indexer::default());
+        let mut app = TestApplication::new::<Talker>(builder, 10, 2).unwrap();
+
+        app.tick().unwrap().tick().unwrap().tick().unwrap();
+
+        app.key(KeyCode::Char('p')).unwrap();
+
+        assert_eq!(app.context(), &["indexed 3 times, here: false"]);
+    }
+
+    #[test]
+    fn active_background_screens_are_not_updated_in_the_background() {
+        let builder = Builder::with_context(Log::new()).screen(Talker).background_screen(Indexer::default());
+        let mut app = TestApplication::new::<Indexer>(builder, 10, 2).unwrap();
+
+        app.tick().unwrap().tick().unwrap();
+
+        app.send(Message::Published(String::from("news"), Arc::new(()))).unwrap();
+
+        assert_eq!(app.context(), &["indexed 0 times, here: false"]);
+    }
+
+    #[test]
+    fn keys_bound_in_keymaps_arrive_as_their_actions() {
+        let builder = Builder::with_context(Log::new())
+            .screen(Talker)
+            .screen(Listener)
+            .keymap(KeyMap::new().bind(KeyBinding::char('x'), "global x").bind(KeyBinding::char('y'), "global y"))
+            .unwrap()
+            .screen_keymap::<Talker, _>(KeyMap::new().bind(KeyBinding::char('x'), "talker x"))
+            .unwrap();
+
+        let mut app = TestApplication::new::<Talker>(builder, 10, 2).unwrap();
+
+        app.key(KeyCode::Char('x')).unwrap().key(KeyCode::Char('y')).unwrap().key(KeyCode::Char('p')).unwrap();
+
+        assert_eq!(app.context(), &["talker did talker x", "talker did global y", "published news"]);
+    }
+
+    #[test]
+    fn conflicting_keymaps_are_refused_by_the_builder() {
+        let builder = Builder::new().keymap(KeyMap::new().bind(KeyBinding::ctrl('s'), "save")).unwrap();
+
+        let error = builder.keymap(KeyMap::new().bind(KeyBinding::ctrl('s'), "search")).err().unwrap();
+
+        assert_eq!(error.to_string(), "ctrl+s is bound to both \"save\" and \"search\" in the Global context");
+    }
+
+    // Named by a letter, goes to the others by theirs: lowercase switches and uppercase pushes. Backspace pops,
+    // left goes back and any other letter is noted along with the ones before, as is pasted text.
+    #[derive(Default)]
+    struct Page<const NAME: char> {
+        letters: String,
+    }
+
+    impl<const NAME: char> Screen<Log> for Page<NAME> {
+        fn render(&self, f: &mut Frame<'_>, _: &Log) {
+            f.render_widget(self.letters.as_str(), f.size());
+        }
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            let key = match message {
+                | Message::Key(key) => key,
+                #[cfg(feature = "paste")]
+                | Message::Paste(text) => {
+                    log.push(format!("{NAME} pasted {text:?}"));
+                    return Ok(None);
+                },
+                | _ => return Ok(None),
+            };
+
+            Ok(match key.code {
+                | KeyCode::Char('a') => Some(Command::screen::<Page<'a'>, _>()),
+                | KeyCode::Char('b') => Some(Command::screen::<Page<'b'>, _>()),
+                | KeyCode::Char('c') => Some(Command::screen::<Page<'c'>, _>()),
+                | KeyCode::Char('A') => Some(Command::push_screen::<Page<'a'>, _>()),
+                | KeyCode::Char('B') => Some(Command::push_screen::<Page<'b'>, _>()),
+                | KeyCode::Backspace => Some(Command::PopScreen),
+                | KeyCode::Left => Some(command::back()),
+                | KeyCode::Char(letter) => {
+                    self.letters.push(letter);
+                    log.push(format!("{NAME} got {}", self.letters));
+                    None
+                },
+                | _ => None,
+            })
+        }
+
+        fn init(&mut self, log: &mut Log) -> Option<Command> {
+            log.push(format!("{NAME} init"));
+            None
+        }
+
+        fn on_enter(&mut self, log: &mut Log) -> Option<Command> {
+            log.push(format!("{NAME} enter"));
+            None
+        }
+
+        fn on_exit(&mut self, log: &mut Log) -> Option<Command> {
+            log.push(format!("{NAME} exit"));
+            None
+        }
+    }
+
+    // Starts on the first page, the third one isn't registered.
+    fn pages() -> TestApplication<Log> {
+        let builder = Builder::with_context(Log::new()).screen(Page::<'a'>::default()).screen(Page::<'b'>::default());
+
+        TestApplication::new::<Page<'a'>>(builder, 10, 2).unwrap()
+    }
+
+    #[test]
+    fn popping_returns_to_the_screen_below_as_it_was() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('x')).unwrap().key(KeyCode::Char('B')).unwrap();
+        app.key(KeyCode::Backspace).unwrap().key(KeyCode::Char('y')).unwrap();
+
+        assert_eq!(app.context().last().unwrap(), "a got xy");
+    }
+
+    #[test]
+    fn only_the_pushed_screen_receives_input() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('B')).unwrap().key(KeyCode::Char('x')).unwrap();
+
+        assert!(app.context().iter().all(|entry| !entry.starts_with("a got")));
+        assert_eq!(app.context().last().unwrap(), "b got x");
+    }
+
+    #[test]
+    fn popping_the_last_screen_is_an_error() {
+        let mut app = pages();
+
+        let result = app.key(KeyCode::Backspace);
+
+        assert!(matches!(result, Err(RuntimeError::EmptyScreenStack(_))));
+    }
+
+    #[test]
+    fn going_back_returns_to_the_previous_screen_as_it_was() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('x')).unwrap().key(KeyCode::Char('b')).unwrap();
+        app.key(KeyCode::Left).unwrap().key(KeyCode::Char('y')).unwrap();
+
+        assert_eq!(app.context().last().unwrap(), "a got xy");
+    }
+
+    #[test]
+    fn going_back_without_a_previous_screen_is_an_error() {
+        let mut app = pages();
+
+        let result = app.key(KeyCode::Left);
+
+        assert!(matches!(result, Err(RuntimeError::NoPreviousScreen(_))));
+    }
+
+    #[test]
+    fn switching_to_an_unregistered_screen_is_an_error() {
+        let mut app = pages();
+
+        let Err(RuntimeError::MissingScreen(error)) = app.key(KeyCode::Char('c')) else {
+            panic!("expected a missing screen");
+        };
+
+        let expected = format!("could not find a registered screen for: {:?}", TypeId::of::<Page<'c'>>());
+
+        assert_eq!(error.to_string(), expected);
+    }
+
+    #[test]
+    fn switching_to_a_stacked_screen_is_an_error() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('B')).unwrap();
+
+        assert!(matches!(app.key(KeyCode::Char('a')), Err(RuntimeError::ScreenInUse(_))));
+    }
+
+    #[test]
+    fn pushing_the_active_screen_is_an_error() {
+        let mut app = pages();
+
+        assert!(matches!(app.key(KeyCode::Char('A')), Err(RuntimeError::ScreenInUse(_))));
+    }
+
+    #[test]
+    fn screens_are_initialized_once_on_their_first_activation() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('b')).unwrap().key(KeyCode::Char('a')).unwrap().key(KeyCode::Char('b')).unwrap();
+        app.key(KeyCode::Char('a')).unwrap();
+
+        let inits = app.context().iter().filter(|entry| entry.ends_with("init")).collect::<Vec<_>>();
+
+        assert_eq!(inits, ["a init", "b init"]);
+    }
+
+    #[test]
+    fn the_screen_left_hears_about_it_before_the_one_entered() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('b')).unwrap();
+
+        assert_eq!(app.context(), &["a init", "a enter", "a exit", "b init", "b enter"]);
+    }
+
+    #[test]
+    fn pushing_and_popping_run_the_hooks() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('B')).unwrap().key(KeyCode::Backspace).unwrap();
+
+        assert_eq!(app.context()[2..], ["a exit", "b init", "b enter", "b exit", "a enter"]);
+    }
+
+    #[test]
+    fn switching_to_the_active_screen_runs_no_hooks() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('a')).unwrap();
+
+        assert_eq!(app.context(), &["a init", "a enter"]);
+    }
+
+    // Records a macro between q and w, plays it back on @ and an unknown one on !.
+    struct Recorder;
+
+    impl Screen<Log> for Recorder {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, log: &mut Log) -> Result<Option<Command>, ScreenError> {
+            let Message::Key(key) = message else {
+                return Ok(None);
+            };
+
+            let KeyCode::Char(letter) = key.code else {
+                return Ok(None);
+            };
+
+            log.push(format!("got {letter}"));
+
+            Ok(match letter {
+                | 'q' => Some(Command::StartMacro(String::from("m"))),
+                | 'w' => Some(Command::StopMacro),
+                | '@' => Some(Command::PlayMacro(String::from("m"))),
+                | '!' => Some(Command::PlayMacro(String::from("unknown"))),
+                | _ => None,
+            })
+        }
+    }
+
+    fn recorder() -> TestApplication<Log> {
+        TestApplication::new::<Recorder>(Builder::with_context(Log::new()).screen(Recorder), 10, 2).unwrap()
+    }
+
+    #[test]
+    fn recorded_macros_replay_the_keys_in_between() {
+        let mut app = recorder();
+
+        app.type_text("qxyw").unwrap().key(KeyCode::Char('@')).unwrap();
+
+        assert_eq!(app.context()[4..], ["got @", "got x", "got y"]);
+    }
+
+    #[test]
+    fn playing_an_empty_or_unknown_macro_does_nothing() {
+        let mut app = recorder();
+
+        app.type_text("qw@!").unwrap();
+
+        assert_eq!(app.context(), &["got q", "got w", "got @", "got !"]);
+    }
+
+    #[test]
+    fn only_messages_that_could_change_the_frame_draw_one() {
+        let mut app = pages();
+
+        assert_eq!(app.frames(), 1);
+
+        app.tick().unwrap().tick().unwrap();
+
+        assert_eq!(app.frames(), 1);
+
+        app.key(KeyCode::Char('x')).unwrap();
+
+        assert_eq!(app.frames(), 2);
+
+        app.send(Message::FocusGained).unwrap().tick().unwrap();
+
+        assert_eq!(app.frames(), 3);
+    }
+
+    #[test]
+    fn frames_identical_to_the_last_one_are_not_drawn_again() {
+        let builder = Builder::with_context(Log::new()).screen(Page::<'a'>::default()).skip_unchanged_frames(true);
+        let mut app = TestApplication::new::<Page<'a'>>(builder, 10, 2).unwrap();
+
+        app.send(Message::FocusGained).unwrap();
+
+        assert_eq!(app.frames(), 1);
+
+        app.key(KeyCode::Char('x')).unwrap();
+
+        assert_eq!(app.frames(), 2);
+        assert_eq!(app.lines(), ["x         ", "          "]);
+    }
+
+    #[test]
+    fn aligned_ticks_land_on_the_next_boundary_after_a_slow_frame() {
+        let rate = time::Duration::from_millis(100);
+        let builder = Builder::with_context(Log::new()).screen(Talker).tick_rate(rate).align_ticks(true);
+        let mut app = builder.manual_clock().build(io::sink(), TestBackend::new(10, 2)).unwrap();
+
+        app.start_headless(TypeId::of::<Talker>()).unwrap();
+
+        for frame in [0, 30, 250, 1_001] {
+            app.clock.advance(time::Duration::from_millis(frame));
+
+            let until = app.until_next_tick();
+            let now = app.clock.system_now().duration_since(time::UNIX_EPOCH).unwrap();
+
+            assert!(!until.is_zero() && until <= rate, "{until:?} after {frame}ms");
+            assert_eq!((now + until).as_nanos() % rate.as_nanos(), 0, "after {frame}ms");
+        }
+    }
+
+    // Keeps what's recorded under each name: the increments of counters and the values of histograms.
+    #[cfg(feature = "metrics")]
+    #[derive(Clone, Default)]
+    struct Recorded(Arc<Mutex<HashMap<String, Vec<f64>>>>);
+
+    #[cfg(feature = "metrics")]
+    struct Metric(Recorded, String);
+
+    #[cfg(feature = "metrics")]
+    impl Recorded {
+        fn get(&self, name: &str) -> Vec<f64> {
+            self.0.lock().unwrap().get(name).cloned().unwrap_or_default()
+        }
+
+        fn metric(&self, key: &metrics::Key) -> Arc<Metric> {
+            Arc::new(Metric(self.clone(), key.name().to_string()))
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    impl Metric {
+        fn push(&self, value: f64) {
+            self.0.0.lock().unwrap().entry(self.1.clone()).or_default().push(value);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    impl metrics::CounterFn for Metric {
+        fn increment(&self, value: u64) {
+            self.push(value as f64);
+        }
+
+        fn absolute(&self, value: u64) {
+            self.push(value as f64);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    impl metrics::HistogramFn for Metric {
+        fn record(&self, value: f64) {
+            self.push(value);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    impl metrics::Recorder for Recorded {
+        fn describe_counter(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {}
+
+        fn describe_gauge(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {}
+
+        fn describe_histogram(&self, _: metrics::KeyName, _: Option<metrics::Unit>, _: metrics::SharedString) {}
+
+        fn register_counter(&self, key: &metrics::Key, _: &metrics::Metadata<'_>) -> metrics::Counter {
+            metrics::Counter::from_arc(self.metric(key))
+        }
+
+        fn register_gauge(&self, _: &metrics::Key, _: &metrics::Metadata<'_>) -> metrics::Gauge {
+            metrics::Gauge::noop()
+        }
+
+        fn register_histogram(&self, key: &metrics::Key, _: &metrics::Metadata<'_>) -> metrics::Histogram {
+            metrics::Histogram::from_arc(self.metric(key))
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn metrics_count_frames_and_time_them_by_the_clock() {
+        use crossterm::event::{Event, KeyEvent};
+
+        let recorded = Recorded::default();
+        let rate = time::Duration::from_millis(100);
+        let builder = Builder::with_context(Log::new()).screen(Page::<'a'>::default()).tick_rate(rate);
+        let mut app = builder.manual_clock().build(io::sink(), TestBackend::new(10, 2)).unwrap();
+
+        metrics::with_local_recorder(&recorded, || {
+            app.start_headless(TypeId::of::<Page<'a'>>()).unwrap();
+
+            // Read 5ms before it's handled, along with one that changes nothing.
+            let key = Event::Key(KeyEvent::from(KeyCode::Char('x')));
+            let events = vec![(app.clock.now(), key), (app.clock.now(), Event::FocusGained)];
+
+            app.clock.advance(time::Duration::from_millis(5));
+            app.cycle(events, None).unwrap();
+
+            app.clock.advance(rate);
+            app.cycle(Vec::new(), Some(rate)).unwrap();
+        });
+
+        let total = |name| recorded.get(name).iter().sum::<f64>();
+
+        assert_eq!(total("ratata_messages_processed"), 3.0);
+        assert_eq!(total("ratata_frames_rendered"), 2.0);
+        assert_eq!(total("ratata_frames_skipped"), 1.0);
+        assert_eq!(recorded.get("ratata_frame_time_seconds"), [0.0, 0.0]);
+        assert_eq!(recorded.get("ratata_update_latency_seconds"), [0.005, 0.005]);
+        assert_eq!(recorded.get("ratata_input_latency_seconds"), [0.005, 0.005]);
+        assert_eq!(app.terminal.backend().buffer().content[0].symbol(), "x");
+    }
+
+    // Returns the command it's given once it's started, and quits on the first tick in case that didn't. Fails on
+    // what it saved before being restored.
+    struct Starter(fn() -> Command);
+
+    impl Screen<Log> for Starter {
+        fn render(&self, _: &mut Frame<'_>, _: &Log) {}
+
+        fn update(&mut self, message: Message, _: &mut Log) -> Result<Option<Command>, ScreenError> {
+            match message {
+                | Message::AutosaveAvailable(_) => Err(ScreenError::from("broken")),
+                | Message::Tick(_) => Ok(Some(Command::Quit)),
+                | _ => Ok(None),
+            }
+        }
+
+        fn init(&mut self, _: &mut Log) -> Option<Command> {
+            Some((self.0)())
+        }
+    }
+
+    // Runs for real, what would go to the terminal is written to the returned writer. Raw mode can't be managed,
+    // there is no terminal to put into it.
+    fn run(builder: Builder<Log>, start: fn() -> Command) -> (Result<(), RuntimeError>, Shared) {
+        let _lock = panic_hook::lock();
+
+        let written = Shared::default();
+        let builder = builder.screen(Starter(start)).terminal_state(TerminalState::default());
+
+        let result = builder.build(written.clone(), TestBackend::new(10, 2))
+            .map_err(RuntimeError::Terminal)
+            .and_then(|app| app.run::<Starter>());
+
+        (result, written)
+    }
+
+    #[test]
+    fn custom_commands_go_to_the_handler_and_what_it_returns_is_handled() {
+        let handled = Rc::new(RefCell::new(Vec::new()));
+        let payloads = Rc::clone(&handled);
+
+        let builder = Builder::with_context(Log::new()).command_handler(move |payload, _: &mut Log| {
+            payloads.borrow_mut().push(*payload.downcast::<u8>().unwrap());
+            Some(Command::crossterm(crossterm::terminal::SetTitle("handled")).then(Command::Quit))
+        });
+
+        let (result, written) = run(builder, || Command::custom(7u8));
+
+        result.unwrap();
+
+        assert_eq!(*handled.borrow(), [7]);
+        assert!(written.take().contains(&ansi(crossterm::terminal::SetTitle("handled"))));
+    }
+
+    #[test]
+    fn a_failing_screen_stops_the_run_and_the_terminal_is_restored() {
+        let directory = env::temp_dir().join(format!("ratata-failing-screen-{}", process::id()));
+
+        Autosave::new(directory.clone(), time::Duration::MAX).save(any::type_name::<Starter>(), b"saved").unwrap();
+
+        let builder = Builder::with_context(Log::new())
+            .manage_alternate_screen(true)
+            .autosave(&directory, time::Duration::MAX);
+        let (result, written) = run(builder, || Command::Render);
+
+        fs::remove_dir_all(directory).unwrap();
+
+        let Err(RuntimeError::Screen(error)) = result else {
+            panic!("expected the screen's error, got {result:?}");
+        };
+
+        assert_eq!(error.to_string(), "broken");
+        assert_eq!(written.take(), [ansi(EnterAlternateScreen), ansi(LeaveAlternateScreen)].concat());
+    }
+
+    #[cfg(feature = "paste")]
+    #[test]
+    fn pasted_text_reaches_the_active_screen_at_once() {
+        let mut app = pages();
+
+        app.key(KeyCode::Char('B')).unwrap().event(crossterm::event::Event::Paste(String::from("x\ny"))).unwrap();
+
+        assert_eq!(app.context().last().unwrap(), "b pasted \"x\\ny\"");
+        assert!(app.context().iter().all(|entry| !entry.starts_with("a pasted")));
+    }
+
+    #[cfg(feature = "paste")]
+    #[test]
+    fn bracketed_paste_is_disabled_on_exit() {
+        let (result, written) = run(Builder::with_context(Log::new()).bracketed_paste(true), || Command::Quit);
+
+        result.unwrap();
+
+        assert_eq!(written.take(), [ansi(EnableBracketedPaste), ansi(DisableBracketedPaste)].concat());
+    }
+
+    // The lock is held across the run on purpose, the panic hook it installs mustn't interleave with other tests'.
+    #[cfg(feature = "tokio")]
+    #[allow(clippy::await_holding_lock)]
+    #[tokio::test]
+    async fn running_on_tokio_restores_the_terminal_after_quitting() {
+        let _lock = panic_hook::lock();
+
+        let written = Shared::default();
+        let builder = Builder::with_context(Log::new())
+            .screen(Starter(|| Command::Quit))
+            .terminal_state(TerminalState::default())
+            .manage_alternate_screen(true);
+
+        let app = builder.build(written.clone(), TestBackend::new(10, 2)).unwrap();
+
+        app.run_async::<Starter>().await.unwrap();
+
+        assert_eq!(written.take(), [ansi(EnterAlternateScreen), ansi(LeaveAlternateScreen)].concat());
+    }
 }
\ No newline at end of file
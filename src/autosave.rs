@@ -0,0 +1,101 @@
+use std::{fs, io, time};
+use std::io::Write;
+use std::path::PathBuf;
+
+pub(crate) struct Autosave {
+    directory: PathBuf,
+    interval: time::Duration,
+    last: time::Instant,
+}
+
+impl Autosave {
+    pub(crate) fn new(directory: PathBuf, interval: time::Duration) -> Self {
+        Self {
+            directory,
+            interval,
+            last: time::Instant::now(),
+        }
+    }
+
+    pub(crate) fn due(&mut self) -> bool {
+        if self.last.elapsed() < self.interval {
+            return false;
+        }
+
+        self.last = time::Instant::now();
+        true
+    }
+
+    // Screens are saved under their type name, with everything that isn't safe in a file name replaced.
+    fn path(&self, screen: &str) -> PathBuf {
+        let name = screen.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect::<String>();
+
+        self.directory.join(format!("{name}.autosave"))
+    }
+
+    // Written to a temporary file first, so a crash halfway through never leaves a torn save behind.
+    pub(crate) fn save(&self, screen: &str, state: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.directory)?;
+
+        let path = self.path(screen);
+        let temporary = path.with_extension("autosave.tmp");
+
+        let mut file = fs::File::create(&temporary)?;
+
+        file.write_all(state)?;
+        file.sync_all()?;
+
+        fs::rename(&temporary, &path)
+    }
+
+    #[inline(always)]
+    pub(crate) fn load(&self, screen: &str) -> Option<Vec<u8>> {
+        fs::read(self.path(screen)).ok()
+    }
+
+    pub(crate) fn discard(&self, screen: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(screen)) {
+            | Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            | _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    #[test]
+    fn saves_are_kept_per_screen_until_discarded() {
+        let directory = env::temp_dir().join(format!("ratata-autosave-{}", std::process::id()));
+        let autosave = Autosave::new(directory.join("nested"), time::Duration::ZERO);
+
+        autosave.save("app::Editor<'_>", b"draft").unwrap();
+        autosave.save("app::Editor<'_>", b"second draft").unwrap();
+
+        assert_eq!(autosave.load("app::Editor<'_>").as_deref(), Some(&b"second draft"[..]));
+        assert_eq!(autosave.load("app::Other"), None);
+        assert!(directory.join("nested").join("app__Editor____.autosave").exists());
+        assert!(!directory.join("nested").join("app__Editor____.autosave.tmp").exists());
+
+        autosave.discard("app::Editor<'_>").unwrap();
+        autosave.discard("app::Editor<'_>").unwrap();
+
+        assert_eq!(autosave.load("app::Editor<'_>"), None);
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn saving_is_due_once_per_interval() {
+        let mut autosave = Autosave::new(PathBuf::from("unused"), time::Duration::from_secs(3600));
+
+        assert!(!autosave.due());
+
+        autosave.interval = time::Duration::ZERO;
+
+        assert!(autosave.due());
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use ratatui::buffer::Buffer;
+use ratatui::style::Modifier;
+
+// How long the screen stays inverted.
+const FLASH: Duration = Duration::from_millis(120);
+
+// What `Command::Bell` does. Whether BEL beeps, flashes or does nothing at all is up to the terminal and how
+// it's configured, the visual bell looks the same everywhere. With reduced motion the screen doesn't flash, a
+// visual bell shows a toast instead.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Bell {
+    #[default]
+    Audible,
+    // Briefly inverts the whole screen instead.
+    Visual,
+    Both,
+    Off,
+}
+
+// What ringing the bell comes down to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Ring {
+    pub(crate) audible: bool,
+    pub(crate) flash: bool,
+    pub(crate) toast: bool,
+}
+
+impl Bell {
+    pub(crate) fn ring(self, reduced_motion: bool) -> Ring {
+        let (audible, visual) = (matches!(self, Self::Audible | Self::Both), matches!(self, Self::Visual | Self::Both));
+
+        Ring {
+            audible,
+            flash: visual && !reduced_motion,
+            // Unless it beeps as well, that's noticeable enough.
+            toast: visual && reduced_motion && !audible,
+        }
+    }
+}
+
+pub(crate) struct Bel;
+
+impl crossterm::Command for Bel {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x07")
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct VisualBell {
+    until: Option<Instant>,
+}
+
+impl VisualBell {
+    #[inline(always)]
+    pub(crate) fn flash(&mut self, now: Instant) {
+        self.until = Some(now + FLASH);
+    }
+
+    // Until the frame after the flash ended, which takes it off the screen again.
+    #[inline(always)]
+    pub(crate) fn is_pending(&self) -> bool {
+        self.until.is_some()
+    }
+
+    pub(crate) fn render(&mut self, buf: &mut Buffer, now: Instant) {
+        match self.until {
+            | Some(until) if until > now => {
+                buf.content.iter_mut().for_each(|cell| cell.modifier.toggle(Modifier::REVERSED));
+            },
+            | _ => self.until = None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring(audible: bool, flash: bool, toast: bool) -> Ring {
+        Ring { audible, flash, toast }
+    }
+
+    #[test]
+    fn rings_as_configured() {
+        assert_eq!(Bell::Audible.ring(false), ring(true, false, false));
+        assert_eq!(Bell::Visual.ring(false), ring(false, true, false));
+        assert_eq!(Bell::Both.ring(false), ring(true, true, false));
+        assert_eq!(Bell::Off.ring(false), Ring::default());
+    }
+
+    #[test]
+    fn reduced_motion_never_flashes() {
+        assert_eq!(Bell::Audible.ring(true), ring(true, false, false));
+        assert_eq!(Bell::Visual.ring(true), ring(false, false, true));
+        assert_eq!(Bell::Both.ring(true), ring(true, false, false));
+        assert_eq!(Bell::Off.ring(true), Ring::default());
+    }
+
+    #[test]
+    fn the_flash_inverts_the_screen_until_it_ends() {
+        let mut bell = VisualBell::default();
+        let mut buf = Buffer::empty(ratatui::layout::Rect::new(0, 0, 2, 1));
+
+        let now = Instant::now();
+
+        bell.flash(now);
+        bell.render(&mut buf, now);
+
+        assert!(buf.content.iter().all(|cell| cell.modifier == Modifier::REVERSED));
+
+        buf.reset();
+        bell.render(&mut buf, now + FLASH);
+
+        assert!(!bell.is_pending());
+        assert!(buf.content.iter().all(|cell| cell.modifier.is_empty()));
+    }
+}
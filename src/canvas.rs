@@ -0,0 +1,443 @@
+use std::mem;
+use std::cell::Cell;
+use std::time::Duration;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::command::Command;
+use crate::message::TickMsg;
+use crate::motion;
+
+// Braille cells have 2x4 dots, the bits of every dot by column and row.
+const BRAILLE_BITS: [[u8; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+const BRAILLE: u32 = 0x2800;
+
+// Half blocks split a cell into a top and a bottom dot.
+const BLOCKS: [&str; 4] = [" ", "▀", "▄", "█"];
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Dots {
+    #[default]
+    Braille,
+    // Square dots at half the resolution.
+    HalfBlocks,
+}
+
+impl Dots {
+    // Dots per cell, horizontally and vertically.
+    #[inline(always)]
+    fn per_cell(self) -> (i32, i32) {
+        match self {
+            | Self::Braille => (2, 4),
+            | Self::HalfBlocks => (1, 2),
+        }
+    }
+
+    #[inline(always)]
+    fn bit(self, column: i32, row: i32) -> u8 {
+        match self {
+            | Self::Braille => BRAILLE_BITS[column as usize][row as usize],
+            | Self::HalfBlocks => 1 << row,
+        }
+    }
+}
+
+// Cohen–Sutherland outcodes.
+const LEFT: u8 = 1;
+const RIGHT: u8 = 2;
+const TOP: u8 = 4;
+const BOTTOM: u8 = 8;
+
+#[inline(always)]
+fn outcode((x, y): (i64, i64), (width, height): (i64, i64)) -> u8 {
+    let mut code = 0;
+
+    if x < 0 {
+        code |= LEFT;
+    } else if x >= width {
+        code |= RIGHT;
+    }
+
+    if y < 0 {
+        code |= TOP;
+    } else if y >= height {
+        code |= BOTTOM;
+    }
+
+    code
+}
+
+// The part of the line from `a` to `b` inside of `size`, `None` if it misses it entirely.
+fn clip(mut a: (i64, i64), mut b: (i64, i64), size: (i64, i64)) -> Option<((i64, i64), (i64, i64))> {
+    let (width, height) = size;
+
+    if width <= 0 || height <= 0 {
+        return None;
+    }
+
+    let (mut code_a, mut code_b) = (outcode(a, size), outcode(b, size));
+
+    loop {
+        if code_a | code_b == 0 {
+            break Some((a, b));
+        }
+
+        if code_a & code_b != 0 {
+            break None;
+        }
+
+        let code = if code_a != 0 { code_a } else { code_b };
+
+        // Where the line crosses the edge, the products may not fit an i64.
+        let along = |from: i64, to: i64, at: i64, start: i64, end: i64| {
+            (from as i128 + (to - from) as i128 * (at - start) as i128 / (end - start) as i128) as i64
+        };
+
+        let point = match code {
+            | code if code & TOP != 0 => (along(a.0, b.0, 0, a.1, b.1), 0),
+            | code if code & BOTTOM != 0 => (along(a.0, b.0, height - 1, a.1, b.1), height - 1),
+            | code if code & RIGHT != 0 => (width - 1, along(a.1, b.1, width - 1, a.0, b.0)),
+            | _ => (0, along(a.1, b.1, 0, a.0, b.0)),
+        };
+
+        match code == code_a {
+            | true => (a, code_a) = (point, outcode(point, size)),
+            | false => (b, code_b) = (point, outcode(point, size)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Grid {
+    width: u16,
+    height: u16,
+    bits: Vec<u8>,
+    // Every cell takes the color of the last dot drawn into it.
+    colors: Vec<Option<Color>>,
+}
+
+impl Grid {
+    fn resize(&mut self, width: u16, height: u16) {
+        let cells = width as usize * height as usize;
+
+        (self.width, self.height) = (width, height);
+
+        self.bits.resize(cells, 0);
+        self.colors.resize(cells, None);
+    }
+
+    fn clear(&mut self) {
+        self.bits.fill(0);
+        self.colors.fill(None);
+    }
+}
+
+// Draws into the back buffer of a `Canvas`, in dots from the top left. Anything outside of it is clipped.
+#[derive(Debug)]
+pub struct Painter<'a> {
+    grid: &'a mut Grid,
+    dots: Dots,
+    color: Option<Color>,
+}
+
+impl Painter<'_> {
+    #[inline(always)]
+    pub fn width(&self) -> i32 {
+        self.grid.width as i32 * self.dots.per_cell().0
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> i32 {
+        self.grid.height as i32 * self.dots.per_cell().1
+    }
+
+    // For everything drawn after, `None` keeps the canvas style.
+    pub fn color(&mut self, color: Option<Color>) -> &mut Self {
+        self.color = color;
+        self
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.grid.clear();
+        self
+    }
+
+    pub fn point(&mut self, x: i32, y: i32) -> &mut Self {
+        if !(0..self.width()).contains(&x) || !(0..self.height()).contains(&y) {
+            return self;
+        }
+
+        let (columns, rows) = self.dots.per_cell();
+        let cell = (y / rows) as usize * self.grid.width as usize + (x / columns) as usize;
+
+        self.grid.bits[cell] |= self.dots.bit(x % columns, y % rows);
+
+        if self.color.is_some() {
+            self.grid.colors[cell] = self.color;
+        }
+
+        self
+    }
+
+    // Bresenham's, both ends included. Clipped to the canvas first, so only the dots that can be seen are
+    // walked however far off the line goes.
+    pub fn line(&mut self, (x0, y0): (i32, i32), (x1, y1): (i32, i32)) -> &mut Self {
+        let size = (self.width() as i64, self.height() as i64);
+
+        let Some(((x0, y0), (x1, y1))) = clip((x0 as i64, y0 as i64), (x1 as i64, y1 as i64), size) else {
+            return self;
+        };
+
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+
+        let (mut x, mut y, mut error) = (x0, y0, dx + dy);
+
+        loop {
+            // Inside of the canvas after clipping, which fits an i32.
+            self.point(x as i32, y as i32);
+
+            if (x, y) == (x1, y1) {
+                break self;
+            }
+
+            let doubled = 2 * error;
+
+            if doubled >= dy {
+                error += dy;
+                x += sx;
+            }
+
+            if doubled <= dx {
+                error += dx;
+                y += sy;
+            }
+        }
+    }
+
+    pub fn polyline(&mut self, points: &[(i32, i32)]) -> &mut Self {
+        for pair in points.windows(2) {
+            self.line(pair[0], pair[1]);
+        }
+
+        if let [point] = points {
+            self.point(point.0, point.1);
+        }
+
+        self
+    }
+
+    // The midpoint algorithm, just the outline.
+    pub fn circle(&mut self, (cx, cy): (i32, i32), radius: i32) -> &mut Self {
+        let (mut x, mut y, mut error) = (radius.abs(), 0, 1 - radius.abs());
+
+        while x >= y {
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.point(cx + px, cy + py);
+            }
+
+            y += 1;
+
+            match error < 0 {
+                | true => error += 2 * y + 1,
+                | false => {
+                    x -= 1;
+                    error += 2 * (y - x) + 1;
+                },
+            }
+        }
+
+        self
+    }
+}
+
+// Plots dots at a finer resolution than cells, for charts and simple games. Drawing goes into a back buffer
+// that's only shown once it's presented, so a frame drawn over several updates never shows up half done.
+// Both buffers take the size of the area the canvas was last rendered into, blank dots are left transparent.
+#[derive(Debug, Default)]
+pub struct Canvas {
+    dots: Dots,
+    style: Style,
+    front: Grid,
+    back: Grid,
+    area: Cell<Rect>,
+    // The tick the last frame was drawn for.
+    drawn: Option<u64>,
+}
+
+impl Canvas {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dots(mut self, dots: Dots) -> Self {
+        self.dots = dots;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // Keeps what was drawn so far, unless the size changed.
+    pub fn back(&mut self) -> Painter<'_> {
+        let area = self.area.get();
+
+        if (self.back.width, self.back.height) != (area.width, area.height) {
+            self.back.resize(area.width, area.height);
+            self.back.clear();
+        }
+
+        Painter { grid: &mut self.back, dots: self.dots, color: None }
+    }
+
+    // Shows the back buffer and starts the next one blank.
+    pub fn present(&mut self) {
+        mem::swap(&mut self.front, &mut self.back);
+        self.back.clear();
+    }
+
+    // Draws a new frame from scratch and presents it, at most once per tick. `f` gets the time since the
+    // previous tick, for moving things along at the same speed whatever the tick rate. Returns the command to
+    // get the frame on screen, as ticks don't cause redraws by themselves. With reduced motion only the first
+    // frame is drawn, changes can still be drawn with `back` and `present`.
+    pub fn frame<F>(&mut self, tick: &TickMsg, f: F) -> Option<Command>
+        where F: FnOnce(&mut Painter<'_>, Duration),
+    {
+        if self.drawn == Some(tick.frame) || (self.drawn.is_some() && motion::reduced()) {
+            return None;
+        }
+
+        self.drawn = Some(tick.frame);
+
+        let mut painter = self.back();
+
+        painter.clear();
+        f(&mut painter, tick.elapsed);
+
+        self.present();
+
+        Some(Command::Render)
+    }
+}
+
+impl Widget for &Canvas {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.area.set(area);
+
+        let front = &self.front;
+
+        for row in 0..front.height.min(area.height) {
+            for column in 0..front.width.min(area.width) {
+                let cell = row as usize * front.width as usize + column as usize;
+                let bits = front.bits[cell];
+
+                if bits == 0 {
+                    continue;
+                }
+
+                let style = match front.colors[cell] {
+                    | Some(color) => self.style.fg(color),
+                    | None => self.style,
+                };
+
+                let symbol = match self.dots {
+                    | Dots::Braille => char::from_u32(BRAILLE + bits as u32).unwrap_or(' ').to_string(),
+                    | Dots::HalfBlocks => BLOCKS[bits as usize & 3].to_string(),
+                };
+
+                buf.set_string(area.x + column, area.y + row, symbol, style);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn canvas(width: u16, height: u16) -> (Canvas, Buffer) {
+        let canvas = Canvas::new().dots(Dots::HalfBlocks);
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+
+        (&canvas).render(buf.area, &mut buf);
+
+        (canvas, buf)
+    }
+
+    fn symbols(canvas: &Canvas, buf: &mut Buffer) -> Vec<String> {
+        buf.reset();
+        canvas.render(buf.area, buf);
+
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    fn tick(frame: u64) -> TickMsg {
+        TickMsg { elapsed: Duration::from_millis(16), frame }
+    }
+
+    #[test]
+    fn clips_lines_to_the_canvas() {
+        assert_eq!(clip((-10, 1), (10, 1), (4, 4)), Some(((0, 1), (3, 1))));
+        assert_eq!(clip((-10, -1), (10, -1), (4, 4)), None);
+        assert_eq!(clip((-4, -4), (8, 8), (4, 4)), Some(((0, 0), (3, 3))));
+        assert_eq!(clip((0, 0), (1, 1), (0, 4)), None);
+    }
+
+    #[test]
+    fn lines_far_off_the_canvas_are_cheap() {
+        let (mut canvas, mut buf) = canvas(3, 2);
+
+        canvas.back().line((i32::MIN, 1), (i32::MAX, 1)).line((1, i32::MIN), (1, i32::MAX));
+        canvas.present();
+
+        assert_eq!(symbols(&canvas, &mut buf), ["▄█▄", " █ "]);
+    }
+
+    #[test]
+    fn draws_lines_and_points() {
+        let (mut canvas, mut buf) = canvas(2, 2);
+
+        canvas.back().line((0, 0), (1, 3)).point(1, 0);
+        canvas.present();
+
+        assert_eq!(symbols(&canvas, &mut buf), ["█▀", " █"]);
+    }
+
+    #[test]
+    fn draws_once_per_tick() {
+        let _lock = motion::lock();
+        let (mut canvas, _) = canvas(2, 2);
+
+        motion::set_reduced(false);
+
+        assert!(matches!(canvas.frame(&tick(0), |painter, _| { painter.point(0, 0); }), Some(Command::Render)));
+        assert!(canvas.frame(&tick(0), |_, _| unreachable!()).is_none());
+        assert!(canvas.frame(&tick(1), |_, _| ()).is_some());
+    }
+
+    #[test]
+    fn reduced_motion_keeps_the_first_frame() {
+        let _lock = motion::lock();
+        let (mut canvas, mut buf) = canvas(1, 1);
+
+        motion::set_reduced(true);
+
+        let first = canvas.frame(&tick(0), |painter, _| { painter.point(0, 0); });
+        let second = canvas.frame(&tick(1), |_, _| unreachable!());
+
+        motion::set_reduced(false);
+
+        assert!(first.is_some());
+        assert!(second.is_none());
+        assert_eq!(symbols(&canvas, &mut buf), ["▀"]);
+    }
+}
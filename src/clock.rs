@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time;
+
+// The time as the application and components that go by it see it. Reads the system clock, unless it's a
+// manual one, which only moves when it's advanced, e.g. by `TestApplication::tick`. Clones share the time.
+#[derive(Debug, Clone, Default)]
+pub struct Clock(Option<Arc<Manual>>);
+
+#[derive(Debug)]
+struct Manual {
+    instant: time::Instant,
+    system: time::SystemTime,
+    // In nanoseconds.
+    advanced: AtomicU64,
+}
+
+impl Clock {
+    #[inline(always)]
+    pub fn system() -> Self {
+        Self(None)
+    }
+
+    // Starts at the current time.
+    pub fn manual() -> Self {
+        Self(Some(Arc::new(Manual {
+            instant: time::Instant::now(),
+            system: time::SystemTime::now(),
+            advanced: AtomicU64::new(0),
+        })))
+    }
+
+    #[inline(always)]
+    pub fn is_manual(&self) -> bool {
+        self.0.is_some()
+    }
+
+    // Does nothing to the system clock.
+    pub fn advance(&self, by: time::Duration) {
+        if let Some(manual) = &self.0 {
+            manual.advanced.fetch_add(u64::try_from(by.as_nanos()).unwrap_or(u64::MAX), Ordering::Relaxed);
+        }
+    }
+
+    #[inline(always)]
+    fn advanced(manual: &Manual) -> time::Duration {
+        time::Duration::from_nanos(manual.advanced.load(Ordering::Relaxed))
+    }
+
+    pub fn now(&self) -> time::Instant {
+        match &self.0 {
+            | Some(manual) => manual.instant + Self::advanced(manual),
+            | None => time::Instant::now(),
+        }
+    }
+
+    // The wall clock, for what's scheduled at a time of day.
+    pub fn system_now(&self) -> time::SystemTime {
+        match &self.0 {
+            | Some(manual) => manual.system + Self::advanced(manual),
+            | None => time::SystemTime::now(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn elapsed(&self, since: time::Instant) -> time::Duration {
+        self.now().saturating_duration_since(since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_manual_clock_only_moves_when_advanced() {
+        let clock = Clock::manual();
+        let (start, system) = (clock.now(), clock.system_now());
+
+        assert_eq!(clock.now(), start);
+
+        clock.clone().advance(time::Duration::from_secs(3));
+
+        assert_eq!(clock.elapsed(start), time::Duration::from_secs(3));
+        assert_eq!(clock.system_now(), system + time::Duration::from_secs(3));
+    }
+}
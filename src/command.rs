@@ -1,13 +1,25 @@
 use std::fmt;
-use std::any::TypeId;
+use std::any::{Any, TypeId};
+use std::sync::Arc;
+use std::path::PathBuf;
+use std::process;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use std::{future::Future, pin::Pin};
+
+use ratatui::buffer::Buffer;
 
 use crate::screen::Screen;
+use crate::message::Payload;
+use crate::message::Message;
+use crate::tooltip::Tooltip;
 
 mod macros {
     #[macro_export]
     macro_rules! __batch {
-        ($($command:expr),* $(,)?) => ($crate::command::Command::Batch(vec![$($command),*]));
-        () => (Vec::new());
+        () => ($crate::command::Command::Batch(Vec::new()));
+        // A single command doesn't need to be wrapped (and allocated) at all.
+        ($command:expr $(,)?) => ($command);
+        ($($command:expr),+ $(,)?) => ($crate::command::Command::Batch(vec![$($command),+]));
     }
 
     pub use __batch as batch;
@@ -15,43 +27,285 @@ mod macros {
 
 pub use macros::batch;
 
+#[derive(Debug)]
 pub enum Command {
     Batch(Vec<Self>),
     Screen(TypeId),
+    PushScreen(TypeId),
+    PopScreen,
+    PreviousScreen,
+    // Only needed to redraw after a tick, anything else causes a redraw anyway.
+    Render,
+    OpenOverlay(TypeId),
+    CloseOverlay,
     EnableRawMode,
     DisableRawMode,
+    EnterAlternateScreen,
+    LeaveAlternateScreen,
+    #[cfg(feature = "paste")]
+    EnableBracketedPaste,
+    #[cfg(feature = "paste")]
+    DisableBracketedPaste,
     Crossterm(#[allow(private_interfaces)] ObjectSafeCrosstermCommand),
+    SuppressRecording(bool),
+    StartMacro(String),
+    StopMacro,
+    PlayMacro(String),
+    LogAction(String),
+    ExportActionLog(PathBuf),
+    // Writes the contents to the file, e.g. from `Table::export_to_file`.
+    Export(PathBuf, String),
+    PushFilter(fn(&mut Buffer)),
+    PopFilter,
+    Publish(String, Payload),
+    SendTo(TypeId, Box<Message>),
+    Detached(Box<Self>),
+    // The results of the work the command starts arrive as `Message::Routed`.
+    Correlated(u64, Box<Self>),
+    RouteTo(TypeId, Box<Self>),
+    // Both deliver their message to the screen that returned the command, whether it's still active or not. Once
+    // it's popped or replaced, what's still running is cancelled unless it's detached (see `Command::detached`).
+    Spawn(Work),
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    Perform(Task),
+    #[cfg(feature = "update-check")]
+    CheckForUpdates { url: String, current: String },
+    ShowTooltip(Tooltip),
+    HideTooltip,
+    // Shown in the bottom right corner for a few seconds.
+    Toast(String),
+    ContinueOnboarding,
+    ResetOnboarding,
+    // Handed to `Builder::command_handler`, for effects of the application's own.
+    Custom(Box<dyn Any + Send>),
+    // See `exec`.
+    Exec(process::Command),
+    // Restores the terminal and stops the process like ctrl+z would outside of raw mode. Once it's continued
+    // the terminal modes are turned back on and `Message::Resumed` is sent. Does nothing outside of unix.
+    Suspend,
+    // Rings the terminal's bell, or flashes the screen, see `Builder::bell`.
+    Bell,
     Quit,
 }
 
+// Constructors naming a screen take the context it's a screen for as well, which is inferred when the screen
+// only implements `Screen` for one, e.g. `Command::screen::<Menu, _>()`.
 impl Command {
     #[inline(always)]
-    pub fn screen<S: Screen + 'static>() -> Command {
+    pub fn screen<S, Ctx>() -> Command
+        where S: Screen<Ctx> + 'static,
+    {
         Self::Screen(TypeId::of::<S>())
     }
 
+    #[inline(always)]
+    pub fn push_screen<S, Ctx>() -> Command
+        where S: Screen<Ctx> + 'static,
+    {
+        Self::PushScreen(TypeId::of::<S>())
+    }
+
+    // Rendered on top of the active screen, which stays active but no longer receives input.
+    #[inline(always)]
+    pub fn open_overlay<S, Ctx>() -> Command
+        where S: Screen<Ctx> + 'static,
+    {
+        Self::OpenOverlay(TypeId::of::<S>())
+    }
+
     #[inline(always)]
     pub fn crossterm<C>(command: C) -> Command
         where C: crossterm::Command + 'static,
     {
         Self::Crossterm(ObjectSafeCrosstermCommand(Box::new(command)))
     }
+
+    pub fn publish<T, P>(topic: T, payload: P) -> Command
+        where T: Into<String>, P: Any + Send + Sync,
+    {
+        Self::Publish(topic.into(), Arc::new(payload))
+    }
+
+    #[inline(always)]
+    pub fn custom<T: Any + Send>(value: T) -> Command {
+        Self::Custom(Box::new(value))
+    }
+
+    // Delivered to the screen whether it's active or not.
+    #[inline(always)]
+    pub fn send_to<S, Ctx>(message: Message) -> Command
+        where S: Screen<Ctx> + 'static,
+    {
+        Self::SendTo(TypeId::of::<S>(), Box::new(message))
+    }
+
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    pub fn perform<F>(future: F) -> Command
+        where F: Future<Output = Message> + Send + 'static,
+    {
+        Self::Perform(Task(Box::pin(future)))
+    }
+
+    // Usually called with the application's own `env!("CARGO_PKG_VERSION")`.
+    #[cfg(feature = "update-check")]
+    pub fn check_for_updates<U, V>(url: U, current: V) -> Command
+        where U: Into<String>, V: Into<String>,
+    {
+        Self::CheckForUpdates { url: url.into(), current: current.into() }
+    }
+
+    // The work the command starts outlives the screen that returned it, e.g. saving on the way out.
+    #[inline(always)]
+    pub fn detached(self) -> Command {
+        Self::Detached(Box::new(self))
+    }
+
+    // Tags what the work the command starts produces with the id, e.g. to tell requests apart.
+    #[inline(always)]
+    pub fn correlated(self, correlation: u64) -> Command {
+        Self::Correlated(correlation, Box::new(self))
+    }
+
+    // What the work the command starts produces goes to the screen instead of the one that returned it, and is
+    // only cancelled along with that screen.
+    #[inline(always)]
+    pub fn route_to<S, Ctx>(self) -> Command
+        where S: Screen<Ctx> + 'static,
+    {
+        Self::RouteTo(TypeId::of::<S>(), Box::new(self))
+    }
+
+    // Chaining onto an existing batch reuses its allocation instead of nesting batches.
+    pub fn then(self, next: Command) -> Command {
+        match self {
+            | Self::Batch(commands) if commands.is_empty() => next,
+            | Self::Batch(mut commands) => {
+                commands.push(next);
+                Self::Batch(commands)
+            },
+            | command => Self::Batch(vec![command, next]),
+        }
+    }
+}
+
+#[inline(always)]
+pub fn back() -> Command {
+    Command::PreviousScreen
+}
+
+pub fn spawn<F>(work: F) -> Command
+    where F: FnOnce() -> Message + Send + 'static,
+{
+    Command::Spawn(Work(Box::new(work)))
+}
+
+// Runs the process with the terminal as it was before the application started, waiting for it to exit. The
+// application is drawn again after and `Message::ExecFinished` is sent to the screen that returned the command,
+// e.g. for opening `$EDITOR`. Ignored in dry runs.
+#[inline(always)]
+pub fn exec(process: process::Command) -> Command {
+    Command::Exec(process)
+}
+
+// Runs on a thread of its own, the message it returns is delivered to the screen that returned it, see
+// `Command::Spawn`.
+pub struct Work(pub(crate) Box<dyn FnOnce() -> Message + Send>);
+
+impl fmt::Debug for Work {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Work").finish_non_exhaustive()
+    }
+}
+
+// Resolved on a background executor, the message it produces is delivered to the screen that returned it, see
+// `Command::Perform`.
+#[cfg(any(feature = "futures", feature = "tokio"))]
+pub struct Task(pub(crate) Pin<Box<dyn Future<Output = Message> + Send>>);
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+impl fmt::Debug for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Task").finish_non_exhaustive()
+    }
 }
 
 pub(crate) trait ObjectSafeCommand {
     fn object_safe_write_ansi(&self, f: &mut dyn fmt::Write) -> fmt::Result;
+
+    fn as_any(&self) -> &dyn Any;
 }
 
-impl<T: crossterm::Command> ObjectSafeCommand for T {
+impl<T: crossterm::Command + 'static> ObjectSafeCommand for T {
     fn object_safe_write_ansi(&self, mut f: &mut dyn fmt::Write) -> fmt::Result {
         self.write_ansi(&mut f)
     }
+
+    #[inline(always)]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
 }
 
 pub(crate) struct ObjectSafeCrosstermCommand(Box<dyn ObjectSafeCommand>);
 
+impl ObjectSafeCrosstermCommand {
+    // The command itself, for telling what it does.
+    #[inline(always)]
+    pub(crate) fn as_any(&self) -> &dyn Any {
+        self.0.as_any()
+    }
+}
+
 impl crossterm::Command for ObjectSafeCrosstermCommand {
     fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
         self.0.object_safe_write_ansi(f)
     }
+}
+
+impl fmt::Debug for ObjectSafeCrosstermCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ansi = String::new();
+
+        self.0.object_safe_write_ansi(&mut ansi)?;
+
+        f.debug_tuple("ObjectSafeCrosstermCommand").field(&ansi).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actions(command: Command) -> Vec<String> {
+        match command {
+            | Command::Batch(commands) => commands.into_iter().flat_map(actions).collect(),
+            | Command::LogAction(action) => vec![action],
+            | command => panic!("unexpected command: {command:?}"),
+        }
+    }
+
+    #[test]
+    fn chained_commands_stay_in_order() {
+        let command = Command::LogAction(String::from("a"))
+            .then(Command::LogAction(String::from("b")))
+            .then(Command::LogAction(String::from("c")));
+
+        assert!(matches!(&command, Command::Batch(commands) if commands.len() == 3));
+        assert_eq!(actions(command), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn chaining_onto_an_empty_batch_is_just_the_next_command() {
+        let command = batch!().then(Command::LogAction(String::from("a")));
+
+        assert!(matches!(command, Command::LogAction(action) if action == "a"));
+    }
+
+    #[test]
+    fn a_batch_of_one_is_the_command_itself() {
+        assert!(matches!(batch!(Command::Render), Command::Render));
+        assert!(matches!(batch!(Command::Render,), Command::Render));
+        assert!(matches!(batch!(Command::Render, Command::PopScreen), Command::Batch(commands) if commands.len() == 2));
+        assert!(matches!(batch!(), Command::Batch(commands) if commands.is_empty()));
+    }
 }
\ No newline at end of file
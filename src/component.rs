@@ -0,0 +1,490 @@
+use std::any::Any;
+use std::fmt;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Rect, Size};
+use ratatui::style::Style;
+use ratatui::text::Text;
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use crate::command::Command;
+use crate::message::Message;
+
+// What a component did with a message. Ignored messages are handed back, to be offered to its parent.
+#[derive(Debug)]
+pub enum Outcome {
+    Handled(Option<Command>),
+    Ignored(Message),
+}
+
+impl Outcome {
+    #[inline(always)]
+    pub fn is_handled(&self) -> bool {
+        matches!(self, Self::Handled(_))
+    }
+}
+
+// For finding components by their type, implemented for everything that can be.
+pub trait AsAny: Any {
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<T: Any> AsAny for T {
+    #[inline(always)]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline(always)]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+// A part of a screen that handles messages of its own, made of other components. Parents own their children and
+// say which of them has the focus, messages go to the innermost focused component first and bubble up through its
+// ancestors until one of them handles it, see `dispatch`. E.g. a panel closing on esc when the input in it had no
+// use for it.
+//
+// Components with an id (see `with_id`) can be looked up from the tree by it, as long as their ancestors list
+// their children.
+pub trait Component: AsAny {
+    fn render(&self, area: Rect, buf: &mut Buffer);
+
+    // How much room the component would like out of what's available, before it's rendered. Containers sizing
+    // themselves to their contents ask their children, e.g. `Popup`. All of it by default.
+    fn measure(&self, available: Size) -> Size {
+        available
+    }
+
+    // Only sees the messages its focused child ignored, if it has one.
+    fn handle(&mut self, message: Message) -> Outcome {
+        Outcome::Ignored(message)
+    }
+
+    fn focused_child(&mut self) -> Option<&mut dyn Component> {
+        None
+    }
+
+    fn id(&self) -> Option<&str> {
+        None
+    }
+
+    // Every child, the focused one included.
+    fn children(&self) -> Vec<&dyn Component> {
+        Vec::new()
+    }
+
+    fn children_mut(&mut self) -> Vec<&mut dyn Component> {
+        Vec::new()
+    }
+
+    // What the component is found as by its type, the component it wraps for wrappers like `Identified`.
+    fn inner(&self) -> &dyn Any {
+        self.as_any()
+    }
+
+    fn inner_mut(&mut self) -> &mut dyn Any {
+        self.as_any_mut()
+    }
+
+    fn with_id<I: Into<String>>(self, id: I) -> Identified<Self>
+        where Self: Sized,
+    {
+        Identified { id: id.into(), component: self }
+    }
+}
+
+// A component along with the id it's found by, see `Component::with_id`. Ids are expected to be unique within a
+// tree, of components with the same id the first one in depth first order is found.
+#[derive(Debug)]
+pub struct Identified<C> {
+    id: String,
+    component: C,
+}
+
+impl<C> Identified<C> {
+    #[inline(always)]
+    pub fn get(&self) -> &C {
+        &self.component
+    }
+
+    #[inline(always)]
+    pub fn get_mut(&mut self) -> &mut C {
+        &mut self.component
+    }
+}
+
+impl<C: Component> Component for Identified<C> {
+    #[inline(always)]
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.component.render(area, buf)
+    }
+
+    #[inline(always)]
+    fn measure(&self, available: Size) -> Size {
+        self.component.measure(available)
+    }
+
+    #[inline(always)]
+    fn handle(&mut self, message: Message) -> Outcome {
+        self.component.handle(message)
+    }
+
+    #[inline(always)]
+    fn focused_child(&mut self) -> Option<&mut dyn Component> {
+        self.component.focused_child()
+    }
+
+    #[inline(always)]
+    fn id(&self) -> Option<&str> {
+        Some(&self.id)
+    }
+
+    #[inline(always)]
+    fn children(&self) -> Vec<&dyn Component> {
+        self.component.children()
+    }
+
+    #[inline(always)]
+    fn children_mut(&mut self) -> Vec<&mut dyn Component> {
+        self.component.children_mut()
+    }
+
+    #[inline(always)]
+    fn inner(&self) -> &dyn Any {
+        self.component.inner()
+    }
+
+    #[inline(always)]
+    fn inner_mut(&mut self) -> &mut dyn Any {
+        self.component.inner_mut()
+    }
+}
+
+// As wide as its widest line and as tall as its lines, cut off to what's available.
+impl Component for Text<'static> {
+    #[inline(always)]
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Widget::render(self, area, buf)
+    }
+
+    fn measure(&self, available: Size) -> Size {
+        let (width, height) = (u16::try_from(self.width()), u16::try_from(self.height()));
+
+        Size::new(
+            width.unwrap_or(u16::MAX).min(available.width),
+            height.unwrap_or(u16::MAX).min(available.height),
+        )
+    }
+}
+
+// A bordered box around a component, shrink-wrapped to the size the component measures and centered in the area
+// it's rendered to. What's below it is cleared.
+#[derive(Debug)]
+pub struct Popup<C> {
+    content: C,
+    title: String,
+    style: Style,
+}
+
+impl<C: Component> Popup<C> {
+    pub fn new(content: C) -> Self {
+        Self { content, title: String::new(), style: Style::default() }
+    }
+
+    pub fn title<T: Into<String>>(mut self, title: T) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn content(&self) -> &C {
+        &self.content
+    }
+
+    #[inline(always)]
+    pub fn content_mut(&mut self) -> &mut C {
+        &mut self.content
+    }
+
+    // Where the popup goes in the area.
+    pub fn placement(&self, area: Rect) -> Rect {
+        let size = self.measure(area.as_size());
+
+        Rect::new(
+            area.x + (area.width - size.width) / 2,
+            area.y + (area.height - size.height) / 2,
+            size.width,
+            size.height,
+        )
+    }
+}
+
+impl<C: Component> Component for Popup<C> {
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        let area = self.placement(area);
+        let block = Block::default().borders(Borders::ALL).title(self.title.as_str()).style(self.style);
+
+        Clear.render(area, buf);
+
+        self.content.render(block.inner(area), buf);
+
+        block.render(area, buf);
+    }
+
+    // The content's size along with the border, wide enough for the title.
+    fn measure(&self, available: Size) -> Size {
+        let inner = Size::new(available.width.saturating_sub(2), available.height.saturating_sub(2));
+        let content = self.content.measure(inner);
+        let title = u16::try_from(self.title.chars().count()).unwrap_or(u16::MAX);
+
+        Size::new(
+            content.width.max(title).saturating_add(2).min(available.width),
+            content.height.saturating_add(2).min(available.height),
+        )
+    }
+
+    #[inline(always)]
+    fn focused_child(&mut self) -> Option<&mut dyn Component> {
+        Some(&mut self.content)
+    }
+
+    #[inline(always)]
+    fn children(&self) -> Vec<&dyn Component> {
+        vec![&self.content]
+    }
+
+    #[inline(always)]
+    fn children_mut(&mut self) -> Vec<&mut dyn Component> {
+        vec![&mut self.content]
+    }
+}
+
+// Offers the message to the focused component, then to its ancestors up to and including the one it's given.
+pub fn dispatch(component: &mut dyn Component, message: Message) -> Outcome {
+    let message = match component.focused_child() {
+        | Some(child) => match dispatch(child, message) {
+            | Outcome::Ignored(message) => message,
+            | handled => return handled,
+        },
+        | None => message,
+    };
+
+    component.handle(message)
+}
+
+// The component with the id, itself or any of its descendants.
+pub fn find<'a>(component: &'a dyn Component, id: &str) -> Option<&'a dyn Component> {
+    if component.id() == Some(id) {
+        return Some(component);
+    }
+
+    component.children().into_iter().find_map(|child| find(child, id))
+}
+
+pub fn find_mut<'a>(component: &'a mut dyn Component, id: &str) -> Option<&'a mut dyn Component> {
+    if component.id() == Some(id) {
+        return Some(component);
+    }
+
+    component.children_mut().into_iter().find_map(|child| find_mut(child, id))
+}
+
+// The components making up a screen, from its root. Messages the whole tree ignores are the screen's to handle.
+pub struct Tree {
+    root: Box<dyn Component>,
+}
+
+impl fmt::Debug for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tree").finish_non_exhaustive()
+    }
+}
+
+impl Tree {
+    pub fn new<C: Component + 'static>(root: C) -> Self {
+        Self { root: Box::new(root) }
+    }
+
+    #[inline(always)]
+    pub fn dispatch(&mut self, message: Message) -> Outcome {
+        dispatch(self.root.as_mut(), message)
+    }
+
+    #[inline(always)]
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        self.root.render(area, buf)
+    }
+
+    #[inline(always)]
+    pub fn measure(&self, available: Size) -> Size {
+        self.root.measure(available)
+    }
+
+    // The component with the id, `None` if there isn't one or it's not a `C`. E.g. to set its value, or focus it.
+    pub fn get<C: Component>(&self, id: &str) -> Option<&C> {
+        find(self.root.as_ref(), id)?.inner().downcast_ref()
+    }
+
+    pub fn get_mut<C: Component>(&mut self, id: &str) -> Option<&mut C> {
+        find_mut(self.root.as_mut(), id)?.inner_mut().downcast_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::KeyCode;
+
+    use ratatui::style::Style;
+
+    use crate::message::{key, KeyMsg};
+
+    #[derive(Default)]
+    struct Input {
+        text: String,
+    }
+
+    impl Component for Input {
+        fn render(&self, area: Rect, buf: &mut Buffer) {
+            buf.set_stringn(area.x, area.y, &self.text, area.width as usize, Style::default());
+        }
+
+        fn handle(&mut self, message: Message) -> Outcome {
+            match message {
+                | Message::Key(KeyMsg { code: KeyCode::Char(c), .. }) => {
+                    self.text.push(c);
+                    Outcome::Handled(None)
+                },
+                | message => Outcome::Ignored(message),
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Panel {
+        input: Input,
+        open: bool,
+    }
+
+    impl Component for Panel {
+        fn render(&self, area: Rect, buf: &mut Buffer) {
+            if self.open {
+                self.input.render(area, buf);
+            }
+        }
+
+        fn handle(&mut self, message: Message) -> Outcome {
+            match message {
+                | Message::Key(key) if key.code == KeyCode::Esc && self.open => {
+                    self.open = false;
+                    Outcome::Handled(Some(Command::Render))
+                },
+                | message => Outcome::Ignored(message),
+            }
+        }
+
+        fn focused_child(&mut self) -> Option<&mut dyn Component> {
+            self.open.then_some(&mut self.input as &mut dyn Component)
+        }
+    }
+
+    #[test]
+    fn messages_the_focused_child_ignores_bubble_up() {
+        let mut panel = Panel { open: true, ..Panel::default() };
+
+        assert!(matches!(dispatch(&mut panel, Message::Key(key(KeyCode::Char('a')))), Outcome::Handled(None)));
+        assert_eq!(panel.input.text, "a");
+        assert!(panel.open);
+
+        assert!(matches!(dispatch(&mut panel, Message::Key(key(KeyCode::Esc))), Outcome::Handled(Some(_))));
+        assert!(!panel.open);
+    }
+
+    #[test]
+    fn what_the_whole_tree_ignores_is_handed_back() {
+        let mut tree = Tree::new(Panel::default());
+
+        let Outcome::Ignored(Message::Key(ignored)) = tree.dispatch(Message::Key(key(KeyCode::Char('a')))) else {
+            panic!("the closed panel's input handled the key");
+        };
+
+        assert_eq!(ignored.code, KeyCode::Char('a'));
+        assert!(!tree.dispatch(Message::Resize(10, 10)).is_handled());
+    }
+
+    struct Form {
+        inputs: Vec<Identified<Input>>,
+        focused: usize,
+    }
+
+    impl Component for Form {
+        fn render(&self, area: Rect, buf: &mut Buffer) {
+            for (y, input) in (area.top()..area.bottom()).zip(&self.inputs) {
+                input.render(Rect { y, height: 1, ..area }, buf);
+            }
+        }
+
+        fn focused_child(&mut self) -> Option<&mut dyn Component> {
+            self.inputs.get_mut(self.focused).map(|input| input as &mut dyn Component)
+        }
+
+        fn children(&self) -> Vec<&dyn Component> {
+            self.inputs.iter().map(|input| input as &dyn Component).collect()
+        }
+
+        fn children_mut(&mut self) -> Vec<&mut dyn Component> {
+            self.inputs.iter_mut().map(|input| input as &mut dyn Component).collect()
+        }
+    }
+
+    #[test]
+    fn descendants_are_found_by_their_id() {
+        let panel = Tree::new(Panel { input: Input::default(), open: true }.with_id("panel"));
+
+        assert!(panel.get::<Panel>("panel").is_some_and(|panel| panel.open));
+        assert!(panel.get::<Input>("panel").is_none());
+
+        let inputs = ["name", "search"].map(|id| Input::default().with_id(id)).into();
+        let mut tree = Tree::new(Form { inputs, focused: 0 });
+
+        tree.get_mut::<Input>("search").unwrap().text.push_str("ratatui");
+        tree.dispatch(Message::Key(key(KeyCode::Char('x'))));
+
+        assert_eq!(tree.get::<Input>("search").map(|input| input.text.as_str()), Some("ratatui"));
+        assert_eq!(tree.get::<Input>("name").map(|input| input.text.as_str()), Some("x"));
+        assert!(tree.get::<Input>("missing").is_none());
+        assert!(tree.get::<Form>("search").is_none());
+    }
+
+    #[test]
+    fn popups_are_shrink_wrapped_to_their_content() {
+        let popup = Popup::new(Text::raw("Save changes?\n[y/n]")).title("Quit");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 21, 6));
+
+        assert_eq!(popup.measure(Size::new(80, 24)), Size::new(15, 4));
+        assert_eq!(popup.measure(Size::new(10, 3)), Size::new(10, 3));
+
+        popup.render(buf.area, &mut buf);
+
+        let lines = (0..6).map(|y| (0..21).map(|x| buf.get(x, y).symbol()).collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(lines, [
+            "                     ",
+            "   ┌Quit─────────┐   ",
+            "   │Save changes?│   ",
+            "   │[y/n]        │   ",
+            "   └─────────────┘   ",
+            "                     ",
+        ]);
+    }
+}
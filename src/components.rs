@@ -0,0 +1,65 @@
+mod ansi_text;
+mod scrollbar;
+mod progress_tree;
+mod live_text;
+mod flex;
+mod grid;
+mod toolbar;
+mod secret_prompt;
+mod numeric;
+mod choice;
+mod select;
+mod button;
+mod chips;
+mod heatmap;
+mod timeline;
+mod graph;
+mod minimap;
+mod selection;
+mod text_area;
+mod undo;
+mod table;
+mod which_key;
+mod export;
+mod form_flow;
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "spellcheck")]
+mod spelling;
+
+pub use ansi_text::AnsiText;
+pub use scrollbar::{Scrollable, ScrollbarAdapter};
+pub use progress_tree::ProgressTree;
+pub use live_text::LiveText;
+pub use flex::{Flex, FlexItem, Justify, Align};
+pub use grid::{Grid, GridItem};
+pub use toolbar::Toolbar;
+pub use secret_prompt::{SecretPrompt, Secret, Prompt};
+pub use numeric::{NumberInput, Slider, ValueChanged};
+pub use choice::{RadioGroup, CheckboxGroup};
+pub use select::{Select, SelectionChanged};
+pub use button::{Button, ButtonRow, ButtonPressed};
+pub use chips::{Badge, Chips, ChipsInput, TagsChanged};
+pub use heatmap::Heatmap;
+pub use timeline::{Timeline, Interval};
+pub use graph::{Graph, GraphView, NodeSelected, LayoutReady};
+pub use minimap::MinimapAdapter;
+pub use selection::{Selection, SelectionMode, Clipboard};
+pub use text_area::{TextArea, TextInput, TextEvent};
+pub use undo::{History, Snapshot};
+pub use table::{Table, Column, Truncation, Aggregate, RowSelected, CellEdited, TableEvent};
+pub use which_key::WhichKey;
+pub use export::ExportFormat;
+pub use form_flow::{FormFlow, FormStep, FormValues, FormEvent, FieldError};
+#[cfg(feature = "csv")]
+pub use csv::{CsvSource, CsvChunk};
+#[cfg(feature = "spellcheck")]
+pub use spelling::{Dictionary, SpellChecked, Suggestions};
+
+use ratatui::layout::Rect;
+
+// For hit testing the mouse against where something was last rendered.
+#[inline(always)]
+pub(crate) fn contains(area: Rect, column: u16, row: u16) -> bool {
+    (area.left()..area.right()).contains(&column) && (area.top()..area.bottom()).contains(&row)
+}
@@ -0,0 +1,144 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Paragraph, Widget, Wrap};
+
+use crate::vt::Parser;
+
+use super::Scrollable;
+
+#[derive(Debug, Clone)]
+pub struct AnsiText {
+    parser: Parser,
+    style: Style,
+    wrap: bool,
+    scroll: u16,
+}
+
+impl Default for AnsiText {
+    fn default() -> Self {
+        Self {
+            parser: Parser::default(),
+            style: Style::default(),
+            wrap: true,
+            scroll: 0,
+        }
+    }
+}
+
+impl AnsiText {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse<T: AsRef<[u8]>>(input: T) -> Self {
+        let mut text = Self::new();
+        text.push(input);
+        text
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn push<T: AsRef<[u8]>>(&mut self, input: T) {
+        self.parser.feed(input.as_ref());
+    }
+
+    pub fn clear(&mut self) {
+        self.parser = Parser::default();
+    }
+
+    #[inline(always)]
+    pub fn lines(&self) -> &[Line<'static>] {
+        self.parser.lines()
+    }
+
+    #[inline(always)]
+    pub fn set_scroll(&mut self, offset: u16) {
+        self.scroll = offset;
+    }
+}
+
+impl Widget for &AnsiText {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let paragraph = Paragraph::new(Text::from(self.lines().to_vec()))
+            .style(self.style)
+            .scroll((self.scroll, 0));
+
+        match self.wrap {
+            | true => paragraph.wrap(Wrap { trim: false }).render(area, buf),
+            | false => paragraph.render(area, buf),
+        }
+    }
+}
+
+impl Scrollable for AnsiText {
+    fn content_length(&self, viewport: Rect) -> usize {
+        if !self.wrap || viewport.width == 0 {
+            return self.lines().len();
+        }
+
+        // Approximates word wrapping by assuming lines are broken at exactly the viewport width.
+        self.lines().iter().map(|line| line.width().div_ceil(viewport.width as usize).max(1)).sum()
+    }
+
+    #[inline(always)]
+    fn offset(&self) -> usize {
+        self.scroll as usize
+    }
+
+    #[inline(always)]
+    fn set_offset(&mut self, offset: usize) {
+        self.scroll = offset.min(u16::MAX as usize) as u16;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::style::Color;
+
+    #[test]
+    fn output_is_rendered_in_its_colors() {
+        let mut text = AnsiText::new();
+
+        text.push("\x1B[31mer");
+        text.push("ror\x1B[0m: done\n");
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 1));
+
+        (&text).render(buf.area, &mut buf);
+
+        assert_eq!((0..12).map(|x| buf.get(x, 0).symbol()).collect::<String>(), "error: done ");
+        assert_eq!(buf.get(4, 0).fg, Color::Red);
+        assert_eq!(buf.get(5, 0).fg, Color::Reset);
+    }
+
+    #[test]
+    fn wrapped_lines_count_towards_the_scrollable_length() {
+        let mut text = AnsiText::parse("\x1B[1m0123456789\x1B[0m\n\nshort");
+        let viewport = Rect::new(0, 0, 4, 2);
+
+        assert_eq!(text.content_length(viewport), 6);
+
+        text = text.wrap(false);
+        text.set_offset(2);
+
+        assert_eq!(text.content_length(viewport), 3);
+        assert_eq!(text.offset(), 2);
+
+        text.clear();
+
+        assert_eq!(text.lines().len(), 1);
+    }
+}
@@ -0,0 +1,288 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::message::{KeyMsg, MouseMsg};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonPressed {
+    pub index: usize,
+}
+
+#[derive(Debug)]
+pub struct Button {
+    label: String,
+    focused: bool,
+    style: Style,
+    focused_style: Style,
+    area: Cell<Rect>,
+}
+
+impl Button {
+    pub fn new<T: Into<String>>(label: T) -> Self {
+        Self {
+            label: label.into(),
+            focused: false,
+            style: Style::default(),
+            focused_style: Style::default().add_modifier(Modifier::REVERSED),
+            area: Cell::new(Rect::default()),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn focused_style(mut self, style: Style) -> Self {
+        self.focused_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    #[inline(always)]
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    #[inline(always)]
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    // The label in brackets with a space on both sides.
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.label.chars().count() as u16 + 4
+    }
+
+    // Enter or space while focused, returns whether the button was pressed.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> bool {
+        self.focused && matches!(key.code, KeyCode::Enter | KeyCode::Char(' '))
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> bool {
+        matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left)) && contains(self.area.get(), mouse.column, mouse.row)
+    }
+}
+
+impl Widget for &Button {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = Rect { width: area.width.min(self.width()), height: area.height.min(1), ..area };
+
+        self.area.set(area);
+
+        let style = if self.focused { self.style.patch(self.focused_style) } else { self.style };
+
+        buf.set_stringn(area.x, area.y, format!("[ {} ]", self.label), area.width as usize, style);
+    }
+}
+
+// A row of buttons as found at the bottom of dialogs. The focus starts on the default button, enter presses
+// the focused one and escape the cancel button.
+#[derive(Debug)]
+pub struct ButtonRow {
+    buttons: Vec<Button>,
+    focused: usize,
+    default: Option<usize>,
+    cancel: Option<usize>,
+    gap: u16,
+    alignment: Alignment,
+    default_style: Style,
+}
+
+impl ButtonRow {
+    pub fn new<I, T>(labels: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        let mut row = Self {
+            buttons: labels.into_iter().map(Button::new).collect(),
+            focused: 0,
+            default: None,
+            cancel: None,
+            gap: 2,
+            alignment: Alignment::Right,
+            default_style: Style::default().add_modifier(Modifier::BOLD),
+        };
+
+        row.focus(0);
+        row
+    }
+
+    // OK / cancel, with OK as the default.
+    pub fn ok_cancel() -> Self {
+        Self::new(["OK", "Cancel"]).default_button(0).cancel_button(1)
+    }
+
+    pub fn default_button(mut self, index: usize) -> Self {
+        self.default = (index < self.buttons.len()).then_some(index);
+        self.focus(index);
+        self
+    }
+
+    pub fn cancel_button(mut self, index: usize) -> Self {
+        self.cancel = (index < self.buttons.len()).then_some(index);
+        self
+    }
+
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.buttons = self.buttons.into_iter().map(|b| b.style(style)).collect();
+        self
+    }
+
+    pub fn focused_style(mut self, style: Style) -> Self {
+        self.buttons = self.buttons.into_iter().map(|b| b.focused_style(style)).collect();
+        self
+    }
+
+    // Patched over the default button's style.
+    pub fn default_style(mut self, style: Style) -> Self {
+        self.default_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    pub fn focus(&mut self, index: usize) {
+        if index >= self.buttons.len() {
+            return;
+        }
+
+        self.focused = index;
+        self.buttons.iter_mut().enumerate().for_each(|(i, button)| button.set_focused(i == index));
+    }
+
+    fn cycle_focus(&mut self, forward: bool) {
+        let count = self.buttons.len().max(1);
+
+        match forward {
+            | true => self.focus((self.focused + 1) % count),
+            | false => self.focus((self.focused + count - 1) % count),
+        }
+    }
+
+    // Left, right and tab move the focus.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<ButtonPressed> {
+        match key.code {
+            | KeyCode::Esc => return self.cancel.map(|index| ButtonPressed { index }),
+            | KeyCode::Left | KeyCode::BackTab => self.cycle_focus(false),
+            | KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => self.cycle_focus(false),
+            | KeyCode::Right | KeyCode::Tab => self.cycle_focus(true),
+            | _ => {
+                let focused = self.buttons.get_mut(self.focused)?;
+
+                return focused.handle_key(key).then_some(ButtonPressed { index: self.focused });
+            },
+        }
+
+        None
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<ButtonPressed> {
+        let index = self.buttons.iter_mut().position(|button| button.handle_mouse(mouse))?;
+
+        self.focus(index);
+
+        Some(ButtonPressed { index })
+    }
+}
+
+impl Widget for &ButtonRow {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let total = self.buttons.iter().map(Button::width).sum::<u16>()
+            + self.gap * self.buttons.len().saturating_sub(1) as u16;
+
+        let free = area.width.saturating_sub(total);
+
+        let mut x = area.x + match self.alignment {
+            | Alignment::Left => 0,
+            | Alignment::Center => free / 2,
+            | Alignment::Right => free,
+        };
+
+        for (index, button) in self.buttons.iter().enumerate() {
+            let rest = Rect { x, width: area.right().saturating_sub(x), ..area };
+
+            button.render(rest, buf);
+
+            if self.default == Some(index) {
+                buf.set_style(button.area.get(), self.default_style);
+            }
+
+            x = x.saturating_add(button.width() + self.gap).min(area.right());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn line(buf: &Buffer) -> String {
+        (0..buf.area.width).map(|x| buf.get(x, 0).symbol()).collect()
+    }
+
+    #[test]
+    fn buttons_are_only_pressed_by_keys_while_focused() {
+        let mut button = Button::new("Save");
+
+        assert_eq!(button.width(), 8);
+        assert!(!button.handle_key(&key(KeyCode::Enter)));
+
+        button.set_focused(true);
+
+        assert!(button.handle_key(&key(KeyCode::Char(' '))));
+        assert!(!button.handle_key(&key(KeyCode::Char('s'))));
+    }
+
+    #[test]
+    fn dialogs_start_on_the_default_button_and_cancel_with_escape() {
+        let mut row = ButtonRow::ok_cancel();
+
+        assert_eq!(row.focused(), 0);
+        assert_eq!(row.handle_key(&key(KeyCode::Esc)), Some(ButtonPressed { index: 1 }));
+        assert_eq!(row.handle_key(&key(KeyCode::Tab)), None);
+        assert_eq!(row.handle_key(&key(KeyCode::Tab)), None);
+        assert_eq!(row.handle_key(&key(KeyCode::Enter)), Some(ButtonPressed { index: 0 }));
+    }
+
+    #[test]
+    fn rows_are_aligned_and_clicked_where_they_are_drawn() {
+        let mut row = ButtonRow::ok_cancel().gap(1);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 1));
+        let click = |column| mouse(MouseEventKind::Down(MouseButton::Left), column, 0);
+
+        (&row).render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf), "   [ OK ] [ Cancel ]");
+        assert!(buf.get(3, 0).modifier.contains(Modifier::BOLD | Modifier::REVERSED));
+        assert_eq!(row.handle_mouse(&click(12)), Some(ButtonPressed { index: 1 }));
+        assert_eq!(row.focused(), 1);
+        assert_eq!(row.handle_mouse(&click(9)), None);
+    }
+}
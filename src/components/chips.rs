@@ -0,0 +1,364 @@
+use std::mem;
+use std::cell::RefCell;
+
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::message::{KeyMsg, MouseMsg};
+
+const REMOVE: &str = "×";
+
+// A label on a background of its own, like a status or a count.
+#[derive(Debug, Clone)]
+pub struct Badge {
+    label: String,
+    style: Style,
+}
+
+impl Badge {
+    pub fn new<T: Into<String>>(label: T) -> Self {
+        Self {
+            label: label.into(),
+            style: Style::default().add_modifier(Modifier::REVERSED),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // Padded with a space on both sides.
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.label.chars().count() as u16 + 2
+    }
+}
+
+impl Widget for &Badge {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        buf.set_stringn(area.x, area.y, format!(" {} ", self.label), area.width as usize, self.style);
+    }
+}
+
+// Places items of the given widths left to right, wrapping onto the next row. Items that don't fit anymore
+// get an empty area.
+fn flow(widths: impl IntoIterator<Item = u16>, gap: u16, area: Rect) -> Vec<Rect> {
+    let (mut x, mut y) = (area.x, area.y);
+
+    widths.into_iter().map(|width| {
+        if x > area.x && x.saturating_add(width) > area.right() {
+            (x, y) = (area.x, y + 1);
+        }
+
+        let placed = Rect::new(x, y, width, 1).intersection(area);
+
+        x = x.saturating_add(width + gap);
+
+        placed
+    }).collect()
+}
+
+// A row of badges, wrapped when they don't fit.
+#[derive(Debug, Clone)]
+pub struct Chips {
+    badges: Vec<Badge>,
+    gap: u16,
+}
+
+impl Chips {
+    pub fn new<I, T>(labels: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        Self {
+            badges: labels.into_iter().map(Badge::new).collect(),
+            gap: 1,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.badges = self.badges.into_iter().map(|badge| badge.style(style)).collect();
+        self
+    }
+
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+}
+
+impl Widget for &Chips {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let areas = flow(self.badges.iter().map(Badge::width), self.gap, area);
+
+        for (badge, area) in self.badges.iter().zip(areas) {
+            badge.render(area, buf);
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagsChanged {
+    pub tags: Vec<String>,
+}
+
+// Tags followed by the text being typed. Enter or a comma adds the text as a tag, backspace on an empty input
+// removes the last one and tab completes from the suggestions. Clicking a tag removes it.
+#[derive(Debug)]
+pub struct ChipsInput {
+    tags: Vec<String>,
+    input: String,
+    suggestions: Vec<String>,
+    style: Style,
+    input_style: Style,
+    hint_style: Style,
+    // Where every tag was last rendered.
+    areas: RefCell<Vec<Rect>>,
+}
+
+impl Default for ChipsInput {
+    fn default() -> Self {
+        Self {
+            tags: Vec::new(),
+            input: String::new(),
+            suggestions: Vec::new(),
+            style: Style::default().add_modifier(Modifier::REVERSED),
+            input_style: Style::default(),
+            hint_style: Style::default().add_modifier(Modifier::DIM),
+            areas: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl ChipsInput {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn tags<I, T>(mut self, tags: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn suggestions<I, T>(mut self, suggestions: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.suggestions = suggestions.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn input_style(mut self, style: Style) -> Self {
+        self.input_style = style;
+        self
+    }
+
+    pub fn hint_style(mut self, style: Style) -> Self {
+        self.hint_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn value(&self) -> &[String] {
+        &self.tags
+    }
+
+    #[inline(always)]
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    // The first suggestion starting with the input that isn't a tag already, ignoring case.
+    pub fn completion(&self) -> Option<&str> {
+        let input = self.input.to_lowercase();
+
+        if input.is_empty() {
+            return None;
+        }
+
+        self.suggestions.iter()
+            .filter(|suggestion| !self.tags.contains(suggestion))
+            .find(|suggestion| suggestion.to_lowercase().starts_with(&input))
+            .map(String::as_str)
+    }
+
+    #[inline(always)]
+    fn changed(&self) -> Option<TagsChanged> {
+        Some(TagsChanged { tags: self.tags.clone() })
+    }
+
+    // Surrounding whitespace is trimmed, empty and duplicate tags are ignored.
+    pub fn add(&mut self, tag: &str) -> Option<TagsChanged> {
+        let tag = tag.trim();
+
+        if tag.is_empty() || self.tags.iter().any(|existing| existing == tag) {
+            return None;
+        }
+
+        self.tags.push(tag.to_owned());
+        self.changed()
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<TagsChanged> {
+        if index >= self.tags.len() {
+            return None;
+        }
+
+        self.tags.remove(index);
+        self.changed()
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<TagsChanged> {
+        match key.code {
+            | KeyCode::Enter | KeyCode::Char(',') => {
+                let input = mem::take(&mut self.input);
+                return self.add(&input);
+            },
+            | KeyCode::Tab => {
+                if let Some(completion) = self.completion() {
+                    self.input = completion.to_owned();
+                }
+            },
+            | KeyCode::Backspace if self.input.is_empty() => return self.tags.len().checked_sub(1).and_then(|last| self.remove(last)),
+            | KeyCode::Backspace => {
+                self.input.pop();
+            },
+            | KeyCode::Esc => self.input.clear(),
+            | KeyCode::Char(c) => self.input.push(c),
+            | _ => (),
+        }
+
+        None
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<TagsChanged> {
+        if !matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left)) {
+            return None;
+        }
+
+        let clicked = self.areas.borrow().iter().position(|area| contains(*area, mouse.column, mouse.row));
+
+        clicked.and_then(|index| self.remove(index))
+    }
+}
+
+impl Widget for &ChipsInput {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let labels = self.tags.iter().map(|tag| format!(" {tag} {REMOVE} ")).collect::<Vec<_>>();
+
+        // The input goes last, as if it were one more tag.
+        let widths = labels.iter().map(|label| label.chars().count() as u16)
+            .chain([self.completion().unwrap_or(&self.input).chars().count() as u16 + 1]);
+
+        let mut areas = flow(widths, 1, area);
+
+        let input = areas.pop().unwrap_or_default();
+
+        for (label, area) in labels.iter().zip(&areas) {
+            buf.set_stringn(area.x, area.y, label, area.width as usize, self.style);
+        }
+
+        *self.areas.borrow_mut() = areas;
+
+        if input.is_empty() {
+            return;
+        }
+
+        let (x, _) = buf.set_stringn(input.x, input.y, &self.input, input.width as usize, self.input_style);
+
+        // The rest of the completion is hinted after what was typed.
+        let rest = self.completion().map(|completion| completion.chars().skip(self.input.chars().count()).collect::<String>());
+
+        let (x, _) = buf.set_stringn(x, input.y, rest.unwrap_or_default(), input.right().saturating_sub(x) as usize, self.hint_style);
+
+        if x < input.right() {
+            buf.set_string(x, input.y, "_", self.input_style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    fn type_text(input: &mut ChipsInput, text: &str) -> Option<TagsChanged> {
+        text.chars().map(|c| input.handle_key(&key(KeyCode::Char(c)))).last().flatten()
+    }
+
+    #[test]
+    fn chips_wrap_when_they_dont_fit() {
+        let chips = Chips::new(["rust", "tui", "terminal"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 2));
+
+        (&chips).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), [" rust   tui ", " terminal   "]);
+    }
+
+    #[test]
+    fn tags_are_added_once_and_removed_from_the_end() {
+        let mut input = ChipsInput::new().tags(["bug"]);
+
+        assert_eq!(type_text(&mut input, " ui ,"), Some(TagsChanged { tags: vec!["bug".into(), "ui".into()] }));
+        assert_eq!(type_text(&mut input, "bug"), None);
+        assert_eq!(input.handle_key(&key(KeyCode::Enter)), None);
+        assert_eq!(input.handle_key(&key(KeyCode::Backspace)), Some(TagsChanged { tags: vec!["bug".into()] }));
+        assert_eq!(input.value(), ["bug"]);
+    }
+
+    #[test]
+    fn suggestions_complete_what_is_typed() {
+        let mut input = ChipsInput::new().tags(["Bug"]).suggestions(["Bug", "Build", "Docs"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 1));
+
+        type_text(&mut input, "bu");
+
+        assert_eq!(input.completion(), Some("Build"));
+
+        (&input).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), [" Bug ×  build_  "]);
+        assert!(buf.get(10, 0).modifier.contains(Modifier::DIM));
+
+        input.handle_key(&key(KeyCode::Tab));
+
+        assert_eq!(input.input(), "Build");
+    }
+
+    #[test]
+    fn clicking_a_tag_removes_it() {
+        let mut input = ChipsInput::new().tags(["a", "b"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 1));
+
+        (&input).render(buf.area, &mut buf);
+
+        assert_eq!(input.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 7, 0)), Some(TagsChanged {
+            tags: vec!["a".into()],
+        }));
+        assert_eq!(input.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 15, 0)), None);
+    }
+}
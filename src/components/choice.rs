@@ -0,0 +1,335 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::message::{KeyMsg, MouseMsg};
+
+// What radio and checkbox groups have in common, one item per row with a marker in front of the label.
+#[derive(Debug)]
+struct Choices {
+    labels: Vec<String>,
+    disabled: Vec<bool>,
+    cursor: usize,
+    style: Style,
+    cursor_style: Style,
+    disabled_style: Style,
+    area: Cell<Rect>,
+}
+
+impl Choices {
+    fn new<I, T>(labels: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        let labels = labels.into_iter().map(Into::into).collect::<Vec<String>>();
+
+        Self {
+            disabled: vec![false; labels.len()],
+            labels,
+            cursor: 0,
+            style: Style::default(),
+            cursor_style: Style::default().add_modifier(Modifier::REVERSED),
+            disabled_style: Style::default().add_modifier(Modifier::DIM),
+            area: Cell::new(Rect::default()),
+        }
+    }
+
+    fn disable(&mut self, index: usize) {
+        if let Some(disabled) = self.disabled.get_mut(index) {
+            *disabled = true;
+        }
+
+        // The cursor never rests on a disabled item if there is any other.
+        if self.cursor == index {
+            self.move_cursor(true);
+        }
+    }
+
+    #[inline(always)]
+    fn is_enabled(&self, index: usize) -> bool {
+        self.disabled.get(index).is_some_and(|disabled| !disabled)
+    }
+
+    // Skips disabled items and stops at the ends.
+    fn move_cursor(&mut self, forward: bool) {
+        let next = match forward {
+            | true => (self.cursor + 1..self.labels.len()).find(|i| self.is_enabled(*i)),
+            | false => (0..self.cursor).rev().find(|i| self.is_enabled(*i)),
+        };
+
+        if let Some(next) = next {
+            self.cursor = next;
+        }
+    }
+
+    // Moves the cursor, returns the item to pick when the key picks one.
+    fn handle_key(&mut self, key: &KeyMsg) -> Option<usize> {
+        match key.code {
+            | KeyCode::Up => self.move_cursor(false),
+            | KeyCode::Down => self.move_cursor(true),
+            | KeyCode::Home => self.cursor = (0..self.labels.len()).find(|i| self.is_enabled(*i)).unwrap_or(self.cursor),
+            | KeyCode::End => self.cursor = (0..self.labels.len()).rfind(|i| self.is_enabled(*i)).unwrap_or(self.cursor),
+            | KeyCode::Char(' ') | KeyCode::Enter if self.is_enabled(self.cursor) => return Some(self.cursor),
+            | _ => (),
+        }
+
+        None
+    }
+
+    // Clicking an item moves the cursor there and picks it.
+    fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<usize> {
+        let area = self.area.get();
+
+        if !matches!(mouse.kind, MouseEventKind::Down(MouseButton::Left))
+            || !contains(area, mouse.column, mouse.row)
+        {
+            return None;
+        }
+
+        let index = (mouse.row - area.y) as usize;
+
+        self.is_enabled(index).then(|| {
+            self.cursor = index;
+            index
+        })
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer, marker: impl Fn(usize) -> &'static str) {
+        self.area.set(area);
+
+        for (index, label) in self.labels.iter().enumerate().take(area.height as usize) {
+            let style = match (self.is_enabled(index), index == self.cursor) {
+                | (false, _) => self.style.patch(self.disabled_style),
+                | (true, true) => self.style.patch(self.cursor_style),
+                | (true, false) => self.style,
+            };
+
+            buf.set_stringn(area.x, area.y + index as u16, format!("{} {label}", marker(index)), area.width as usize, style);
+        }
+    }
+}
+
+// A single choice out of several, nothing is selected until one is picked.
+#[derive(Debug)]
+pub struct RadioGroup {
+    choices: Choices,
+    selected: Option<usize>,
+}
+
+impl RadioGroup {
+    pub fn new<I, T>(labels: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        Self {
+            choices: Choices::new(labels),
+            selected: None,
+        }
+    }
+
+    pub fn disable(mut self, index: usize) -> Self {
+        self.choices.disable(index);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.choices.style = style;
+        self
+    }
+
+    pub fn cursor_style(mut self, style: Style) -> Self {
+        self.choices.cursor_style = style;
+        self
+    }
+
+    pub fn disabled_style(mut self, style: Style) -> Self {
+        self.choices.disabled_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    #[inline(always)]
+    pub fn selected_label(&self) -> Option<&str> {
+        self.selected.map(|index| self.choices.labels[index].as_str())
+    }
+
+    // Disabled items can't be selected.
+    pub fn select(&mut self, index: usize) -> bool {
+        if !self.choices.is_enabled(index) || self.selected == Some(index) {
+            return false;
+        }
+
+        self.selected = Some(index);
+        self.choices.cursor = index;
+
+        true
+    }
+
+    // Up and down move the cursor, space or enter select. Returns the newly selected item.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<usize> {
+        self.choices.handle_key(key).filter(|index| self.select(*index))
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<usize> {
+        self.choices.handle_mouse(mouse).filter(|index| self.select(*index))
+    }
+}
+
+impl Widget for &RadioGroup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.choices.render(area, buf, |index| if self.selected == Some(index) { "(•)" } else { "( )" });
+    }
+}
+
+#[derive(Debug)]
+pub struct CheckboxGroup {
+    choices: Choices,
+    checked: Vec<bool>,
+}
+
+impl CheckboxGroup {
+    pub fn new<I, T>(labels: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        let choices = Choices::new(labels);
+
+        Self {
+            checked: vec![false; choices.labels.len()],
+            choices,
+        }
+    }
+
+    pub fn disable(mut self, index: usize) -> Self {
+        self.choices.disable(index);
+        self
+    }
+
+    pub fn check(mut self, index: usize) -> Self {
+        if let Some(checked) = self.checked.get_mut(index) {
+            *checked = true;
+        }
+
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.choices.style = style;
+        self
+    }
+
+    pub fn cursor_style(mut self, style: Style) -> Self {
+        self.choices.cursor_style = style;
+        self
+    }
+
+    pub fn disabled_style(mut self, style: Style) -> Self {
+        self.choices.disabled_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_checked(&self, index: usize) -> bool {
+        self.checked.get(index).copied().unwrap_or(false)
+    }
+
+    // Indices of the checked items, in order.
+    pub fn checked(&self) -> Vec<usize> {
+        (0..self.checked.len()).filter(|index| self.checked[*index]).collect()
+    }
+
+    pub fn checked_labels(&self) -> Vec<&str> {
+        self.checked().into_iter().map(|index| self.choices.labels[index].as_str()).collect()
+    }
+
+    // Disabled items keep whatever state they were given.
+    pub fn toggle(&mut self, index: usize) -> bool {
+        if !self.choices.is_enabled(index) {
+            return false;
+        }
+
+        self.checked[index] = !self.checked[index];
+        self.choices.cursor = index;
+
+        true
+    }
+
+    // Up and down move the cursor, space or enter toggle. Returns the toggled item.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<usize> {
+        self.choices.handle_key(key).filter(|index| self.toggle(*index))
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<usize> {
+        self.choices.handle_mouse(mouse).filter(|index| self.toggle(*index))
+    }
+}
+
+impl Widget for &CheckboxGroup {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.choices.render(area, buf, |index| if self.checked[index] { "[x]" } else { "[ ]" });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn the_cursor_skips_disabled_items() {
+        let mut radio = RadioGroup::new(["small", "medium", "large"]).disable(0).disable(1);
+
+        assert_eq!(radio.handle_key(&key(KeyCode::Enter)), Some(2));
+        assert_eq!(radio.handle_key(&key(KeyCode::Home)), None);
+        assert_eq!(radio.handle_key(&key(KeyCode::Enter)), None, "already selected");
+        assert!(!radio.select(1));
+        assert_eq!(radio.selected_label(), Some("large"));
+    }
+
+    #[test]
+    fn only_one_radio_is_selected_at_a_time() {
+        let mut radio = RadioGroup::new(["tabs", "spaces"]);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+
+        (&radio).render(buf.area, &mut buf);
+
+        assert_eq!(radio.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 4, 1)), Some(1));
+        assert_eq!(radio.handle_key(&key(KeyCode::Up)), None);
+        assert_eq!(radio.handle_key(&key(KeyCode::Char(' '))), Some(0));
+
+        (&radio).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), ["(•) tabs  ", "( ) spaces"]);
+    }
+
+    #[test]
+    fn checkboxes_toggle_independently() {
+        let mut checks = CheckboxGroup::new(["bold", "italic", "locked"]).check(2).disable(2);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+
+        assert_eq!(checks.handle_key(&key(KeyCode::Enter)), Some(0));
+        assert_eq!(checks.handle_key(&key(KeyCode::End)), None);
+        assert_eq!(checks.handle_key(&key(KeyCode::Enter)), Some(1));
+        assert!(!checks.toggle(2));
+
+        (&checks).render(buf.area, &mut buf);
+
+        assert_eq!(checks.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 1, 0)), Some(0));
+        assert_eq!(checks.checked_labels(), ["italic", "locked"]);
+        assert_eq!(lines(&buf), ["[x] bold  ", "[x] italic", "[x] locked"]);
+    }
+}
@@ -0,0 +1,270 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ratatui::layout::Alignment;
+
+use crate::command::{self, Command};
+use crate::components::table::Table;
+use crate::message::Message;
+
+// How many of the first rows are looked at to tell which columns hold numbers.
+const SNIFFED: usize = 100;
+
+// Reads the next record, which spans lines where a quoted field does. Fields are quoted with double quotes, which
+// are doubled inside of them. `None` at the end of the input.
+pub(crate) fn read_record<R: BufRead>(reader: &mut R, delimiter: char) -> io::Result<Option<(Vec<String>, u64)>> {
+    let (mut record, mut field) = (Vec::new(), String::new());
+    let (mut quoted, mut read, mut line) = (false, 0, String::new());
+
+    loop {
+        line.clear();
+
+        let length = reader.read_line(&mut line)?;
+
+        if length == 0 {
+            if read == 0 {
+                return Ok(None);
+            }
+
+            break;
+        }
+
+        read += length as u64;
+
+        let content = line.strip_suffix('\n').map_or(line.as_str(), |line| line.strip_suffix('\r').unwrap_or(line));
+        let mut chars = content.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                | '"' if quoted && chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                },
+                | '"' if quoted => quoted = false,
+                | '"' if field.is_empty() => quoted = true,
+                | c if c == delimiter && !quoted => record.push(std::mem::take(&mut field)),
+                | c => field.push(c),
+            }
+        }
+
+        if !quoted {
+            break;
+        }
+
+        // The line break is part of the quoted field.
+        field.push('\n');
+    }
+
+    record.push(field);
+
+    Ok(Some((record, read)))
+}
+
+// Columns where every cell that isn't empty is a number are aligned to the right.
+pub(crate) fn sniff(rows: &[Vec<String>]) -> Vec<Alignment> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+    (0..columns).map(|column| {
+        let mut cells = rows.iter()
+            .filter_map(|row| row.get(column))
+            .map(|cell| cell.trim())
+            .filter(|cell| !cell.is_empty())
+            .peekable();
+
+        match cells.peek().is_some() && cells.all(|cell| cell.parse::<f64>().is_ok()) {
+            | true => Alignment::Right,
+            | false => Alignment::Left,
+        }
+    }).collect()
+}
+
+// Rows read by `CsvSource::load`, delivered to the screen as a custom message.
+#[derive(Debug)]
+pub struct CsvChunk {
+    source: u64,
+    first: bool,
+    rows: io::Result<Vec<Vec<String>>>,
+    // Where the next chunk starts, `None` at the end of the file.
+    next: Option<u64>,
+}
+
+// Streams the rows of a CSV or TSV file into a `Table` on background threads, a chunk at a time, so the first rows
+// are shown before the whole file is read. A list is a table with one column.
+//
+// The first chunk is read with `load`, every chunk that arrives is handed to `apply` which returns the command
+// reading the next one. Columns holding numbers are aligned to the right, unless their alignment is set already.
+#[derive(Debug, Clone)]
+pub struct CsvSource {
+    // Tells chunks of different sources apart.
+    id: u64,
+    path: PathBuf,
+    delimiter: char,
+    header: bool,
+    chunk: usize,
+}
+
+impl CsvSource {
+    // Tab separated for .tsv and .tab files, comma separated otherwise.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        static SOURCES: AtomicU64 = AtomicU64::new(0);
+
+        let path = path.into();
+
+        let delimiter = match path.extension().and_then(|extension| extension.to_str()) {
+            | Some("tsv" | "tab") => '\t',
+            | _ => ',',
+        };
+
+        Self { id: SOURCES.fetch_add(1, Ordering::Relaxed), path, delimiter, header: true, chunk: 1000 }
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    // Whether the first record is the table's header, it is by default.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    // How many rows are read at a time.
+    pub fn chunk_size(mut self, rows: usize) -> Self {
+        self.chunk = rows.max(1);
+        self
+    }
+
+    #[inline(always)]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline(always)]
+    pub fn load(&self) -> Command {
+        self.load_from(0)
+    }
+
+    fn load_from(&self, offset: u64) -> Command {
+        let (source, path, delimiter, chunk) = (self.id, self.path.clone(), self.delimiter, self.chunk);
+
+        command::spawn(move || {
+            let mut next = Some(offset);
+
+            let rows = File::open(&path).and_then(|file| {
+                let mut reader = BufReader::new(file);
+
+                reader.seek(SeekFrom::Start(offset))?;
+
+                let mut rows = Vec::new();
+
+                while rows.len() < chunk {
+                    let Some((record, read)) = read_record(&mut reader, delimiter)? else {
+                        next = None;
+                        break;
+                    };
+
+                    rows.push(record);
+                    next = next.map(|next| next + read);
+                }
+
+                if reader.fill_buf()?.is_empty() {
+                    next = None;
+                }
+
+                Ok(rows)
+            });
+
+            Message::custom(CsvChunk { source, first: offset == 0, rows, next })
+        })
+    }
+
+    // Returns the command reading the next chunk, `None` once the file has been read or the chunk wasn't from
+    // this source. The rows of earlier chunks stay in the table after an error.
+    pub fn apply(&self, table: &mut Table, chunk: CsvChunk) -> io::Result<Option<Command>> {
+        if chunk.source != self.id {
+            return Ok(None);
+        }
+
+        let mut rows = chunk.rows?;
+
+        if chunk.first {
+            if self.header && !rows.is_empty() {
+                table.set_header(rows.remove(0));
+            }
+
+            table.set_rows(Vec::<Vec<String>>::new());
+
+            for (index, alignment) in sniff(&rows[..rows.len().min(SNIFFED)]).into_iter().enumerate() {
+                table.column_mut(index).alignment.get_or_insert(alignment);
+            }
+        }
+
+        table.push_rows(rows);
+
+        Ok(chunk.next.map(|next| self.load_from(next)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{env, fs};
+
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Widget;
+
+    #[test]
+    fn quoted_fields_keep_delimiters_quotes_and_line_breaks() {
+        let mut input = "a,\"b, \"\"c\"\"\",d\r\n\"multi\nline\",,\nlast".as_bytes();
+
+        let records = std::iter::from_fn(|| read_record(&mut input, ',').unwrap()).collect::<Vec<_>>();
+
+        assert_eq!(records, [
+            (vec!["a".to_owned(), "b, \"c\"".into(), "d".into()], 16),
+            (vec!["multi\nline".to_owned(), "".into(), "".into()], 15),
+            (vec!["last".to_owned()], 4),
+        ]);
+    }
+
+    #[test]
+    fn numbers_are_aligned_to_the_right() {
+        let rows = [["x", "1", ""], ["y", "-2.5", "z"], ["z", "", "3"]].map(|row| row.map(String::from).to_vec());
+
+        assert_eq!(sniff(&rows), [Alignment::Left, Alignment::Right, Alignment::Left]);
+    }
+
+    #[test]
+    fn files_are_streamed_into_the_table_a_chunk_at_a_time() {
+        let path = env::temp_dir().join(format!("ratata-csv-{}.tsv", std::process::id()));
+
+        fs::write(&path, "name\tsize\nalpha\t10\nbeta\t200\ngamma\t3\n").unwrap();
+
+        let source = CsvSource::new(&path).chunk_size(2);
+        let mut table = Table::new(Vec::<Vec<String>>::new());
+
+        let mut command = Some(source.load());
+        let mut chunks = 0;
+
+        while let Some(Command::Spawn(work)) = command {
+            command = source.apply(&mut table, work.0().downcast().unwrap()).unwrap();
+            chunks += 1;
+        }
+
+        fs::remove_file(&path).unwrap();
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 4));
+
+        (&table).render(buf.area, &mut buf);
+
+        let lines = (0..4).map(|y| (0..10).map(|x| buf.get(x, y).symbol()).collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(chunks, 2);
+        assert_eq!(table.rows().len(), 3);
+        assert_eq!(lines, ["name  size", "alpha   10", "beta   200", "gamma    3"]);
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt::Write;
+
+use ratatui::layout::Alignment;
+
+use unicode_width::UnicodeWidthStr;
+
+// What a component's contents are exported as, see `Table::export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    // Fields with commas, quotes or line breaks are quoted.
+    Csv,
+    // An array of objects keyed by the header's cells, or of arrays without a header.
+    Json,
+    // Aligned like the columns are, for pasting where there's no table.
+    Text,
+}
+
+pub(crate) fn csv(rows: &[&[String]]) -> String {
+    let field = |field: &String| match field.contains([',', '"', '\n', '\r']) {
+        | true => format!("\"{}\"", field.replace('"', "\"\"")),
+        | false => field.clone(),
+    };
+
+    rows.iter().map(|row| row.iter().map(field).collect::<Vec<_>>().join(",") + "\n").collect()
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+
+    escaped.push('"');
+
+    for c in text.chars() {
+        match c {
+            | '"' => escaped.push_str("\\\""),
+            | '\\' => escaped.push_str("\\\\"),
+            | '\n' => escaped.push_str("\\n"),
+            | '\r' => escaped.push_str("\\r"),
+            | '\t' => escaped.push_str("\\t"),
+            | c if c.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", c as u32);
+            },
+            | c => escaped.push(c),
+        }
+    }
+
+    escaped.push('"');
+    escaped
+}
+
+pub(crate) fn json(header: Option<&[String]>, rows: &[&[String]]) -> String {
+    let row = |row: &&[String]| match header {
+        // Cells past the header are keyed by their index.
+        | Some(header) => {
+            let key = |index: usize| header.get(index).cloned().unwrap_or_else(|| index.to_string());
+            let fields = row.iter().enumerate().map(|(index, cell)| {
+                format!("{}: {}", json_string(&key(index)), json_string(cell))
+            });
+
+            format!("{{{}}}", fields.collect::<Vec<_>>().join(", "))
+        },
+        | None => format!("[{}]", row.iter().map(|cell| json_string(cell)).collect::<Vec<_>>().join(", ")),
+    };
+
+    match rows.is_empty() {
+        | true => String::from("[]\n"),
+        | false => format!("[\n  {}\n]\n", rows.iter().map(row).collect::<Vec<_>>().join(",\n  ")),
+    }
+}
+
+// Every row in full, so columns are as wide as their widest cell. Trailing spaces are left out.
+pub(crate) fn text(rows: &[&[String]], alignments: &[Alignment], spacing: usize) -> String {
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+
+    let widths = (0..columns)
+        .map(|column| rows.iter().filter_map(|row| row.get(column)).map(|cell| cell.width()).max().unwrap_or(0))
+        .collect::<Vec<_>>();
+
+    let mut text = String::new();
+
+    for row in rows {
+        let mut line = String::new();
+
+        for (index, cell) in row.iter().enumerate() {
+            let padding = widths[index] - cell.width();
+
+            let left = match alignments.get(index).copied().unwrap_or(Alignment::Left) {
+                | Alignment::Left => 0,
+                | Alignment::Center => padding / 2,
+                | Alignment::Right => padding,
+            };
+
+            if index > 0 {
+                line.push_str(&" ".repeat(spacing));
+            }
+
+            let _ = write!(line, "{:left$}{cell}{:right$}", "", "", right = padding - left);
+        }
+
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<Vec<String>> {
+        let rows = [["name", "note"], ["a,b", "say \"hi\""], ["c", "two\nlines"]];
+
+        rows.map(|row| row.map(String::from).to_vec()).to_vec()
+    }
+
+    #[test]
+    fn csv_quotes_only_what_needs_it() {
+        let rows = rows();
+        let rows = rows.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        assert_eq!(csv(&rows), "name,note\n\"a,b\",\"say \"\"hi\"\"\"\nc,\"two\nlines\"\n");
+    }
+
+    #[test]
+    fn json_keys_cells_by_the_header() {
+        let rows = rows();
+        let rows = rows.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        assert_eq!(json(Some(rows[0]), &rows[1..]), concat!(
+            "[\n",
+            "  {\"name\": \"a,b\", \"note\": \"say \\\"hi\\\"\"},\n",
+            "  {\"name\": \"c\", \"note\": \"two\\nlines\"}\n",
+            "]\n",
+        ));
+
+        assert_eq!(json(None, &rows[..1]), "[\n  [\"name\", \"note\"]\n]\n");
+        assert_eq!(json(None, &[]), "[]\n");
+        assert_eq!(json_string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn text_is_aligned_like_the_columns() {
+        let rows = [["item", "cost"], ["tea", "3"], ["biscuits", "12"]].map(|row| row.map(String::from).to_vec());
+        let rows = rows.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+        let text = text(&rows, &[Alignment::Left, Alignment::Right], 2);
+
+        assert_eq!(text, "item      cost\ntea          3\nbiscuits    12\n");
+    }
+}
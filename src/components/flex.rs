@@ -0,0 +1,326 @@
+use std::fmt;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Direction, Rect};
+use ratatui::widgets::Widget;
+
+// How leftover space on the main axis is distributed when no item grows into it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Justify {
+    #[default]
+    Start,
+    End,
+    Center,
+    SpaceBetween,
+    SpaceAround,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    #[default]
+    Stretch,
+    Start,
+    Center,
+    End,
+}
+
+type Render = Box<dyn Fn(Rect, &mut Buffer)>;
+
+pub struct FlexItem {
+    basis: u16,
+    grow: u16,
+    shrink: u16,
+    cross: Option<u16>,
+    render: Render,
+}
+
+impl FlexItem {
+    pub fn new<F>(basis: u16, render: F) -> Self
+        where F: Fn(Rect, &mut Buffer) + 'static,
+    {
+        Self {
+            basis,
+            grow: 0,
+            shrink: 1,
+            cross: None,
+            render: Box::new(render),
+        }
+    }
+
+    pub fn grow(mut self, grow: u16) -> Self {
+        self.grow = grow;
+        self
+    }
+
+    pub fn shrink(mut self, shrink: u16) -> Self {
+        self.shrink = shrink;
+        self
+    }
+
+    // The size on the cross axis, used unless the item is stretched.
+    pub fn cross(mut self, size: u16) -> Self {
+        self.cross = Some(size);
+        self
+    }
+}
+
+impl fmt::Debug for FlexItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FlexItem")
+            .field("basis", &self.basis)
+            .field("grow", &self.grow)
+            .field("shrink", &self.shrink)
+            .field("cross", &self.cross)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+pub struct Flex {
+    direction: Direction,
+    wrap: bool,
+    gap: u16,
+    justify: Justify,
+    align: Align,
+    items: Vec<FlexItem>,
+}
+
+impl Default for Flex {
+    fn default() -> Self {
+        Self {
+            direction: Direction::Horizontal,
+            wrap: false,
+            gap: 0,
+            justify: Justify::default(),
+            align: Align::default(),
+            items: Vec::new(),
+        }
+    }
+}
+
+// Splits `space` according to the weights, the rounding remainder goes to the first items.
+fn distribute(space: u32, weights: &[u32]) -> Vec<u32> {
+    let total = weights.iter().sum::<u32>();
+
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let mut shares = weights.iter().map(|w| space * w / total).collect::<Vec<_>>();
+    let mut remainder = space - shares.iter().sum::<u32>();
+
+    for (share, _) in shares.iter_mut().zip(weights).filter(|(_, w)| **w > 0) {
+        if remainder == 0 {
+            break;
+        }
+
+        *share += 1;
+        remainder -= 1;
+    }
+
+    shares
+}
+
+impl Flex {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    pub fn row() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    pub fn column() -> Self {
+        Self::default().direction(Direction::Vertical)
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+
+    pub fn wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    pub fn justify(mut self, justify: Justify) -> Self {
+        self.justify = justify;
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn item(mut self, item: FlexItem) -> Self {
+        self.items.push(item);
+        self
+    }
+
+    // Items are wrapped greedily, lines share the cross axis evenly.
+    fn lines(&self, main: u16) -> Vec<&[FlexItem]> {
+        if !self.wrap {
+            return vec![&self.items[..]];
+        }
+
+        let mut lines = Vec::new();
+        let (mut start, mut used) = (0, 0u32);
+
+        for (index, item) in self.items.iter().enumerate() {
+            let needed = if index == start { item.basis as u32 } else { used + self.gap as u32 + item.basis as u32 };
+
+            if index > start && needed > main as u32 {
+                lines.push(&self.items[start..index]);
+                start = index;
+                used = item.basis as u32;
+            } else {
+                used = needed;
+            }
+        }
+
+        if start < self.items.len() {
+            lines.push(&self.items[start..]);
+        }
+
+        lines
+    }
+
+    fn sizes(&self, line: &[FlexItem], main: u16) -> Vec<u16> {
+        let gaps = self.gap as u32 * line.len().saturating_sub(1) as u32;
+        let basis = line.iter().map(|i| i.basis as u32).sum::<u32>();
+        let available = (main as u32).saturating_sub(gaps);
+
+        let mut sizes = line.iter().map(|i| i.basis as u32).collect::<Vec<_>>();
+
+        if available > basis {
+            let grown = distribute(available - basis, &line.iter().map(|i| i.grow as u32).collect::<Vec<_>>());
+            sizes.iter_mut().zip(grown).for_each(|(size, extra)| *size += extra);
+        } else if available < basis {
+            // Like CSS, bigger items give up proportionally more.
+            let weights = line.iter().map(|i| i.shrink as u32 * i.basis as u32).collect::<Vec<_>>();
+            let shrunk = distribute(basis - available, &weights);
+            sizes.iter_mut().zip(shrunk).for_each(|(size, less)| *size = size.saturating_sub(less));
+        }
+
+        sizes.into_iter().map(|size| size as u16).collect()
+    }
+
+    // The area of every item, in the order they were added.
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let horizontal = self.direction == Direction::Horizontal;
+
+        let (main, cross) = match horizontal {
+            | true => (area.width, area.height),
+            | false => (area.height, area.width),
+        };
+
+        let lines = self.lines(main);
+
+        let line_gaps = self.gap.saturating_mul(lines.len().saturating_sub(1) as u16);
+        let line_cross = cross.saturating_sub(line_gaps) / lines.len().max(1) as u16;
+
+        let mut areas = Vec::with_capacity(self.items.len());
+
+        for (index, line) in lines.into_iter().enumerate() {
+            let sizes = self.sizes(line, main);
+
+            let gaps = self.gap as u32 * line.len().saturating_sub(1) as u32;
+            let free = (main as u32).saturating_sub(sizes.iter().map(|s| *s as u32).sum::<u32>() + gaps);
+
+            let count = line.len() as u32;
+
+            let (mut offset, spacing) = match self.justify {
+                | Justify::Start => (0, 0),
+                | Justify::End => (free, 0),
+                | Justify::Center => (free / 2, 0),
+                | Justify::SpaceBetween if count > 1 => (0, free / (count - 1)),
+                | Justify::SpaceBetween => (0, 0),
+                | Justify::SpaceAround => (free / count.max(1) / 2, free / count.max(1)),
+            };
+
+            let line_offset = (index as u16).saturating_mul(line_cross.saturating_add(self.gap));
+
+            for (item, size) in line.iter().zip(sizes) {
+                let extent = match self.align {
+                    | Align::Stretch => line_cross,
+                    | _ => item.cross.unwrap_or(line_cross).min(line_cross),
+                };
+
+                let cross_offset = line_offset.saturating_add(match self.align {
+                    | Align::Stretch | Align::Start => 0,
+                    | Align::Center => (line_cross - extent) / 2,
+                    | Align::End => line_cross - extent,
+                });
+
+                let main_offset = offset.min(main as u32) as u16;
+
+                let (x, y, width, height) = match horizontal {
+                    | true => (main_offset, cross_offset, size, extent),
+                    | false => (cross_offset, main_offset, extent, size),
+                };
+
+                areas.push(Rect::new(area.x.saturating_add(x), area.y.saturating_add(y), width, height).intersection(area));
+
+                offset += size as u32 + self.gap as u32 + spacing;
+            }
+        }
+
+        areas
+    }
+}
+
+impl Widget for &Flex {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (item, area) in self.items.iter().zip(self.layout(area)) {
+            if !area.is_empty() {
+                (item.render)(area, buf);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items(flex: Flex, count: usize) -> Flex {
+        (0..count).fold(flex, |flex, _| flex.item(FlexItem::new(4, |_, _| ())))
+    }
+
+    #[test]
+    fn grows_into_the_free_space() {
+        let flex = Flex::row().item(FlexItem::new(2, |_, _| ())).item(FlexItem::new(2, |_, _| ()).grow(1));
+
+        assert_eq!(flex.layout(Rect::new(0, 0, 10, 1)), [Rect::new(0, 0, 2, 1), Rect::new(2, 0, 8, 1)]);
+    }
+
+    #[test]
+    fn wraps_onto_new_lines() {
+        let flex = items(Flex::row().wrap(true), 3);
+
+        assert_eq!(flex.layout(Rect::new(0, 0, 8, 2)), [
+            Rect::new(0, 0, 4, 1),
+            Rect::new(4, 0, 4, 1),
+            Rect::new(0, 1, 4, 1),
+        ]);
+    }
+
+    #[test]
+    fn huge_gaps_near_the_edge_dont_overflow() {
+        let flex = items(Flex::column().wrap(true).gap(u16::MAX), 3);
+
+        for area in flex.layout(Rect::new(u16::MAX - 8, u16::MAX - 8, 8, 8)) {
+            assert!(area.x >= u16::MAX - 8 && area.y >= u16::MAX - 8);
+        }
+    }
+}
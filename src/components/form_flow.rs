@@ -0,0 +1,378 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Widget;
+
+const SEPARATOR: &str = " › ";
+
+// Also handed the rest of the form's values, for fields that depend on others.
+type Check = Box<dyn Fn(&str, &FormValues) -> Result<(), String>>;
+
+type Build<T> = Box<dyn Fn(&FormValues) -> Result<T, Vec<FieldError>>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new<F: Into<String>, M: Into<String>>(field: F, message: M) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+// What was entered on every step so far, by field name. Fields nothing was entered in are empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormValues(BTreeMap<String, String>);
+
+impl FormValues {
+    #[inline(always)]
+    pub fn get(&self, field: &str) -> &str {
+        self.0.get(field).map_or("", String::as_str)
+    }
+
+    // `None` for fields that are empty or don't parse.
+    pub fn parse<T: FromStr>(&self, field: &str) -> Option<T> {
+        self.get(field).trim().parse().ok()
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(field, value)| (field.as_str(), value.as_str()))
+    }
+}
+
+// A page of a `FormFlow`, with the checks its fields have to pass before the next one is shown. Fields are checked
+// in the order they were added.
+pub struct FormStep {
+    title: String,
+    fields: Vec<(String, Check)>,
+}
+
+impl FormStep {
+    pub fn new<T: Into<String>>(title: T) -> Self {
+        Self { title: title.into(), fields: Vec::new() }
+    }
+
+    // The check returns what's wrong with the value, e.g. `Err("isn't an email address".into())`.
+    pub fn field<N, F>(mut self, name: N, check: F) -> Self
+        where N: Into<String>, F: Fn(&str, &FormValues) -> Result<(), String> + 'static,
+    {
+        self.fields.push((name.into(), Box::new(check)));
+        self
+    }
+
+    // Fails for values that are empty or only whitespace.
+    pub fn required<N: Into<String>>(self, name: N) -> Self {
+        self.field(name, |value, _| match value.trim().is_empty() {
+            | true => Err(String::from("is required")),
+            | false => Ok(()),
+        })
+    }
+
+    #[inline(always)]
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    fn has(&self, field: &str) -> bool {
+        self.fields.iter().any(|(name, _)| name == field)
+    }
+}
+
+impl fmt::Debug for FormStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormStep")
+            .field("title", &self.title)
+            .field("fields", &self.fields.iter().map(|(name, _)| name).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+// Returned by `FormFlow::advance` and `FormFlow::back`, the result e.g. to wrap into `Message::custom`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormEvent<T> {
+    // Moved to the step, by its index. The screen fills its inputs back in from `FormFlow::value`.
+    Step(usize),
+    // Some fields didn't pass their checks, see `FormFlow::errors`.
+    Invalid,
+    Submitted(T),
+}
+
+// Leads through a form of multiple steps, like a wizard. The screen owns the inputs of the current step and hands
+// their values to `set` as they change, the flow keeps them all when going back and forth between steps. Moving
+// on checks the current step's fields, completing the last step builds the result from all of them. Errors from
+// building it send the flow back to the first step with an offending field.
+//
+// Rendered as the titles of the steps, the current one highlighted.
+pub struct FormFlow<T> {
+    steps: Vec<FormStep>,
+    current: usize,
+    values: FormValues,
+    errors: Vec<FieldError>,
+    build: Build<T>,
+    style: Style,
+    current_style: Style,
+}
+
+impl<T> FormFlow<T> {
+    pub fn new<F>(build: F) -> Self
+        where F: Fn(&FormValues) -> Result<T, Vec<FieldError>> + 'static,
+    {
+        Self {
+            steps: Vec::new(),
+            current: 0,
+            values: FormValues::default(),
+            errors: Vec::new(),
+            build: Box::new(build),
+            style: Style::default().add_modifier(Modifier::DIM),
+            current_style: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    pub fn step(mut self, step: FormStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn current_style(mut self, style: Style) -> Self {
+        self.current_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    #[inline(always)]
+    pub fn current_step(&self) -> Option<&FormStep> {
+        self.steps.get(self.current)
+    }
+
+    #[inline(always)]
+    pub fn is_last(&self) -> bool {
+        self.current + 1 >= self.steps.len()
+    }
+
+    // The field's error goes away once its value changes.
+    pub fn set<N, V>(&mut self, field: N, value: V)
+        where N: Into<String>, V: Into<String>,
+    {
+        let (field, value) = (field.into(), value.into());
+
+        if self.values.0.get(&field) != Some(&value) {
+            self.errors.retain(|error| error.field != field);
+            self.values.0.insert(field, value);
+        }
+    }
+
+    #[inline(always)]
+    pub fn value(&self, field: &str) -> &str {
+        self.values.get(field)
+    }
+
+    #[inline(always)]
+    pub fn values(&self) -> &FormValues {
+        &self.values
+    }
+
+    #[inline(always)]
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    // The first of the field's errors, e.g. to show under its input.
+    pub fn error(&self, field: &str) -> Option<&str> {
+        self.errors.iter().find(|error| error.field == field).map(|error| error.message.as_str())
+    }
+
+    fn check(&self, step: &FormStep) -> Vec<FieldError> {
+        step.fields.iter()
+            .filter_map(|(name, check)| check(self.values.get(name), &self.values).err().map(|message| {
+                FieldError::new(name.as_str(), message)
+            }))
+            .collect()
+    }
+
+    // Moves on to the next step once the current one's fields pass their checks, on the last step submits.
+    pub fn advance(&mut self) -> Option<FormEvent<T>> {
+        let step = self.current_step()?;
+
+        self.errors = self.check(step);
+
+        if !self.errors.is_empty() {
+            return Some(FormEvent::Invalid);
+        }
+
+        if !self.is_last() {
+            self.current += 1;
+            return Some(FormEvent::Step(self.current));
+        }
+
+        // Steps skipped back over could've been left invalid through fields depending on each other.
+        let errors = self.steps.iter().flat_map(|step| self.check(step)).collect::<Vec<_>>();
+
+        let result = match errors.is_empty() {
+            | true => (self.build)(&self.values),
+            | false => Err(errors),
+        };
+
+        match result {
+            | Ok(result) => Some(FormEvent::Submitted(result)),
+            | Err(errors) => {
+                let first = errors.iter().find_map(|error| self.steps.iter().position(|step| step.has(&error.field)));
+
+                self.errors = errors;
+                self.current = first.unwrap_or(self.current);
+
+                Some(FormEvent::Invalid)
+            },
+        }
+    }
+
+    // Goes back a step without checking anything, what was entered is kept. `None` on the first step.
+    pub fn back(&mut self) -> Option<FormEvent<T>> {
+        self.current = self.current.checked_sub(1)?;
+        self.errors.clear();
+
+        Some(FormEvent::Step(self.current))
+    }
+}
+
+impl<T> fmt::Debug for FormFlow<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FormFlow")
+            .field("steps", &self.steps)
+            .field("current", &self.current)
+            .field("values", &self.values)
+            .field("errors", &self.errors)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Widget for &FormFlow<T> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut spans = Vec::new();
+
+        for (index, step) in self.steps.iter().enumerate() {
+            if index > 0 {
+                spans.push(Span::styled(SEPARATOR, self.style));
+            }
+
+            let style = match index == self.current {
+                | true => self.current_style,
+                | false => self.style,
+            };
+
+            spans.push(Span::styled(step.title.as_str(), style));
+        }
+
+        Line::from(spans).render(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct Signup {
+        email: String,
+        age: u8,
+    }
+
+    fn flow() -> FormFlow<Signup> {
+        let email = |value: &str, _: &FormValues| match value.contains('@') {
+            | true => Ok(()),
+            | false => Err(String::from("isn't an email address")),
+        };
+
+        FormFlow::new(|values| {
+            let age = values.parse::<u8>("age").ok_or_else(|| vec![FieldError::new("age", "isn't a number")])?;
+
+            match values.get("email").ends_with(".invalid") {
+                | true => Err(vec![FieldError::new("email", "can't be reached")]),
+                | false => Ok(Signup { email: values.get("email").to_owned(), age }),
+            }
+        })
+        .step(FormStep::new("Account").required("email").field("email", email))
+        .step(FormStep::new("Profile").required("age"))
+        .step(FormStep::new("Confirm"))
+    }
+
+    #[test]
+    fn steps_are_only_left_once_their_fields_pass() {
+        let mut flow = flow();
+
+        assert_eq!(flow.advance(), Some(FormEvent::Invalid));
+        assert_eq!(flow.errors(), [
+            FieldError::new("email", "is required"),
+            FieldError::new("email", "isn't an email address"),
+        ]);
+
+        flow.set("email", "me@example.com");
+
+        assert!(flow.errors().is_empty());
+        assert_eq!(flow.advance(), Some(FormEvent::Step(1)));
+        assert_eq!(flow.back(), Some(FormEvent::Step(0)));
+        assert_eq!(flow.back(), None);
+        assert_eq!(flow.value("email"), "me@example.com");
+    }
+
+    #[test]
+    fn the_last_step_submits_the_result_built_from_every_step() {
+        let mut flow = flow();
+
+        flow.set("email", "me@example.com");
+        flow.advance();
+        flow.set("age", "42");
+
+        assert_eq!(flow.advance(), Some(FormEvent::Step(2)));
+        assert!(flow.is_last());
+        assert_eq!(flow.advance(), Some(FormEvent::Submitted(Signup { email: "me@example.com".into(), age: 42 })));
+    }
+
+    #[test]
+    fn errors_from_building_the_result_go_back_to_their_step() {
+        let mut flow = flow();
+
+        flow.set("email", "me@example.invalid");
+        flow.advance();
+        flow.set("age", "42");
+        flow.advance();
+
+        assert_eq!(flow.advance(), Some(FormEvent::Invalid));
+        assert_eq!(flow.current(), 0);
+        assert_eq!(flow.error("email"), Some("can't be reached"));
+        assert_eq!(flow.value("age"), "42");
+    }
+
+    #[test]
+    fn the_current_step_is_highlighted() {
+        let mut flow = flow();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 28, 1));
+
+        flow.set("email", "me@example.com");
+        flow.advance();
+
+        (&flow).render(buf.area, &mut buf);
+
+        let line = (0..28).map(|x| buf.get(x, 0).symbol()).collect::<String>();
+
+        assert_eq!(line, "Account › Profile › Confirm ");
+        assert!(buf.get(10, 0).modifier.contains(Modifier::BOLD));
+        assert!(buf.get(0, 0).modifier.contains(Modifier::DIM));
+    }
+}
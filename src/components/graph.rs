@@ -0,0 +1,880 @@
+use std::cell::Cell;
+use std::collections::{HashSet, VecDeque};
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::command::{self, Command};
+use crate::message::{KeyMsg, Message, MouseMsg};
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+// Indexed by the directions a cell connects to.
+const LINES: [&str; 16] = [" ", "│", "│", "│", "─", "┘", "┐", "┤", "─", "└", "┌", "├", "─", "┴", "┬", "┼"];
+
+// How often the order within the layers is improved, alternating between looking at the layer before and after.
+const SWEEPS: usize = 8;
+
+// Shift and the arrow keys pan by this many cells.
+const PAN: i32 = 4;
+
+#[derive(Debug, Clone, Copy)]
+struct Scale {
+    boxed: bool,
+    max_label: usize,
+    row_gap: i32,
+    layer_gap: i32,
+}
+
+// From the most detailed zoom level to the least, the gap between layers needs room for an arrow.
+const SCALES: [Scale; 3] = [
+    Scale { boxed: true, max_label: 24, row_gap: 1, layer_gap: 6 },
+    Scale { boxed: false, max_label: 12, row_gap: 1, layer_gap: 4 },
+    Scale { boxed: false, max_label: 3, row_gap: 0, layer_gap: 3 },
+];
+
+// Nodes are numbered in the order they were added. Self loops and duplicate edges are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct Graph {
+    labels: Vec<String>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Graph {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node<T: Into<String>>(&mut self, label: T) -> usize {
+        self.labels.push(label.into());
+        self.labels.len() - 1
+    }
+
+    // Edges between nodes that don't exist are ignored.
+    pub fn edge(&mut self, from: usize, to: usize) {
+        if from < self.labels.len() && to < self.labels.len() {
+            self.edges.push((from, to));
+        }
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn label(&self, node: usize) -> Option<&str> {
+        self.labels.get(node).map(String::as_str)
+    }
+
+    // Depth first, an edge back to a node that's still being visited closes a cycle and is turned around.
+    fn acyclic_edges(&self) -> Vec<(usize, usize, bool)> {
+        let count = self.labels.len();
+
+        let mut seen = HashSet::new();
+        let mut successors = vec![Vec::new(); count];
+
+        for &(from, to) in &self.edges {
+            if from != to && seen.insert((from, to)) {
+                successors[from].push(to);
+            }
+        }
+
+        let mut state = vec![0u8; count];
+        let mut edges = Vec::new();
+
+        for root in 0..count {
+            if state[root] != 0 {
+                continue;
+            }
+
+            state[root] = 1;
+
+            let mut stack = vec![(root, 0)];
+
+            while let Some(top) = stack.last_mut() {
+                let (node, next) = *top;
+
+                top.1 += 1;
+
+                match successors[node].get(next) {
+                    | Some(&to) if state[to] == 0 => {
+                        state[to] = 1;
+                        edges.push((node, to, false));
+                        stack.push((to, 0));
+                    },
+                    | Some(&to) if state[to] == 1 => edges.push((to, node, true)),
+                    | Some(&to) => edges.push((node, to, false)),
+                    | None => {
+                        state[node] = 2;
+                        stack.pop();
+                    },
+                }
+            }
+        }
+
+        edges
+    }
+
+    // Layered from left to right: every node goes one layer after its furthest predecessor, edges spanning
+    // several layers pass through a dummy slot in each one, and the slots are ordered by the average position
+    // of their neighbours to cut down on crossings.
+    fn layout(&self) -> Layered {
+        let count = self.labels.len();
+        let edges = self.acyclic_edges();
+
+        let mut layer_of = vec![0; count];
+        let mut indegree = vec![0; count];
+        let mut successors = vec![Vec::new(); count];
+
+        for &(from, to, _) in &edges {
+            successors[from].push(to);
+            indegree[to] += 1;
+        }
+
+        let mut queue = (0..count).filter(|node| indegree[*node] == 0).collect::<VecDeque<_>>();
+
+        while let Some(node) = queue.pop_front() {
+            for &to in &successors[node] {
+                layer_of[to] = layer_of[to].max(layer_of[node] + 1);
+                indegree[to] -= 1;
+
+                if indegree[to] == 0 {
+                    queue.push_back(to);
+                }
+            }
+        }
+
+        // Every node and dummy is a vertex, the first `count` of them are the nodes.
+        let mut slots = (0..count).map(Slot::Node).collect::<Vec<_>>();
+        let mut predecessors = vec![Vec::new(); count];
+        let mut successors = vec![Vec::new(); count];
+
+        let mut chains = Vec::new();
+
+        for (from, to, reversed) in edges {
+            let mut chain = vec![from];
+
+            for layer in layer_of[from] + 1..layer_of[to] {
+                slots.push(Slot::Dummy);
+                layer_of.push(layer);
+                predecessors.push(Vec::new());
+                successors.push(Vec::new());
+                chain.push(slots.len() - 1);
+            }
+
+            chain.push(to);
+
+            for pair in chain.windows(2) {
+                successors[pair[0]].push(pair[1]);
+                predecessors[pair[1]].push(pair[0]);
+            }
+
+            chains.push((chain, reversed));
+        }
+
+        let depth = layer_of.iter().max().map_or(0, |last| last + 1);
+
+        let mut layers = vec![Vec::new(); depth];
+
+        for (vertex, layer) in layer_of.iter().enumerate() {
+            layers[*layer].push(vertex);
+        }
+
+        let mut position = vec![0; slots.len()];
+
+        for layer in &layers {
+            layer.iter().enumerate().for_each(|(index, vertex)| position[*vertex] = index);
+        }
+
+        for sweep in 0..SWEEPS {
+            let (order, neighbours) = match sweep % 2 {
+                | 0 => ((1..depth).collect::<Vec<_>>(), &predecessors),
+                | _ => ((0..depth.saturating_sub(1)).rev().collect(), &successors),
+            };
+
+            for layer in order {
+                // Vertices without neighbours on that side keep their place.
+                let mut keyed = layers[layer].iter().map(|vertex| {
+                    let neighbours = &neighbours[*vertex];
+
+                    let key = match neighbours.is_empty() {
+                        | true => position[*vertex] as f64,
+                        | false => neighbours.iter().map(|n| position[*n] as f64).sum::<f64>() / neighbours.len() as f64,
+                    };
+
+                    (key, *vertex)
+                }).collect::<Vec<_>>();
+
+                keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+                layers[layer] = keyed.into_iter().map(|(_, vertex)| vertex).collect();
+                layers[layer].iter().enumerate().for_each(|(index, vertex)| position[*vertex] = index);
+            }
+        }
+
+        let at = |vertex: usize| (layer_of[vertex], position[vertex]);
+
+        Layered {
+            layers: layers.iter().map(|layer| layer.iter().map(|vertex| slots[*vertex]).collect()).collect(),
+            nodes: (0..count).map(at).collect(),
+            chains: chains.into_iter().map(|(chain, reversed)| Chain {
+                slots: chain.into_iter().map(at).collect(),
+                reversed,
+            }).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Node(usize),
+    Dummy,
+}
+
+// An edge as the (layer, index) of every slot it passes through, from left to right. A reversed edge points
+// from right to left.
+#[derive(Debug)]
+struct Chain {
+    slots: Vec<(usize, usize)>,
+    reversed: bool,
+}
+
+#[derive(Debug)]
+struct Layered {
+    layers: Vec<Vec<Slot>>,
+    // Where every node ended up.
+    nodes: Vec<(usize, usize)>,
+    chains: Vec<Chain>,
+}
+
+// A slot's cells on the whole graph, which starts at (0, 0).
+#[derive(Debug, Clone, Copy)]
+struct Place {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+}
+
+impl Place {
+    #[inline(always)]
+    fn middle(&self) -> i32 {
+        self.y + self.height / 2
+    }
+
+    #[inline(always)]
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+#[derive(Debug)]
+struct Geometry {
+    places: Vec<Vec<Place>>,
+    // The column the edges leaving every layer turn in.
+    channels: Vec<i32>,
+    width: i32,
+    height: i32,
+}
+
+// The part of the graph shown in `area`, starting at `offset`.
+struct View {
+    area: Rect,
+    offset: (i32, i32),
+}
+
+impl View {
+    fn cell(&self, x: i32, y: i32) -> Option<(u16, u16)> {
+        let (x, y) = (x - self.offset.0, y - self.offset.1);
+
+        ((0..self.area.width as i32).contains(&x) && (0..self.area.height as i32).contains(&y))
+            .then(|| (self.area.x + x as u16, self.area.y + y as u16))
+    }
+
+    fn put(&self, buf: &mut Buffer, x: i32, y: i32, text: &str, style: Style) {
+        for (column, c) in (x..).zip(text.chars()) {
+            if let Some((x, y)) = self.cell(column, y) {
+                buf.get_mut(x, y).set_char(c).set_style(style);
+            }
+        }
+    }
+}
+
+// The directions every visible cell of the edges connects to.
+struct Lines {
+    view: View,
+    bits: Vec<u8>,
+}
+
+impl Lines {
+    fn mark(&mut self, x: i32, y: i32, bit: u8) {
+        if let Some((x, y)) = self.view.cell(x, y) {
+            let area = self.view.area;
+            self.bits[(y - area.y) as usize * area.width as usize + (x - area.x) as usize] |= bit;
+        }
+    }
+
+    // Connects the cells along straight runs between the points.
+    fn trace(&mut self, points: &[(i32, i32)]) {
+        for pair in points.windows(2) {
+            let ((mut x, mut y), to) = (pair[0], pair[1]);
+
+            while (x, y) != to {
+                let (dx, dy, out, into) = if to.0 > x {
+                    (1, 0, RIGHT, LEFT)
+                } else if to.0 < x {
+                    (-1, 0, LEFT, RIGHT)
+                } else if to.1 > y {
+                    (0, 1, DOWN, UP)
+                } else {
+                    (0, -1, UP, DOWN)
+                };
+
+                self.mark(x, y, out);
+                self.mark(x + dx, y + dy, into);
+
+                (x, y) = (x + dx, y + dy);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NodeSelected {
+    pub index: usize,
+}
+
+// The result of `GraphView::spawn_layout`, delivered to the active screen as a custom message.
+#[derive(Debug)]
+pub struct LayoutReady {
+    revision: u64,
+    layout: Layered,
+}
+
+// Draws a directed graph in layers from left to right, edges point from a node's right side into the left
+// side of the next. Edges closing a cycle point back to the left instead.
+//
+// Nothing is shown until the graph is laid out, either right away with `layout` or, for large graphs, on a
+// background thread with `spawn_layout` and then `apply` once the result arrives.
+//
+// The arrow keys and clicking select nodes, tab cycles through them in the order they were added. Shift and the
+// arrow keys or dragging pan, plus and minus or the mouse wheel zoom and home returns to the top left.
+#[derive(Debug)]
+pub struct GraphView {
+    graph: Graph,
+    layout: Option<Layered>,
+    // Bumped for every new graph, so a layout that took too long isn't applied to the wrong one.
+    revision: u64,
+    selected: Option<usize>,
+    // Into `SCALES`, zero is the most detailed.
+    zoom: usize,
+    offset: (i32, i32),
+    node_style: Style,
+    selected_style: Style,
+    edge_style: Style,
+    area: Cell<Rect>,
+    drag: Option<(u16, u16, (i32, i32))>,
+}
+
+impl GraphView {
+    pub fn new(graph: Graph) -> Self {
+        Self {
+            graph,
+            layout: None,
+            revision: 0,
+            selected: None,
+            zoom: 0,
+            offset: (0, 0),
+            node_style: Style::default(),
+            selected_style: Style::default().add_modifier(Modifier::REVERSED),
+            edge_style: Style::default().add_modifier(Modifier::DIM),
+            area: Cell::new(Rect::default()),
+            drag: None,
+        }
+    }
+
+    pub fn node_style(mut self, style: Style) -> Self {
+        self.node_style = style;
+        self
+    }
+
+    // Patched over the node style.
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    pub fn edge_style(mut self, style: Style) -> Self {
+        self.edge_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn graph(&self) -> &Graph {
+        &self.graph
+    }
+
+    // Shows nothing until the new graph is laid out.
+    pub fn set_graph(&mut self, graph: Graph) {
+        self.graph = graph;
+        self.layout = None;
+        self.revision += 1;
+        self.selected = self.selected.filter(|selected| *selected < self.graph.len());
+    }
+
+    #[inline(always)]
+    pub fn is_laid_out(&self) -> bool {
+        self.layout.is_some()
+    }
+
+    pub fn layout(&mut self) {
+        self.layout = Some(self.graph.layout());
+    }
+
+    pub fn spawn_layout(&self) -> Command {
+        let (graph, revision) = (self.graph.clone(), self.revision);
+
+        command::spawn(move || Message::custom(LayoutReady { revision, layout: graph.layout() }))
+    }
+
+    // Returns whether the layout was for the current graph.
+    pub fn apply(&mut self, ready: LayoutReady) -> bool {
+        if ready.revision != self.revision {
+            return false;
+        }
+
+        self.layout = Some(ready.layout);
+        self.clamp_offset();
+
+        true
+    }
+
+    #[inline(always)]
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    // Scrolls the node into view.
+    pub fn select(&mut self, index: usize) -> Option<NodeSelected> {
+        if index >= self.graph.len() || self.selected == Some(index) {
+            return None;
+        }
+
+        self.selected = Some(index);
+        self.reveal();
+
+        Some(NodeSelected { index })
+    }
+
+    fn label(&self, node: usize, scale: Scale) -> String {
+        let label = &self.graph.labels[node];
+
+        match label.chars().count() > scale.max_label {
+            | true => label.chars().take(scale.max_label.saturating_sub(1)).chain(['…']).collect(),
+            | false => label.clone(),
+        }
+    }
+
+    fn geometry(&self) -> Option<(&Layered, Geometry)> {
+        let layout = self.layout.as_ref()?;
+        let scale = SCALES[self.zoom];
+
+        let node_height = if scale.boxed { 3 } else { 1 };
+        let padding = if scale.boxed { 4 } else { 2 };
+        let slot_height = node_height + scale.row_gap;
+
+        let tallest = layout.layers.iter().map(Vec::len).max().unwrap_or(0) as i32;
+
+        let (mut places, mut channels, mut x) = (Vec::new(), Vec::new(), 0);
+
+        for layer in &layout.layers {
+            let widths = layer.iter().map(|slot| match slot {
+                | Slot::Node(node) => self.label(*node, scale).chars().count() as i32 + padding,
+                | Slot::Dummy => 1,
+            }).collect::<Vec<_>>();
+
+            let width = widths.iter().copied().max().unwrap_or(1);
+
+            // Shorter layers are centered on the tallest one.
+            let top = (tallest - layer.len() as i32) * slot_height / 2;
+
+            places.push(layer.iter().zip(widths).enumerate().map(|(index, (slot, own))| {
+                let y = top + index as i32 * slot_height;
+
+                match slot {
+                    | Slot::Node(_) => Place { x, y, width: own, height: node_height },
+                    | Slot::Dummy => Place { x, y: y + node_height / 2, width, height: 1 },
+                }
+            }).collect());
+
+            channels.push(x + width + scale.layer_gap / 2);
+
+            x += width + scale.layer_gap;
+        }
+
+        let geometry = Geometry {
+            places,
+            channels,
+            width: (x - scale.layer_gap).max(0),
+            height: (tallest * slot_height - scale.row_gap).max(0),
+        };
+
+        Some((layout, geometry))
+    }
+
+    fn node_at(&self, x: i32, y: i32) -> Option<usize> {
+        let (layout, geometry) = self.geometry()?;
+
+        layout.nodes.iter().position(|(layer, index)| geometry.places[*layer][*index].contains(x, y))
+    }
+
+    fn clamp_offset(&mut self) {
+        let Some((_, geometry)) = self.geometry() else {
+            return;
+        };
+
+        let area = self.area.get();
+
+        self.offset = (
+            self.offset.0.clamp(0, (geometry.width - area.width as i32).max(0)),
+            self.offset.1.clamp(0, (geometry.height - area.height as i32).max(0)),
+        );
+    }
+
+    fn reveal(&mut self) {
+        let Some((layout, geometry)) = self.geometry() else {
+            return;
+        };
+
+        let Some((layer, index)) = self.selected.map(|selected| layout.nodes[selected]) else {
+            return;
+        };
+
+        let (place, area) = (geometry.places[layer][index], self.area.get());
+
+        let reveal = |offset: i32, start: i32, length: i32, visible: u16| {
+            offset.min(start).max(start + length - visible as i32)
+        };
+
+        self.offset = (
+            reveal(self.offset.0, place.x, place.width, area.width),
+            reveal(self.offset.1, place.y, place.height, area.height),
+        );
+    }
+
+    fn pan(&mut self, dx: i32, dy: i32) {
+        self.offset = (self.offset.0 + dx, self.offset.1 + dy);
+        self.clamp_offset();
+    }
+
+    fn set_zoom(&mut self, zoom: usize) {
+        self.zoom = zoom.min(SCALES.len() - 1);
+        self.clamp_offset();
+        self.reveal();
+    }
+
+    // The closest node in the layer next to the selected one, or the next one in its own layer.
+    fn neighbour(&self, layers: isize, rows: isize) -> Option<usize> {
+        let (layout, geometry) = self.geometry()?;
+
+        let Some(selected) = self.selected else {
+            return (!self.graph.is_empty()).then_some(0);
+        };
+
+        let (layer, index) = layout.nodes[selected];
+
+        if layers == 0 {
+            let mut index = index as isize + rows;
+
+            while let Some(slot) = layout.layers[layer].get(usize::try_from(index).ok()?) {
+                if let Slot::Node(node) = slot {
+                    return Some(*node);
+                }
+
+                index += rows;
+            }
+
+            return None;
+        }
+
+        let middle = geometry.places[layer][index].middle();
+        let next = layer.checked_add_signed(layers)?;
+
+        layout.layers.get(next)?.iter().zip(&geometry.places[next])
+            .filter_map(|(slot, place)| match slot {
+                | Slot::Node(node) => Some((*node, (place.middle() - middle).abs())),
+                | Slot::Dummy => None,
+            })
+            .min_by_key(|(_, distance)| *distance)
+            .map(|(node, _)| node)
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<NodeSelected> {
+        let count = self.graph.len();
+
+        let step = match key.code {
+            | KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down
+                if key.modifiers.contains(KeyModifiers::SHIFT) =>
+            {
+                match key.code {
+                    | KeyCode::Left => self.pan(-PAN, 0),
+                    | KeyCode::Right => self.pan(PAN, 0),
+                    | KeyCode::Up => self.pan(0, -PAN / 2),
+                    | _ => self.pan(0, PAN / 2),
+                }
+
+                None
+            },
+            | KeyCode::Left => self.neighbour(-1, 0),
+            | KeyCode::Right => self.neighbour(1, 0),
+            | KeyCode::Up => self.neighbour(0, -1),
+            | KeyCode::Down => self.neighbour(0, 1),
+            | KeyCode::Tab if count > 0 => Some(self.selected.map_or(0, |selected| (selected + 1) % count)),
+            | KeyCode::BackTab if count > 0 => Some(self.selected.map_or(count - 1, |selected| (selected + count - 1) % count)),
+            | KeyCode::Char('+') | KeyCode::Char('=') => {
+                self.set_zoom(self.zoom.saturating_sub(1));
+                None
+            },
+            | KeyCode::Char('-') => {
+                self.set_zoom(self.zoom + 1);
+                None
+            },
+            | KeyCode::Home => {
+                self.offset = (0, 0);
+                None
+            },
+            | _ => None,
+        };
+
+        step.and_then(|index| self.select(index))
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<NodeSelected> {
+        let area = self.area.get();
+        let inside = contains(area, mouse.column, mouse.row);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if inside => {
+                let x = (mouse.column - area.x) as i32 + self.offset.0;
+                let y = (mouse.row - area.y) as i32 + self.offset.1;
+
+                match self.node_at(x, y) {
+                    | Some(index) => return self.select(index),
+                    | None => self.drag = Some((mouse.column, mouse.row, self.offset)),
+                }
+            },
+            | MouseEventKind::Drag(MouseButton::Left) => {
+                if let Some((column, row, (x, y))) = self.drag {
+                    self.offset = (x + column as i32 - mouse.column as i32, y + row as i32 - mouse.row as i32);
+                    self.clamp_offset();
+                }
+            },
+            | MouseEventKind::Up(MouseButton::Left) => self.drag = None,
+            | MouseEventKind::ScrollUp if inside => self.set_zoom(self.zoom.saturating_sub(1)),
+            | MouseEventKind::ScrollDown if inside => self.set_zoom(self.zoom + 1),
+            | _ => (),
+        }
+
+        None
+    }
+}
+
+impl Widget for &GraphView {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.area.set(area);
+
+        let Some((layout, geometry)) = self.geometry() else {
+            return;
+        };
+
+        let place = |(layer, index): (usize, usize)| geometry.places[layer][index];
+
+        let mut lines = Lines {
+            view: View { area, offset: self.offset },
+            bits: vec![0; area.width as usize * area.height as usize],
+        };
+
+        let mut arrows = Vec::new();
+
+        for chain in &layout.chains {
+            for pair in chain.slots.windows(2) {
+                let (from, to) = (place(pair[0]), place(pair[1]));
+
+                // Edges pass straight through dummies and stop right before nodes.
+                let end = match layout.layers[pair[1].0][pair[1].1] {
+                    | Slot::Node(_) => to.x - 1,
+                    | Slot::Dummy => to.x + to.width,
+                };
+
+                let channel = geometry.channels[pair[0].0];
+
+                lines.trace(&[
+                    (from.x + from.width, from.middle()),
+                    (channel, from.middle()),
+                    (channel, to.middle()),
+                    (end, to.middle()),
+                ]);
+            }
+
+            let (first, last) = (place(chain.slots[0]), place(chain.slots[chain.slots.len() - 1]));
+
+            lines.mark(first.x + first.width, first.middle(), LEFT);
+            lines.mark(last.x - 1, last.middle(), RIGHT);
+
+            arrows.push(match chain.reversed {
+                | true => (first.x + first.width, first.middle(), "◀"),
+                | false => (last.x - 1, last.middle(), "▶"),
+            });
+        }
+
+        let Lines { view, bits } = lines;
+
+        for (index, bits) in bits.into_iter().enumerate().filter(|(_, bits)| *bits != 0) {
+            let (x, y) = (area.x + (index % area.width as usize) as u16, area.y + (index / area.width as usize) as u16);
+
+            buf.set_string(x, y, LINES[bits as usize], self.edge_style);
+        }
+
+        for (x, y, arrow) in arrows {
+            view.put(buf, x, y, arrow, self.edge_style);
+        }
+
+        let scale = SCALES[self.zoom];
+
+        for (node, at) in layout.nodes.iter().enumerate() {
+            let Place { x, y, width, .. } = place(*at);
+
+            let style = match self.selected == Some(node) {
+                | true => self.node_style.patch(self.selected_style),
+                | false => self.node_style,
+            };
+
+            let label = self.label(node, scale);
+
+            match scale.boxed {
+                | true => {
+                    let border = "─".repeat(width as usize - 2);
+
+                    view.put(buf, x, y, &format!("┌{border}┐"), style);
+                    view.put(buf, x, y + 1, &format!("│ {label} │"), style);
+                    view.put(buf, x, y + 2, &format!("└{border}┘"), style);
+                },
+                | false => view.put(buf, x, y, &format!("[{label}]"), style),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    // a → b → c, with a shortcut from a to c and c leading back to b.
+    fn cyclic() -> Graph {
+        let mut graph = Graph::new();
+        let (a, b, c) = (graph.node("a"), graph.node("b"), graph.node("c"));
+
+        graph.edge(a, b);
+        graph.edge(b, c);
+        graph.edge(a, c);
+        graph.edge(a, c);
+        graph.edge(c, b);
+        graph.edge(c, c);
+
+        graph
+    }
+
+    #[test]
+    fn nodes_are_layered_after_their_predecessors() {
+        let layered = cyclic().layout();
+
+        assert_eq!(layered.nodes.iter().map(|(layer, _)| *layer).collect::<Vec<_>>(), [0, 1, 2]);
+        // The shortcut passes through a dummy in the middle layer.
+        assert_eq!(layered.layers[1].len(), 2);
+        assert_eq!(layered.chains.iter().map(|chain| chain.reversed).collect::<Vec<_>>(), [false, false, true, false]);
+    }
+
+    #[test]
+    fn edges_are_drawn_between_the_layers() {
+        let mut graph = Graph::new();
+        let (a, b) = (graph.node("a"), graph.node("b"));
+
+        graph.edge(b, a);
+
+        let mut view = GraphView::new(graph);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 16, 3));
+
+        (&view).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), ["                "; 3], "not laid out yet");
+
+        view.layout();
+        (&view).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), ["┌───┐      ┌───┐", "│ b │─────▶│ a │", "└───┘      └───┘"]);
+
+        let mut buf = Buffer::empty(buf.area);
+
+        view.handle_key(&key(KeyCode::Char('-')));
+        (&view).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf)[0], "[b]───▶[a]      ");
+    }
+
+    #[test]
+    fn nodes_are_selected_by_keys_and_clicks() {
+        let mut view = GraphView::new(cyclic());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 5));
+
+        view.layout();
+        view.handle_key(&key(KeyCode::Char('-')));
+        (&view).render(buf.area, &mut buf);
+
+        assert_eq!(view.handle_key(&key(KeyCode::Right)), Some(NodeSelected { index: 0 }));
+        assert_eq!(view.handle_key(&key(KeyCode::Right)), Some(NodeSelected { index: 1 }));
+        assert_eq!(view.handle_key(&key(KeyCode::BackTab)), Some(NodeSelected { index: 0 }));
+        assert_eq!(view.handle_key(&key(KeyCode::Left)), None);
+
+        let (layer, index) = view.layout.as_ref().unwrap().nodes[2];
+        let place = view.geometry().unwrap().1.places[layer][index];
+        let click = mouse(MouseEventKind::Down(MouseButton::Left), place.x as u16, place.y as u16);
+
+        assert_eq!(view.handle_mouse(&click), Some(NodeSelected { index: 2 }));
+    }
+
+    #[test]
+    fn layouts_of_replaced_graphs_are_not_applied() {
+        let mut view = GraphView::new(cyclic());
+        let stale = LayoutReady { revision: view.revision, layout: view.graph().layout() };
+
+        view.select(2);
+        view.set_graph(Graph::new());
+
+        assert_eq!(view.selected(), None);
+        assert!(!view.apply(stale));
+        assert!(!view.is_laid_out());
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::widgets::Widget;
+
+use crate::message::KeyMsg;
+
+// Also told whether the item has the focus.
+type Render = Box<dyn Fn(Rect, &mut Buffer, bool)>;
+
+pub struct GridItem {
+    row: usize,
+    column: usize,
+    row_span: usize,
+    column_span: usize,
+    render: Render,
+}
+
+impl GridItem {
+    pub fn new<F>(row: usize, column: usize, render: F) -> Self
+        where F: Fn(Rect, &mut Buffer, bool) + 'static,
+    {
+        Self {
+            row,
+            column,
+            row_span: 1,
+            column_span: 1,
+            render: Box::new(render),
+        }
+    }
+
+    pub fn span(mut self, rows: usize, columns: usize) -> Self {
+        self.row_span = rows.max(1);
+        self.column_span = columns.max(1);
+        self
+    }
+
+    #[inline(always)]
+    fn covers(&self, row: usize, column: usize) -> bool {
+        (self.row..self.row + self.row_span).contains(&row) && (self.column..self.column + self.column_span).contains(&column)
+    }
+}
+
+impl fmt::Debug for GridItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GridItem")
+            .field("row", &self.row)
+            .field("column", &self.column)
+            .field("row_span", &self.row_span)
+            .field("column_span", &self.column_span)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Grid {
+    rows: Vec<Constraint>,
+    columns: Vec<Constraint>,
+    gap: u16,
+    items: Vec<GridItem>,
+    focused: Option<usize>,
+}
+
+impl Grid {
+    pub fn new<R, C>(rows: R, columns: C) -> Self
+        where R: IntoIterator<Item = Constraint>, C: IntoIterator<Item = Constraint>,
+    {
+        Self {
+            rows: rows.into_iter().collect(),
+            columns: columns.into_iter().collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn gap(mut self, gap: u16) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    // The first item gets the focus, the ones after it can be reached with `focus_next`.
+    pub fn item(mut self, item: GridItem) -> Self {
+        self.items.push(item);
+        self.focused = self.focus_order().first().copied();
+        self
+    }
+
+    #[inline(always)]
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    pub fn focus(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.focused = Some(index);
+        }
+    }
+
+    // Item indices in reading order, top to bottom and left to right by their top left cell.
+    pub fn focus_order(&self) -> Vec<usize> {
+        let mut order = (0..self.items.len()).collect::<Vec<_>>();
+
+        order.sort_by_key(|&index| (self.items[index].row, self.items[index].column));
+
+        order
+    }
+
+    fn cycle_focus(&mut self, forward: bool) {
+        let order = self.focus_order();
+
+        let Some(position) = self.focused.and_then(|focused| order.iter().position(|i| *i == focused)) else {
+            self.focused = order.first().copied();
+            return;
+        };
+
+        let next = match forward {
+            | true => (position + 1) % order.len(),
+            | false => (position + order.len() - 1) % order.len(),
+        };
+
+        self.focused = Some(order[next]);
+    }
+
+    #[inline(always)]
+    pub fn focus_next(&mut self) {
+        self.cycle_focus(true)
+    }
+
+    #[inline(always)]
+    pub fn focus_previous(&mut self) {
+        self.cycle_focus(false)
+    }
+
+    // Walks cell by cell from the focused item until another item is hit, gaps in the grid are skipped.
+    pub fn focus_towards(&mut self, rows: isize, columns: isize) {
+        let Some(focused) = self.focused else {
+            return;
+        };
+
+        let item = &self.items[focused];
+
+        let (mut row, mut column) = (item.row as isize, item.column as isize);
+        let (height, width) = (self.rows.len() as isize, self.columns.len() as isize);
+
+        loop {
+            row += rows;
+            column += columns;
+
+            if !(0..height).contains(&row) || !(0..width).contains(&column) || (rows, columns) == (0, 0) {
+                return;
+            }
+
+            let hit = self.items.iter().position(|item| item.covers(row as usize, column as usize));
+
+            if let Some(index) = hit.filter(|index| *index != focused) {
+                self.focused = Some(index);
+                return;
+            }
+        }
+    }
+
+    // Tab and the arrow keys move the focus, returns whether the key was used.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> bool {
+        match key.code {
+            | KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => self.focus_previous(),
+            | KeyCode::Tab => self.focus_next(),
+            | KeyCode::BackTab => self.focus_previous(),
+            | KeyCode::Up => self.focus_towards(-1, 0),
+            | KeyCode::Down => self.focus_towards(1, 0),
+            | KeyCode::Left => self.focus_towards(0, -1),
+            | KeyCode::Right => self.focus_towards(0, 1),
+            | _ => return false,
+        }
+
+        true
+    }
+
+    // The area of every item, in the order they were added. Spans reaching past the grid are cut off.
+    pub fn layout(&self, area: Rect) -> Vec<Rect> {
+        let rows = Layout::vertical(self.rows.iter().copied()).spacing(self.gap).split(area);
+        let columns = Layout::horizontal(self.columns.iter().copied()).spacing(self.gap).split(area);
+
+        self.items.iter().map(|item| {
+            let (first_row, last_row) = (
+                rows.get(item.row),
+                rows.get((item.row + item.row_span).min(rows.len()).saturating_sub(1)),
+            );
+            let (first_column, last_column) = (
+                columns.get(item.column),
+                columns.get((item.column + item.column_span).min(columns.len()).saturating_sub(1)),
+            );
+
+            match (first_row, last_row, first_column, last_column) {
+                | (Some(top), Some(bottom), Some(left), Some(right)) =>
+                    Rect::new(left.x, top.y, right.right() - left.x, bottom.bottom() - top.y),
+                | _ => Rect::default(),
+            }
+        }).collect()
+    }
+}
+
+impl Widget for &Grid {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for (index, (item, area)) in self.items.iter().zip(self.layout(area)).enumerate() {
+            if !area.is_empty() {
+                (item.render)(area, buf, self.focused == Some(index));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::key;
+
+    fn nothing(_: Rect, _: &mut Buffer, _: bool) {}
+
+    // A sidebar spanning both rows, with a gap below the header.
+    //
+    //   side  head  head
+    //   side  ----  body
+    fn dashboard() -> Grid {
+        Grid::new([Constraint::Length(2); 2], [Constraint::Length(3); 3])
+            .gap(1)
+            .item(GridItem::new(1, 2, nothing))
+            .item(GridItem::new(0, 1, nothing).span(1, 2))
+            .item(GridItem::new(0, 0, nothing).span(2, 1))
+    }
+
+    #[test]
+    fn spans_cover_the_gaps_between_their_cells() {
+        assert_eq!(dashboard().layout(Rect::new(0, 0, 11, 5)), [
+            Rect::new(8, 3, 3, 2),
+            Rect::new(4, 0, 7, 2),
+            Rect::new(0, 0, 3, 5),
+        ]);
+    }
+
+    #[test]
+    fn the_focus_moves_in_reading_order_and_by_direction() {
+        let mut grid = dashboard();
+
+        assert_eq!(grid.focus_order(), [2, 1, 0]);
+        assert_eq!(grid.focused(), Some(2));
+
+        grid.handle_key(&key(KeyCode::Tab));
+
+        assert_eq!(grid.focused(), Some(1));
+
+        grid.handle_key(&key(KeyCode::Down));
+
+        assert_eq!(grid.focused(), Some(1), "nothing below the header's first column");
+
+        grid.focus_towards(0, 1);
+        grid.handle_key(&key(KeyCode::Left));
+
+        assert_eq!(grid.focused(), Some(2));
+
+        grid.focus(0);
+        grid.handle_key(&key(KeyCode::Up));
+        grid.handle_key(&key(KeyCode::BackTab));
+
+        assert_eq!(grid.focused(), Some(2));
+        assert!(!grid.handle_key(&key(KeyCode::Enter)));
+    }
+
+    #[test]
+    fn only_the_focused_item_is_told_it_has_the_focus() {
+        let mark = |area: Rect, buf: &mut Buffer, focused: bool| {
+            buf.get_mut(area.x, area.y).set_char(if focused { '*' } else { '.' });
+        };
+
+        let grid = Grid::new([Constraint::Length(1)], [Constraint::Length(1); 2])
+            .item(GridItem::new(0, 0, mark))
+            .item(GridItem::new(0, 1, mark))
+            .item(GridItem::new(3, 0, mark));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+
+        (&grid).render(buf.area, &mut buf);
+
+        assert_eq!((buf.get(0, 0).symbol(), buf.get(1, 0).symbol()), ("*", "."));
+    }
+}
@@ -0,0 +1,270 @@
+use std::time;
+use std::cell::Cell;
+use std::collections::HashMap;
+
+use crossterm::event::MouseEventKind;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::command::Command;
+use crate::message::MouseMsg;
+use crate::scheduler::civil;
+use crate::tooltip::Tooltip;
+
+const MONTHS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+// Room for the weekday labels on the left, every other row is labelled like on GitHub.
+const WEEKDAYS: [&str; 7] = ["", "Mon", "", "Wed", "", "Fri", ""];
+const LABEL_WIDTH: u16 = 4;
+
+const CELL: &str = "■";
+const CELL_WIDTH: u16 = 2;
+
+// The delay before a hovered day shows its value.
+const HOVER_DELAY: time::Duration = time::Duration::from_millis(300);
+
+#[inline(always)]
+fn day_of(time: time::SystemTime) -> i64 {
+    match time.duration_since(time::UNIX_EPOCH) {
+        | Ok(since) => (since.as_secs() / 86400) as i64,
+        | Err(before) => -(before.duration().as_secs().div_ceil(86400) as i64),
+    }
+}
+
+// The epoch was a thursday, weeks start on sunday.
+#[inline(always)]
+fn weekday(day: i64) -> i64 {
+    (day + 4).rem_euclid(7)
+}
+
+// A contribution graph, one column per week and one row per weekday, in UTC. Values on the same day add up.
+#[derive(Debug)]
+pub struct Heatmap {
+    values: HashMap<i64, f64>,
+    last_day: i64,
+    weeks: u16,
+    levels: Vec<Color>,
+    style: Style,
+    // The top left cell as last rendered, and the first day shown there.
+    origin: Cell<(Rect, i64)>,
+    hovered: Option<i64>,
+}
+
+impl Heatmap {
+    // Ends with the week `until` falls in.
+    pub fn new(until: time::SystemTime) -> Self {
+        Self {
+            values: HashMap::new(),
+            last_day: day_of(until),
+            weeks: 53,
+            levels: vec![
+                Color::Rgb(22, 27, 34),
+                Color::Rgb(14, 68, 41),
+                Color::Rgb(0, 109, 50),
+                Color::Rgb(38, 166, 65),
+                Color::Rgb(57, 211, 83),
+            ],
+            style: Style::default(),
+            origin: Cell::new((Rect::default(), 0)),
+            hovered: None,
+        }
+    }
+
+    pub fn weeks(mut self, weeks: u16) -> Self {
+        self.weeks = weeks.max(1);
+        self
+    }
+
+    // From empty to the busiest day, at least two of them.
+    pub fn levels<I: IntoIterator<Item = Color>>(mut self, levels: I) -> Self {
+        let levels = levels.into_iter().collect::<Vec<_>>();
+
+        if levels.len() >= 2 {
+            self.levels = levels;
+        }
+
+        self
+    }
+
+    // For the labels.
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn value(mut self, day: time::SystemTime, value: f64) -> Self {
+        self.add(day, value);
+        self
+    }
+
+    pub fn values<I>(mut self, values: I) -> Self
+        where I: IntoIterator<Item = (time::SystemTime, f64)>,
+    {
+        values.into_iter().for_each(|(day, value)| self.add(day, value));
+        self
+    }
+
+    pub fn add(&mut self, day: time::SystemTime, value: f64) {
+        *self.values.entry(day_of(day)).or_default() += value;
+    }
+
+    // Zero for days without a value, the levels above it split the range up to the busiest day evenly.
+    fn level(&self, day: i64, max: f64) -> usize {
+        let value = self.values.get(&day).copied().unwrap_or(0.);
+        let steps = (self.levels.len() - 1) as f64;
+
+        match value > 0. && max > 0. {
+            | true => ((value / max * steps).ceil() as usize).clamp(1, self.levels.len() - 1),
+            | false => 0,
+        }
+    }
+
+    fn day_at(&self, column: u16, row: u16) -> Option<(i64, Rect)> {
+        let (origin, first) = self.origin.get();
+
+        if origin.is_empty() || column < origin.x || row < origin.y || row >= origin.y + 7 {
+            return None;
+        }
+
+        let (week, weekday) = ((column - origin.x) / CELL_WIDTH, row - origin.y);
+
+        let day = first + week as i64 * 7 + weekday as i64;
+
+        (week < origin.width && day <= self.last_day)
+            .then(|| (day, Rect::new(origin.x + week * CELL_WIDTH, row, 1, 1)))
+    }
+
+    // Hovering a day shows its date and value in a tooltip.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<Command> {
+        if !matches!(mouse.kind, MouseEventKind::Moved) {
+            return None;
+        }
+
+        match self.day_at(mouse.column, mouse.row) {
+            | Some((day, _)) if self.hovered == Some(day) => None,
+            | Some((day, anchor)) => {
+                self.hovered = Some(day);
+
+                let (year, month, date) = civil(day);
+                let value = self.values.get(&day).copied().unwrap_or(0.);
+
+                let text = format!("{year}-{month:02}-{date:02}: {value}");
+
+                Some(Command::ShowTooltip(Tooltip::new(anchor, text).delay(HOVER_DELAY)))
+            },
+            | None => self.hovered.take().map(|_| Command::HideTooltip),
+        }
+    }
+}
+
+impl Widget for &Heatmap {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // The month labels take up the first row.
+        let grid = Rect {
+            x: area.x + LABEL_WIDTH.min(area.width),
+            y: area.y + 1.min(area.height),
+            width: area.width.saturating_sub(LABEL_WIDTH),
+            height: area.height.saturating_sub(1).min(7),
+        };
+
+        // Only the most recent weeks are shown when not all of them fit.
+        let weeks = self.weeks.min(grid.width / CELL_WIDTH);
+
+        let first = self.last_day - weekday(self.last_day) - 7 * (weeks as i64 - 1);
+
+        self.origin.set((Rect { width: weeks, ..grid }, first));
+
+        if weeks == 0 || grid.height == 0 {
+            return;
+        }
+
+        let max = (0..weeks as i64 * 7)
+            .filter_map(|offset| self.values.get(&(first + offset)))
+            .fold(0., |max: f64, value| max.max(*value));
+
+        for (row, label) in WEEKDAYS.iter().enumerate().take(grid.height as usize) {
+            buf.set_stringn(area.x, grid.y + row as u16, label, LABEL_WIDTH as usize, self.style);
+        }
+
+        for week in 0..weeks {
+            let start = first + week as i64 * 7;
+            let x = grid.x + week * CELL_WIDTH;
+
+            // A month is labelled above the first week starting in it, if the label fits before the next one.
+            let (_, month, date) = civil(start);
+
+            if date <= 7 && week + 2 <= weeks && area.height > 0 {
+                buf.set_stringn(x, area.y, MONTHS[month as usize - 1], grid.right().saturating_sub(x) as usize, self.style);
+            }
+
+            for weekday in 0..grid.height {
+                let day = start + weekday as i64;
+
+                if day > self.last_day {
+                    break;
+                }
+
+                let color = self.levels[self.level(day, max)];
+
+                buf.set_string(x, grid.y + weekday, CELL, Style::default().fg(color));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::mouse;
+
+    // Noon on the day in january 2024, the 1st was a monday.
+    fn january(day: u64) -> time::SystemTime {
+        time::UNIX_EPOCH + time::Duration::from_secs(1704067200 + (day - 1) * 86400 + 43200)
+    }
+
+    fn heatmap() -> Heatmap {
+        Heatmap::new(january(17))
+            .weeks(4)
+            .levels([Color::Black, Color::Gray, Color::White])
+            .values([(january(2), 1.), (january(3), 2.), (january(3), 2.)])
+    }
+
+    #[test]
+    fn days_are_colored_relative_to_the_busiest_one() {
+        let heatmap = heatmap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 8));
+
+        (&heatmap).render(buf.area, &mut buf);
+
+        let row = |y| (0..12).map(|x| buf.get(x, y).symbol()).collect::<String>();
+
+        assert_eq!(row(0), "        Jan ");
+        assert_eq!(row(4), "Wed ■ ■ ■ ■ ");
+        assert_eq!(row(5), "    ■ ■ ■   ", "nothing after the last day");
+
+        assert_eq!(buf.get(6, 3).fg, Color::Gray);
+        assert_eq!(buf.get(6, 4).fg, Color::White);
+        assert_eq!(buf.get(8, 4).fg, Color::Black);
+    }
+
+    #[test]
+    fn hovering_a_day_shows_its_value() {
+        let mut heatmap = heatmap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 8));
+        let hover = |column, row| mouse(MouseEventKind::Moved, column, row);
+
+        (&heatmap).render(buf.area, &mut buf);
+
+        let Some(Command::ShowTooltip(_)) = heatmap.handle_mouse(&hover(7, 4)) else {
+            panic!("no tooltip for january 3rd");
+        };
+
+        assert!(heatmap.handle_mouse(&hover(6, 4)).is_none(), "still the same day");
+        assert!(matches!(heatmap.handle_mouse(&hover(0, 0)), Some(Command::HideTooltip)));
+        assert!(heatmap.handle_mouse(&hover(10, 5)).is_none());
+    }
+}
@@ -0,0 +1,120 @@
+use std::time;
+use std::cell::RefCell;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::Widget;
+
+use crate::clock::Clock;
+use crate::format;
+
+// Renders e.g. "updated 5s ago", which changes on its own while nothing else on the screen does.
+#[derive(Debug, Clone)]
+pub struct LiveText {
+    prefix: String,
+    since: time::Instant,
+    clock: Clock,
+    style: Style,
+    // What was rendered last, to tell whether a redraw would show anything new.
+    rendered: RefCell<String>,
+}
+
+impl LiveText {
+    pub fn new<T: Into<String>>(prefix: T, since: time::Instant) -> Self {
+        Self {
+            prefix: prefix.into(),
+            since,
+            clock: Clock::system(),
+            style: Style::default(),
+            rendered: RefCell::new(String::new()),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // E.g. the one of a `TestApplication`, to check what's shown after some time passed.
+    pub fn clock(mut self, clock: Clock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    #[inline(always)]
+    pub fn set_since(&mut self, since: time::Instant) {
+        self.since = since;
+    }
+
+    pub fn text(&self) -> String {
+        match self.prefix.is_empty() {
+            | true => format::relative(self.clock.elapsed(self.since)),
+            | false => format!("{} {}", self.prefix, format::relative(self.clock.elapsed(self.since))),
+        }
+    }
+
+    // How long until the text changes, the cadence slows down as the shown unit grows.
+    #[inline(always)]
+    pub fn refresh_in(&self) -> time::Duration {
+        self.next_change().saturating_duration_since(self.clock.now())
+    }
+
+    // For `Screen::next_change`, so the screen is drawn again once the text changes.
+    pub fn next_change(&self) -> time::Instant {
+        let elapsed = self.clock.elapsed(self.since);
+
+        let unit = match elapsed.as_secs() {
+            | 0..=59 => 1,
+            | 60..=3599 => 60,
+            | 3600..=86399 => 3600,
+            | _ => 86400,
+        };
+
+        self.since + time::Duration::from_secs((elapsed.as_secs() / unit + 1) * unit)
+    }
+
+    #[inline(always)]
+    pub fn is_stale(&self) -> bool {
+        *self.rendered.borrow() != self.text()
+    }
+}
+
+impl Widget for &LiveText {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let text = self.text();
+
+        buf.set_stringn(area.x, area.y, &text, area.width as usize, self.style);
+
+        *self.rendered.borrow_mut() = text;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_on_the_next_shown_unit() {
+        let clock = Clock::manual();
+        let text = LiveText::new("", clock.now()).clock(clock.clone());
+
+        clock.advance(time::Duration::from_millis(1500));
+
+        assert_eq!(text.refresh_in(), time::Duration::from_millis(500));
+
+        clock.advance(time::Duration::from_secs(90));
+
+        assert_eq!(text.refresh_in(), time::Duration::from_millis(28_500));
+    }
+
+    #[test]
+    fn is_not_stale_right_after_rendering() {
+        let text = LiveText::new("updated", time::Instant::now());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 1));
+
+        text.render(buf.area, &mut buf);
+
+        assert!(!text.is_stale());
+    }
+}
@@ -0,0 +1,245 @@
+use std::cell::Cell;
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+
+use crate::components::{contains, Scrollable};
+use crate::message::MouseMsg;
+
+// The offset of every dot of a braille cell, in the order of their bits.
+const DOTS: [(usize, usize); 8] = [(0, 0), (0, 1), (0, 2), (1, 0), (1, 1), (1, 2), (0, 3), (1, 3)];
+
+const BRAILLE: u32 = 0x2800;
+
+// Whether any cell in the block isn't blank.
+fn filled(content: &Buffer, x: usize, y: usize, width: usize, height: usize) -> bool {
+    let area = content.area;
+
+    (y..(y + height).min(area.height as usize)).any(|y| {
+        (x..(x + width).min(area.width as usize)).any(|x| {
+            !content.get(area.x + x as u16, area.y + y as u16).symbol().trim().is_empty()
+        })
+    })
+}
+
+// An overview of the whole content next to the viewport, every braille dot standing for a block of cells
+// that isn't blank. The part currently in view is highlighted, clicking or dragging on the map jumps there.
+#[derive(Debug)]
+pub struct MinimapAdapter {
+    width: u16,
+    style: Style,
+    viewport_style: Style,
+    wheel_step: usize,
+    // Remembered from the last render, so mouse events can be mapped onto the content.
+    viewport: Cell<Rect>,
+    map: Cell<Rect>,
+    rows_per_cell: Cell<usize>,
+    dragging: bool,
+}
+
+impl Default for MinimapAdapter {
+    fn default() -> Self {
+        Self {
+            width: 12,
+            style: Style::default().add_modifier(Modifier::DIM),
+            viewport_style: Style::default().bg(Color::DarkGray),
+            wheel_step: 3,
+            viewport: Cell::new(Rect::default()),
+            map: Cell::new(Rect::default()),
+            rows_per_cell: Cell::new(4),
+            dragging: false,
+        }
+    }
+}
+
+impl MinimapAdapter {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // Patched over the style of the rows in view.
+    pub fn viewport_style(mut self, style: Style) -> Self {
+        self.viewport_style = style;
+        self
+    }
+
+    pub fn wheel_step(mut self, step: usize) -> Self {
+        self.wheel_step = step;
+        self
+    }
+
+    // Returns the area left over for the content, the map takes the rightmost columns.
+    pub fn layout(&self, area: Rect) -> Rect {
+        Rect { width: area.width.saturating_sub(self.width), ..area }
+    }
+
+    // `content` holds all of it, like a paragraph rendered into a buffer as tall as its lines. Content that
+    // doesn't fit is squeezed, but never stretched.
+    pub fn render<S: Scrollable>(&self, scrollable: &S, content: &Buffer, area: Rect, buf: &mut Buffer) {
+        let viewport = self.layout(area);
+        let map = Rect { x: viewport.right(), width: area.width - viewport.width, ..area };
+
+        self.viewport.set(viewport);
+        self.map.set(map);
+
+        if map.is_empty() {
+            return;
+        }
+
+        let columns_per_dot = (content.area.width as usize).div_ceil(map.width as usize * 2).max(1);
+        let rows_per_dot = (content.area.height as usize).div_ceil(map.height as usize * 4).max(1);
+
+        let rows_per_cell = rows_per_dot * 4;
+
+        self.rows_per_cell.set(rows_per_cell);
+
+        let offset = scrollable.offset();
+        let shown = offset / rows_per_cell..(offset + viewport.height as usize).div_ceil(rows_per_cell);
+
+        for row in 0..map.height {
+            let style = match shown.contains(&(row as usize)) {
+                | true => self.style.patch(self.viewport_style),
+                | false => self.style,
+            };
+
+            for column in 0..map.width {
+                let bits = DOTS.iter().enumerate()
+                    .filter(|(_, (dx, dy))| {
+                        let x = (column as usize * 2 + dx) * columns_per_dot;
+                        let y = (row as usize * 4 + dy) * rows_per_dot;
+
+                        filled(content, x, y, columns_per_dot, rows_per_dot)
+                    })
+                    .fold(0, |bits, (bit, _)| bits | 1 << bit);
+
+                let dots = char::from_u32(BRAILLE + bits).unwrap_or(' ');
+
+                buf.get_mut(map.x + column, map.y + row).set_char(dots).set_style(style);
+            }
+        }
+    }
+
+    fn max_offset<S: Scrollable>(&self, scrollable: &S) -> usize {
+        let viewport = self.viewport.get();
+
+        scrollable.content_length(viewport).saturating_sub(viewport.height as usize)
+    }
+
+    // Centers the viewport on the content under the row.
+    fn jump_to<S: Scrollable>(&self, scrollable: &mut S, row: u16) {
+        let (map, rows_per_cell) = (self.map.get(), self.rows_per_cell.get());
+
+        let relative = row.saturating_sub(map.y).min(map.height.saturating_sub(1)) as usize;
+        let target = relative * rows_per_cell + rows_per_cell / 2;
+
+        let offset = target.saturating_sub(self.viewport.get().height as usize / 2);
+
+        scrollable.set_offset(offset.min(self.max_offset(scrollable)));
+    }
+
+    // Returns whether the event was consumed by the map.
+    pub fn handle_mouse<S: Scrollable>(&mut self, scrollable: &mut S, mouse: &MouseMsg) -> bool {
+        let on_map = contains(self.map.get(), mouse.column, mouse.row);
+        let on_viewport = on_map || contains(self.viewport.get(), mouse.column, mouse.row);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if on_map => {
+                self.dragging = true;
+                self.jump_to(scrollable, mouse.row);
+            },
+            | MouseEventKind::Drag(MouseButton::Left) if self.dragging => self.jump_to(scrollable, mouse.row),
+            | MouseEventKind::Up(MouseButton::Left) if self.dragging => self.dragging = false,
+            | MouseEventKind::ScrollDown if on_viewport => {
+                let offset = (scrollable.offset() + self.wheel_step).min(self.max_offset(scrollable));
+                scrollable.set_offset(offset);
+            },
+            | MouseEventKind::ScrollUp if on_viewport => {
+                scrollable.set_offset(scrollable.offset().saturating_sub(self.wheel_step));
+            },
+            | _ => return false,
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::mouse;
+
+    struct Lines {
+        count: usize,
+        offset: usize,
+    }
+
+    impl Scrollable for Lines {
+        fn content_length(&self, _: Rect) -> usize {
+            self.count
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, offset: usize) {
+            self.offset = offset;
+        }
+    }
+
+    // Four columns and sixteen rows with two marks, on a map one cell wide and four tall.
+    fn rendered(lines: &Lines) -> (MinimapAdapter, Buffer) {
+        let adapter = MinimapAdapter::new().width(1).wheel_step(3);
+        let mut content = Buffer::empty(Rect::new(0, 0, 4, 16));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 4));
+
+        content.get_mut(0, 0).set_char('x');
+        content.get_mut(3, 5).set_char('y');
+
+        adapter.render(lines, &content, buf.area, &mut buf);
+
+        (adapter, buf)
+    }
+
+    #[test]
+    fn blocks_that_arent_blank_become_dots() {
+        let (_, buf) = rendered(&Lines { count: 16, offset: 0 });
+
+        assert_eq!((0..4).map(|y| buf.get(3, y).symbol()).collect::<String>(), "⠁⠐⠀⠀");
+        assert_eq!(buf.get(3, 0).bg, Color::DarkGray);
+        assert_eq!(buf.get(3, 1).bg, Color::Reset);
+    }
+
+    #[test]
+    fn clicking_the_map_centers_the_viewport_there() {
+        let mut lines = Lines { count: 16, offset: 0 };
+        let (mut adapter, _) = rendered(&lines);
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Down(MouseButton::Left), 3, 1)));
+        assert_eq!(lines.offset, 4);
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Drag(MouseButton::Left), 0, 9)));
+        assert_eq!(lines.offset, 12, "never past the end");
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::ScrollUp, 1, 1)));
+        assert_eq!(lines.offset, 9);
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Up(MouseButton::Left), 0, 0)));
+        assert!(!adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Down(MouseButton::Left), 1, 1)));
+    }
+}
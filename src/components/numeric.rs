@@ -0,0 +1,407 @@
+use std::cell::Cell;
+use std::cmp::Ordering;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::format::{self, Locale};
+use crate::message::{KeyMsg, MouseMsg};
+
+// Returned whenever an input changed its value, e.g. to wrap into `Message::custom`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValueChanged {
+    pub value: f64,
+    pub previous: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Range {
+    min: f64,
+    max: f64,
+    step: f64,
+}
+
+impl Range {
+    // Values land on multiples of the step counted from the minimum, or from zero without one.
+    fn snap(&self, value: f64) -> f64 {
+        let value = value.clamp(self.min, self.max);
+        let origin = if self.min.is_finite() { self.min } else { 0. };
+
+        match self.step > 0. {
+            | true => (origin + ((value - origin) / self.step).round() * self.step).clamp(self.min, self.max),
+            | false => value,
+        }
+    }
+
+    fn fraction(&self, value: f64) -> f64 {
+        match self.max > self.min {
+            | true => (value - self.min) / (self.max - self.min),
+            | false => 0.,
+        }
+    }
+}
+
+impl Default for Range {
+    fn default() -> Self {
+        Self { min: f64::NEG_INFINITY, max: f64::INFINITY, step: 1. }
+    }
+}
+
+// Page up/down and shift move by this many steps at once.
+const LARGE_STEP: f64 = 10.;
+
+fn steps(key: &KeyMsg) -> f64 {
+    match key.modifiers.contains(KeyModifiers::SHIFT) {
+        | true => LARGE_STEP,
+        | false => 1.,
+    }
+}
+
+#[derive(Debug)]
+pub struct NumberInput {
+    value: f64,
+    range: Range,
+    precision: usize,
+    unit: Option<String>,
+    style: Style,
+    editing_style: Style,
+    // Typed digits, committed with enter.
+    editing: Option<String>,
+    area: Cell<Rect>,
+}
+
+impl NumberInput {
+    pub fn new(value: f64) -> Self {
+        Self {
+            value,
+            range: Range::default(),
+            precision: 0,
+            unit: None,
+            style: Style::default(),
+            editing_style: Style::default().add_modifier(Modifier::UNDERLINED),
+            editing: None,
+            area: Cell::new(Rect::default()),
+        }
+    }
+
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.range.min = min.min(max);
+        self.range.max = max.max(min);
+        self.value = self.range.snap(self.value);
+        self
+    }
+
+    pub fn step(mut self, step: f64) -> Self {
+        self.range.step = step.abs();
+        self.value = self.range.snap(self.value);
+        self
+    }
+
+    // Digits after the decimal separator.
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn unit<T: Into<String>>(mut self, unit: T) -> Self {
+        self.unit = Some(unit.into());
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn editing_style(mut self, style: Style) -> Self {
+        self.editing_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    #[inline(always)]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    pub fn set_value(&mut self, value: f64) -> Option<ValueChanged> {
+        let previous = self.value;
+
+        self.value = self.range.snap(value);
+
+        (self.value != previous).then_some(ValueChanged { value: self.value, previous })
+    }
+
+    // Accepts the locale's decimal separator as well as a dot.
+    fn commit(&mut self) -> Option<ValueChanged> {
+        let typed = self.editing.take()?;
+        let normalized = typed.replace(Locale::current().decimal_separator, ".");
+
+        normalized.parse::<f64>().ok().filter(|v| v.is_finite()).and_then(|value| self.set_value(value))
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<ValueChanged> {
+        let separator = Locale::current().decimal_separator;
+
+        match (key.code, &mut self.editing) {
+            | (KeyCode::Char(c), editing) if c.is_ascii_digit() || c == separator || c == '.' || (c == '-' && editing.is_none()) =>
+                editing.get_or_insert_with(String::new).push(c),
+            | (KeyCode::Backspace, Some(editing)) => {
+                editing.pop();
+            },
+            | (KeyCode::Esc, Some(_)) => self.editing = None,
+            | (KeyCode::Enter, Some(_)) => return self.commit(),
+            | (KeyCode::Up, None) => return self.set_value(self.value + self.range.step * steps(key)),
+            | (KeyCode::Down, None) => return self.set_value(self.value - self.range.step * steps(key)),
+            | (KeyCode::PageUp, None) => return self.set_value(self.value + self.range.step * LARGE_STEP),
+            | (KeyCode::PageDown, None) => return self.set_value(self.value - self.range.step * LARGE_STEP),
+            | (KeyCode::Home, None) if self.range.min.is_finite() => return self.set_value(self.range.min),
+            | (KeyCode::End, None) if self.range.max.is_finite() => return self.set_value(self.range.max),
+            | _ => (),
+        }
+
+        None
+    }
+
+    // The wheel adjusts the value while the pointer is over the input.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<ValueChanged> {
+        if !contains(self.area.get(), mouse.column, mouse.row) {
+            return None;
+        }
+
+        match mouse.kind {
+            | MouseEventKind::ScrollUp => self.set_value(self.value + self.range.step),
+            | MouseEventKind::ScrollDown => self.set_value(self.value - self.range.step),
+            | _ => None,
+        }
+    }
+}
+
+impl Widget for &NumberInput {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.area.set(area);
+
+        let (text, style) = match &self.editing {
+            | Some(editing) => (format!("{editing}_"), self.editing_style),
+            | None => (format::decimal(self.value, self.precision), self.style),
+        };
+
+        let text = match &self.unit {
+            | Some(unit) => format!("{text} {unit}"),
+            | None => text,
+        };
+
+        buf.set_stringn(area.x, area.y, text, area.width as usize, style);
+    }
+}
+
+#[derive(Debug)]
+pub struct Slider {
+    value: f64,
+    range: Range,
+    style: Style,
+    filled_style: Style,
+    track: Cell<Rect>,
+    dragging: bool,
+}
+
+impl Slider {
+    pub fn new(value: f64, min: f64, max: f64) -> Self {
+        let range = Range { min: min.min(max), max: max.max(min), step: 0. };
+
+        Self {
+            value: range.snap(value),
+            range,
+            style: Style::default(),
+            filled_style: Style::default(),
+            track: Cell::new(Rect::default()),
+            dragging: false,
+        }
+    }
+
+    // Without a step the keyboard moves in hundredths of the range.
+    pub fn step(mut self, step: f64) -> Self {
+        self.range.step = step.abs();
+        self.value = self.range.snap(self.value);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn filled_style(mut self, style: Style) -> Self {
+        self.filled_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: f64) -> Option<ValueChanged> {
+        let previous = self.value;
+
+        self.value = self.range.snap(value);
+
+        (self.value != previous).then_some(ValueChanged { value: self.value, previous })
+    }
+
+    fn key_step(&self) -> f64 {
+        match self.range.step > 0. {
+            | true => self.range.step,
+            | false => (self.range.max - self.range.min) / 100.,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<ValueChanged> {
+        match key.code {
+            | KeyCode::Right | KeyCode::Up => self.set_value(self.value + self.key_step() * steps(key)),
+            | KeyCode::Left | KeyCode::Down => self.set_value(self.value - self.key_step() * steps(key)),
+            | KeyCode::PageUp => self.set_value(self.value + self.key_step() * LARGE_STEP),
+            | KeyCode::PageDown => self.set_value(self.value - self.key_step() * LARGE_STEP),
+            | KeyCode::Home => self.set_value(self.range.min),
+            | KeyCode::End => self.set_value(self.range.max),
+            | _ => None,
+        }
+    }
+
+    fn drag_to(&mut self, column: u16) -> Option<ValueChanged> {
+        let track = self.track.get();
+
+        let relative = column.saturating_sub(track.x).min(track.width.saturating_sub(1)) as f64;
+        let span = track.width.saturating_sub(1).max(1) as f64;
+
+        self.set_value(self.range.min + (self.range.max - self.range.min) * relative / span)
+    }
+
+    // Clicking or dragging on the bar jumps to that position, the wheel steps.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<ValueChanged> {
+        let on_track = contains(self.track.get(), mouse.column, mouse.row);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if on_track => {
+                self.dragging = true;
+                self.drag_to(mouse.column)
+            },
+            | MouseEventKind::Drag(MouseButton::Left) if self.dragging => self.drag_to(mouse.column),
+            | MouseEventKind::Up(MouseButton::Left) => {
+                self.dragging = false;
+                None
+            },
+            | MouseEventKind::ScrollUp if on_track => self.set_value(self.value + self.key_step()),
+            | MouseEventKind::ScrollDown if on_track => self.set_value(self.value - self.key_step()),
+            | _ => None,
+        }
+    }
+}
+
+impl Widget for &Slider {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let track = Rect { height: area.height.min(1), ..area };
+
+        self.track.set(track);
+
+        if track.is_empty() {
+            return;
+        }
+
+        let handle = (self.range.fraction(self.value) * track.width.saturating_sub(1) as f64).round() as u16;
+
+        for offset in 0..track.width {
+            let (symbol, style) = match offset.cmp(&handle) {
+                | Ordering::Less => ("━", self.filled_style),
+                | Ordering::Equal => ("●", self.filled_style),
+                | Ordering::Greater => ("─", self.style),
+            };
+
+            buf.get_mut(track.x + offset, track.y).set_symbol(symbol).set_style(style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, key_with, mouse};
+
+    fn line(buf: &Buffer) -> String {
+        (0..buf.area.width).map(|x| buf.get(x, 0).symbol()).collect()
+    }
+
+    #[test]
+    fn values_snap_to_the_steps_within_the_range() {
+        let mut input = NumberInput::new(7.).range(0., 100.).step(5.);
+
+        assert_eq!(input.value(), 5.);
+        assert_eq!(input.handle_key(&key(KeyCode::Up)), Some(ValueChanged { value: 10., previous: 5. }));
+        assert_eq!(input.handle_key(&key_with(KeyCode::Up, KeyModifiers::SHIFT)).map(|c| c.value), Some(60.));
+        assert_eq!(input.handle_key(&key(KeyCode::PageUp)).map(|c| c.value), Some(100.));
+        assert_eq!(input.handle_key(&key(KeyCode::Up)), None);
+        assert_eq!(input.handle_key(&key(KeyCode::Home)).map(|c| c.value), Some(0.));
+    }
+
+    #[test]
+    fn typed_values_are_committed_with_enter() {
+        let mut input = NumberInput::new(1.).range(0., 10.).step(0.5).precision(1).unit("kg");
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 1));
+
+        for c in ['-', '2', '-', '.', '7'] {
+            input.handle_key(&key(KeyCode::Char(c)));
+        }
+
+        input.handle_key(&key(KeyCode::Backspace));
+        (&input).render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf), "-2._ kg ");
+
+        input.handle_key(&key(KeyCode::Esc));
+
+        assert!(!input.is_editing());
+
+        for c in "3.7".chars() {
+            input.handle_key(&key(KeyCode::Char(c)));
+        }
+
+        assert_eq!(input.handle_key(&key(KeyCode::Enter)), Some(ValueChanged { value: 3.5, previous: 1. }));
+
+        let mut buf = Buffer::empty(buf.area);
+
+        (&input).render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf), "3.5 kg  ");
+    }
+
+    #[test]
+    fn sliders_follow_clicks_and_drags_along_the_track() {
+        let mut slider = Slider::new(50., 0., 100.);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 11, 1));
+
+        (&slider).render(buf.area, &mut buf);
+
+        assert_eq!(line(&buf), "━━━━━●─────");
+
+        slider.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 2, 0));
+        slider.handle_mouse(&mouse(MouseEventKind::Drag(MouseButton::Left), 30, 0));
+
+        assert_eq!(slider.value(), 100.);
+
+        slider.handle_mouse(&mouse(MouseEventKind::Up(MouseButton::Left), 30, 0));
+        slider.handle_mouse(&mouse(MouseEventKind::Drag(MouseButton::Left), 0, 0));
+
+        assert_eq!(slider.value(), 100.);
+        assert_eq!(slider.handle_key(&key(KeyCode::Left)).map(|c| c.value), Some(99.));
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::Widget;
+
+use crate::format;
+use crate::message::ProgressMsg;
+
+#[derive(Debug, Default)]
+pub struct ProgressTree {
+    order: Vec<u64>,
+    nodes: HashMap<u64, ProgressMsg>,
+    style: Style,
+    bar_style: Style,
+}
+
+impl ProgressTree {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn bar_style(mut self, style: Style) -> Self {
+        self.bar_style = style;
+        self
+    }
+
+    // A parent that would make the operation its own ancestor is dropped, it's shown as a root instead.
+    pub fn update(&mut self, mut progress: ProgressMsg) {
+        if progress.parent.is_some_and(|parent| self.is_ancestor_or_self(progress.id, parent)) {
+            progress.parent = None;
+        }
+
+        if !self.nodes.contains_key(&progress.id) {
+            self.order.push(progress.id);
+        }
+
+        self.nodes.insert(progress.id, progress);
+    }
+
+    #[inline(always)]
+    pub fn get(&self, id: u64) -> Option<&ProgressMsg> {
+        self.nodes.get(&id)
+    }
+
+    // Removes the operation together with all of its children.
+    pub fn remove(&mut self, id: u64) {
+        let mut pending = vec![id];
+
+        while let Some(id) = pending.pop() {
+            if self.nodes.remove(&id).is_some() {
+                pending.extend(self.nodes.values().filter(|n| n.parent == Some(id)).map(|n| n.id));
+            }
+        }
+
+        self.order.retain(|id| self.nodes.contains_key(id));
+    }
+
+    pub fn is_finished(&self, id: u64) -> bool {
+        let rows = self.rows_from(id, 0);
+
+        !rows.is_empty() && rows.iter().all(|(_, node)| node.fraction.is_some_and(|f| f >= 1.))
+    }
+
+    // Every parent link already in the tree was checked on the way in, so following them always ends.
+    fn is_ancestor_or_self(&self, ancestor: u64, mut id: u64) -> bool {
+        loop {
+            if id == ancestor {
+                return true;
+            }
+
+            match self.nodes.get(&id).and_then(|node| node.parent) {
+                | Some(parent) => id = parent,
+                | None => return false,
+            }
+        }
+    }
+
+    fn children(&self, parent: Option<u64>) -> impl Iterator<Item = &ProgressMsg> {
+        self.order.iter()
+            .filter_map(|id| self.nodes.get(id))
+            // Operations whose parent is unknown are shown as roots.
+            .filter(move |node| match parent {
+                | None => node.parent.is_none_or(|parent| !self.nodes.contains_key(&parent)),
+                | parent => node.parent == parent,
+            })
+    }
+
+    fn rows_from(&self, id: u64, depth: usize) -> Vec<(usize, &ProgressMsg)> {
+        let mut rows = Vec::new();
+        let mut pending = vec![(id, depth)];
+
+        // Depth first without recursing, however deep the tree goes.
+        while let Some((id, depth)) = pending.pop() {
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+
+            rows.push((depth, node));
+
+            let children = self.children(Some(id)).map(|child| (child.id, depth + 1)).collect::<Vec<_>>();
+
+            // Reversed, so they're taken in order.
+            pending.extend(children.into_iter().rev());
+        }
+
+        rows
+    }
+
+    fn rows(&self) -> Vec<(usize, &ProgressMsg)> {
+        self.children(None).flat_map(|root| self.rows_from(root.id, 0)).collect()
+    }
+}
+
+#[inline(always)]
+fn format_rate(bytes_per_second: f64) -> String {
+    format!("{}/s", format::bytes(bytes_per_second))
+}
+
+impl Widget for &ProgressTree {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+
+        let bar_width = (area.width / 3).min(20);
+
+        for (row, (depth, node)) in self.rows().into_iter().take(area.height as usize).enumerate() {
+            let y = area.y + row as u16;
+
+            let percentage = match node.fraction {
+                | Some(fraction) => format!("{:>4.0}%", fraction.clamp(0., 1.) * 100.),
+                | None => String::from("  --%"),
+            };
+
+            let rate = node.bytes_per_second.map(format_rate).unwrap_or_default();
+
+            let suffix = format!(" {percentage} {rate:>11}");
+            let suffix_width = bar_width + suffix.chars().count() as u16;
+
+            let label_width = area.width.saturating_sub(suffix_width + 1) as usize;
+            let label = format!("{}{}", "  ".repeat(depth), node.label);
+
+            buf.set_stringn(area.x, y, label, label_width, self.style);
+
+            if suffix_width > area.width {
+                continue;
+            }
+
+            let x = area.right() - suffix_width;
+            let filled = node.fraction.map_or(0, |f| (f.clamp(0., 1.) * bar_width as f64).round() as u16);
+
+            for offset in 0..bar_width {
+                let symbol = if offset < filled { "█" } else { "░" };
+                buf.get_mut(x + offset, y).set_symbol(symbol).set_style(self.bar_style);
+            }
+
+            buf.set_string(x + bar_width, y, suffix, self.style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(id: u64, parent: Option<u64>, fraction: Option<f64>) -> ProgressMsg {
+        ProgressMsg { id, parent, label: format!("task {id}"), fraction, bytes_per_second: None }
+    }
+
+    fn rows(tree: &ProgressTree) -> Vec<(usize, u64)> {
+        tree.rows().into_iter().map(|(depth, node)| (depth, node.id)).collect()
+    }
+
+    #[test]
+    fn children_are_nested_under_their_parent() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(1, None, None));
+        tree.update(progress(2, Some(1), None));
+        tree.update(progress(3, None, None));
+        tree.update(progress(4, Some(2), None));
+
+        assert_eq!(rows(&tree), [(0, 1), (1, 2), (2, 4), (0, 3)]);
+    }
+
+    #[test]
+    fn an_unknown_parent_makes_a_root() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(2, Some(1), None));
+
+        assert_eq!(rows(&tree), [(0, 2)]);
+    }
+
+    #[test]
+    fn finished_once_every_descendant_is() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(1, None, Some(1.)));
+        tree.update(progress(2, Some(1), Some(0.5)));
+
+        assert!(!tree.is_finished(1));
+
+        tree.update(progress(2, Some(1), Some(1.)));
+
+        assert!(tree.is_finished(1));
+        assert!(!tree.is_finished(3));
+    }
+
+    #[test]
+    fn removing_takes_the_children_along() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(1, None, None));
+        tree.update(progress(2, Some(1), None));
+        tree.update(progress(3, None, None));
+        tree.remove(1);
+
+        assert_eq!(rows(&tree), [(0, 3)]);
+    }
+
+    #[test]
+    fn its_own_parent_is_ignored() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(1, Some(1), Some(1.)));
+
+        assert_eq!(rows(&tree), [(0, 1)]);
+        assert!(tree.is_finished(1));
+    }
+
+    #[test]
+    fn parent_cycles_are_broken() {
+        let mut tree = ProgressTree::new();
+
+        tree.update(progress(1, Some(3), None));
+        tree.update(progress(2, Some(1), None));
+        tree.update(progress(3, Some(2), None));
+
+        assert_eq!(rows(&tree), [(0, 3), (1, 1), (2, 2)]);
+        assert!(!tree.is_finished(2));
+    }
+
+    #[test]
+    fn deep_chains_dont_overflow_the_stack() {
+        let mut tree = ProgressTree::new();
+
+        (0..2_000).for_each(|id| tree.update(progress(id, id.checked_sub(1), Some(1.))));
+
+        assert!(tree.is_finished(0));
+    }
+}
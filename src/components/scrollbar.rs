@@ -0,0 +1,203 @@
+use std::cell::Cell;
+
+use crossterm::event::{MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::{Scrollbar, ScrollbarOrientation, ScrollbarState, StatefulWidget};
+
+use crate::components::contains;
+use crate::message::MouseMsg;
+
+pub trait Scrollable {
+    // The number of rows the content occupies when rendered into `viewport`.
+    fn content_length(&self, viewport: Rect) -> usize;
+
+    fn offset(&self) -> usize;
+
+    fn set_offset(&mut self, offset: usize);
+}
+
+#[derive(Debug)]
+pub struct ScrollbarAdapter {
+    track_style: Style,
+    thumb_style: Style,
+    wheel_step: usize,
+    // Remembered from the last render, so mouse events can be mapped onto the track.
+    viewport: Cell<Rect>,
+    track: Cell<Rect>,
+    dragging: bool,
+}
+
+impl Default for ScrollbarAdapter {
+    fn default() -> Self {
+        Self {
+            track_style: Style::default(),
+            thumb_style: Style::default(),
+            wheel_step: 3,
+            viewport: Cell::new(Rect::default()),
+            track: Cell::new(Rect::default()),
+            dragging: false,
+        }
+    }
+}
+
+impl ScrollbarAdapter {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn track_style(mut self, style: Style) -> Self {
+        self.track_style = style;
+        self
+    }
+
+    pub fn thumb_style(mut self, style: Style) -> Self {
+        self.thumb_style = style;
+        self
+    }
+
+    pub fn wheel_step(mut self, step: usize) -> Self {
+        self.wheel_step = step;
+        self
+    }
+
+    // Returns the area left over for the content, the scrollbar takes the rightmost column.
+    pub fn layout(&self, area: Rect) -> Rect {
+        Rect { width: area.width.saturating_sub(1), ..area }
+    }
+
+    pub fn render<S: Scrollable>(&self, scrollable: &S, area: Rect, buf: &mut Buffer) {
+        let viewport = self.layout(area);
+
+        self.viewport.set(viewport);
+        self.track.set(Rect { x: viewport.right(), width: area.width - viewport.width, ..area });
+
+        let mut state = ScrollbarState::new(self.max_offset(scrollable))
+            .position(scrollable.offset())
+            .viewport_content_length(viewport.height as usize);
+
+        Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .track_style(self.track_style)
+            .thumb_style(self.thumb_style)
+            .render(area, buf, &mut state);
+    }
+
+    fn max_offset<S: Scrollable>(&self, scrollable: &S) -> usize {
+        let viewport = self.viewport.get();
+
+        scrollable.content_length(viewport).saturating_sub(viewport.height as usize)
+    }
+
+    fn drag_to<S: Scrollable>(&self, scrollable: &mut S, row: u16) {
+        let track = self.track.get();
+
+        let relative = row.saturating_sub(track.y).min(track.height.saturating_sub(1)) as usize;
+        let span = track.height.saturating_sub(1).max(1) as usize;
+
+        scrollable.set_offset(self.max_offset(scrollable) * relative / span);
+    }
+
+    // Returns whether the event was consumed by the scrollbar.
+    pub fn handle_mouse<S: Scrollable>(&mut self, scrollable: &mut S, mouse: &MouseMsg) -> bool {
+        let on_track = contains(self.track.get(), mouse.column, mouse.row);
+        let on_viewport = on_track || contains(self.viewport.get(), mouse.column, mouse.row);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if on_track => {
+                self.dragging = true;
+                self.drag_to(scrollable, mouse.row);
+            },
+            | MouseEventKind::Drag(MouseButton::Left) if self.dragging => self.drag_to(scrollable, mouse.row),
+            | MouseEventKind::Up(MouseButton::Left) if self.dragging => self.dragging = false,
+            | MouseEventKind::ScrollDown if on_viewport => {
+                let offset = (scrollable.offset() + self.wheel_step).min(self.max_offset(scrollable));
+                scrollable.set_offset(offset);
+            },
+            | MouseEventKind::ScrollUp if on_viewport => {
+                scrollable.set_offset(scrollable.offset().saturating_sub(self.wheel_step));
+            },
+            | _ => return false,
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::mouse;
+
+    struct Lines {
+        count: usize,
+        offset: usize,
+    }
+
+    impl Scrollable for Lines {
+        fn content_length(&self, _: Rect) -> usize {
+            self.count
+        }
+
+        fn offset(&self) -> usize {
+            self.offset
+        }
+
+        fn set_offset(&mut self, offset: usize) {
+            self.offset = offset;
+        }
+    }
+
+    fn rendered(lines: &Lines) -> ScrollbarAdapter {
+        let adapter = ScrollbarAdapter::new();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 10));
+
+        adapter.render(lines, buf.area, &mut buf);
+
+        adapter
+    }
+
+    #[test]
+    fn takes_the_rightmost_column() {
+        assert_eq!(ScrollbarAdapter::new().layout(Rect::new(2, 0, 10, 5)), Rect::new(2, 0, 9, 5));
+    }
+
+    #[test]
+    fn the_wheel_scrolls_within_bounds() {
+        let mut lines = Lines { count: 14, offset: 0 };
+        let mut adapter = rendered(&lines);
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::ScrollDown, 3, 3)));
+        assert_eq!(lines.offset, 3);
+
+        adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::ScrollDown, 3, 3));
+        assert_eq!(lines.offset, 4);
+
+        adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::ScrollUp, 3, 3));
+        assert_eq!(lines.offset, 1);
+
+        assert!(!adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::ScrollUp, 3, 12)));
+    }
+
+    #[test]
+    fn dragging_the_thumb_follows_the_track() {
+        let mut lines = Lines { count: 100, offset: 0 };
+        let mut adapter = rendered(&lines);
+
+        assert!(!adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Down(MouseButton::Left), 3, 3)));
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Down(MouseButton::Left), 9, 0)));
+        assert_eq!(lines.offset, 0);
+
+        // Keeps following once the pointer leaves the track.
+        adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Drag(MouseButton::Left), 4, 20));
+        assert_eq!(lines.offset, 90);
+
+        assert!(adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Up(MouseButton::Left), 4, 20)));
+        assert!(!adapter.handle_mouse(&mut lines, &mouse(MouseEventKind::Drag(MouseButton::Left), 9, 0)));
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, iter, mem};
+use std::sync::atomic::{self, Ordering};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::Widget;
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+use crate::message::KeyMsg;
+
+// Wiped from memory when dropped (with the zeroize feature), and never printed by `Debug`.
+pub struct Secret(String);
+
+impl Secret {
+    #[inline(always)]
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        wipe(&mut self.0);
+    }
+}
+
+fn wipe(buffer: &mut String) {
+    #[cfg(feature = "zeroize")]
+    buffer.zeroize();
+
+    #[cfg(not(feature = "zeroize"))]
+    truncate(buffer, 0);
+}
+
+// Overwrites what's cut off before giving it up, `String::truncate` and `pop` leave the bytes in the spare capacity.
+fn truncate(buffer: &mut String, len: usize) {
+    let removed = buffer.len().saturating_sub(len);
+
+    buffer.truncate(len);
+
+    // Fits into the capacity the removed bytes just left, so it writes over them without reallocating.
+    buffer.extend(iter::repeat_n('\0', removed));
+    atomic::compiler_fence(Ordering::SeqCst);
+
+    buffer.truncate(len);
+}
+
+#[derive(Debug)]
+pub enum Prompt {
+    Submitted(Secret),
+    Cancelled,
+}
+
+// Nothing about the input is ever rendered, not even its length. While the prompt is open, the
+// screen should send `Command::SuppressRecording(true)` so keys don't end up in crash reports or macros.
+pub struct SecretPrompt {
+    label: String,
+    style: Style,
+    buffer: String,
+}
+
+impl SecretPrompt {
+    const INITIAL_CAPACITY: usize = 64;
+
+    pub fn new<T: Into<String>>(label: T) -> Self {
+        Self {
+            label: label.into(),
+            style: Style::default(),
+            buffer: String::with_capacity(Self::INITIAL_CAPACITY),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // Grows the buffer by hand, so the old allocation is wiped instead of being left behind by a reallocation.
+    fn push(&mut self, c: char) {
+        if self.buffer.len() + c.len_utf8() > self.buffer.capacity() {
+            let mut grown = String::with_capacity(self.buffer.capacity() * 2 + c.len_utf8());
+
+            grown.push_str(&self.buffer);
+
+            wipe(&mut mem::replace(&mut self.buffer, grown));
+        }
+
+        self.buffer.push(c);
+    }
+
+    fn take(&mut self) -> Secret {
+        Secret(mem::replace(&mut self.buffer, String::with_capacity(Self::INITIAL_CAPACITY)))
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<Prompt> {
+        match key.code {
+            | KeyCode::Enter => return Some(Prompt::Submitted(self.take())),
+            | KeyCode::Esc => {
+                wipe(&mut self.buffer);
+                return Some(Prompt::Cancelled);
+            },
+            | KeyCode::Backspace => {
+                let len = self.buffer.char_indices().next_back().map_or(0, |(index, _)| index);
+                truncate(&mut self.buffer, len);
+            },
+            | KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => wipe(&mut self.buffer),
+            | KeyCode::Char(c) if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => self.push(c),
+            | _ => (),
+        }
+
+        None
+    }
+}
+
+impl fmt::Debug for SecretPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretPrompt")
+            .field("label", &self.label)
+            .field("style", &self.style)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for SecretPrompt {
+    fn drop(&mut self) {
+        wipe(&mut self.buffer);
+    }
+}
+
+impl Widget for &SecretPrompt {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.style);
+        buf.set_stringn(area.x, area.y, format!("{} (input hidden)", self.label), area.width as usize, self.style);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{key, key_with};
+
+    fn typed(prompt: &mut SecretPrompt, text: &str) {
+        text.chars().for_each(|c| assert!(prompt.handle_key(&key(KeyCode::Char(c))).is_none()));
+    }
+
+    fn submit(prompt: &mut SecretPrompt) -> String {
+        match prompt.handle_key(&key(KeyCode::Enter)) {
+            | Some(Prompt::Submitted(secret)) => secret.expose().to_string(),
+            | other => panic!("expected a submission, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn submits_what_was_typed() {
+        let mut prompt = SecretPrompt::new("Password");
+
+        typed(&mut prompt, "hunter2");
+
+        assert_eq!(submit(&mut prompt), "hunter2");
+        assert_eq!(submit(&mut prompt), "");
+    }
+
+    #[test]
+    fn backspace_removes_whole_characters() {
+        let mut prompt = SecretPrompt::new("Password");
+
+        typed(&mut prompt, "pässé");
+        prompt.handle_key(&key(KeyCode::Backspace));
+
+        assert_eq!(submit(&mut prompt), "päss");
+    }
+
+    #[test]
+    fn ctrl_u_clears_and_escape_cancels() {
+        let mut prompt = SecretPrompt::new("Password");
+
+        typed(&mut prompt, "oops");
+        prompt.handle_key(&key_with(KeyCode::Char('u'), KeyModifiers::CONTROL));
+        typed(&mut prompt, "ok");
+
+        assert_eq!(submit(&mut prompt), "ok");
+        assert!(matches!(prompt.handle_key(&key(KeyCode::Esc)), Some(Prompt::Cancelled)));
+    }
+
+    #[test]
+    fn debug_never_shows_the_secret() {
+        let mut prompt = SecretPrompt::new("Password");
+
+        typed(&mut prompt, "hunter2");
+
+        assert!(!format!("{prompt:?}").contains("hunter2"));
+        assert_eq!(format!("{:?}", Secret(String::from("hunter2"))), "Secret(..)");
+    }
+}
@@ -0,0 +1,343 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use crate::components::contains;
+use crate::message::{KeyMsg, MouseMsg};
+
+const ARROW: &str = "▾";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelectionChanged {
+    pub index: usize,
+    pub previous: Option<usize>,
+}
+
+// Shows the current choice on a single row, the list opens in a popup below it. Typing while it's open
+// narrows the list down. Render it after its surroundings, so the popup ends up on top of them.
+#[derive(Debug)]
+pub struct Select {
+    options: Vec<String>,
+    selected: Option<usize>,
+    placeholder: String,
+    open: bool,
+    filter: String,
+    // Position in the filtered list.
+    highlighted: usize,
+    max_visible: u16,
+    style: Style,
+    highlight_style: Style,
+    area: Cell<Rect>,
+    // The rows of the popup's list and the first filtered option shown, as last rendered.
+    list: Cell<Rect>,
+    offset: Cell<usize>,
+}
+
+impl Select {
+    pub fn new<I, T>(options: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        Self {
+            options: options.into_iter().map(Into::into).collect(),
+            selected: None,
+            placeholder: String::new(),
+            open: false,
+            filter: String::new(),
+            highlighted: 0,
+            max_visible: 8,
+            style: Style::default(),
+            highlight_style: Style::default().add_modifier(Modifier::REVERSED),
+            area: Cell::new(Rect::default()),
+            list: Cell::new(Rect::default()),
+            offset: Cell::new(0),
+        }
+    }
+
+    pub fn placeholder<T: Into<String>>(mut self, placeholder: T) -> Self {
+        self.placeholder = placeholder.into();
+        self
+    }
+
+    // How many options the popup shows before it scrolls.
+    pub fn max_visible(mut self, rows: u16) -> Self {
+        self.max_visible = rows.max(1);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn highlight_style(mut self, style: Style) -> Self {
+        self.highlight_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    #[inline(always)]
+    pub fn selected_label(&self) -> Option<&str> {
+        self.selected.map(|index| self.options[index].as_str())
+    }
+
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn select(&mut self, index: usize) -> Option<SelectionChanged> {
+        let previous = self.selected;
+
+        if index >= self.options.len() || previous == Some(index) {
+            return None;
+        }
+
+        self.selected = Some(index);
+
+        Some(SelectionChanged { index, previous })
+    }
+
+    // Indices of the options containing the filter, ignoring case.
+    pub fn matches(&self) -> Vec<usize> {
+        let filter = self.filter.to_lowercase();
+
+        (0..self.options.len()).filter(|index| self.options[*index].to_lowercase().contains(&filter)).collect()
+    }
+
+    pub fn open(&mut self) {
+        self.open = true;
+        self.filter.clear();
+        self.highlighted = self.selected.unwrap_or(0);
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.filter.clear();
+    }
+
+    fn pick(&mut self, position: usize) -> Option<SelectionChanged> {
+        let index = self.matches().get(position).copied();
+
+        self.close();
+
+        index.and_then(|index| self.select(index))
+    }
+
+    fn move_highlight(&mut self, forward: bool) {
+        let last = self.matches().len().saturating_sub(1);
+
+        self.highlighted = match forward {
+            | true => (self.highlighted + 1).min(last),
+            | false => self.highlighted.saturating_sub(1).min(last),
+        };
+    }
+
+    // Enter, space or down open the list. While open, typing filters it and enter picks the highlighted option.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> Option<SelectionChanged> {
+        if !self.open {
+            if matches!(key.code, KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Down) {
+                self.open();
+            }
+
+            return None;
+        }
+
+        match key.code {
+            | KeyCode::Enter => return self.pick(self.highlighted),
+            | KeyCode::Esc => self.close(),
+            | KeyCode::Up => self.move_highlight(false),
+            | KeyCode::Down => self.move_highlight(true),
+            | KeyCode::Backspace => {
+                self.filter.pop();
+                self.highlighted = 0;
+            },
+            | KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.highlighted = 0;
+            },
+            | _ => (),
+        }
+
+        None
+    }
+
+    // Clicking the field toggles the list, clicking an option picks it and clicking anywhere else closes it.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<SelectionChanged> {
+        let list = self.list.get();
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if contains(self.area.get(), mouse.column, mouse.row) => {
+                match self.open {
+                    | true => self.close(),
+                    | false => self.open(),
+                }
+
+                None
+            },
+            | MouseEventKind::Down(MouseButton::Left) if self.open && contains(list, mouse.column, mouse.row) =>
+                self.pick(self.offset.get() + (mouse.row - list.y) as usize),
+            | MouseEventKind::Down(_) => {
+                self.close();
+                None
+            },
+            | MouseEventKind::ScrollUp if self.open && contains(list, mouse.column, mouse.row) => {
+                self.move_highlight(false);
+                None
+            },
+            | MouseEventKind::ScrollDown if self.open && contains(list, mouse.column, mouse.row) => {
+                self.move_highlight(true);
+                None
+            },
+            | _ => None,
+        }
+    }
+}
+
+impl Widget for &Select {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let field = Rect { height: area.height.min(1), ..area };
+
+        self.area.set(field);
+
+        if field.is_empty() {
+            return;
+        }
+
+        let (label, style) = match self.selected_label() {
+            | Some(label) => (label, self.style),
+            | None => (self.placeholder.as_str(), self.style.add_modifier(Modifier::DIM)),
+        };
+
+        buf.set_style(field, self.style);
+        buf.set_stringn(field.x, field.y, label, field.width.saturating_sub(2) as usize, style);
+        buf.set_string(field.right().saturating_sub(1), field.y, ARROW, self.style);
+
+        if !self.open {
+            self.list.set(Rect::default());
+            return;
+        }
+
+        let matches = self.matches();
+
+        let visible = (matches.len() as u16).clamp(1, self.max_visible);
+
+        let popup = Rect::new(field.x, field.y + 1, field.width, visible + 2).intersection(buf.area);
+
+        let block = match self.filter.is_empty() {
+            | true => Block::default(),
+            | false => Block::default().title(format!(" {} ", self.filter)),
+        };
+
+        let block = block.borders(Borders::ALL).style(self.style);
+        let list = block.inner(popup);
+
+        Clear.render(popup, buf);
+        block.render(popup, buf);
+
+        self.list.set(list);
+
+        // Keeps the highlighted option in view.
+        let offset = self.highlighted.saturating_sub(list.height.saturating_sub(1) as usize);
+
+        self.offset.set(offset);
+
+        for (row, (position, index)) in matches.iter().enumerate().skip(offset).take(list.height as usize).enumerate() {
+            let style = if position == self.highlighted { self.highlight_style } else { self.style };
+
+            let y = list.y + row as u16;
+
+            buf.set_style(Rect { y, height: 1, ..list }, style);
+            buf.set_stringn(list.x, y, &self.options[*index], list.width as usize, style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    fn languages() -> Select {
+        Select::new(["Rust", "Ruby", "Python", "Go"]).placeholder("pick").max_visible(2)
+    }
+
+    #[test]
+    fn typing_narrows_the_list_down() {
+        let mut select = languages();
+
+        assert_eq!(select.handle_key(&key(KeyCode::Char('r'))), None);
+        assert!(!select.is_open());
+
+        select.handle_key(&key(KeyCode::Enter));
+
+        for c in "RU".chars() {
+            select.handle_key(&key(KeyCode::Char(c)));
+        }
+
+        assert_eq!(select.matches(), [0, 1]);
+
+        select.handle_key(&key(KeyCode::Down));
+        select.handle_key(&key(KeyCode::Down));
+
+        assert_eq!(select.handle_key(&key(KeyCode::Enter)), Some(SelectionChanged { index: 1, previous: None }));
+        assert_eq!(select.selected_label(), Some("Ruby"));
+        assert!(!select.is_open());
+
+        select.handle_key(&key(KeyCode::Char(' ')));
+
+        assert_eq!(select.handle_key(&key(KeyCode::Enter)), None, "opens on the selected option");
+    }
+
+    #[test]
+    fn the_popup_scrolls_to_the_highlighted_option() {
+        let mut select = languages();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 5));
+
+        (&select).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf)[0], "pick   ▾");
+
+        select.handle_key(&key(KeyCode::Down));
+        select.handle_key(&key(KeyCode::Down));
+        select.handle_key(&key(KeyCode::Down));
+
+        (&select).render(buf.area, &mut buf);
+
+        assert_eq!(lines(&buf), ["pick   ▾", "┌──────┐", "│Ruby  │", "│Python│", "└──────┘"]);
+    }
+
+    #[test]
+    fn options_are_picked_by_clicking_them() {
+        let mut select = languages();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 5));
+        let click = |column, row| mouse(MouseEventKind::Down(MouseButton::Left), column, row);
+
+        (&select).render(buf.area, &mut buf);
+        select.handle_mouse(&click(3, 0));
+        (&select).render(buf.area, &mut buf);
+
+        assert_eq!(select.handle_mouse(&click(2, 3)), Some(SelectionChanged { index: 1, previous: None }));
+
+        select.handle_mouse(&click(3, 0));
+
+        assert!(select.is_open());
+        assert_eq!(select.handle_mouse(&click(20, 20)), None);
+        assert!(!select.is_open());
+    }
+}
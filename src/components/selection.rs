@@ -0,0 +1,256 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::command::Command;
+
+// What whole units a selection grows by, e.g. after a double or triple click.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionMode {
+    #[default]
+    Char,
+    Word,
+    Line,
+}
+
+// Between the anchor, where it started, and the cursor, which moves. Both are byte offsets into the text on
+// character boundaries, empty when they're the same (outside of the word and line modes).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    anchor: usize,
+    cursor: usize,
+    mode: SelectionMode,
+}
+
+#[inline(always)]
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+pub(crate) fn previous(text: &str, at: usize) -> usize {
+    text[..at].char_indices().next_back().map_or(0, |(index, _)| index)
+}
+
+pub(crate) fn next(text: &str, at: usize) -> usize {
+    text[at..].chars().next().map_or(at, |c| at + c.len_utf8())
+}
+
+pub(crate) fn line_start(text: &str, at: usize) -> usize {
+    text[..at].rfind('\n').map_or(0, |index| index + 1)
+}
+
+pub(crate) fn line_end(text: &str, at: usize) -> usize {
+    text[at..].find('\n').map_or(text.len(), |index| at + index)
+}
+
+pub(crate) fn word_start(text: &str, at: usize) -> usize {
+    text[..at].char_indices().rev().take_while(|(_, c)| is_word(*c)).last().map_or(at, |(index, _)| index)
+}
+
+pub(crate) fn word_end(text: &str, at: usize) -> usize {
+    text[at..].char_indices().find(|(_, c)| !is_word(*c)).map_or(text.len(), |(index, _)| at + index)
+}
+
+// Where ctrl+left lands, the start of this word or the one before.
+pub(crate) fn previous_word(text: &str, at: usize) -> usize {
+    let skipped = text[..at].trim_end_matches(|c| !is_word(c)).len();
+
+    word_start(text, skipped)
+}
+
+// Where ctrl+right lands, the end of this word or the next one.
+pub(crate) fn next_word(text: &str, at: usize) -> usize {
+    let skipped = at + text[at..].find(is_word).unwrap_or(text.len() - at);
+
+    word_end(text, skipped)
+}
+
+impl Selection {
+    #[inline(always)]
+    pub fn caret(at: usize) -> Self {
+        Self { anchor: at, cursor: at, mode: SelectionMode::Char }
+    }
+
+    #[inline(always)]
+    pub fn anchor(&self) -> usize {
+        self.anchor
+    }
+
+    #[inline(always)]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    #[inline(always)]
+    pub fn mode(&self) -> SelectionMode {
+        self.mode
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.anchor == self.cursor && self.mode == SelectionMode::Char
+    }
+
+    // Collapses the selection.
+    #[inline(always)]
+    pub fn move_to(&mut self, at: usize) {
+        *self = Self::caret(at);
+    }
+
+    // Keeps the anchor, e.g. with shift held or while dragging.
+    #[inline(always)]
+    pub fn extend_to(&mut self, at: usize) {
+        self.cursor = at;
+    }
+
+    pub fn select(&mut self, anchor: usize, cursor: usize, mode: SelectionMode) {
+        *self = Self { anchor, cursor, mode };
+    }
+
+    pub fn select_all(&mut self, text: &str) {
+        self.select(0, text.len(), SelectionMode::Char);
+    }
+
+    // The selected bytes, grown to whole words or lines in those modes. Lines include their line break.
+    pub fn range(&self, text: &str) -> Range<usize> {
+        let (start, end) = (self.anchor.min(self.cursor), self.anchor.max(self.cursor));
+        let (start, end) = (start.min(text.len()), end.min(text.len()));
+
+        match self.mode {
+            | SelectionMode::Char => start..end,
+            | SelectionMode::Word => word_start(text, start)..word_end(text, end),
+            | SelectionMode::Line => line_start(text, start)..next(text, line_end(text, end)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn selected<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.range(text)]
+    }
+
+    // Replaces what's selected, leaving the caret after what was inserted.
+    pub fn replace(&mut self, text: &mut String, with: &str) {
+        let range = self.range(text);
+
+        text.replace_range(range.clone(), with);
+
+        self.move_to(range.start + with.len());
+    }
+}
+
+// Copied text is kept by the application as well, as terminals rarely let the clipboard be read back. Shared
+// between the text components of a screen (or all of them, in the context) so cut, copy and paste work across
+// them. What's copied goes to the system clipboard through OSC 52, which most terminals support.
+#[derive(Debug, Default, Clone)]
+pub struct Clipboard {
+    contents: Option<String>,
+}
+
+impl Clipboard {
+    // To be returned from `Screen::update`, which writes it to the terminal.
+    pub fn copy<T: Into<String>>(&mut self, text: T) -> Command {
+        let text = text.into();
+        let command = Command::crossterm(SetClipboard(base64(text.as_bytes())));
+
+        self.contents = Some(text);
+
+        command
+    }
+
+    #[inline(always)]
+    pub fn contents(&self) -> Option<&str> {
+        self.contents.as_deref()
+    }
+}
+
+struct SetClipboard(String);
+
+impl crossterm::Command for SetClipboard {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "\x1b]52;c;{}\x07", self.0)
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, byte)| n | (*byte as u32) << (16 - 8 * i));
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                | true => encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char),
+                | false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::Command as _;
+
+    const TEXT: &str = "hello wörld\nsecond line\n";
+
+    #[test]
+    fn the_range_grows_to_whole_words_and_lines() {
+        let mut selection = Selection::default();
+
+        selection.select(9, 10, SelectionMode::Word);
+
+        assert_eq!(selection.selected(TEXT), "wörld");
+
+        selection.select(16, 14, SelectionMode::Line);
+
+        assert_eq!(selection.selected(TEXT), "second line\n");
+
+        selection.select(4, 1, SelectionMode::Char);
+
+        assert_eq!(selection.selected(TEXT), "ell");
+    }
+
+    #[test]
+    fn replacing_leaves_the_caret_after_the_insertion() {
+        let mut text = String::from(TEXT);
+        let mut selection = Selection::caret(0);
+
+        selection.extend_to(5);
+        selection.replace(&mut text, "hi");
+
+        assert_eq!(text, "hi wörld\nsecond line\n");
+        assert_eq!(selection, Selection::caret(2));
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn word_movement_skips_what_separates_words() {
+        assert_eq!(next_word(TEXT, 5), 12);
+        assert_eq!(previous_word(TEXT, 13), 6);
+        assert_eq!(previous_word(TEXT, 6), 0);
+        assert_eq!(next(TEXT, 7), 9);
+        assert_eq!(previous(TEXT, 9), 7);
+    }
+
+    #[test]
+    fn copying_keeps_the_text_and_writes_it_as_osc_52() {
+        let mut clipboard = Clipboard::default();
+
+        let Command::Crossterm(command) = clipboard.copy("hi!") else {
+            panic!("expected a terminal command");
+        };
+
+        let mut ansi = String::new();
+
+        command.write_ansi(&mut ansi).unwrap();
+
+        assert_eq!(ansi, "\x1b]52;c;aGkh\x07");
+        assert_eq!(clipboard.contents(), Some("hi!"));
+        assert_eq!(base64(b"ab"), "YWI=");
+        assert_eq!(base64(b"a"), "YQ==");
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use ratatui::style::{Color, Modifier, Style};
+
+// Words further from a misspelling than this aren't suggested.
+const MAX_DISTANCE: usize = 2;
+
+// A list of correctly spelled words, compared without regard to case. Shared with the background threads checking
+// text against it, so usually kept in an `Arc`.
+#[derive(Debug, Default, Clone)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+#[inline(always)]
+fn is_word(c: char) -> bool {
+    c.is_alphabetic() || c == '\''
+}
+
+// Every word in the text, by where it is. Apostrophes are part of a word, but not at its edges.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut rest = text.char_indices().peekable();
+
+    std::iter::from_fn(move || {
+        let (start, _) = rest.find(|(_, c)| is_word(*c) && *c != '\'')?;
+        let mut end = text.len();
+
+        while let Some(&(index, c)) = rest.peek() {
+            if !is_word(c) {
+                end = index;
+                break;
+            }
+
+            rest.next();
+        }
+
+        Some(start..start + text[start..end].trim_end_matches('\'').len())
+    })
+}
+
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in b.iter().enumerate() {
+            current.push((previous[j] + (a != *b) as usize).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+impl Dictionary {
+    pub fn new<I, W>(words: I) -> Self
+        where I: IntoIterator<Item = W>, W: AsRef<str>,
+    {
+        Self { words: words.into_iter().map(|word| word.as_ref().to_lowercase()).collect() }
+    }
+
+    // In hunspell's .dic format, a count on the first line and a word on every other. Affix flags after a slash
+    // are ignored, so only the words as listed are known.
+    pub fn from_dic(contents: &str) -> Self {
+        let words = contents.lines().skip_while(|line| line.trim().parse::<usize>().is_ok());
+
+        Self::new(words.filter_map(|line| line.split('/').next()).map(str::trim).filter(|word| !word.is_empty()))
+    }
+
+    pub fn insert<W: AsRef<str>>(&mut self, word: W) {
+        self.words.insert(word.as_ref().to_lowercase());
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    // Where the words the dictionary doesn't know are in the text.
+    pub fn misspellings(&self, text: &str) -> Vec<Range<usize>> {
+        words(text).filter(|word| !self.contains(&text[word.clone()])).collect()
+    }
+
+    // The closest known words, closest first, capitalized like the word was.
+    pub fn suggestions(&self, word: &str, limit: usize) -> Vec<String> {
+        let lowercase = word.to_lowercase();
+        let length = lowercase.chars().count();
+
+        let mut close = self.words.iter()
+            .filter(|known| known.chars().count().abs_diff(length) <= MAX_DISTANCE)
+            .map(|known| (distance(&lowercase, known), known))
+            .filter(|(distance, _)| *distance <= MAX_DISTANCE)
+            .collect::<Vec<_>>();
+
+        close.sort();
+
+        let capitalized = word.starts_with(char::is_uppercase);
+
+        close.into_iter().take(limit).map(|(_, known)| match capitalized {
+            | true => known.chars().take(1).flat_map(char::to_uppercase).chain(known.chars().skip(1)).collect(),
+            | false => known.clone(),
+        }).collect()
+    }
+}
+
+// The result of `TextArea::spawn_spell_check`, delivered to the screen as a custom message.
+#[derive(Debug)]
+pub struct SpellChecked {
+    pub(crate) revision: u64,
+    pub(crate) misspellings: Vec<Range<usize>>,
+}
+
+// The result of `TextArea::spawn_suggestions`, delivered to the screen as a custom message.
+#[derive(Debug)]
+pub struct Suggestions {
+    pub(crate) revision: u64,
+    pub(crate) word: Range<usize>,
+    pub(crate) words: Vec<String>,
+}
+
+// Revisions are unique across text components, so a result can't be applied to a different one.
+pub(crate) fn next_revision() -> u64 {
+    static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+    REVISIONS.fetch_add(1, Ordering::Relaxed)
+}
+
+// What a text component knows about its spelling, as of its last check.
+#[derive(Debug)]
+pub(crate) struct Spelling {
+    pub revision: u64,
+    pub misspellings: Vec<Range<usize>>,
+    // The word being corrected, the suggestions for it and which one is highlighted.
+    pub suggestions: Option<(Range<usize>, Vec<String>, usize)>,
+    pub style: Style,
+}
+
+impl Default for Spelling {
+    fn default() -> Self {
+        Self {
+            revision: next_revision(),
+            misspellings: Vec::new(),
+            suggestions: None,
+            style: Style::default().add_modifier(Modifier::UNDERLINED).underline_color(Color::Red),
+        }
+    }
+}
+
+impl Spelling {
+    // Misspellings stay underlined until the next check arrives, the suggestions are for text that's gone.
+    pub fn changed(&mut self) {
+        self.revision = next_revision();
+        self.suggestions = None;
+    }
+
+    pub fn is_misspelled(&self, offset: usize) -> bool {
+        self.misspellings.iter().any(|word| word.contains(&offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_words_the_dictionary_does_not_know() {
+        let dictionary = Dictionary::from_dic("3\nthe/S\nDog\ndon't\n");
+        let text = "The dgo don't 'barks'";
+
+        let misspelled = dictionary.misspellings(text).into_iter().map(|word| &text[word]).collect::<Vec<_>>();
+
+        assert_eq!(misspelled, ["dgo", "barks"]);
+    }
+
+    #[test]
+    fn suggests_the_closest_words_first() {
+        let dictionary = Dictionary::new(["hello", "help", "yellow", "world"]);
+
+        assert_eq!(dictionary.suggestions("helo", 2), ["hello", "help"]);
+        assert_eq!(dictionary.suggestions("Wrold", 3), ["World"]);
+        assert!(dictionary.suggestions("xyz", 3).is_empty());
+        assert_eq!(distance("kitten", "sitting"), 3);
+    }
+}
@@ -0,0 +1,1184 @@
+use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::command::Command;
+use crate::format;
+use crate::components::contains;
+use crate::components::export::{self, ExportFormat};
+use crate::components::scrollbar::Scrollable;
+use crate::components::selection::Clipboard;
+use crate::components::text_area::{TextEvent, TextInput};
+use crate::message::{KeyMsg, MouseMsg};
+
+const ELLIPSIS: char = '…';
+
+// Which part of a cell too wide for its column is left out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    Start,
+    Middle,
+    #[default]
+    End,
+}
+
+// Columns are as wide as the widest of their cells in view, within their minimum and maximum. When that's too
+// wide for the table, the widest columns give way first. What's left over is shared between the columns with a
+// weight, in proportion to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Column {
+    min: u16,
+    max: u16,
+    weight: u16,
+    truncation: Truncation,
+    // Left unless set, but a data source may pick one for what the column holds.
+    pub(crate) alignment: Option<Alignment>,
+}
+
+impl Default for Column {
+    fn default() -> Self {
+        Self { min: 0, max: u16::MAX, weight: 0, truncation: Truncation::End, alignment: None }
+    }
+}
+
+impl Column {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min(mut self, width: u16) -> Self {
+        self.min = width;
+        self
+    }
+
+    pub fn max(mut self, width: u16) -> Self {
+        self.max = width;
+        self
+    }
+
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    pub fn alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = Some(alignment);
+        self
+    }
+}
+
+// The characters fitting into the width, from the back when reversed.
+fn fitting<I: Iterator<Item = char>>(chars: I, width: usize) -> impl Iterator<Item = char> {
+    chars.scan(0, move |taken, c| {
+        *taken += c.width().unwrap_or(0);
+        (*taken <= width).then_some(c)
+    })
+}
+
+fn truncate(cell: &str, width: usize, truncation: Truncation) -> Cow<'_, str> {
+    if cell.width() <= width {
+        return Cow::Borrowed(cell);
+    }
+
+    let Some(kept) = width.checked_sub(1) else {
+        return Cow::Borrowed("");
+    };
+
+    let tail = |width| fitting(cell.chars().rev(), width).collect::<Vec<_>>().into_iter().rev();
+
+    Cow::Owned(match truncation {
+        | Truncation::End => fitting(cell.chars(), kept).chain([ELLIPSIS]).collect(),
+        | Truncation::Start => [ELLIPSIS].into_iter().chain(tail(kept)).collect(),
+        | Truncation::Middle => {
+            fitting(cell.chars(), kept - kept / 2).chain([ELLIPSIS]).chain(tail(kept / 2)).collect()
+        },
+    })
+}
+
+// Summarizes a column of a group's rows in the group's row. Cells that aren't numbers are left out, of all but the
+// count of cells that aren't empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    Count,
+    Sum,
+    Mean,
+    Min,
+    Max,
+}
+
+impl Aggregate {
+    fn summarize<'a, I: Iterator<Item = &'a str>>(self, cells: I) -> String {
+        if self == Self::Count {
+            return format::integer(cells.filter(|cell| !cell.trim().is_empty()).count() as i64);
+        }
+
+        let numbers = cells.filter_map(|cell| cell.trim().parse::<f64>().ok()).collect::<Vec<_>>();
+
+        let value = match self {
+            | _ if numbers.is_empty() => return String::new(),
+            | Self::Sum => numbers.iter().sum(),
+            | Self::Mean => numbers.iter().sum::<f64>() / numbers.len() as f64,
+            | Self::Min => numbers.iter().copied().fold(f64::INFINITY, f64::min),
+            | Self::Max => numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            | Self::Count => unreachable!(),
+        };
+
+        match value.fract() == 0. {
+            | true => format::decimal(value, 0),
+            | false => format::decimal(value, 2),
+        }
+    }
+}
+
+// What's shown between the pinned rows, by index into the rows or groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Line {
+    Row(usize),
+    Group(usize),
+}
+
+#[derive(Debug)]
+struct Group {
+    key: String,
+    rows: Vec<usize>,
+    // The group's own row, its key in the first column and the aggregates in theirs.
+    summary: Vec<String>,
+}
+
+type GroupKey = Box<dyn Fn(&[String]) -> String>;
+
+struct Grouping {
+    key: GroupKey,
+    collapsed: HashSet<String>,
+    groups: Vec<Group>,
+    lines: Vec<Line>,
+}
+
+impl fmt::Debug for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Grouping").field("groups", &self.groups).field("collapsed", &self.collapsed).finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowSelected {
+    pub index: usize,
+    pub previous: Option<usize>,
+}
+
+// Entered into a cell of an editable table, it's in the table's rows already. Put the previous value back with
+// `set_cell` if it's refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CellEdited {
+    pub row: usize,
+    pub col: usize,
+    pub value: String,
+}
+
+#[derive(Debug)]
+pub enum TableEvent {
+    Selected(RowSelected),
+    Edited(CellEdited),
+    // Cut or copied while editing a cell, see `TextEvent::Copied`.
+    Copied(Command),
+}
+
+// Rows of cells in columns, only the rows in view are rendered. Header rows are pinned above the rows that scroll
+// and footer rows below them, each in their own style. A list is a table with a single column. The widths of the
+// columns are negotiated on every render, see `Column`, so they follow resizing and scrolling.
+//
+// Up and down move the selection, page up and down move it by what's in view, home and end to either end. Clicking
+// a row selects it, the mouse wheel scrolls.
+//
+// In an editable table, left and right move between the cells of the selected row and enter edits the focused one
+// in a `TextInput`. Enter again keeps the edit, escape (or clicking elsewhere) drops it.
+//
+// Grouped rows are shown below a row for their group, in the order the groups first appear, which summarizes them
+// and collapses or expands them like a tree. Enter, space or clicking it toggles the group, left collapses it and
+// right expands it.
+#[derive(Debug)]
+pub struct Table {
+    header: Vec<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    footer: Vec<Vec<String>>,
+    columns: Vec<Column>,
+    grouping: Option<Grouping>,
+    aggregates: Vec<(usize, Aggregate)>,
+    // Both by line, which are the rows themselves unless they're grouped.
+    selected: Option<usize>,
+    // The first line in view.
+    offset: usize,
+    editable: bool,
+    // The focused cell of the selected row, and its editor while it's edited.
+    column: usize,
+    editing: Option<TextInput>,
+    column_spacing: u16,
+    wheel_step: usize,
+    style: Style,
+    header_style: Style,
+    footer_style: Style,
+    group_style: Style,
+    selected_style: Style,
+    cell_style: Style,
+    // Where the rows that scroll were last rendered, without the pinned ones, and how wide the columns were.
+    body: Cell<Rect>,
+    widths: RefCell<Vec<u16>>,
+}
+
+impl Table {
+    pub fn new<R, C, T>(rows: R) -> Self
+        where R: IntoIterator<Item = C>, C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        Self {
+            header: Vec::new(),
+            rows: rows.into_iter().map(|row| row.into_iter().map(Into::into).collect()).collect(),
+            footer: Vec::new(),
+            columns: Vec::new(),
+            grouping: None,
+            aggregates: Vec::new(),
+            selected: None,
+            offset: 0,
+            editable: false,
+            column: 0,
+            editing: None,
+            column_spacing: 1,
+            wheel_step: 3,
+            style: Style::default(),
+            header_style: Style::default().add_modifier(Modifier::BOLD),
+            footer_style: Style::default().add_modifier(Modifier::DIM),
+            group_style: Style::default().add_modifier(Modifier::BOLD),
+            selected_style: Style::default().add_modifier(Modifier::REVERSED),
+            cell_style: Style::default().add_modifier(Modifier::UNDERLINED),
+            body: Cell::new(Rect::default()),
+            widths: RefCell::new(Vec::new()),
+        }
+    }
+
+    // Another pinned row above the rows that scroll.
+    pub fn header<C, T>(mut self, row: C) -> Self
+        where C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.header.push(row.into_iter().map(Into::into).collect());
+        self
+    }
+
+    // Another pinned row below the rows that scroll.
+    pub fn footer<C, T>(mut self, row: C) -> Self
+        where C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.footer.push(row.into_iter().map(Into::into).collect());
+        self
+    }
+
+    // Columns past these are sized by their content.
+    pub fn columns<I: IntoIterator<Item = Column>>(mut self, columns: I) -> Self {
+        self.columns = columns.into_iter().collect();
+        self
+    }
+
+    // Rows with the same key are grouped together.
+    pub fn group_by<F>(mut self, key: F) -> Self
+        where F: Fn(&[String]) -> String + 'static,
+    {
+        self.grouping = Some(Grouping {
+            key: Box::new(key),
+            collapsed: HashSet::new(),
+            groups: Vec::new(),
+            lines: Vec::new(),
+        });
+        self.regroup();
+        self
+    }
+
+    // Shown in the column of each group's row.
+    pub fn aggregate(mut self, column: usize, aggregate: Aggregate) -> Self {
+        self.aggregates.push((column, aggregate));
+        self.regroup();
+        self
+    }
+
+    pub fn editable(mut self) -> Self {
+        self.editable = true;
+        self
+    }
+
+    pub fn column_spacing(mut self, spacing: u16) -> Self {
+        self.column_spacing = spacing;
+        self
+    }
+
+    pub fn wheel_step(mut self, step: usize) -> Self {
+        self.wheel_step = step;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn header_style(mut self, style: Style) -> Self {
+        self.header_style = style;
+        self
+    }
+
+    pub fn footer_style(mut self, style: Style) -> Self {
+        self.footer_style = style;
+        self
+    }
+
+    pub fn group_style(mut self, style: Style) -> Self {
+        self.group_style = style;
+        self
+    }
+
+    // Patched over the style of the selected row.
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    // Patched over the focused cell of an editable table.
+    pub fn cell_style(mut self, style: Style) -> Self {
+        self.cell_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn rows(&self) -> &[Vec<String>] {
+        &self.rows
+    }
+
+    // Keeps the selection if the row is still there.
+    pub fn set_rows<R, C, T>(&mut self, rows: R)
+        where R: IntoIterator<Item = C>, C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.rows = rows.into_iter().map(|row| row.into_iter().map(Into::into).collect()).collect();
+        self.editing = None;
+        self.regroup();
+    }
+
+    // Appended, e.g. as they're loaded.
+    pub fn push_rows<R, C, T>(&mut self, rows: R)
+        where R: IntoIterator<Item = C>, C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.rows.extend(rows.into_iter().map(|row| row.into_iter().map(Into::into).collect()));
+        self.regroup();
+    }
+
+    // The rows in the order they're shown, in every group whether it's collapsed or not.
+    fn ordered_rows(&self) -> Vec<&[String]> {
+        match &self.grouping {
+            | Some(grouping) => {
+                grouping.groups.iter().flat_map(|group| &group.rows).map(|row| self.rows[*row].as_slice()).collect()
+            },
+            | None => self.rows.iter().map(Vec::as_slice).collect(),
+        }
+    }
+
+    // The header and the rows, as they're ordered. JSON is keyed by the last header row.
+    pub fn export(&self, format: ExportFormat) -> String {
+        let (header, rows) = (self.header.iter().map(Vec::as_slice), self.ordered_rows());
+
+        match format {
+            | ExportFormat::Csv => export::csv(&header.chain(rows).collect::<Vec<_>>()),
+            | ExportFormat::Json => export::json(self.header.last().map(Vec::as_slice), &rows),
+            | ExportFormat::Text => {
+                let rows = header.chain(rows).collect::<Vec<_>>();
+                let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+                let alignments = (0..columns).map(|index| self.column(index).alignment.unwrap_or(Alignment::Left));
+
+                export::text(&rows, &alignments.collect::<Vec<_>>(), self.column_spacing as usize)
+            },
+        }
+    }
+
+    // Written once the command's run.
+    pub fn export_to_file<P: Into<PathBuf>>(&self, format: ExportFormat, path: P) -> Command {
+        Command::Export(path.into(), self.export(format))
+    }
+
+    pub fn export_to_clipboard(&self, format: ExportFormat, clipboard: &mut Clipboard) -> Command {
+        clipboard.copy(self.export(format))
+    }
+
+    // Replaces the header rows with this one.
+    pub fn set_header<C, T>(&mut self, row: C)
+        where C: IntoIterator<Item = T>, T: Into<String>,
+    {
+        self.header = vec![row.into_iter().map(Into::into).collect()];
+    }
+
+    fn line_count(&self) -> usize {
+        match &self.grouping {
+            | Some(grouping) => grouping.lines.len(),
+            | None => self.rows.len(),
+        }
+    }
+
+    fn line(&self, index: usize) -> Line {
+        match &self.grouping {
+            | Some(grouping) => grouping.lines[index],
+            | None => Line::Row(index),
+        }
+    }
+
+    fn cells(&self, line: Line) -> &[String] {
+        match (line, &self.grouping) {
+            | (Line::Group(group), Some(grouping)) => &grouping.groups[group].summary,
+            | (Line::Row(row), _) | (Line::Group(row), None) => &self.rows[row],
+        }
+    }
+
+    // Regroups the rows after they've changed, the selection stays on the same row or group.
+    fn regroup(&mut self) {
+        let selected = self.selected.map(|line| self.line(line));
+        let selected_group = match (selected, &self.grouping) {
+            | (Some(Line::Group(group)), Some(grouping)) => Some(grouping.groups[group].key.clone()),
+            | _ => None,
+        };
+
+        if let Some(grouping) = &mut self.grouping {
+            let mut groups = Vec::<Group>::new();
+            let mut indices = HashMap::new();
+
+            for (index, row) in self.rows.iter().enumerate() {
+                let key = (grouping.key)(row);
+
+                let group = *indices.entry(key.clone()).or_insert_with(|| {
+                    groups.push(Group { key, rows: Vec::new(), summary: Vec::new() });
+                    groups.len() - 1
+                });
+
+                groups[group].rows.push(index);
+            }
+
+            for group in &mut groups {
+                let symbol = if grouping.collapsed.contains(&group.key) { '▸' } else { '▾' };
+
+                group.summary = vec![format!("{symbol} {} ({})", group.key, group.rows.len())];
+
+                for (column, aggregate) in &self.aggregates {
+                    if group.summary.len() <= *column {
+                        group.summary.resize(column + 1, String::new());
+                    }
+
+                    let cells = group.rows.iter().filter_map(|row| self.rows[*row].get(*column)).map(String::as_str);
+
+                    group.summary[*column] = aggregate.summarize(cells);
+                }
+            }
+
+            grouping.lines = groups.iter().enumerate().flat_map(|(index, group)| {
+                let rows = match grouping.collapsed.contains(&group.key) {
+                    | true => &[][..],
+                    | false => &group.rows[..],
+                };
+
+                [Line::Group(index)].into_iter().chain(rows.iter().map(|row| Line::Row(*row)))
+            }).collect();
+
+            grouping.groups = groups;
+        }
+
+        self.selected = match (selected, selected_group, &self.grouping) {
+            | (Some(Line::Row(row)), _, Some(grouping)) => {
+                grouping.lines.iter().position(|line| *line == Line::Row(row))
+            },
+            | (_, Some(key), Some(grouping)) => grouping.lines.iter().position(|line| match line {
+                | Line::Group(group) => grouping.groups[*group].key == key,
+                | Line::Row(_) => false,
+            }),
+            | _ => self.selected.filter(|selected| *selected < self.line_count()),
+        };
+
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    // Collapsing a group with the selection in it selects the group.
+    pub fn set_collapsed(&mut self, key: &str, collapsed: bool) {
+        let Some(grouping) = &mut self.grouping else {
+            return;
+        };
+
+        match collapsed {
+            | true => grouping.collapsed.insert(key.to_owned()),
+            | false => grouping.collapsed.remove(key),
+        };
+
+        let selected = self.selected.map(|line| self.line(line));
+
+        if let (Some(Line::Row(row)), true, Some(grouping)) = (selected, collapsed, &self.grouping) {
+            let group = grouping.groups.iter().position(|group| group.key == key && group.rows.contains(&row));
+
+            if let Some(group) = group {
+                self.selected = grouping.lines.iter().position(|line| *line == Line::Group(group));
+            }
+        }
+
+        self.regroup();
+    }
+
+    #[inline(always)]
+    pub fn is_collapsed(&self, key: &str) -> bool {
+        self.grouping.as_ref().is_some_and(|grouping| grouping.collapsed.contains(key))
+    }
+
+    // The key of the selected group, if it's a group that's selected rather than a row.
+    pub fn selected_group(&self) -> Option<&str> {
+        match (self.line(self.selected?), &self.grouping) {
+            | (Line::Group(group), Some(grouping)) => Some(&grouping.groups[group].key),
+            | _ => None,
+        }
+    }
+
+    fn toggle(&mut self, group: usize, collapsed: Option<bool>) {
+        let Some(grouping) = &self.grouping else {
+            return;
+        };
+
+        let key = grouping.groups[group].key.clone();
+        let collapsed = collapsed.unwrap_or(!grouping.collapsed.contains(&key));
+
+        self.set_collapsed(&key, collapsed);
+    }
+
+    // Rows too short for the column are filled up with empty cells.
+    pub fn set_cell<T: Into<String>>(&mut self, row: usize, col: usize, value: T) {
+        let Some(row) = self.rows.get_mut(row) else {
+            return;
+        };
+
+        if row.len() <= col {
+            row.resize(col + 1, String::new());
+        }
+
+        row[col] = value.into();
+
+        self.regroup();
+    }
+
+    #[inline(always)]
+    pub fn focused_column(&self) -> usize {
+        self.column
+    }
+
+    #[inline(always)]
+    pub fn is_editing(&self) -> bool {
+        self.editing.is_some()
+    }
+
+    // The selected row, `None` while a group is selected.
+    pub fn selected(&self) -> Option<usize> {
+        match self.line(self.selected?) {
+            | Line::Row(row) => Some(row),
+            | Line::Group(_) => None,
+        }
+    }
+
+    // Scrolls the row into view, expanding its group.
+    pub fn select(&mut self, index: usize) -> Option<RowSelected> {
+        let group = self.grouping.as_ref()
+            .and_then(|grouping| grouping.groups.iter().find(|group| group.rows.contains(&index)))
+            .map(|group| group.key.clone());
+
+        if let Some(group) = group {
+            self.set_collapsed(&group, false);
+        }
+
+        let line = (0..self.line_count()).find(|line| self.line(*line) == Line::Row(index))?;
+
+        self.select_line(line)
+    }
+
+    fn select_line(&mut self, line: usize) -> Option<RowSelected> {
+        let previous = self.selected();
+
+        if line >= self.line_count() || self.selected == Some(line) {
+            return None;
+        }
+
+        self.selected = Some(line);
+        self.reveal(line);
+
+        match self.line(line) {
+            | Line::Row(index) => Some(RowSelected { index, previous }),
+            | Line::Group(_) => None,
+        }
+    }
+
+    // How many rows scroll into view, as last rendered.
+    #[inline(always)]
+    fn page(&self) -> usize {
+        self.body.get().height.max(1) as usize
+    }
+
+    fn max_offset(&self) -> usize {
+        self.line_count().saturating_sub(self.page())
+    }
+
+    fn reveal(&mut self, index: usize) {
+        self.offset = self.offset.clamp((index + 1).saturating_sub(self.page()), index);
+    }
+
+    fn column_count(&self, row: usize) -> usize {
+        self.header.iter().map(Vec::len).chain([self.rows[row].len(), self.columns.len()]).max().unwrap_or(0)
+    }
+
+    // While a cell is edited, its editor gets every key.
+    fn edit(&mut self, key: &KeyMsg, clipboard: &mut Clipboard) -> Option<TableEvent> {
+        let row = self.selected()?;
+        let editor = self.editing.as_mut()?;
+
+        if key.code == KeyCode::Esc {
+            self.editing = None;
+            return None;
+        }
+
+        match editor.handle_key(key, clipboard)? {
+            | TextEvent::Submitted(value) => {
+                self.editing = None;
+                self.set_cell(row, self.column, value.clone());
+
+                Some(TableEvent::Edited(CellEdited { row, col: self.column, value }))
+            },
+            | TextEvent::Copied(command) => Some(TableEvent::Copied(command)),
+            | TextEvent::Changed => None,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg, clipboard: &mut Clipboard) -> Option<TableEvent> {
+        if self.editing.is_some() {
+            return self.edit(key, clipboard);
+        }
+
+        let (last, page) = (self.line_count().checked_sub(1)?, self.page());
+        let (selected, line) = (self.selected.unwrap_or(0), self.selected.map(|line| self.line(line)));
+
+        let index = match (key.code, line) {
+            | (KeyCode::Enter | KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right, Some(Line::Group(group))) => {
+                let collapsed = match key.code {
+                    | KeyCode::Left => Some(true),
+                    | KeyCode::Right => Some(false),
+                    | _ => None,
+                };
+
+                self.toggle(group, collapsed);
+                return None;
+            },
+            | (KeyCode::Enter, Some(Line::Row(row))) if self.editable => {
+                let value = self.rows[row].get(self.column).cloned().unwrap_or_default();
+
+                self.editing = Some(TextInput::new(value));
+                return None;
+            },
+            | (KeyCode::Left, Some(_)) if self.editable => {
+                self.column = self.column.saturating_sub(1);
+                return None;
+            },
+            | (KeyCode::Right, Some(Line::Row(row))) if self.editable => {
+                self.column = (self.column + 1).min(self.column_count(row).saturating_sub(1));
+                return None;
+            },
+            | (KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown, None) => 0,
+            | (KeyCode::Up, Some(_)) => selected.saturating_sub(1),
+            | (KeyCode::Down, Some(_)) => (selected + 1).min(last),
+            | (KeyCode::PageUp, Some(_)) => selected.saturating_sub(page),
+            | (KeyCode::PageDown, Some(_)) => (selected + page).min(last),
+            | (KeyCode::Home, _) => 0,
+            | (KeyCode::End, _) => last,
+            | _ => return None,
+        };
+
+        self.select_line(index).map(TableEvent::Selected)
+    }
+
+    // The focused cell of the selected row, if it's in view, as last rendered.
+    fn focused_cell(&self) -> Option<Rect> {
+        let (body, widths) = (self.body.get(), self.widths.borrow());
+
+        let selected = self.selected.filter(|line| matches!(self.line(*line), Line::Row(_)))?;
+        let row = selected.checked_sub(self.offset).filter(|row| *row < body.height as usize)?;
+        let width = *widths.get(self.column)?;
+        let x = widths[..self.column].iter().map(|width| width + self.column_spacing).sum::<u16>();
+
+        Some(Rect::new(body.x + x, body.y + row as u16, width, 1).intersection(body))
+    }
+
+    fn column_at(&self, x: u16) -> Option<usize> {
+        let mut left = self.body.get().x;
+
+        self.widths.borrow().iter().position(|width| {
+            left += width + self.column_spacing;
+            x < left
+        })
+    }
+
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<RowSelected> {
+        let body = self.body.get();
+        let on_editor = self.focused_cell().is_some_and(|cell| contains(cell, mouse.column, mouse.row));
+
+        if let Some(editor) = &mut self.editing {
+            match mouse.kind {
+                | MouseEventKind::Down(_) if !on_editor => self.editing = None,
+                | _ => {
+                    editor.handle_mouse(mouse);
+                    return None;
+                },
+            }
+        }
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if contains(body, mouse.column, mouse.row) => {
+                if let (true, Some(column)) = (self.editable, self.column_at(mouse.column)) {
+                    self.column = column;
+                }
+
+                let line = self.offset + (mouse.row - body.y) as usize;
+
+                if let Some(Line::Group(group)) = (line < self.line_count()).then(|| self.line(line)) {
+                    self.select_line(line);
+                    self.toggle(group, None);
+
+                    return None;
+                }
+
+                self.select_line(line)
+            },
+            | MouseEventKind::ScrollDown if contains(body, mouse.column, mouse.row) => {
+                self.offset = (self.offset + self.wheel_step).min(self.max_offset());
+                None
+            },
+            | MouseEventKind::ScrollUp if contains(body, mouse.column, mouse.row) => {
+                self.offset = self.offset.saturating_sub(self.wheel_step);
+                None
+            },
+            | _ => None,
+        }
+    }
+
+    #[inline(always)]
+    fn column(&self, index: usize) -> Column {
+        self.columns.get(index).copied().unwrap_or_default()
+    }
+
+    #[cfg(feature = "csv")]
+    pub(crate) fn column_mut(&mut self, index: usize) -> &mut Column {
+        if self.columns.len() <= index {
+            self.columns.resize(index + 1, Column::default());
+        }
+
+        &mut self.columns[index]
+    }
+
+    // Only the rows in view are sampled, however many there are.
+    fn widths(&self, width: u16) -> Vec<u16> {
+        let visible = (self.offset..self.line_count()).take(self.page()).map(|line| self.cells(self.line(line)));
+        let sampled = self.header.iter().map(Vec::as_slice).chain(self.footer.iter().map(Vec::as_slice)).chain(visible)
+            .collect::<Vec<_>>();
+
+        let count = sampled.iter().map(|row| row.len()).max().unwrap_or(0).max(self.columns.len());
+
+        let mut widths = (0..count).map(|index| {
+            let Column { min, max, .. } = self.column(index);
+            let content = sampled.iter().filter_map(|row| row.get(index)).map(|cell| cell.width()).max();
+
+            (content.unwrap_or(0).min(u16::MAX as usize) as u16).clamp(min, max.max(min))
+        }).collect::<Vec<_>>();
+
+        let spacing = self.column_spacing as usize * count.saturating_sub(1);
+        let available = (width as usize).saturating_sub(spacing);
+
+        let mut total = widths.iter().map(|width| *width as usize).sum::<usize>();
+
+        while total > available {
+            let shrinkable = (0..count).filter(|index| widths[*index] > self.column(*index).min);
+
+            let Some(widest) = shrinkable.max_by_key(|index| (widths[*index], Reverse(*index))) else {
+                break;
+            };
+
+            widths[widest] -= 1;
+            total -= 1;
+        }
+
+        let fitted = widths.clone();
+
+        // One cell at a time to whichever weighted column is furthest behind its share.
+        while total < available {
+            let growable = (0..count).filter(|index| {
+                let Column { weight, max, .. } = self.column(*index);
+                weight > 0 && widths[*index] < max
+            });
+
+            let behind = growable.min_by(|a, b| {
+                let grown = |index: usize| (widths[index] - fitted[index] + 1) as u32;
+                (grown(*a) * self.column(*b).weight as u32).cmp(&(grown(*b) * self.column(*a).weight as u32))
+            });
+
+            let Some(behind) = behind else {
+                break;
+            };
+
+            widths[behind] += 1;
+            total += 1;
+        }
+
+        widths
+    }
+
+    fn render_row(&self, row: &[String], widths: &[u16], area: Rect, buf: &mut Buffer, style: Style) {
+        buf.set_style(area, style);
+
+        let mut x = area.x;
+
+        for (index, (cell, width)) in row.iter().zip(widths).enumerate() {
+            let column = self.column(index);
+            let cell = truncate(cell, *width as usize, column.truncation);
+
+            let padding = match column.alignment.unwrap_or(Alignment::Left) {
+                | Alignment::Left => 0,
+                | Alignment::Center => (*width as usize - cell.width()) / 2,
+                | Alignment::Right => *width as usize - cell.width(),
+            };
+
+            let (left, room) = (x.saturating_add(padding as u16), *width - padding as u16);
+
+            buf.set_stringn(left, area.y, cell, room.min(area.right().saturating_sub(left)) as usize, style);
+            x = x.saturating_add(width + self.column_spacing);
+        }
+    }
+}
+
+impl Scrollable for Table {
+    // The pinned rows count as well, they take up the viewport too.
+    fn content_length(&self, _: Rect) -> usize {
+        self.header.len() + self.line_count() + self.footer.len()
+    }
+
+    #[inline(always)]
+    fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn set_offset(&mut self, offset: usize) {
+        self.offset = offset.min(self.max_offset());
+    }
+}
+
+impl Widget for &Table {
+    // The header is kept before the footer, and both before the rows that scroll, when there's no room for all.
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let header = (self.header.len() as u16).min(area.height);
+        let footer = (self.footer.len() as u16).min(area.height - header);
+
+        let body = Rect { y: area.y + header, height: area.height - header - footer, ..area };
+
+        self.body.set(body);
+
+        let widths = self.widths(area.width);
+
+        self.widths.replace(widths.clone());
+
+        let pinned = self.header.iter().zip(area.top()..).take(header as usize).map(|row| (row, self.header_style))
+            .chain(self.footer.iter().zip(body.bottom()..).take(footer as usize).map(|row| (row, self.footer_style)));
+
+        for ((row, y), style) in pinned {
+            self.render_row(row, &widths, Rect { y, height: 1, ..area }, buf, style);
+        }
+
+        buf.set_style(body, self.style);
+
+        for (index, y) in (self.offset..self.line_count()).zip(body.top()..body.bottom()) {
+            let line = self.line(index);
+
+            let style = match line {
+                | Line::Group(_) => self.style.patch(self.group_style),
+                | Line::Row(_) => self.style,
+            };
+
+            let style = match self.selected == Some(index) {
+                | true => style.patch(self.selected_style),
+                | false => style,
+            };
+
+            self.render_row(self.cells(line), &widths, Rect { y, height: 1, ..body }, buf, style);
+        }
+
+        if let (true, Some(cell)) = (self.editable, self.focused_cell()) {
+            match &self.editing {
+                | Some(editor) => editor.render(cell, buf),
+                | None => buf.set_style(cell, self.cell_style),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, mouse};
+
+    fn table() -> Table {
+        Table::new((0..20).map(|row| [format!("r{row}"), format!("{}", row * 10)]))
+            .header(["name", "value"])
+            .footer(["total", "1900"])
+    }
+
+    fn rendered(table: &Table, width: u16, height: u16) -> Vec<String> {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+
+        table.render(buf.area, &mut buf);
+
+        (0..height).map(|y| (0..width).map(|x| buf.get(x, y).symbol()).collect()).collect()
+    }
+
+    #[test]
+    fn the_header_and_footer_stay_in_view_while_scrolling() {
+        let mut table = table();
+
+        assert_eq!(rendered(&table, 11, 4), ["name  value", "r0    0    ", "r1    10   ", "total 1900 "]);
+
+        table.handle_key(&key(KeyCode::End), &mut Clipboard::default());
+
+        assert_eq!(rendered(&table, 11, 4), ["name  value", "r18   180  ", "r19   190  ", "total 1900 "]);
+        assert_eq!(table.offset(), 18);
+        assert_eq!(table.max_offset(), table.content_length(Rect::default()) - 4);
+    }
+
+    #[test]
+    fn pinned_rows_keep_their_own_style() {
+        let mut table = table().header_style(Style::default().add_modifier(Modifier::ITALIC));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 11, 4));
+
+        table.select(0);
+        (&table).render(buf.area, &mut buf);
+
+        assert!(buf.get(10, 0).modifier.contains(Modifier::ITALIC));
+        assert!(buf.get(10, 1).modifier.contains(Modifier::REVERSED));
+        assert!(buf.get(10, 3).modifier.contains(Modifier::DIM));
+        assert!(!buf.get(10, 2).modifier.intersects(Modifier::ITALIC | Modifier::DIM | Modifier::REVERSED));
+    }
+
+    #[test]
+    fn paging_moves_by_the_rows_in_view() {
+        let mut table = table();
+
+        rendered(&table, 11, 6);
+
+        let selected = table.handle_key(&key(KeyCode::Down), &mut Clipboard::default());
+
+        assert!(matches!(selected, Some(TableEvent::Selected(RowSelected { index: 0, previous: None }))));
+
+        table.handle_key(&key(KeyCode::PageDown), &mut Clipboard::default());
+
+        assert_eq!(table.selected(), Some(4));
+        assert_eq!(table.offset(), 1);
+
+        table.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 0, 2));
+
+        assert_eq!(table.selected(), Some(2));
+        assert_eq!(table.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 0, 0)), None);
+    }
+
+    #[test]
+    fn the_footer_gives_way_before_the_header() {
+        assert_eq!(rendered(&table(), 11, 1), ["name  value"]);
+        assert_eq!(rendered(&table(), 11, 2), ["name  value", "total 1900 "]);
+    }
+
+    #[test]
+    fn columns_fit_their_content_in_view_and_share_what_is_left() {
+        let mut table = Table::new([["a", "bb", "c"], ["aaaa", "b", "c"]])
+            .columns([Column::new(), Column::new().weight(1), Column::new().weight(3).max(4)]);
+
+        rendered(&table, 14, 2);
+
+        assert_eq!(table.widths(14), [4, 4, 4]);
+        assert_eq!(table.widths(20), [4, 10, 4]);
+
+        table.set_rows([["a".repeat(30)], ["a".into()], ["a".into()]]);
+        table.set_offset(1);
+
+        assert_eq!(table.widths(8), [1, 1, 4]);
+    }
+
+    #[test]
+    fn the_widest_columns_give_way_down_to_their_minimum() {
+        let table = Table::new([["long cell", "longer cell", "x"]]).columns([Column::new().min(8)]);
+
+        assert_eq!(table.widths(20), [8, 9, 1]);
+        assert_eq!(table.widths(12), [8, 1, 1]);
+    }
+
+    #[test]
+    fn cells_are_truncated_where_their_column_says() {
+        assert_eq!(truncate("abcdefgh", 5, Truncation::End), "abcd…");
+        assert_eq!(truncate("abcdefgh", 5, Truncation::Start), "…efgh");
+        assert_eq!(truncate("abcdefgh", 6, Truncation::Middle), "abc…gh");
+        assert_eq!(truncate("漢字漢字", 4, Truncation::End), "漢…");
+        assert_eq!(truncate("short", 5, Truncation::Start), "short");
+
+        let column = Column::new().max(10).truncation(Truncation::Start);
+        let table = Table::new([["/home/user/file.txt"]]).columns([column]);
+
+        assert_eq!(rendered(&table, 10, 1), ["…/file.txt"]);
+    }
+
+    #[test]
+    fn edited_cells_are_kept_on_enter_and_dropped_on_escape() {
+        let (mut table, mut clipboard) = (table().editable(), Clipboard::default());
+
+        rendered(&table, 11, 4);
+
+        for code in [KeyCode::Down, KeyCode::Right, KeyCode::Right, KeyCode::Enter, KeyCode::Char('5')] {
+            table.handle_key(&key(code), &mut clipboard);
+        }
+
+        assert!(table.is_editing());
+        assert_eq!(rendered(&table, 11, 4)[1], "r0    05   ");
+
+        let edited = table.handle_key(&key(KeyCode::Enter), &mut clipboard);
+
+        let Some(TableEvent::Edited(edited)) = edited else {
+            panic!("expected the edit");
+        };
+
+        assert_eq!(edited, CellEdited { row: 0, col: 1, value: "05".into() });
+        assert_eq!(table.rows()[0], ["r0", "05"]);
+
+        for code in [KeyCode::Down, KeyCode::Enter, KeyCode::Backspace, KeyCode::Esc] {
+            table.handle_key(&key(code), &mut clipboard);
+        }
+
+        assert!(!table.is_editing());
+        assert_eq!(table.rows()[1], ["r1", "10"]);
+    }
+
+    #[test]
+    fn clicking_a_cell_focuses_it_and_ends_the_edit() {
+        let (mut table, mut clipboard) = (table().editable(), Clipboard::default());
+
+        rendered(&table, 11, 4);
+        table.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 7, 2));
+
+        assert_eq!((table.selected(), table.focused_column()), (Some(1), 1));
+
+        table.handle_key(&key(KeyCode::Enter), &mut clipboard);
+        rendered(&table, 11, 4);
+        table.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 0, 1));
+
+        assert!(!table.is_editing());
+        assert_eq!((table.selected(), table.focused_column()), (Some(0), 0));
+    }
+
+    fn grouped() -> Table {
+        Table::new([["a", "x", "2"], ["b", "y", "3"], ["a", "z", "4.5"]])
+            .group_by(|row| row[0].clone())
+            .aggregate(1, Aggregate::Count)
+            .aggregate(2, Aggregate::Sum)
+    }
+
+    #[test]
+    fn groups_summarize_their_rows_and_collapse() {
+        let (mut table, mut clipboard) = (grouped(), Clipboard::default());
+
+        assert_eq!(rendered(&table, 20, 5), [
+            "▾ a (2) 2 6.50      ",
+            "a       x 2         ",
+            "a       z 4.5       ",
+            "▾ b (1) 1 3         ",
+            "b       y 3         ",
+        ]);
+
+        assert!(table.handle_key(&key(KeyCode::Down), &mut clipboard).is_none());
+
+        table.handle_key(&key(KeyCode::Left), &mut clipboard);
+
+        assert_eq!(rendered(&table, 20, 3), ["▸ a (2) 2 6.50      ", "▾ b (1) 1 3         ", "b       y 3         "]);
+        assert_eq!(table.selected_group(), Some("a"));
+
+        assert_eq!(table.select(2), Some(RowSelected { index: 2, previous: None }));
+        assert!(!table.is_collapsed("a"));
+
+        table.set_collapsed("a", true);
+
+        assert_eq!((table.selected(), table.selected_group()), (None, Some("a")));
+    }
+
+    #[test]
+    fn edited_rows_move_to_their_new_group_with_the_selection() {
+        let mut table = grouped().editable();
+
+        table.select(1);
+        table.set_cell(1, 0, "a");
+
+        assert_eq!(table.selected(), Some(1));
+        assert_eq!(table.cells(table.line(0)), ["▾ a (3)", "3", "9.50"]);
+        assert_eq!(table.line_count(), 4);
+    }
+
+    #[test]
+    fn aggregates_leave_out_what_is_not_a_number() {
+        let cells = ["3", "", "x", "1", " 2 "];
+
+        let summaries = [Aggregate::Count, Aggregate::Sum, Aggregate::Mean, Aggregate::Min, Aggregate::Max]
+            .map(|aggregate| aggregate.summarize(cells.into_iter()));
+
+        assert_eq!(summaries, ["4", "6", "2", "1", "3"]);
+        assert_eq!(Aggregate::Sum.summarize(["x"].into_iter()), "");
+    }
+
+    #[test]
+    fn cells_are_aligned_within_their_column() {
+        let columns = [Column::new().alignment(Alignment::Right), Column::new().alignment(Alignment::Center)];
+        let table = Table::new([["1", "ab"], ["200", "abcd"]]).columns(columns);
+
+        assert_eq!(rendered(&table, 8, 2), ["  1  ab ", "200 abcd"]);
+    }
+
+    #[test]
+    fn exports_the_rows_in_the_order_they_are_shown() {
+        let mut table = grouped().header(["team", "name", "hours"]).column_spacing(2);
+
+        table.set_collapsed("b", true);
+
+        assert_eq!(table.export(ExportFormat::Csv), "team,name,hours\na,x,2\na,z,4.5\nb,y,3\n");
+
+        let mut table = table.columns([Column::new(), Column::new(), Column::new().alignment(Alignment::Right)]);
+        let text = "team  name  hours\na     x         2\na     z       4.5\nb     y         3\n";
+
+        assert_eq!(table.export(ExportFormat::Text), text);
+
+        let mut clipboard = Clipboard::default();
+
+        assert!(matches!(table.export_to_clipboard(ExportFormat::Json, &mut clipboard), Command::Crossterm(_)));
+        assert!(clipboard.contents().unwrap().starts_with("[\n  {\"team\": \"a\", \"name\": \"x\""));
+        let command = table.export_to_file(ExportFormat::Csv, "out.csv");
+
+        assert!(matches!(command, Command::Export(path, _) if path.as_os_str() == "out.csv"));
+
+        table.set_rows(Vec::<Vec<String>>::new());
+
+        assert_eq!(table.export(ExportFormat::Json), "[]\n");
+    }
+}
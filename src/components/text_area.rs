@@ -0,0 +1,839 @@
+use std::cell::Cell;
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "spellcheck")]
+use std::sync::Arc;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::command::Command;
+use crate::components::contains;
+use crate::components::selection::{self as text, Clipboard, Selection};
+use crate::components::undo::{History, Snapshot};
+#[cfg(feature = "spellcheck")]
+use crate::components::spelling::{self, Dictionary, SpellChecked, Spelling, Suggestions};
+use crate::message::{KeyMsg, MouseMsg};
+#[cfg(feature = "spellcheck")]
+use crate::{command, message::Message};
+
+// Returned when the screen might want to act on an edit.
+#[derive(Debug)]
+pub enum TextEvent {
+    Changed,
+    // Enter in a `TextInput`, with what was entered.
+    Submitted(String),
+    // Cut or copied, return it from `Screen::update` to put it on the system clipboard as well.
+    Copied(Command),
+}
+
+// Multiple lines of editable text, without wrapping. Scrolls to keep the cursor in view. Selection, cut, copy and
+// paste go through `Selection` and a `Clipboard` shared with the screen's other text components. Ctrl+z undoes,
+// ctrl+y (or ctrl+shift+z) redoes, see `History`.
+//
+// With the spellcheck feature, misspelled words are underlined once checked on a background thread with
+// `spawn_spell_check` and the result applied with `apply_spell_check`. Suggestions for the word at the cursor work
+// the same way, they're shown below it until one is picked with enter or tab, or escape closes them.
+#[derive(Debug)]
+pub struct TextArea {
+    text: String,
+    selection: Selection,
+    history: History,
+    #[cfg(feature = "spellcheck")]
+    spelling: Spelling,
+    single_line: bool,
+    placeholder: Option<String>,
+    style: Style,
+    placeholder_style: Style,
+    selection_style: Style,
+    cursor_style: Style,
+    // The first visible line and column, updated when rendered.
+    scroll: Cell<(usize, usize)>,
+    area: Cell<Rect>,
+    dragging: bool,
+}
+
+impl TextArea {
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        let text = text.into();
+
+        Self {
+            selection: Selection::caret(text.len()),
+            text,
+            history: History::default(),
+            #[cfg(feature = "spellcheck")]
+            spelling: Spelling::default(),
+            single_line: false,
+            placeholder: None,
+            style: Style::default(),
+            placeholder_style: Style::default().add_modifier(Modifier::DIM),
+            selection_style: Style::default().add_modifier(Modifier::REVERSED),
+            cursor_style: Style::default().add_modifier(Modifier::REVERSED),
+            scroll: Cell::new((0, 0)),
+            area: Cell::new(Rect::default()),
+            dragging: false,
+        }
+    }
+
+    // Shown while there's no text.
+    pub fn placeholder<T: Into<String>>(mut self, placeholder: T) -> Self {
+        self.placeholder = Some(placeholder.into());
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn placeholder_style(mut self, style: Style) -> Self {
+        self.placeholder_style = style;
+        self
+    }
+
+    pub fn selection_style(mut self, style: Style) -> Self {
+        self.selection_style = style;
+        self
+    }
+
+    pub fn cursor_style(mut self, style: Style) -> Self {
+        self.cursor_style = style;
+        self
+    }
+
+    // How many edits can be undone.
+    pub fn history_limit(mut self, limit: usize) -> Self {
+        self.history = History::new(limit);
+        self
+    }
+
+    // Patched over the style of misspelled words, a red underline by default.
+    #[cfg(feature = "spellcheck")]
+    pub fn misspelling_style(mut self, style: Style) -> Self {
+        self.spelling.style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text<T: Into<String>>(&mut self, text: T) {
+        self.text = self.single_lined(text.into());
+        self.selection.move_to(self.text.len());
+        self.changed();
+    }
+
+    fn changed(&mut self) {
+        #[cfg(feature = "spellcheck")]
+        self.spelling.changed();
+    }
+
+    #[inline(always)]
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    // Offsets past the end or inside a character are moved back to the closest boundary.
+    pub fn set_selection(&mut self, mut selection: Selection) {
+        let clamp = |at: usize| (0..=at.min(self.text.len())).rev().find(|at| self.text.is_char_boundary(*at));
+
+        selection.select(
+            clamp(selection.anchor()).unwrap_or(0),
+            clamp(selection.cursor()).unwrap_or(0),
+            selection.mode(),
+        );
+
+        self.selection = selection;
+    }
+
+    #[inline(always)]
+    pub fn selected(&self) -> &str {
+        self.selection.selected(&self.text)
+    }
+
+    #[inline(always)]
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    #[inline(always)]
+    pub fn set_history(&mut self, history: History) {
+        self.history = history;
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot { text: self.text.clone(), selection: self.selection }
+    }
+
+    fn apply(&mut self, snapshot: Option<Snapshot>) -> Option<TextEvent> {
+        let Snapshot { text, selection } = snapshot?;
+
+        self.text = text;
+        self.set_selection(selection);
+        self.changed();
+
+        Some(TextEvent::Changed)
+    }
+
+    pub fn undo(&mut self) -> Option<TextEvent> {
+        let undone = self.history.undo(self.snapshot());
+        self.apply(undone)
+    }
+
+    pub fn redo(&mut self) -> Option<TextEvent> {
+        let redone = self.history.redo(self.snapshot());
+        self.apply(redone)
+    }
+
+    // The text, the selection and the history, e.g. for `Screen::autosave`. Read back with `restore`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        self.snapshot().encode(&mut bytes);
+        bytes.extend(self.history.to_bytes());
+
+        bytes
+    }
+
+    // Whether the bytes were produced by `to_bytes`, nothing changes if not.
+    pub fn restore(&mut self, mut bytes: &[u8]) -> bool {
+        let Some((snapshot, history)) = Snapshot::decode(&mut bytes).zip(History::from_bytes(bytes)) else {
+            return false;
+        };
+
+        self.history = history;
+        self.apply(Some(snapshot));
+
+        true
+    }
+
+    // Where the misspelled words are in the text, as of the last check.
+    #[cfg(feature = "spellcheck")]
+    #[inline(always)]
+    pub fn misspellings(&self) -> &[std::ops::Range<usize>] {
+        &self.spelling.misspellings
+    }
+
+    #[cfg(feature = "spellcheck")]
+    pub fn spawn_spell_check(&self, dictionary: &Arc<Dictionary>) -> Command {
+        let (dictionary, text, revision) = (Arc::clone(dictionary), self.text.clone(), self.spelling.revision);
+
+        command::spawn(move || {
+            Message::custom(SpellChecked { revision, misspellings: dictionary.misspellings(&text) })
+        })
+    }
+
+    // Returns whether the check was of the current text.
+    #[cfg(feature = "spellcheck")]
+    pub fn apply_spell_check(&mut self, checked: SpellChecked) -> bool {
+        if checked.revision != self.spelling.revision {
+            return false;
+        }
+
+        self.spelling.misspellings = checked.misspellings;
+
+        true
+    }
+
+    // `None` if the cursor isn't in or next to a word.
+    #[cfg(feature = "spellcheck")]
+    pub fn spawn_suggestions(&self, dictionary: &Arc<Dictionary>, limit: usize) -> Option<Command> {
+        let cursor = self.selection.cursor();
+        let word = spelling::words(&self.text).find(|word| (word.start..=word.end).contains(&cursor))?;
+
+        let (dictionary, text) = (Arc::clone(dictionary), self.text[word.clone()].to_owned());
+        let revision = self.spelling.revision;
+
+        Some(command::spawn(move || {
+            Message::custom(Suggestions { revision, words: dictionary.suggestions(&text, limit), word })
+        }))
+    }
+
+    // Returns whether the suggestions are shown, they aren't if there are none or the text changed since.
+    #[cfg(feature = "spellcheck")]
+    pub fn apply_suggestions(&mut self, suggestions: Suggestions) -> bool {
+        if suggestions.revision != self.spelling.revision || suggestions.words.is_empty() {
+            return false;
+        }
+
+        self.spelling.suggestions = Some((suggestions.word, suggestions.words, 0));
+
+        true
+    }
+
+    // While suggestions are shown, the arrow keys pick one and enter or tab replaces the word with it.
+    #[cfg(feature = "spellcheck")]
+    fn handle_suggestions(&mut self, key: &KeyMsg) -> Option<Option<TextEvent>> {
+        let (word, words, highlighted) = self.spelling.suggestions.as_mut()?;
+
+        match key.code {
+            | KeyCode::Up => *highlighted = highlighted.checked_sub(1).unwrap_or(words.len() - 1),
+            | KeyCode::Down => *highlighted = (*highlighted + 1) % words.len(),
+            | KeyCode::Enter | KeyCode::Tab => {
+                let (word, replacement) = (word.clone(), words[*highlighted].clone());
+
+                self.selection.select(word.start, word.end, Default::default());
+
+                return Some(self.insert(&replacement, None));
+            },
+            | KeyCode::Esc => self.spelling.suggestions = None,
+            | _ => {
+                self.spelling.suggestions = None;
+                return None;
+            },
+        }
+
+        Some(None)
+    }
+
+    fn single_lined(&self, text: String) -> String {
+        match self.single_line {
+            | true => text.replace(['\r', '\n'], " "),
+            | false => text.replace("\r\n", "\n"),
+        }
+    }
+
+    // Recorded in the history, typed characters are joined into words there.
+    fn insert(&mut self, inserted: &str, typed: Option<char>) -> Option<TextEvent> {
+        let inserted = self.single_lined(inserted.to_owned());
+
+        self.history.record(self.snapshot(), typed);
+
+        self.selection.replace(&mut self.text, &inserted);
+        self.changed();
+
+        Some(TextEvent::Changed)
+    }
+
+    // The selection, or what's between the cursor and `to` without one.
+    fn delete_towards(&mut self, to: usize) -> Option<TextEvent> {
+        if self.selection.is_empty() {
+            let cursor = self.selection.cursor();
+
+            if to == cursor {
+                return None;
+            }
+
+            self.selection.select(cursor, to, Default::default());
+        }
+
+        self.insert("", None)
+    }
+
+    fn move_cursor(&mut self, to: usize, extend: bool) {
+        match extend {
+            | true => self.selection.extend_to(to),
+            | false => self.selection.move_to(to),
+        }
+    }
+
+    // Down or up a line, keeping to the same column as far as the line allows.
+    fn vertically(&self, down: bool) -> usize {
+        let cursor = self.selection.cursor();
+        let start = text::line_start(&self.text, cursor);
+        let column = self.text[start..cursor].width();
+
+        let line = match down {
+            | true => match self.text[cursor..].find('\n') {
+                | Some(index) => cursor + index + 1,
+                | None => return self.text.len(),
+            },
+            | false => match start {
+                | 0 => return 0,
+                | start => text::line_start(&self.text, start - 1),
+            },
+        };
+
+        self.at_column(line, column)
+    }
+
+    fn at_column(&self, line: usize, column: usize) -> usize {
+        let mut width = 0;
+
+        for (index, c) in self.text[line..text::line_end(&self.text, line)].char_indices() {
+            width += c.width().unwrap_or(0);
+
+            if width > column {
+                return line + index;
+            }
+        }
+
+        text::line_end(&self.text, line)
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg, clipboard: &mut Clipboard) -> Option<TextEvent> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        let (cursor, range) = (self.selection.cursor(), self.selection.range(&self.text));
+
+        #[cfg(feature = "spellcheck")]
+        if let Some(handled) = self.handle_suggestions(key) {
+            return handled;
+        }
+
+        let to = match key.code {
+            | KeyCode::Char('a') if ctrl => {
+                self.selection.select_all(&self.text);
+                return None;
+            },
+            | KeyCode::Char('c') if ctrl && !range.is_empty() => {
+                return Some(TextEvent::Copied(clipboard.copy(self.selected())));
+            },
+            | KeyCode::Char('x') if ctrl && !range.is_empty() => {
+                let command = clipboard.copy(self.selected());
+
+                self.insert("", None);
+
+                return Some(TextEvent::Copied(command));
+            },
+            | KeyCode::Char('v') if ctrl => {
+                let pasted = clipboard.contents()?.to_owned();
+                return self.insert(&pasted, None);
+            },
+            | KeyCode::Char('z') if ctrl => return self.undo(),
+            | KeyCode::Char('y' | 'Z') if ctrl => return self.redo(),
+            | KeyCode::Char(_) if ctrl || key.modifiers.contains(KeyModifiers::ALT) => return None,
+            | KeyCode::Char(c) => return self.insert(c.encode_utf8(&mut [0; 4]), Some(c)),
+            | KeyCode::Enter if self.single_line => return Some(TextEvent::Submitted(self.text.clone())),
+            | KeyCode::Enter => return self.insert("\n", Some('\n')),
+            | KeyCode::Backspace if ctrl => return self.delete_towards(text::previous_word(&self.text, cursor)),
+            | KeyCode::Backspace => return self.delete_towards(text::previous(&self.text, cursor)),
+            | KeyCode::Delete if ctrl => return self.delete_towards(text::next_word(&self.text, cursor)),
+            | KeyCode::Delete => return self.delete_towards(text::next(&self.text, cursor)),
+            // Without shift, a selection collapses to the side it's left by.
+            | KeyCode::Left if !shift && !range.is_empty() => range.start,
+            | KeyCode::Right if !shift && !range.is_empty() => range.end,
+            | KeyCode::Left if ctrl => text::previous_word(&self.text, cursor),
+            | KeyCode::Right if ctrl => text::next_word(&self.text, cursor),
+            | KeyCode::Left => text::previous(&self.text, cursor),
+            | KeyCode::Right => text::next(&self.text, cursor),
+            | KeyCode::Up => self.vertically(false),
+            | KeyCode::Down => self.vertically(true),
+            | KeyCode::Home if ctrl => 0,
+            | KeyCode::End if ctrl => self.text.len(),
+            | KeyCode::Home => text::line_start(&self.text, cursor),
+            | KeyCode::End => text::line_end(&self.text, cursor),
+            | _ => return None,
+        };
+
+        self.move_cursor(to, shift);
+        self.history.interrupt();
+
+        None
+    }
+
+    // Pasted text replaces the selection, in a `TextInput` line breaks become spaces.
+    #[inline(always)]
+    pub fn handle_paste(&mut self, pasted: &str) -> Option<TextEvent> {
+        self.insert(pasted, None)
+    }
+
+    // Where the position is in the text, as last rendered.
+    fn offset_at(&self, column: u16, row: u16) -> usize {
+        let (area, (line, scroll)) = (self.area.get(), self.scroll.get());
+
+        let mut start = 0;
+
+        for _ in 0..line + (row - area.y) as usize {
+            match self.text[start..].find('\n') {
+                | Some(index) => start += index + 1,
+                | None => return self.text.len(),
+            }
+        }
+
+        self.at_column(start, scroll + (column - area.x) as usize)
+    }
+
+    // Clicking places the cursor, dragging selects.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> Option<TextEvent> {
+        let inside = contains(self.area.get(), mouse.column, mouse.row);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if inside => {
+                self.dragging = true;
+                self.selection.move_to(self.offset_at(mouse.column, mouse.row));
+                self.history.interrupt();
+
+                #[cfg(feature = "spellcheck")]
+                {
+                    self.spelling.suggestions = None;
+                }
+            },
+            | MouseEventKind::Drag(MouseButton::Left) if self.dragging => {
+                let area = self.area.get();
+
+                let column = mouse.column.clamp(area.left(), area.right().saturating_sub(1));
+                let row = mouse.row.clamp(area.top(), area.bottom().saturating_sub(1));
+
+                self.selection.extend_to(self.offset_at(column, row));
+            },
+            | MouseEventKind::Up(MouseButton::Left) => self.dragging = false,
+            | _ => (),
+        }
+
+        None
+    }
+
+    // Keeps the cursor in view, moving as little as possible.
+    fn scroll_to_cursor(&self, area: Rect) -> (usize, usize) {
+        let cursor = self.selection.cursor();
+        let (line, scroll) = self.scroll.get();
+
+        let cursor_line = self.text[..cursor].matches('\n').count();
+        let cursor_column = self.text[text::line_start(&self.text, cursor)..cursor].width();
+
+        let (height, width) = (area.height.max(1) as usize, area.width.max(1) as usize);
+
+        (
+            line.clamp(cursor_line.saturating_sub(height - 1), cursor_line),
+            scroll.clamp(cursor_column.saturating_sub(width - 1), cursor_column),
+        )
+    }
+}
+
+impl Widget for &TextArea {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.area.set(area);
+
+        buf.set_style(area, self.style);
+
+        if let (true, Some(placeholder)) = (self.text.is_empty(), &self.placeholder) {
+            buf.set_stringn(area.x, area.y, placeholder, area.width as usize, self.placeholder_style);
+        }
+
+        let (first_line, scroll) = self.scroll_to_cursor(area);
+        let (cursor, selected) = (self.selection.cursor(), self.selection.range(&self.text));
+
+        self.scroll.set((first_line, scroll));
+
+        let mut start = self.text.split_inclusive('\n').take(first_line).map(str::len).sum::<usize>();
+
+        for y in area.top()..area.bottom() {
+            if start > self.text.len() {
+                break;
+            }
+
+            let end = text::line_end(&self.text, start);
+            let mut column = 0;
+
+            // The cursor is drawn past the last character of its line as well.
+            let cells = self.text[start..end].char_indices().map(|(index, c)| (start + index, c)).chain([(end, ' ')]);
+
+            for (offset, c) in cells {
+                let width = c.width().unwrap_or(0);
+
+                if column >= scroll && column + width <= scroll + area.width as usize && width > 0 {
+                    let style = match offset {
+                        | offset if offset == cursor => self.cursor_style,
+                        | offset if selected.contains(&offset) => self.selection_style,
+                        | _ => self.style,
+                    };
+
+                    #[cfg(feature = "spellcheck")]
+                    let style = match self.spelling.is_misspelled(offset) {
+                        | true => style.patch(self.spelling.style),
+                        | false => style,
+                    };
+
+                    if offset < end || offset == cursor {
+                        let x = area.x + (column - scroll) as u16;
+
+                        buf.get_mut(x, y).set_char(c).set_style(style);
+                    }
+                }
+
+                column += width;
+            }
+
+            start = end + 1;
+        }
+
+        #[cfg(feature = "spellcheck")]
+        self.render_suggestions(area, buf);
+    }
+}
+
+impl TextArea {
+    // Below the word, or above it when there's no room below.
+    #[cfg(feature = "spellcheck")]
+    fn render_suggestions(&self, area: Rect, buf: &mut Buffer) {
+        let Some((word, words, highlighted)) = &self.spelling.suggestions else {
+            return;
+        };
+
+        let (first_line, scroll) = self.scroll.get();
+
+        let line = self.text[..word.start].matches('\n').count().saturating_sub(first_line);
+        let column = self.text[text::line_start(&self.text, word.start)..word.start].width().saturating_sub(scroll);
+
+        let width = words.iter().map(|word| word.width()).max().unwrap_or(0);
+        let (x, below) = (area.x + column.min(area.width as usize) as u16, area.y + line as u16 + 1);
+
+        let y = match below + words.len() as u16 <= buf.area.bottom() {
+            | true => below,
+            | false => (below - 1).saturating_sub(words.len() as u16),
+        };
+
+        let popup = Rect::new(x, y, width as u16 + 2, words.len() as u16).intersection(buf.area);
+
+        for (index, (y, word)) in (popup.top()..popup.bottom()).zip(words).enumerate() {
+            let style = match index == *highlighted {
+                | true => self.selection_style,
+                | false => self.style,
+            };
+
+            buf.set_stringn(popup.x, y, format!(" {word:width$} "), popup.width as usize, style);
+        }
+    }
+}
+
+// A single line of editable text, enter submits it.
+#[derive(Debug)]
+pub struct TextInput(TextArea);
+
+impl TextInput {
+    pub fn new<T: Into<String>>(text: T) -> Self {
+        let mut area = TextArea::new("");
+
+        area.single_line = true;
+        area.set_text(text);
+
+        Self(area)
+    }
+
+    pub fn placeholder<T: Into<String>>(self, placeholder: T) -> Self {
+        Self(self.0.placeholder(placeholder))
+    }
+
+    pub fn style(self, style: Style) -> Self {
+        Self(self.0.style(style))
+    }
+}
+
+impl Deref for TextInput {
+    type Target = TextArea;
+
+    #[inline(always)]
+    fn deref(&self) -> &TextArea {
+        &self.0
+    }
+}
+
+impl DerefMut for TextInput {
+    #[inline(always)]
+    fn deref_mut(&mut self) -> &mut TextArea {
+        &mut self.0
+    }
+}
+
+impl Widget for &TextInput {
+    #[inline(always)]
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.0.render(Rect { height: area.height.min(1), ..area }, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, key_with, mouse};
+
+    fn typed(area: &mut TextArea, clipboard: &mut Clipboard, text: &str) {
+        for c in text.chars() {
+            let code = match c {
+                | '\n' => KeyCode::Enter,
+                | c => KeyCode::Char(c),
+            };
+
+            area.handle_key(&key(code), clipboard);
+        }
+    }
+
+    fn rendered(area: &TextArea, width: u16, height: u16) -> Vec<String> {
+        let mut buf = Buffer::empty(Rect::new(0, 0, width, height));
+
+        area.render(buf.area, &mut buf);
+
+        (0..height).map(|y| (0..width).map(|x| buf.get(x, y).symbol()).collect()).collect()
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let (mut area, mut clipboard) = (TextArea::new("hello world"), Clipboard::default());
+
+        area.handle_key(&key_with(KeyCode::Left, KeyModifiers::CONTROL | KeyModifiers::SHIFT), &mut clipboard);
+
+        assert_eq!(area.selected(), "world");
+
+        typed(&mut area, &mut clipboard, "there\nagain");
+
+        assert_eq!(area.text(), "hello there\nagain");
+    }
+
+    #[test]
+    fn cut_and_paste_go_through_the_shared_clipboard() {
+        let mut clipboard = Clipboard::default();
+        let (mut area, mut input) = (TextArea::new("one\ntwo"), TextInput::new("x"));
+
+        area.handle_key(&key_with(KeyCode::Char('a'), KeyModifiers::CONTROL), &mut clipboard);
+
+        let cut = area.handle_key(&key_with(KeyCode::Char('x'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert!(matches!(cut, Some(TextEvent::Copied(Command::Crossterm(_)))));
+        assert_eq!(area.text(), "");
+
+        input.handle_key(&key_with(KeyCode::Char('v'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert_eq!(input.text(), "xone two");
+        let submitted = input.handle_key(&key(KeyCode::Enter), &mut clipboard);
+
+        assert!(matches!(submitted, Some(TextEvent::Submitted(text)) if text == "xone two"));
+    }
+
+    #[test]
+    fn up_and_down_keep_the_column() {
+        let (mut area, mut clipboard) = (TextArea::new("abcdef\nab\nabcdef"), Clipboard::default());
+
+        area.set_selection(Selection::caret(5));
+        area.handle_key(&key(KeyCode::Down), &mut clipboard);
+
+        assert_eq!(area.selection().cursor(), 9);
+
+        area.handle_key(&key(KeyCode::Down), &mut clipboard);
+
+        assert_eq!(area.selection().cursor(), 12);
+
+        area.handle_key(&key_with(KeyCode::Up, KeyModifiers::SHIFT), &mut clipboard);
+
+        assert_eq!(area.selected(), "\nab");
+    }
+
+    #[test]
+    fn scrolls_to_keep_the_cursor_in_view() {
+        let area = TextArea::new("one\ntwo\nthree");
+
+        assert_eq!(rendered(&area, 4, 2), ["o   ", "ree "]);
+
+        let mut area = TextArea::new("abcdefgh");
+
+        area.set_selection(Selection::caret(2));
+
+        assert_eq!(rendered(&area, 4, 1), ["abcd"]);
+    }
+
+    #[test]
+    fn dragging_selects() {
+        let mut area = TextArea::new("first\nsecond");
+
+        rendered(&area, 10, 2);
+
+        area.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 2, 0));
+        area.handle_mouse(&mouse(MouseEventKind::Drag(MouseButton::Left), 3, 1));
+        area.handle_mouse(&mouse(MouseEventKind::Up(MouseButton::Left), 3, 1));
+
+        assert_eq!(area.selected(), "rst\nsec");
+    }
+
+    #[test]
+    fn undo_and_redo_restore_the_text_and_the_selection() {
+        let (mut area, mut clipboard) = (TextArea::new(""), Clipboard::default());
+
+        typed(&mut area, &mut clipboard, "hello world");
+        area.handle_key(&key_with(KeyCode::Char('a'), KeyModifiers::CONTROL), &mut clipboard);
+        area.handle_key(&key(KeyCode::Backspace), &mut clipboard);
+
+        area.handle_key(&key_with(KeyCode::Char('z'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert_eq!((area.text(), area.selected()), ("hello world", "hello world"));
+
+        area.handle_key(&key_with(KeyCode::Char('z'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert_eq!(area.text(), "hello ");
+
+        area.handle_key(&key_with(KeyCode::Char('y'), KeyModifiers::CONTROL), &mut clipboard);
+        area.handle_key(&key_with(KeyCode::Char('y'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert_eq!(area.text(), "");
+        assert!(area.redo().is_none());
+    }
+
+    #[test]
+    fn history_survives_being_saved_and_restored() {
+        let (mut area, mut clipboard) = (TextArea::new("draft"), Clipboard::default());
+
+        typed(&mut area, &mut clipboard, " one");
+
+        let saved = area.to_bytes();
+        let mut restored = TextInput::new("");
+
+        assert!(restored.restore(&saved));
+        assert_eq!(restored.text(), "draft one");
+
+        restored.handle_key(&key_with(KeyCode::Char('z'), KeyModifiers::CONTROL), &mut clipboard);
+
+        assert_eq!(restored.text(), "draft ");
+        assert!(!restored.restore(&saved[..3]));
+        assert_eq!(restored.text(), "draft ");
+    }
+
+    #[cfg(feature = "spellcheck")]
+    #[test]
+    fn misspellings_are_underlined_once_checked() {
+        let dictionary = Arc::new(Dictionary::new(["some", "text"]));
+        let mut area = TextArea::new("some txet");
+
+        let Command::Spawn(work) = area.spawn_spell_check(&dictionary) else {
+            panic!("expected work for a background thread");
+        };
+
+        let checked = work.0().downcast::<SpellChecked>().unwrap();
+
+        assert_eq!(checked.misspellings, vec![5..9]);
+        assert!(area.apply_spell_check(checked));
+
+        let buf = {
+            let mut buf = Buffer::empty(Rect::new(0, 0, 10, 1));
+            (&area).render(buf.area, &mut buf);
+            buf
+        };
+
+        assert!(buf.get(5, 0).modifier.contains(Modifier::UNDERLINED));
+        assert!(!buf.get(4, 0).modifier.contains(Modifier::UNDERLINED));
+
+        let Command::Spawn(stale) = area.spawn_spell_check(&dictionary) else {
+            panic!("expected work for a background thread");
+        };
+
+        area.set_text("some text");
+
+        assert!(!area.apply_spell_check(stale.0().downcast().unwrap()));
+    }
+
+    #[cfg(feature = "spellcheck")]
+    #[test]
+    fn picking_a_suggestion_replaces_the_word() {
+        let (dictionary, mut clipboard) = (Arc::new(Dictionary::new(["text", "test"])), Clipboard::default());
+        let mut area = TextArea::new("some txet");
+
+        let Some(Command::Spawn(work)) = area.spawn_suggestions(&dictionary, 5) else {
+            panic!("expected work for a background thread");
+        };
+
+        assert!(area.apply_suggestions(work.0().downcast().unwrap()));
+        assert_eq!(rendered(&area, 12, 3)[1], "      test  ");
+
+        area.handle_key(&key(KeyCode::Down), &mut clipboard);
+        area.handle_key(&key(KeyCode::Tab), &mut clipboard);
+
+        assert_eq!(area.text(), "some text");
+        assert_eq!(rendered(&area, 12, 3)[1], "            ");
+    }
+}
@@ -0,0 +1,348 @@
+use std::cell::Cell;
+
+use crossterm::event::{KeyCode, MouseButton, MouseEventKind};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::Widget;
+
+use crate::components::contains;
+use crate::format;
+use crate::message::{KeyMsg, MouseMsg};
+
+// Zooming in or out changes the visible range by this factor, panning moves it by this fraction.
+const ZOOM: f64 = 1.5;
+const PAN: f64 = 0.1;
+
+// Roughly the room each label on the axis gets.
+const TICK_SPACING: u16 = 12;
+
+// Zooming in stops at this many times the precision of the times in view, past that neighbouring columns
+// (and ticks) would land on the same value.
+const MIN_SPAN: f64 = 1024.;
+
+#[derive(Debug, Clone)]
+pub struct Interval {
+    label: String,
+    start: f64,
+    end: f64,
+    style: Option<Style>,
+}
+
+impl Interval {
+    pub fn new<T: Into<String>>(label: T, start: f64, end: f64) -> Self {
+        Self {
+            label: label.into(),
+            start: start.min(end),
+            end: end.max(start),
+            style: None,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = Some(style);
+        self
+    }
+}
+
+// A step of 1, 2 or 5 times a power of ten, as close to `rough` as possible without going below it.
+fn nice_step(rough: f64) -> f64 {
+    let magnitude = 10f64.powf(rough.log10().floor());
+
+    [1., 2., 5., 10.].into_iter().map(|m| m * magnitude).find(|step| *step >= rough).unwrap_or(10. * magnitude)
+}
+
+// One interval per row with its label on the left, on a shared time axis at the bottom. Times are plain
+// numbers, e.g. seconds since the start of a pipeline.
+#[derive(Debug)]
+pub struct Timeline {
+    intervals: Vec<Interval>,
+    // The visible range of time, `None` to fit every interval.
+    view: Option<(f64, f64)>,
+    scroll: usize,
+    label_width: u16,
+    style: Style,
+    bar_style: Style,
+    axis_style: Style,
+    unit: String,
+    // Where the bars were last rendered and the range they showed, for panning with the mouse.
+    bars: Cell<Rect>,
+    shown: Cell<(f64, f64)>,
+    drag: Option<(u16, (f64, f64))>,
+}
+
+impl Timeline {
+    pub fn new<I: IntoIterator<Item = Interval>>(intervals: I) -> Self {
+        Self {
+            intervals: intervals.into_iter().collect(),
+            view: None,
+            scroll: 0,
+            label_width: 16,
+            style: Style::default(),
+            bar_style: Style::default().add_modifier(Modifier::REVERSED),
+            axis_style: Style::default().add_modifier(Modifier::DIM),
+            unit: String::new(),
+            bars: Cell::new(Rect::default()),
+            shown: Cell::new((0., 1.)),
+            drag: None,
+        }
+    }
+
+    pub fn label_width(mut self, width: u16) -> Self {
+        self.label_width = width;
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    // Used for intervals without a style of their own.
+    pub fn bar_style(mut self, style: Style) -> Self {
+        self.bar_style = style;
+        self
+    }
+
+    pub fn axis_style(mut self, style: Style) -> Self {
+        self.axis_style = style;
+        self
+    }
+
+    // Appended to the axis labels, like "s".
+    pub fn unit<T: Into<String>>(mut self, unit: T) -> Self {
+        self.unit = unit.into();
+        self
+    }
+
+    pub fn push(&mut self, interval: Interval) {
+        self.intervals.push(interval);
+    }
+
+    // Every interval, with an empty range widened so it can still be drawn.
+    pub fn extent(&self) -> (f64, f64) {
+        let start = self.intervals.iter().map(|i| i.start).fold(f64::INFINITY, f64::min);
+        let end = self.intervals.iter().map(|i| i.end).fold(f64::NEG_INFINITY, f64::max);
+
+        match start.is_finite() && end > start {
+            | true => (start, end),
+            | false if start.is_finite() => (start, start + 1.),
+            | false => (0., 1.),
+        }
+    }
+
+    #[inline(always)]
+    pub fn view(&self) -> (f64, f64) {
+        self.view.unwrap_or_else(|| self.extent())
+    }
+
+    pub fn set_view(&mut self, start: f64, end: f64) {
+        if end > start {
+            self.view = Some((start, end));
+        }
+    }
+
+    #[inline(always)]
+    pub fn fit(&mut self) {
+        self.view = None;
+    }
+
+    // Keeps the time at `anchor` (a fraction of the view from its start) in place.
+    pub fn zoom(&mut self, factor: f64, anchor: f64) {
+        let (start, end) = self.view();
+        let at = start + (end - start) * anchor;
+
+        let min_span = start.abs().max(end.abs()).max(1.) * f64::EPSILON * MIN_SPAN;
+        let factor = factor.max(min_span / (end - start));
+
+        self.set_view(at - (at - start) * factor, at + (end - at) * factor);
+    }
+
+    pub fn pan(&mut self, fraction: f64) {
+        let (start, end) = self.view();
+        let shift = (end - start) * fraction;
+
+        self.set_view(start + shift, end + shift);
+    }
+
+    // Plus and minus zoom, left and right pan, up and down scroll through the rows, home fits everything.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> bool {
+        match key.code {
+            | KeyCode::Char('+') | KeyCode::Char('=') => self.zoom(1. / ZOOM, 0.5),
+            | KeyCode::Char('-') => self.zoom(ZOOM, 0.5),
+            | KeyCode::Left => self.pan(-PAN),
+            | KeyCode::Right => self.pan(PAN),
+            | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            | KeyCode::Down => self.scroll = (self.scroll + 1).min(self.intervals.len().saturating_sub(1)),
+            | KeyCode::Home => self.fit(),
+            | _ => return false,
+        }
+
+        true
+    }
+
+    // Dragging pans, the wheel zooms around the pointer.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> bool {
+        let bars = self.bars.get();
+
+        let inside = contains(bars, mouse.column, mouse.row);
+        let anchor = (mouse.column.saturating_sub(bars.x) as f64 / bars.width.max(1) as f64).clamp(0., 1.);
+
+        match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) if inside => self.drag = Some((mouse.column, self.shown.get())),
+            | MouseEventKind::Drag(MouseButton::Left) => {
+                let Some((column, (start, end))) = self.drag else {
+                    return false;
+                };
+
+                let shift = (column as f64 - mouse.column as f64) * (end - start) / bars.width.max(1) as f64;
+
+                self.set_view(start + shift, end + shift);
+            },
+            | MouseEventKind::Up(MouseButton::Left) if self.drag.is_some() => self.drag = None,
+            | MouseEventKind::ScrollUp if inside => self.zoom(1. / ZOOM, anchor),
+            | MouseEventKind::ScrollDown if inside => self.zoom(ZOOM, anchor),
+            | _ => return false,
+        }
+
+        true
+    }
+
+    fn render_axis(&self, area: Rect, buf: &mut Buffer, (start, end): (f64, f64)) {
+        let scale = area.width as f64 / (end - start);
+        let step = nice_step((end - start) * TICK_SPACING as f64 / area.width.max(1) as f64);
+
+        // Enough digits to tell the ticks apart.
+        let precision = (-step.log10().floor()).max(0.) as usize;
+
+        if !step.is_finite() || step <= 0. {
+            return;
+        }
+
+        let first = (start / step).ceil() * step;
+
+        // Counted rather than accumulated, adding a step that small to a time that large changes nothing.
+        let n = (((end - first) / step).ceil().max(0.) as usize).min(area.width as usize);
+
+        for i in 0..n {
+            let tick = first + i as f64 * step;
+
+            if tick >= end {
+                break;
+            }
+
+            let x = area.x + ((tick - start) * scale) as u16;
+            let label = format!("┴{}{}", format::decimal(tick, precision), self.unit);
+
+            buf.set_stringn(x, area.y, label, area.right().saturating_sub(x) as usize, self.axis_style);
+        }
+    }
+}
+
+impl Widget for &Timeline {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.height < 2 {
+            return;
+        }
+
+        let label_width = self.label_width.min(area.width / 2);
+
+        let bars = Rect {
+            x: area.x + label_width,
+            width: area.width - label_width,
+            height: area.height - 1,
+            ..area
+        };
+
+        let (start, end) = self.view();
+
+        self.bars.set(bars);
+        self.shown.set((start, end));
+
+        if bars.is_empty() {
+            return;
+        }
+
+        let scale = bars.width as f64 / (end - start);
+
+        let rows = self.intervals.iter().skip(self.scroll).take(bars.height as usize);
+
+        for (row, interval) in rows.enumerate() {
+            let y = bars.y + row as u16;
+
+            buf.set_stringn(area.x, y, &interval.label, label_width.saturating_sub(1) as usize, self.style);
+
+            if interval.end < start || interval.start > end {
+                continue;
+            }
+
+            // Intervals too short for a single cell still get one.
+            let left = (((interval.start - start) * scale).max(0.).floor() as u16).min(bars.width - 1);
+            let right = (((interval.end - start) * scale).ceil() as u16).clamp(left + 1, bars.width);
+
+            let bar = Rect { x: bars.x + left, y, width: right.saturating_sub(left), height: 1 };
+
+            buf.set_style(bar, interval.style.unwrap_or(self.bar_style));
+        }
+
+        self.render_axis(Rect { y: area.bottom() - 1, height: 1, ..bars }, buf, (start, end));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline() -> Timeline {
+        Timeline::new([Interval::new("build", 0., 10.), Interval::new("test", 10., 20.)]).label_width(6)
+    }
+
+    #[test]
+    fn fits_every_interval_by_default() {
+        assert_eq!(timeline().view(), (0., 20.));
+        assert_eq!(Timeline::new([Interval::new("now", 5., 5.)]).view(), (5., 6.));
+        assert_eq!(Timeline::new([]).view(), (0., 1.));
+    }
+
+    #[test]
+    fn zooms_around_the_anchor_and_pans() {
+        let mut timeline = timeline();
+
+        timeline.zoom(0.5, 0.5);
+        assert_eq!(timeline.view(), (5., 15.));
+
+        timeline.pan(PAN);
+        assert_eq!(timeline.view(), (6., 16.));
+
+        timeline.fit();
+        assert_eq!(timeline.view(), (0., 20.));
+    }
+
+    #[test]
+    fn zooming_in_stops_at_the_precision_of_the_times() {
+        let mut timeline = Timeline::new([Interval::new("late", 1e12, 1e12 + 1.)]);
+
+        (0..200).for_each(|_| timeline.zoom(1. / ZOOM, 0.5));
+
+        let (start, end) = timeline.view();
+
+        assert!(end - start >= 1e12 * f64::EPSILON * MIN_SPAN * 0.99);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 40, 3));
+
+        (&timeline).render(buf.area, &mut buf);
+    }
+
+    #[test]
+    fn draws_bars_and_ticks() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 26, 3));
+
+        (&timeline()).render(buf.area, &mut buf);
+
+        assert_eq!(buf.get(6, 0).modifier, Modifier::REVERSED);
+        assert_eq!(buf.get(25, 0).modifier, Modifier::empty());
+        assert_eq!(buf.get(25, 1).modifier, Modifier::REVERSED);
+        assert_eq!(buf.get(6, 2).symbol(), "┴");
+    }
+}
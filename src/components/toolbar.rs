@@ -0,0 +1,197 @@
+use std::cell::Cell;
+
+use crossterm::event::KeyCode;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use crate::message::KeyMsg;
+
+const OVERFLOW: &str = "»";
+
+// A single row of items (tabs, tool buttons), the ones that don't fit collapse into a "»" menu.
+#[derive(Debug)]
+pub struct Toolbar {
+    items: Vec<String>,
+    selected: usize,
+    style: Style,
+    selected_style: Style,
+    open: bool,
+    // How many items fit the last time the toolbar was rendered.
+    visible: Cell<usize>,
+}
+
+impl Default for Toolbar {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            selected: 0,
+            style: Style::default(),
+            selected_style: Style::default().add_modifier(Modifier::REVERSED),
+            open: false,
+            visible: Cell::new(usize::MAX),
+        }
+    }
+}
+
+// Items are padded with a space on both sides.
+fn width_of(item: &str) -> u16 {
+    u16::try_from(item.chars().count()).unwrap_or(u16::MAX).saturating_add(2)
+}
+
+impl Toolbar {
+    pub fn new<I, T>(items: I) -> Self
+        where I: IntoIterator<Item = T>, T: Into<String>,
+    {
+        Self {
+            items: items.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn selected_style(mut self, style: Style) -> Self {
+        self.selected_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select(&mut self, index: usize) {
+        self.selected = index.min(self.items.len().saturating_sub(1));
+    }
+
+    #[inline(always)]
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    // The menu opens by itself while the selection is one of the collapsed items.
+    fn menu_shown(&self) -> bool {
+        self.open || self.selected >= self.visible.get()
+    }
+
+    // How many items fit into `width`, leaving room for the overflow marker when not all of them do.
+    pub fn fit(&self, width: u16) -> usize {
+        let total = self.items.iter().fold(0u16, |total, item| total.saturating_add(width_of(item)));
+
+        if total <= width {
+            return self.items.len();
+        }
+
+        let available = width.saturating_sub(width_of(OVERFLOW));
+
+        self.items.iter()
+            .scan(0u16, |used, item| {
+                *used = used.saturating_add(width_of(item));
+                Some(*used)
+            })
+            .take_while(|used| *used <= available)
+            .count()
+    }
+
+    pub fn handle_key(&mut self, key: &KeyMsg) -> bool {
+        match key.code {
+            | KeyCode::Left => self.select(self.selected.saturating_sub(1)),
+            | KeyCode::Right => self.select(self.selected + 1),
+            | KeyCode::Down if self.visible.get() < self.items.len() => self.open = true,
+            | KeyCode::Esc if self.open => self.open = false,
+            | _ => return false,
+        }
+
+        true
+    }
+}
+
+impl Widget for &Toolbar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+
+        let visible = self.fit(area.width);
+
+        self.visible.set(visible);
+
+        buf.set_style(Rect { height: 1, ..area }, self.style);
+
+        let mut x = area.x;
+
+        for (index, item) in self.items.iter().enumerate().take(visible) {
+            let style = if index == self.selected { self.selected_style } else { self.style };
+
+            buf.set_string(x, area.y, format!(" {item} "), style);
+
+            x += width_of(item);
+        }
+
+        if visible == self.items.len() {
+            return;
+        }
+
+        let marker = area.right().saturating_sub(width_of(OVERFLOW)).max(area.x);
+        let marker_style = if self.selected >= visible { self.selected_style } else { self.style };
+
+        buf.set_string(marker, area.y, format!(" {OVERFLOW} "), marker_style);
+
+        if !self.menu_shown() {
+            return;
+        }
+
+        // The menu hangs below the marker and is kept inside the buffer.
+        let hidden = &self.items[visible..];
+
+        let menu_width = hidden.iter().map(|i| width_of(i)).max().unwrap_or(0) + 2;
+        let menu_height = hidden.len() as u16 + 2;
+
+        let menu = Rect::new(
+            area.right().saturating_sub(menu_width).max(buf.area.x),
+            area.y + 1,
+            menu_width,
+            menu_height,
+        ).intersection(buf.area);
+
+        Clear.render(menu, buf);
+
+        Block::default().borders(Borders::ALL).style(self.style).render(menu, buf);
+
+        for (offset, item) in hidden.iter().enumerate().take(menu.height.saturating_sub(2) as usize) {
+            let style = if visible + offset == self.selected { self.selected_style } else { self.style };
+
+            let y = menu.y + 1 + offset as u16;
+
+            buf.set_stringn(menu.x + 1, y, format!(" {item} "), menu.width.saturating_sub(2) as usize, style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fits_everything_that_has_room() {
+        assert_eq!(Toolbar::new(["one", "two"]).fit(10), 2);
+    }
+
+    #[test]
+    fn leaves_room_for_the_overflow_marker() {
+        assert_eq!(Toolbar::new(["one", "two", "three"]).fit(13), 2);
+    }
+
+    #[test]
+    fn wide_items_dont_overflow_the_total() {
+        let wide = "x".repeat(40_000);
+
+        assert_eq!(Toolbar::new([wide.as_str(), wide.as_str()]).fit(80), 0);
+    }
+}
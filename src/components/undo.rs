@@ -0,0 +1,241 @@
+use crate::components::selection::{Selection, SelectionMode};
+
+// How many edits can be undone by default.
+const DEFAULT_LIMIT: usize = 100;
+
+// Bumped whenever the encoding changes, older saves are ignored instead of misread.
+const VERSION: u8 = 1;
+
+// The text and the selection as they were before an edit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub text: String,
+    pub selection: Selection,
+}
+
+// The undo and redo stacks of a text component. Typed characters are undone a word at a time, anything else
+// an edit at a time. Encodes to bytes for `Screen::autosave`, so editing history survives a restart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct History {
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+    limit: usize,
+    // Whether the last edit was typing a character that more typing joins.
+    typing: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(DEFAULT_LIMIT)
+    }
+}
+
+impl History {
+    pub fn new(limit: usize) -> Self {
+        Self { undo: Vec::new(), redo: Vec::new(), limit, typing: false }
+    }
+
+    #[inline(always)]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    // Oldest first.
+    #[inline(always)]
+    pub fn undo_stack(&self) -> &[Snapshot] {
+        &self.undo
+    }
+
+    #[inline(always)]
+    pub fn redo_stack(&self) -> &[Snapshot] {
+        &self.redo
+    }
+
+    // Called before every edit with the state it changes. Typing after typing (up to a space) is joined.
+    pub(crate) fn record(&mut self, before: Snapshot, typed: Option<char>) {
+        let joined = self.typing && typed.is_some();
+
+        self.typing = typed.is_some_and(|c| !c.is_whitespace());
+        self.redo.clear();
+
+        if joined {
+            return;
+        }
+
+        self.undo.push(before);
+
+        if self.undo.len() > self.limit {
+            self.undo.remove(0);
+        }
+    }
+
+    // Moving the cursor in between ends what's typed.
+    #[inline(always)]
+    pub(crate) fn interrupt(&mut self) {
+        self.typing = false;
+    }
+
+    pub(crate) fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop()?;
+
+        self.typing = false;
+        self.redo.push(current);
+
+        Some(previous)
+    }
+
+    pub(crate) fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+
+        self.typing = false;
+        self.undo.push(current);
+
+        Some(next)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![VERSION];
+
+        put(&mut bytes, self.limit as u64);
+
+        for stack in [&self.undo, &self.redo] {
+            put(&mut bytes, stack.len() as u64);
+            stack.iter().for_each(|snapshot| snapshot.encode(&mut bytes));
+        }
+
+        bytes
+    }
+
+    // `None` for anything `to_bytes` didn't produce, e.g. a save of an older version.
+    pub fn from_bytes(mut bytes: &[u8]) -> Option<Self> {
+        let (&VERSION, rest) = bytes.split_first()? else {
+            return None;
+        };
+
+        bytes = rest;
+
+        let limit = take(&mut bytes)? as usize;
+
+        let mut stack = || -> Option<Vec<Snapshot>> {
+            (0..take(&mut bytes)?).map(|_| Snapshot::decode(&mut bytes)).collect()
+        };
+
+        let (undo, redo) = (stack()?, stack()?);
+
+        Some(Self { undo, redo, limit, typing: false })
+    }
+}
+
+impl Snapshot {
+    pub(crate) fn encode(&self, bytes: &mut Vec<u8>) {
+        let mode = match self.selection.mode() {
+            | SelectionMode::Char => 0,
+            | SelectionMode::Word => 1,
+            | SelectionMode::Line => 2,
+        };
+
+        put(bytes, self.text.len() as u64);
+        bytes.extend_from_slice(self.text.as_bytes());
+        put(bytes, self.selection.anchor() as u64);
+        put(bytes, self.selection.cursor() as u64);
+        bytes.push(mode);
+    }
+
+    pub(crate) fn decode(bytes: &mut &[u8]) -> Option<Self> {
+        let len = take(bytes)? as usize;
+        let text = String::from_utf8(bytes.get(..len)?.to_vec()).ok()?;
+
+        *bytes = &bytes[len..];
+
+        let (anchor, cursor) = (take(bytes)? as usize, take(bytes)? as usize);
+
+        let (mode, rest) = bytes.split_first()?;
+
+        *bytes = rest;
+
+        let mode = match mode {
+            | 0 => SelectionMode::Char,
+            | 1 => SelectionMode::Word,
+            | 2 => SelectionMode::Line,
+            | _ => return None,
+        };
+
+        // Offsets have to land on the text's characters, or slicing it panics later.
+        if ![anchor, cursor].iter().all(|at| text.is_char_boundary(*at)) {
+            return None;
+        }
+
+        let mut selection = Selection::default();
+
+        selection.select(anchor, cursor, mode);
+
+        Some(Self { text, selection })
+    }
+}
+
+fn put(bytes: &mut Vec<u8>, value: u64) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn take(bytes: &mut &[u8]) -> Option<u64> {
+    let (value, rest) = bytes.split_first_chunk::<8>()?;
+
+    *bytes = rest;
+
+    Some(u64::from_le_bytes(*value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(text: &str) -> Snapshot {
+        Snapshot { text: text.to_owned(), selection: Selection::caret(text.len()) }
+    }
+
+    #[test]
+    fn typing_is_undone_a_word_at_a_time() {
+        let mut history = History::default();
+
+        for (before, c) in [("", 'a'), ("a", 'b'), ("ab", ' '), ("ab ", 'c')] {
+            history.record(snapshot(before), Some(c));
+        }
+
+        history.record(snapshot("ab c"), None);
+
+        let undone = [history.undo(snapshot("")), history.undo(snapshot("")), history.undo(snapshot(""))];
+
+        assert_eq!(undone.map(|s| s.unwrap().text), ["ab c", "ab ", ""]);
+        assert!(!history.can_undo());
+        assert_eq!(history.redo_stack().len(), 3);
+    }
+
+    #[test]
+    fn the_oldest_edits_are_forgotten_past_the_limit() {
+        let mut history = History::new(2);
+
+        ["a", "b", "c"].into_iter().for_each(|text| history.record(snapshot(text), None));
+
+        assert_eq!(history.undo_stack(), [snapshot("b"), snapshot("c")]);
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let mut history = History::new(7);
+
+        history.record(snapshot("wörld"), None);
+        history.record(snapshot("x"), None);
+        history.undo(snapshot("y"));
+
+        let bytes = history.to_bytes();
+
+        assert_eq!(History::from_bytes(&bytes), Some(history));
+        assert_eq!(History::from_bytes(&bytes[..bytes.len() - 1]), None);
+        assert_eq!(History::from_bytes(&[0]), None);
+    }
+}
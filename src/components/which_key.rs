@@ -0,0 +1,177 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::widgets::{Block, Borders, Clear, Widget};
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::keymap::{KeySequence, KeySequences};
+
+// Between the columns of hints.
+const SPACING: usize = 3;
+
+// The keys that can follow a pending prefix and what they do, like which-key in neovim. Built from the sequences
+// every time a key is fed to them, it's empty and takes no room unless a sequence is pending. Shown along the
+// bottom of the area it's rendered to, in as many columns as fit.
+#[derive(Debug, Clone, Default)]
+pub struct WhichKey {
+    pending: String,
+    hints: Vec<(String, String)>,
+    style: Style,
+    key_style: Style,
+}
+
+impl WhichKey {
+    // Keys leading to longer sequences are described by how many there are, e.g. "+3".
+    pub fn new<A, F>(sequences: &KeySequences<A>, describe: F) -> Self
+        where F: Fn(&A) -> String,
+    {
+        let hints = sequences.hints().into_iter().map(|hint| {
+            let description = match (hint.action, hint.continuations) {
+                | (Some(action), _) => describe(action),
+                | (None, more) => format!("+{more}"),
+            };
+
+            (hint.key.to_string(), description)
+        });
+
+        Self {
+            pending: KeySequence(sequences.pending().to_vec()).to_string(),
+            hints: hints.collect(),
+            style: Style::default(),
+            key_style: Style::default().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn key_style(mut self, style: Style) -> Self {
+        self.key_style = style;
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.hints.is_empty()
+    }
+
+    #[inline(always)]
+    fn key_width(&self) -> usize {
+        self.hints.iter().map(|(key, _)| key.width()).max().unwrap_or(0)
+    }
+
+    fn column_width(&self) -> usize {
+        let descriptions = self.hints.iter().map(|(_, description)| description.width()).max().unwrap_or(0);
+
+        // The key, an arrow between spaces and the description.
+        self.key_width() + 3 + descriptions
+    }
+
+    // Where the hints go in the area, as tall as it takes to show them in as many columns as fit.
+    pub fn placement(&self, area: Rect) -> Rect {
+        if self.is_empty() {
+            return Rect::new(area.x, area.bottom(), area.width, 0);
+        }
+
+        let inner = area.width.saturating_sub(2) as usize;
+        let columns = ((inner + SPACING) / (self.column_width() + SPACING)).clamp(1, self.hints.len());
+        let rows = self.hints.len().div_ceil(columns);
+
+        let height = u16::try_from(rows).unwrap_or(u16::MAX).saturating_add(2).min(area.height);
+
+        Rect::new(area.x, area.bottom() - height, area.width, height)
+    }
+}
+
+impl Widget for &WhichKey {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.placement(area);
+
+        if area.is_empty() {
+            return;
+        }
+
+        Clear.render(area, buf);
+
+        let block = Block::default().borders(Borders::ALL).title(format!(" {} ", self.pending)).style(self.style);
+        let inner = block.inner(area);
+
+        block.render(area, buf);
+
+        let rows = inner.height as usize;
+
+        if rows == 0 {
+            return;
+        }
+
+        let (key_width, column_width) = (self.key_width(), self.column_width() + SPACING);
+
+        for (index, (key, description)) in self.hints.iter().enumerate() {
+            let x = inner.x as usize + index / rows * column_width;
+            let y = inner.y + (index % rows) as u16;
+
+            if x >= inner.right() as usize {
+                break;
+            }
+
+            let room = inner.right() as usize - x;
+            let (x, _) = buf.set_stringn(x as u16, y, format!("{key:>key_width$}"), room, self.key_style);
+
+            let room = (inner.right() - x) as usize;
+
+            buf.set_stringn(x, y, format!(" → {description}"), room, self.style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::KeyCode;
+
+    use crate::keymap::{KeyBinding, Step};
+    use crate::message::key;
+
+    fn sequences() -> KeySequences<&'static str> {
+        KeySequences::new()
+            .bind("space f f".parse::<KeySequence>().unwrap(), "files")
+            .bind("space f g".parse::<KeySequence>().unwrap(), "grep")
+            .bind("space b".parse::<KeySequence>().unwrap(), "buffers")
+            .bind("space q".parse::<KeySequence>().unwrap(), "quit")
+            .bind(KeyBinding::char('x'), "delete")
+    }
+
+    #[test]
+    fn nothing_is_shown_without_a_pending_prefix() {
+        let hints = WhichKey::new(&sequences(), |action| action.to_string());
+
+        assert!(hints.is_empty());
+        assert_eq!(hints.placement(Rect::new(0, 0, 40, 10)).height, 0);
+    }
+
+    #[test]
+    fn the_keys_after_the_prefix_are_listed_in_columns() {
+        let mut sequences = sequences();
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char(' '))), Step::Pending);
+
+        let hints = WhichKey::new(&sequences, |action| action.to_string());
+        let mut buf = Buffer::empty(Rect::new(0, 0, 30, 5));
+
+        hints.render(buf.area, &mut buf);
+
+        let lines = (0..5).map(|y| (0..30).map(|x| buf.get(x, y).symbol()).collect::<String>()).collect::<Vec<_>>();
+
+        assert_eq!(lines, [
+            "                              ",
+            "┌ space ─────────────────────┐",
+            "│f → +2        q → quit      │",
+            "│b → buffers                 │",
+            "└────────────────────────────┘",
+        ]);
+    }
+}
@@ -0,0 +1,294 @@
+use std::time;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier};
+
+use crate::bell::VisualBell;
+use crate::clock::Clock;
+use crate::pane::PinnedPane;
+use crate::toast::Toasts;
+use crate::tooltip::Tooltips;
+
+// Everything is composed over the active screen, the remaining slots leave room for custom layers in between.
+pub mod z {
+    pub const BASE: i32 = 0;
+    pub const PINNED: i32 = 100;
+    pub const OVERLAY: i32 = 200;
+    pub const TOOLTIP: i32 = 250;
+    pub const TOAST: i32 = 300;
+    pub const DEBUG: i32 = 400;
+}
+
+pub trait Layer {
+    fn render(&mut self, area: Rect, buf: &mut Buffer);
+
+    fn dims_below(&self) -> bool {
+        false
+    }
+}
+
+impl<F: FnMut(Rect, &mut Buffer)> Layer for F {
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self(area, buf)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Dim {
+    // The terminal's faint attribute, which some terminals ignore.
+    #[default]
+    Modifier,
+    Grayscale,
+    // Scales true colors towards black, other colors fall back to the faint attribute.
+    Darken(f32),
+}
+
+fn luminance(r: u8, g: u8, b: u8) -> u8 {
+    (0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32) as u8
+}
+
+fn darken(color: Color, factor: f32) -> Option<Color> {
+    let scale = |c: u8| (c as f32 * (1. - factor.clamp(0., 1.))) as u8;
+
+    match color {
+        | Color::Rgb(r, g, b) => Some(Color::Rgb(scale(r), scale(g), scale(b))),
+        | Color::Reset => Some(Color::Reset),
+        | _ => None,
+    }
+}
+
+pub fn dim(buf: &mut Buffer, dim: Dim) {
+    for cell in buf.content.iter_mut() {
+        match dim {
+            | Dim::Modifier => {
+                cell.modifier.insert(Modifier::DIM);
+            },
+            | Dim::Grayscale => {
+                cell.fg = match cell.fg {
+                    | Color::Rgb(r, g, b) => {
+                        let gray = luminance(r, g, b);
+                        Color::Rgb(gray, gray, gray)
+                    },
+                    | _ => Color::DarkGray,
+                };
+
+                cell.bg = match cell.bg {
+                    | Color::Rgb(r, g, b) => {
+                        let gray = luminance(r, g, b) / 2;
+                        Color::Rgb(gray, gray, gray)
+                    },
+                    | _ => Color::Reset,
+                };
+            },
+            | Dim::Darken(factor) => match (darken(cell.fg, factor), darken(cell.bg, factor)) {
+                | (Some(fg), Some(bg)) => {
+                    cell.fg = fg;
+                    cell.bg = bg;
+                },
+                | _ => {
+                    cell.modifier.insert(Modifier::DIM);
+                },
+            },
+        }
+    }
+}
+
+pub(crate) struct Compositor<Ctx> {
+    pub(crate) panes: Vec<PinnedPane<Ctx>>,
+    pub(crate) dim: Dim,
+    pub(crate) tooltips: Tooltips,
+    pub(crate) toasts: Toasts,
+    pub(crate) visual_bell: VisualBell,
+    // The application's, which tooltips, toasts and the visual bell go by.
+    pub(crate) clock: Clock,
+    layers: Vec<(i32, Box<dyn Layer>)>,
+    // Filters registered on the builder stay, only the ones pushed at runtime can be popped.
+    filters: Vec<fn(&mut Buffer)>,
+    permanent_filters: usize,
+}
+
+impl<Ctx> Default for Compositor<Ctx> {
+    fn default() -> Self {
+        Self {
+            panes: Vec::new(),
+            dim: Dim::default(),
+            tooltips: Tooltips::default(),
+            toasts: Toasts::default(),
+            visual_bell: VisualBell::default(),
+            clock: Clock::system(),
+            layers: Vec::new(),
+            filters: Vec::new(),
+            permanent_filters: 0,
+        }
+    }
+}
+
+impl<Ctx> Compositor<Ctx> {
+    pub(crate) fn insert(&mut self, z: i32, layer: Box<dyn Layer>) {
+        // Layers on the same z are stacked in the order they were inserted.
+        let index = self.layers.partition_point(|(other, _)| *other <= z);
+
+        self.layers.insert(index, (z, layer));
+    }
+
+    pub(crate) fn add_filter(&mut self, filter: fn(&mut Buffer)) {
+        self.filters.push(filter);
+        self.permanent_filters = self.filters.len();
+    }
+
+    #[inline(always)]
+    pub(crate) fn push_filter(&mut self, filter: fn(&mut Buffer)) {
+        self.filters.push(filter);
+    }
+
+    pub(crate) fn pop_filter(&mut self) {
+        if self.filters.len() > self.permanent_filters {
+            self.filters.pop();
+        }
+    }
+
+    fn render_layer(layer: &mut dyn Layer, buf: &mut Buffer, style: Dim) {
+        if layer.dims_below() {
+            dim(buf, style);
+        }
+
+        layer.render(buf.area, buf);
+    }
+
+    // The earliest a pinned pane or a pending tooltip needs another frame by itself.
+    pub(crate) fn next_change(&self, ctx: &Ctx) -> Option<time::Instant> {
+        self.panes.iter()
+            .filter_map(|pane| pane.screen.next_change(ctx))
+            .chain(self.tooltips.shows_at())
+            .min()
+    }
+
+    // Overlay screens are rendered in between `compose_below` and `compose_above`, on `z::OVERLAY`.
+    pub(crate) fn compose_below(&mut self, buf: &mut Buffer, ctx: &Ctx) {
+        let pinned = self.layers.partition_point(|(z, _)| *z < z::PINNED);
+        let overlay = self.layers.partition_point(|(z, _)| *z < z::OVERLAY);
+
+        let (below, between) = self.layers[..overlay].split_at_mut(pinned);
+
+        below.iter_mut().for_each(|(_, layer)| Self::render_layer(layer.as_mut(), buf, self.dim));
+
+        self.panes.iter_mut().for_each(|pane| pane.render(buf, ctx));
+
+        between.iter_mut().for_each(|(_, layer)| Self::render_layer(layer.as_mut(), buf, self.dim));
+    }
+
+    pub(crate) fn compose_above(&mut self, buf: &mut Buffer) {
+        let overlay = self.layers.partition_point(|(z, _)| *z < z::OVERLAY);
+        let tooltip = self.layers.partition_point(|(z, _)| *z < z::TOOLTIP);
+        let toast = self.layers.partition_point(|(z, _)| *z < z::TOAST);
+
+        let (between, above) = self.layers[overlay..].split_at_mut(tooltip - overlay);
+        let (below_toast, above) = above.split_at_mut(toast - tooltip);
+
+        between.iter_mut().for_each(|(_, layer)| Self::render_layer(layer.as_mut(), buf, self.dim));
+
+        let now = self.clock.now();
+
+        self.tooltips.render(buf, now);
+
+        below_toast.iter_mut().for_each(|(_, layer)| Self::render_layer(layer.as_mut(), buf, self.dim));
+
+        self.toasts.render(buf, now);
+
+        above.iter_mut().for_each(|(_, layer)| Self::render_layer(layer.as_mut(), buf, self.dim));
+
+        self.filters.iter().for_each(|filter| filter(buf));
+
+        self.visual_bell.render(buf, now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::style::Style;
+    use ratatui::widgets::{Paragraph, Widget};
+
+    struct Modal;
+
+    impl Layer for Modal {
+        fn render(&mut self, _: Rect, buf: &mut Buffer) {
+            buf.set_string(0, 0, "m", Style::default());
+        }
+
+        fn dims_below(&self) -> bool {
+            true
+        }
+    }
+
+    fn write(text: &'static str) -> Box<dyn Layer> {
+        Box::new(move |area, buf: &mut Buffer| Paragraph::new(text).render(area, buf))
+    }
+
+    fn line(buf: &Buffer) -> String {
+        (0..buf.area.width).map(|x| buf.get(x, 0).symbol()).collect()
+    }
+
+    #[test]
+    fn layers_are_stacked_by_z_then_insertion() {
+        let mut compositor = Compositor::<()>::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+
+        compositor.insert(z::TOAST + 1, write("top"));
+        compositor.insert(z::BASE, write("base"));
+        compositor.insert(z::BASE, write("two"));
+        compositor.insert(z::OVERLAY + 1, write("o"));
+
+        compositor.compose_below(&mut buf, &());
+
+        assert_eq!(line(&buf), "twoe");
+
+        compositor.compose_above(&mut buf);
+
+        assert_eq!(line(&buf), "tope");
+    }
+
+    #[test]
+    fn layers_can_dim_what_is_below_them() {
+        let mut compositor = Compositor::<()>::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+
+        buf.get_mut(1, 0).fg = Color::Rgb(100, 200, 50);
+
+        compositor.dim = Dim::Darken(0.5);
+        compositor.insert(z::BASE, Box::new(Modal));
+        compositor.compose_below(&mut buf, &());
+
+        assert_eq!(buf.get(0, 0).symbol(), "m");
+        assert_eq!(buf.get(1, 0).fg, Color::Rgb(50, 100, 25));
+    }
+
+    #[test]
+    fn only_filters_pushed_at_runtime_are_popped() {
+        let mut compositor = Compositor::<()>::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+
+        compositor.add_filter(|buf| buf.get_mut(0, 0).fg = Color::Red);
+        compositor.push_filter(|buf| buf.get_mut(0, 0).bg = Color::Blue);
+        compositor.pop_filter();
+        compositor.pop_filter();
+        compositor.compose_above(&mut buf);
+
+        assert_eq!((buf.get(0, 0).fg, buf.get(0, 0).bg), (Color::Red, Color::Reset));
+    }
+
+    #[test]
+    fn grayscale_keeps_the_brightness_of_true_colors() {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 2, 1));
+
+        buf.get_mut(0, 0).fg = Color::Rgb(255, 0, 0);
+        buf.get_mut(1, 0).fg = Color::Yellow;
+
+        dim(&mut buf, Dim::Grayscale);
+
+        assert_eq!(buf.get(0, 0).fg, Color::Rgb(76, 76, 76));
+        assert_eq!(buf.get(1, 0).fg, Color::DarkGray);
+    }
+}
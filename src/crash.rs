@@ -0,0 +1,141 @@
+use std::{fmt, fs, io, time};
+use std::io::Write;
+use std::path::PathBuf;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::panic_hook::PanicHook;
+
+pub(crate) enum Entry {
+    Message(String),
+    Command(String),
+    ScreenSwitch(&'static str),
+    // Something that went wrong without ending the run.
+    Warning(String),
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | Entry::Message(message) => write!(f, "message: {message}"),
+            | Entry::Command(command) => write!(f, "command: {command}"),
+            | Entry::ScreenSwitch(screen) => write!(f, "screen:  {screen}"),
+            | Entry::Warning(warning) => write!(f, "warning: {warning}"),
+        }
+    }
+}
+
+struct Journal {
+    path: PathBuf,
+    capacity: usize,
+    started: time::Instant,
+    entries: VecDeque<(time::Duration, Entry)>,
+}
+
+#[derive(Clone)]
+pub(crate) struct CrashLog(Arc<Mutex<Journal>>);
+
+impl CrashLog {
+    pub(crate) fn new(path: PathBuf, capacity: usize) -> Self {
+        Self(Arc::new(Mutex::new(Journal {
+            path,
+            capacity,
+            started: time::Instant::now(),
+            entries: VecDeque::with_capacity(capacity),
+        })))
+    }
+
+    fn journal(&self) -> MutexGuard<'_, Journal> {
+        // A panic elsewhere must not stop us from writing the report.
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn record(&self, entry: Entry) {
+        let mut journal = self.journal();
+
+        if journal.capacity == 0 {
+            return;
+        }
+
+        if journal.entries.len() == journal.capacity {
+            journal.entries.pop_front();
+        }
+
+        let at = journal.started.elapsed();
+
+        journal.entries.push_back((at, entry));
+    }
+
+    pub(crate) fn dump(&self, reason: &dyn fmt::Display) -> io::Result<()> {
+        let journal = self.journal();
+
+        let mut file = io::BufWriter::new(fs::File::create(&journal.path)?);
+
+        writeln!(file, "ratata crash report")?;
+        writeln!(file, "reason: {reason}")?;
+        writeln!(file, "uptime: {:.3}s", journal.started.elapsed().as_secs_f64())?;
+        writeln!(file, "last {} runtime events:", journal.entries.len())?;
+
+        for (at, entry) in &journal.entries {
+            writeln!(file, "[{:>10.3}s] {entry}", at.as_secs_f64())?;
+        }
+
+        file.flush()
+    }
+
+    // Uninstalled when the application ends, only dumps while it's still around until then.
+    pub(crate) fn install_panic_hook(&self) -> PanicHook {
+        let log = Arc::downgrade(&self.0);
+
+        PanicHook::install(move |info| {
+            if let Some(journal) = log.upgrade() {
+                let _ = CrashLog(journal).dump(info);
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    fn report(capacity: usize, entries: Vec<Entry>) -> String {
+        let path = env::temp_dir().join(format!("ratata-crash-{}-{capacity}.txt", std::process::id()));
+        let log = CrashLog::new(path.clone(), capacity);
+
+        entries.into_iter().for_each(|entry| log.record(entry));
+        log.dump(&"boom").unwrap();
+
+        let report = fs::read_to_string(&path).unwrap();
+
+        fs::remove_file(&path).unwrap();
+
+        report
+    }
+
+    #[test]
+    fn only_the_most_recent_events_are_kept() {
+        let report = report(2, vec![
+            Entry::Message(String::from("Key(a)")),
+            Entry::ScreenSwitch("Menu"),
+            Entry::Command(String::from("Quit")),
+        ]);
+
+        let lines = report.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[..2], ["ratata crash report", "reason: boom"]);
+        assert_eq!(lines[3], "last 2 runtime events:");
+        assert!(lines[4].ends_with("s] screen:  Menu"));
+        assert!(lines[5].ends_with("s] command: Quit"));
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn nothing_is_recorded_without_capacity() {
+        let report = report(0, vec![Entry::Warning(String::from("ignored"))]);
+
+        assert!(report.ends_with("last 0 runtime events:\n"));
+    }
+}
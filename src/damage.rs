@@ -0,0 +1,346 @@
+use std::{io, mem, time};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use ratatui::backend::{Backend, ClearType, WindowSize};
+use ratatui::buffer::Cell;
+use ratatui::layout::Rect;
+
+// What's transmitted next, in the order it was asked for.
+#[derive(Debug)]
+enum Op {
+    Cells(BTreeMap<(u16, u16), Cell>),
+    Clear,
+    ClearRegion(ClearType),
+    HideCursor,
+    ShowCursor,
+    SetCursor(u16, u16),
+}
+
+impl Op {
+    #[inline(always)]
+    fn clears(&self) -> bool {
+        matches!(self, Op::Clear | Op::ClearRegion(_))
+    }
+
+    #[inline(always)]
+    fn moves_cursor(&self) -> bool {
+        matches!(self, Op::SetCursor(..))
+    }
+
+    #[inline(always)]
+    fn toggles_cursor(&self) -> bool {
+        matches!(self, Op::HideCursor | Op::ShowCursor)
+    }
+}
+
+// When a `DamageBackend` can transmit what it held back, shared with the application through
+// `Builder::flush_deadline` so it wakes up for it.
+#[derive(Debug, Clone, Default)]
+pub struct FlushDeadline(Arc<Mutex<Option<time::Instant>>>);
+
+impl FlushDeadline {
+    #[inline(always)]
+    pub fn get(&self) -> Option<time::Instant> {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline(always)]
+    fn set(&self, deadline: Option<time::Instant>) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = deadline;
+    }
+}
+
+// Wraps a backend for slow links (serial, network): cell updates are coalesced between flushes and
+// only transmitted at the capped rate, so a cell that changed several times is sent once.
+pub struct DamageBackend<B: Backend> {
+    inner: B,
+    interval: time::Duration,
+    last_transmission: Option<time::Instant>,
+    // Cursor changes and clears are held back along with the cells, since they depend on each other's order.
+    pending: Vec<Op>,
+    damage: Vec<Rect>,
+    deadline: FlushDeadline,
+}
+
+impl<B: Backend> DamageBackend<B> {
+    pub fn new(inner: B, max_transmissions_per_second: u8) -> Self {
+        Self {
+            inner,
+            interval: time::Duration::from_secs_f32(1. / max_transmissions_per_second.max(1) as f32),
+            last_transmission: None,
+            pending: Vec::new(),
+            damage: Vec::new(),
+            deadline: FlushDeadline::default(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    #[inline(always)]
+    pub fn inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    // The dirty rectangles of the last transmission.
+    #[inline(always)]
+    pub fn damage(&self) -> &[Rect] {
+        &self.damage
+    }
+
+    #[inline(always)]
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn flush_deadline(&self) -> FlushDeadline {
+        self.deadline.clone()
+    }
+
+    // Transmits pending updates regardless of the rate cap, e.g. when no further frames will be drawn.
+    pub fn transmit(&mut self) -> io::Result<()> {
+        self.last_transmission = Some(time::Instant::now());
+        self.deadline.set(None);
+
+        let pending = mem::take(&mut self.pending);
+
+        let cells = pending.iter()
+            .flat_map(|op| match op {
+                | Op::Cells(cells) => Some(cells.keys()),
+                | _ => None,
+            })
+            .flatten()
+            .copied()
+            .collect::<BTreeSet<_>>();
+
+        self.damage = damaged_regions(cells.into_iter());
+
+        for op in pending {
+            match op {
+                | Op::Cells(cells) => self.inner.draw(cells.iter().map(|(&(y, x), cell)| (x, y, cell)))?,
+                | Op::Clear => self.inner.clear()?,
+                | Op::ClearRegion(clear_type) => self.inner.clear_region(clear_type)?,
+                | Op::HideCursor => self.inner.hide_cursor()?,
+                | Op::ShowCursor => self.inner.show_cursor()?,
+                | Op::SetCursor(x, y) => self.inner.set_cursor(x, y)?,
+            }
+        }
+
+        self.inner.flush()
+    }
+
+    fn queue(&mut self, op: Op) {
+        match op {
+            // Cells are placed explicitly, only clearing in between keeps them from being transmitted together.
+            | Op::Cells(cells) => {
+                let mut since_cleared = self.pending.iter_mut().rev().take_while(|op| !op.clears());
+
+                let pending = since_cleared.find_map(|op| match op {
+                    | Op::Cells(cells) => Some(cells),
+                    | _ => None,
+                });
+
+                match pending {
+                    | Some(pending) => pending.extend(cells),
+                    | None => self.pending.push(Op::Cells(cells)),
+                }
+            },
+            // Whatever was drawn or cleared before is wiped from the screen anyway.
+            | Op::Clear => {
+                self.pending.retain(|op| !matches!(op, Op::Cells(_)) && !op.clears());
+                self.pending.push(op);
+            },
+            // Of several cursor changes in a row, only the last one moving it and toggling it matter.
+            | _ => {
+                let run = self.pending.iter().rev().take_while(|op| op.moves_cursor() || op.toggles_cursor()).count();
+                let start = self.pending.len() - run;
+                let replaces = |pending: &Op| match op.moves_cursor() {
+                    | true => pending.moves_cursor(),
+                    | false => pending.toggles_cursor(),
+                };
+
+                if let Some(i) = self.pending[start..].iter().position(replaces) {
+                    self.pending.remove(start + i);
+                }
+
+                self.pending.push(op);
+            },
+        }
+
+        let due = self.last_transmission.map_or_else(time::Instant::now, |last| last + self.interval);
+
+        self.deadline.set(Some(due));
+    }
+}
+
+fn damaged_regions(cells: impl Iterator<Item = (u16, u16)>) -> Vec<Rect> {
+    let mut runs: Vec<Rect> = Vec::new();
+
+    for (y, x) in cells {
+        match runs.last_mut() {
+            | Some(run) if run.y == y && run.right() == x => run.width += 1,
+            | _ => runs.push(Rect::new(x, y, 1, 1)),
+        }
+    }
+
+    // Runs spanning the same columns on consecutive rows are merged into a single rect.
+    let mut regions: Vec<Rect> = Vec::with_capacity(runs.len());
+
+    for run in runs {
+        match regions.iter_mut().find(|r| r.bottom() == run.y && r.x == run.x && r.width == run.width) {
+            | Some(region) => region.height += 1,
+            | None => regions.push(run),
+        }
+    }
+
+    regions
+}
+
+impl<B: Backend> Backend for DamageBackend<B> {
+    fn draw<'a, I>(&mut self, content: I) -> io::Result<()>
+        where I: Iterator<Item = (u16, u16, &'a Cell)>,
+    {
+        let cells = content.map(|(x, y, cell)| ((y, x), cell.clone())).collect::<BTreeMap<_, _>>();
+
+        if !cells.is_empty() {
+            self.queue(Op::Cells(cells));
+        }
+
+        Ok(())
+    }
+
+    fn hide_cursor(&mut self) -> io::Result<()> {
+        self.queue(Op::HideCursor);
+        Ok(())
+    }
+
+    fn show_cursor(&mut self) -> io::Result<()> {
+        self.queue(Op::ShowCursor);
+        Ok(())
+    }
+
+    // Where it will be once what's pending is transmitted.
+    fn get_cursor(&mut self) -> io::Result<(u16, u16)> {
+        let pending = self.pending.iter().rev().find_map(|op| match op {
+            | Op::SetCursor(x, y) => Some((*x, *y)),
+            | _ => None,
+        });
+
+        pending.map_or_else(|| self.inner.get_cursor(), Ok)
+    }
+
+    fn set_cursor(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.queue(Op::SetCursor(x, y));
+        Ok(())
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.queue(Op::Clear);
+        Ok(())
+    }
+
+    fn clear_region(&mut self, clear_type: ClearType) -> io::Result<()> {
+        self.queue(Op::ClearRegion(clear_type));
+        Ok(())
+    }
+
+    fn size(&self) -> io::Result<Rect> {
+        self.inner.size()
+    }
+
+    fn window_size(&mut self) -> io::Result<WindowSize> {
+        self.inner.window_size()
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.last_transmission {
+            | Some(last) if last.elapsed() < self.interval => Ok(()),
+            | _ => self.transmit(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    fn cell(symbol: &str) -> Cell {
+        let mut cell = Cell::default();
+        cell.set_symbol(symbol);
+        cell
+    }
+
+    fn backend() -> DamageBackend<TestBackend> {
+        let mut backend = DamageBackend::new(TestBackend::new(4, 2), 1);
+
+        // Uses up the first transmission, so the next ones are held back.
+        backend.transmit().unwrap();
+        backend
+    }
+
+    #[test]
+    fn holds_back_cells_until_the_rate_cap_allows() {
+        let mut backend = backend();
+
+        backend.draw([(0, 0, &cell("a")), (1, 0, &cell("b")), (0, 1, &cell("c"))].into_iter()).unwrap();
+        backend.flush().unwrap();
+
+        assert!(backend.has_pending());
+        assert_eq!(backend.inner().buffer().get(0, 0).symbol(), " ");
+
+        backend.transmit().unwrap();
+
+        assert_eq!(backend.inner().buffer().get(0, 0).symbol(), "a");
+        assert_eq!(backend.damage(), [Rect::new(0, 0, 2, 1), Rect::new(0, 1, 1, 1)]);
+    }
+
+    #[test]
+    fn clears_in_order_with_the_cells() {
+        let mut backend = backend();
+
+        backend.draw([(0, 0, &cell("a"))].into_iter()).unwrap();
+        backend.clear().unwrap();
+        backend.draw([(1, 0, &cell("b"))].into_iter()).unwrap();
+        backend.transmit().unwrap();
+
+        assert_eq!(backend.inner().buffer().get(0, 0).symbol(), " ");
+        assert_eq!(backend.inner().buffer().get(1, 0).symbol(), "b");
+    }
+
+    #[test]
+    fn holds_back_the_cursor() {
+        let mut backend = backend();
+
+        backend.set_cursor(1, 1).unwrap();
+        backend.set_cursor(2, 1).unwrap();
+
+        assert_eq!(backend.get_cursor().unwrap(), (2, 1));
+        assert_eq!(backend.inner_mut().get_cursor().unwrap(), (0, 0));
+
+        backend.transmit().unwrap();
+
+        assert_eq!(backend.inner_mut().get_cursor().unwrap(), (2, 1));
+    }
+
+    #[test]
+    fn is_due_once_the_rate_cap_allows() {
+        let mut backend = backend();
+        let deadline = backend.flush_deadline();
+
+        assert_eq!(deadline.get(), None);
+
+        backend.draw([(0, 0, &cell("a"))].into_iter()).unwrap();
+
+        assert!(deadline.get().is_some_and(|at| at > time::Instant::now()));
+
+        backend.transmit().unwrap();
+
+        assert_eq!(deadline.get(), None);
+    }
+}
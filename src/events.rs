@@ -2,47 +2,344 @@ use std::thread;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::ops::RangeInclusive;
 
-use crossterm::event::{self, Event, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 
 use thiserror::Error;
 
+use crate::message::Message;
+
 #[derive(Debug, Error)]
 pub enum EventListenerError {
     #[error("failed to read from event stream: {0}")]
     ReadError(#[from] std::io::Error),
     #[error("failed to send acquired event to the bound receiver: {0}")]
-    SendError(#[from] mpsc::SendError<Event>),
+    SendError(#[from] mpsc::SendError<TimedEvent>),
 }
 
+// Stamped as soon as the event is read, for measuring input latency.
+pub type TimedEvent = (Instant, Event);
+
 pub type JoinHandle = thread::JoinHandle<Result<(), EventListenerError>>;
 
-pub fn listen(timeout: Duration) -> (JoinHandle, Receiver<Event>, Arc<AtomicBool>) {
+// Which kinds of key events are delivered. Terminals only report repeats and releases with the kitty keyboard
+// protocol's flags enabled (see `PushKeyboardEnhancementFlags`), others send every key as a press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyEventFilter {
+    pub press: bool,
+    pub repeat: bool,
+    pub release: bool,
+}
+
+impl KeyEventFilter {
+    pub const PRESS: Self = Self { press: true, repeat: false, release: false };
+    pub const ALL: Self = Self { press: true, repeat: true, release: true };
+
+    #[inline(always)]
+    pub fn allows(&self, kind: KeyEventKind) -> bool {
+        match kind {
+            | KeyEventKind::Press => self.press,
+            | KeyEventKind::Repeat => self.repeat,
+            | KeyEventKind::Release => self.release,
+        }
+    }
+}
+
+impl Default for KeyEventFilter {
+    fn default() -> Self {
+        Self::PRESS
+    }
+}
+
+// Only key events are filtered, mouse, resize, focus and paste events always make it through.
+#[inline(always)]
+pub(crate) fn is_delivered(event: &Event, keys: KeyEventFilter) -> bool {
+    match event {
+        | Event::Key(key) => keys.allows(key.kind),
+        | _ => true,
+    }
+}
+
+#[derive(Debug, Default)]
+struct PauseState {
+    requested: AtomicBool,
+    idle: AtomicBool,
+}
+
+// Keeps a listener off the terminal, e.g. while a child process reads from it. Only the listener and whoever
+// pauses it may hold a clone.
+#[derive(Debug, Clone, Default)]
+pub struct ListenerPause(Arc<PauseState>);
+
+impl ListenerPause {
+    // Returns once the listener stopped reading, which takes up to its longest poll timeout, or right away
+    // if it isn't running.
+    pub fn pause(&self) {
+        self.0.requested.store(true, Ordering::SeqCst);
+
+        while !self.0.idle.load(Ordering::SeqCst) && Arc::strong_count(&self.0) > 1 {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[inline(always)]
+    pub fn resume(&self) {
+        self.0.requested.store(false, Ordering::SeqCst);
+    }
+}
+
+#[inline(always)]
+pub fn listen(timeout: Duration) -> (JoinHandle, Receiver<TimedEvent>, Arc<AtomicBool>) {
+    listen_adaptive(timeout..=timeout, KeyEventFilter::default())
+}
+
+// The timeout drops to the lower bound after every event and doubles towards the upper one while idle,
+// so bursts of input are picked up quickly without waking up needlessly when nothing happens.
+#[inline(always)]
+pub fn listen_adaptive(
+    timeouts: RangeInclusive<Duration>,
+    keys: KeyEventFilter,
+) -> (JoinHandle, Receiver<TimedEvent>, Arc<AtomicBool>) {
+    listen_pausable(timeouts, keys, ListenerPause::default())
+}
+
+pub fn listen_pausable(
+    timeouts: RangeInclusive<Duration>,
+    keys: KeyEventFilter,
+    pause: ListenerPause,
+) -> (JoinHandle, Receiver<TimedEvent>, Arc<AtomicBool>) {
     let (tx, rx) = mpsc::channel();
 
+    let (min, max) = (*timeouts.start(), *timeouts.end().max(timeouts.start()));
+
     let quit_handle = Arc::new(AtomicBool::new(false));
 
     let should_quit = quit_handle.clone();
 
+    let mut timeout = min;
+
     let handle = thread::spawn(move || loop {
         if should_quit.load(Ordering::Relaxed) {
             break Ok(());
         }
 
+        // Only counts as idle once it's seen the request, so `pause` can't return while a poll is starting.
+        pause.0.idle.store(false, Ordering::SeqCst);
+
+        if pause.0.requested.load(Ordering::SeqCst) {
+            pause.0.idle.store(true, Ordering::SeqCst);
+            thread::sleep(min.max(Duration::from_millis(1)));
+            continue;
+        }
+
         if !event::poll(timeout)? {
+            timeout = timeout.saturating_mul(2).max(Duration::from_millis(1)).min(max);
             continue;
         }
 
+        timeout = min;
+
         let event = event::read()?;
+        let received = Instant::now();
 
-        // Filter out the KeyEventKind::Release and KeyEventKind::Repeat presses.
-        if !matches!(event, Event::Key(key) if key.kind == KeyEventKind::Press) {
+        if !is_delivered(&event, keys) {
             continue;
         }
 
-        tx.send(event)?;
+        tx.send((received, event))?;
     });
 
     (handle, rx, quit_handle)
 }
+
+// What happens to bursts of keys arriving faster than anyone types, i.e. text pasted into a terminal
+// without bracketed paste. Only runs of printable keys, enter and tab count.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PasteBursts {
+    // Delivered key by key, like any other input.
+    #[default]
+    Keys,
+    // Collapsed into a single `Message::Paste`.
+    #[cfg(feature = "paste")]
+    Paste,
+    // Collapsed into a single `Message::PasteBurst`, for screens to confirm before acting on it.
+    Confirm,
+}
+
+impl PasteBursts {
+    // Pasted text arrives within a millisecond per key, key repeat and fast typists stay well above this. Keys
+    // that may still turn out to be part of a paste are held back for as long.
+    const MAX_GAP: Duration = Duration::from_millis(5);
+    const MIN_KEYS: usize = 6;
+
+    fn character(event: &Event) -> Option<char> {
+        let Event::Key(key) = event else {
+            return None;
+        };
+
+        if key.kind == KeyEventKind::Release || !key.modifiers.difference(KeyModifiers::SHIFT).is_empty() {
+            return None;
+        }
+
+        match key.code {
+            | KeyCode::Char(c) => Some(c),
+            | KeyCode::Enter => Some('\n'),
+            | KeyCode::Tab => Some('\t'),
+            | _ => None,
+        }
+    }
+
+    #[inline(always)]
+    fn keys(run: &[TimedEvent]) -> usize {
+        run.iter().filter(|(_, event)| Self::character(event).is_some()).count()
+    }
+
+    fn collapse(self, text: String) -> Message {
+        match self {
+            #[cfg(feature = "paste")]
+            | Self::Paste => Message::Paste(text),
+            | _ => Message::PasteBurst(text),
+        }
+    }
+
+    #[inline(always)]
+    fn release(event: &Event) -> bool {
+        matches!(event, Event::Key(key) if key.kind == KeyEventKind::Release)
+    }
+}
+
+// Picks out paste bursts across however the events were read. A run still going at the end of a batch is held
+// back until it continues in the next one, or nothing followed it for longer than a paste takes per key.
+#[derive(Debug)]
+pub(crate) struct BurstDetector {
+    mode: PasteBursts,
+    run: Vec<TimedEvent>,
+}
+
+impl BurstDetector {
+    #[inline(always)]
+    pub(crate) fn new(mode: PasteBursts) -> Self {
+        Self { mode, run: Vec::new() }
+    }
+
+    // When the run held back is over, unless another key arrives before.
+    #[inline(always)]
+    pub(crate) fn flush_at(&self) -> Option<Instant> {
+        self.run.last().map(|(last, _)| *last + PasteBursts::MAX_GAP)
+    }
+
+    fn flush(&mut self, messages: &mut Vec<Message>) {
+        match PasteBursts::keys(&self.run) >= PasteBursts::MIN_KEYS {
+            | true => {
+                let text = self.run.drain(..).filter_map(|(_, event)| PasteBursts::character(&event)).collect();
+
+                messages.push(self.mode.collapse(text));
+            },
+            | false => messages.extend(self.run.drain(..).map(|(_, event)| Message::from(event))),
+        }
+    }
+
+    pub(crate) fn messages(&mut self, events: Vec<TimedEvent>, now: Instant) -> Vec<Message> {
+        if self.mode == PasteBursts::Keys {
+            return events.into_iter().map(|(_, event)| Message::from(event)).collect();
+        }
+
+        let mut messages = Vec::with_capacity(events.len());
+
+        for (received, event) in events {
+            let continues = self.flush_at().is_some_and(|until| received < until);
+
+            if !continues {
+                self.flush(&mut messages);
+            }
+
+            // Releases (when they're delivered at all) are kept in the run without counting towards it.
+            match PasteBursts::character(&event) {
+                | Some(_) => self.run.push((received, event)),
+                | None if continues && PasteBursts::release(&event) => self.run.push((received, event)),
+                | None => {
+                    self.flush(&mut messages);
+                    messages.push(Message::from(event));
+                },
+            }
+        }
+
+        if self.flush_at().is_some_and(|until| until <= now) {
+            self.flush(&mut messages);
+        }
+
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crossterm::event::{KeyEvent, KeyEventState, MouseEvent, MouseEventKind};
+
+    fn key(kind: KeyEventKind) -> Event {
+        Event::Key(KeyEvent { code: KeyCode::Char('a'), modifiers: KeyModifiers::NONE, kind, state: KeyEventState::NONE })
+    }
+
+    #[test]
+    fn only_presses_by_default() {
+        let keys = KeyEventFilter::default();
+
+        assert!(is_delivered(&key(KeyEventKind::Press), keys));
+        assert!(!is_delivered(&key(KeyEventKind::Repeat), keys));
+        assert!(!is_delivered(&key(KeyEventKind::Release), keys));
+        assert!(is_delivered(&key(KeyEventKind::Release), KeyEventFilter::ALL));
+    }
+
+    #[test]
+    fn events_other_than_keys_are_always_delivered() {
+        let mouse = MouseEvent { kind: MouseEventKind::Moved, column: 0, row: 0, modifiers: KeyModifiers::NONE };
+        let none = KeyEventFilter { press: false, repeat: false, release: false };
+
+        assert!(is_delivered(&Event::Mouse(mouse), none));
+        assert!(is_delivered(&Event::Resize(80, 24), none));
+        assert!(is_delivered(&Event::FocusLost, none));
+    }
+
+    fn typed(text: &str, start: Instant) -> Vec<TimedEvent> {
+        let key = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+        text.chars().zip(0..).map(|(c, i)| (start + Duration::from_millis(i), key(c))).collect()
+    }
+
+    #[test]
+    fn bursts_read_in_several_batches_are_collapsed() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut bursts = BurstDetector::new(PasteBursts::Confirm);
+
+        assert!(bursts.messages(typed("hel", at(0)), at(2)).is_empty());
+        assert!(bursts.messages(typed("lo!", at(3)), at(5)).is_empty());
+
+        let messages = bursts.messages(Vec::new(), at(10));
+
+        assert!(matches!(&messages[..], [Message::PasteBurst(text)] if text == "hello!"));
+    }
+
+    #[test]
+    fn typed_keys_are_delivered_once_nothing_follows() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut bursts = BurstDetector::new(PasteBursts::Confirm);
+
+        assert!(bursts.messages(typed("a", at(0)), at(0)).is_empty());
+        assert_eq!(bursts.flush_at(), Some(at(0) + PasteBursts::MAX_GAP));
+
+        let messages = bursts.messages(typed("b", at(50)), at(50));
+
+        assert!(matches!(&messages[..], [Message::Key(_)]));
+
+        let messages = bursts.messages(Vec::new(), at(60));
+
+        assert!(matches!(&messages[..], [Message::Key(_)]));
+        assert_eq!(bursts.flush_at(), None);
+    }
+}
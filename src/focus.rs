@@ -0,0 +1,512 @@
+use std::cell::RefCell;
+
+use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
+
+use ratatui::layout::Rect;
+
+use crate::components::contains;
+use crate::message::{KeyMsg, MouseMsg};
+
+// Which of a screen's components has the focus, e.g. to pick the `KeyContext::Component` keymap. Components are
+// told apart by ids of the screen's choosing, usually an enum of its own, and take the focus in the order they
+// were added with tab and shift+tab.
+//
+// Clicking a component focuses it. With `follows_mouse` the focus also moves to whatever the pointer rests over,
+// once it's far enough inside of it, so moving along the border between two components doesn't flicker between
+// them. Components have to be `place`d when they're rendered for either to know where they are.
+//
+// The order tab goes in can be declared instead, components left out of it are skipped but can still be clicked
+// or focused directly. Named groups of components can be jumped between with F6 and shift+F6, like skip links.
+// Trapping the focus in a group, e.g. the buttons of a modal dialog, keeps it there until it's released.
+#[derive(Debug, Clone)]
+pub struct FocusManager<Id> {
+    ids: Vec<Id>,
+    focused: Option<usize>,
+    // By index, the components tab goes through.
+    order: Vec<usize>,
+    groups: Vec<(String, Vec<usize>)>,
+    // The group the focus is trapped in and what had the focus before, innermost last.
+    traps: Vec<(usize, Option<usize>)>,
+    // Where every component was rendered last, the ones rendered later are on top.
+    areas: RefCell<Vec<(usize, Rect)>>,
+    follows_mouse: bool,
+    hysteresis: u16,
+}
+
+impl<Id: PartialEq> FocusManager<Id> {
+    // The first component gets the focus.
+    pub fn new<I>(ids: I) -> Self
+        where I: IntoIterator<Item = Id>,
+    {
+        let ids = ids.into_iter().collect::<Vec<_>>();
+
+        Self {
+            focused: (!ids.is_empty()).then_some(0),
+            order: (0..ids.len()).collect(),
+            groups: Vec::new(),
+            traps: Vec::new(),
+            ids,
+            areas: RefCell::new(Vec::new()),
+            follows_mouse: false,
+            hysteresis: 1,
+        }
+    }
+
+    pub fn follows_mouse(mut self, follows: bool) -> Self {
+        self.follows_mouse = follows;
+        self
+    }
+
+    // How many cells inside of a component the pointer has to be before it takes the focus, 1 by default. Components
+    // too small for it take the focus at their center.
+    pub fn hysteresis(mut self, cells: u16) -> Self {
+        self.hysteresis = cells;
+        self
+    }
+
+    // The order tab goes in, from what has the focus. Components left out are skipped.
+    pub fn order<I>(mut self, ids: I) -> Self
+        where I: IntoIterator<Item = Id>,
+    {
+        self.order = ids.into_iter().filter_map(|id| self.index(&id)).collect();
+        self
+    }
+
+    // Components can be in more than one group, the ones that weren't added are left out.
+    pub fn group<N, I>(mut self, name: N, ids: I) -> Self
+        where N: Into<String>, I: IntoIterator<Item = Id>,
+    {
+        let members = ids.into_iter().filter_map(|id| self.index(&id)).collect();
+
+        self.groups.push((name.into(), members));
+        self
+    }
+
+    #[inline(always)]
+    pub fn set_follows_mouse(&mut self, follows: bool) {
+        self.follows_mouse = follows;
+    }
+
+    #[inline(always)]
+    pub fn is_following_mouse(&self) -> bool {
+        self.follows_mouse
+    }
+
+    #[inline(always)]
+    fn index(&self, id: &Id) -> Option<usize> {
+        self.ids.iter().position(|other| other == id)
+    }
+
+    #[inline(always)]
+    fn group_index(&self, name: &str) -> Option<usize> {
+        self.groups.iter().position(|(other, _)| other == name)
+    }
+
+    // Whether the component can take the focus with the focus trapped where it is.
+    fn reachable(&self, index: usize) -> bool {
+        match self.traps.last() {
+            | Some(&(group, _)) => self.groups[group].1.contains(&index),
+            | None => true,
+        }
+    }
+
+    // The components tab goes through from where the focus is trapped, in order.
+    pub fn tab_order(&self) -> Vec<&Id> {
+        self.order.iter().filter(|index| self.reachable(**index)).map(|index| &self.ids[*index]).collect()
+    }
+
+    // The first group the focused component is in.
+    pub fn focused_group(&self) -> Option<&str> {
+        let focused = self.focused?;
+
+        self.groups.iter().find(|(_, members)| members.contains(&focused)).map(|(name, _)| name.as_str())
+    }
+
+    #[inline(always)]
+    pub fn is_trapped(&self) -> bool {
+        !self.traps.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn focused(&self) -> Option<&Id> {
+        self.focused.map(|index| &self.ids[index])
+    }
+
+    #[inline(always)]
+    pub fn is_focused(&self, id: &Id) -> bool {
+        self.focused() == Some(id)
+    }
+
+    // Returns whether the focus moved, it doesn't for components that weren't added or that are outside of where
+    // the focus is trapped.
+    pub fn focus(&mut self, id: &Id) -> bool {
+        match self.index(id) {
+            | Some(index) if self.focused != Some(index) && self.reachable(index) => {
+                self.focused = Some(index);
+                true
+            },
+            | _ => false,
+        }
+    }
+
+    pub fn blur(&mut self) {
+        self.focused = None;
+    }
+
+    fn cycle(&mut self, forward: bool) {
+        let order = self.order.iter().copied().filter(|index| self.reachable(*index)).collect::<Vec<_>>();
+        let count = order.len();
+
+        let position = self.focused.and_then(|focused| order.iter().position(|index| *index == focused));
+
+        let next = match (position, forward) {
+            | _ if count == 0 => return,
+            | (None, true) => 0,
+            | (None, false) => count - 1,
+            | (Some(position), true) => (position + 1) % count,
+            | (Some(position), false) => (position + count - 1) % count,
+        };
+
+        self.focused = Some(order[next]);
+    }
+
+    #[inline(always)]
+    pub fn focus_next(&mut self) {
+        self.cycle(true)
+    }
+
+    #[inline(always)]
+    pub fn focus_previous(&mut self) {
+        self.cycle(false)
+    }
+
+    // Focuses the group's first component in tab order, or its first one if tab skips all of them. Returns whether
+    // the focus moved.
+    pub fn focus_group(&mut self, name: &str) -> bool {
+        let Some(group) = self.group_index(name) else {
+            return false;
+        };
+
+        let members = &self.groups[group].1;
+        let first = self.order.iter().find(|index| members.contains(index)).or(members.first()).copied();
+
+        match first {
+            | Some(index) if self.focused != Some(index) && self.reachable(index) => {
+                self.focused = Some(index);
+                true
+            },
+            | _ => false,
+        }
+    }
+
+    // To the first group after the focused one, in the order they were added. Not while the focus is trapped.
+    fn cycle_groups(&mut self, forward: bool) {
+        let count = self.groups.len();
+
+        if count == 0 || self.is_trapped() {
+            return;
+        }
+
+        let current = self.focused.and_then(|focused| {
+            self.groups.iter().position(|(_, members)| members.contains(&focused))
+        });
+
+        let next = match (current, forward) {
+            | (None, true) => 0,
+            | (None, false) => count - 1,
+            | (Some(current), true) => (current + 1) % count,
+            | (Some(current), false) => (current + count - 1) % count,
+        };
+
+        let name = self.groups[next].0.clone();
+
+        self.focus_group(&name);
+    }
+
+    // Keeps the focus within the group, focusing its first component unless it has the focus already. Traps nest,
+    // e.g. for a confirmation on top of a dialog. Returns whether the group exists.
+    pub fn trap(&mut self, name: &str) -> bool {
+        let Some(group) = self.group_index(name) else {
+            return false;
+        };
+
+        self.traps.push((group, self.focused));
+
+        if !self.focused.is_some_and(|focused| self.reachable(focused)) {
+            self.focused = None;
+            self.focus_group(name);
+        }
+
+        true
+    }
+
+    // Undoes the innermost trap, giving the focus back to what had it before.
+    pub fn release(&mut self) {
+        if let Some((_, previous)) = self.traps.pop() {
+            self.focused = previous;
+        }
+    }
+
+    // Called while rendering, with the area the component was rendered to.
+    pub fn place(&self, id: &Id, area: Rect) {
+        let Some(index) = self.index(id) else {
+            return;
+        };
+
+        let mut areas = self.areas.borrow_mut();
+
+        areas.retain(|(other, _)| *other != index);
+        areas.push((index, area));
+    }
+
+    // The component the pointer is over, the one rendered last where they overlap.
+    pub fn at(&self, column: u16, row: u16) -> Option<&Id> {
+        let areas = self.areas.borrow();
+        let (index, _) = areas.iter().rev().find(|(_, area)| contains(*area, column, row))?;
+
+        Some(&self.ids[*index])
+    }
+
+    fn is_well_inside(&self, area: Rect, column: u16, row: u16) -> bool {
+        let horizontal = self.hysteresis.min(area.width.saturating_sub(1) / 2);
+        let vertical = self.hysteresis.min(area.height.saturating_sub(1) / 2);
+
+        let inner = Rect::new(
+            area.x + horizontal,
+            area.y + vertical,
+            area.width - horizontal * 2,
+            area.height - vertical * 2,
+        );
+
+        contains(inner, column, row)
+    }
+
+    // Tab and shift+tab move the focus, F6 and shift+F6 between groups. Returns whether the key was used.
+    pub fn handle_key(&mut self, key: &KeyMsg) -> bool {
+        match key.code {
+            | KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => self.focus_previous(),
+            | KeyCode::Tab => self.focus_next(),
+            | KeyCode::BackTab => self.focus_previous(),
+            | KeyCode::F(6) if key.modifiers.contains(KeyModifiers::SHIFT) => self.cycle_groups(false),
+            | KeyCode::F(6) => self.cycle_groups(true),
+            | _ => return false,
+        }
+
+        true
+    }
+
+    // Returns whether the focus moved. The mouse is left for the components to use as well.
+    pub fn handle_mouse(&mut self, mouse: &MouseMsg) -> bool {
+        let hit = self.areas.borrow().iter().rev().find(|(_, area)| contains(*area, mouse.column, mouse.row)).copied();
+
+        let Some((index, area)) = hit else {
+            return false;
+        };
+
+        let moves = match mouse.kind {
+            | MouseEventKind::Down(MouseButton::Left) => true,
+            | MouseEventKind::Moved => self.follows_mouse && self.is_well_inside(area, mouse.column, mouse.row),
+            | _ => false,
+        };
+
+        if !moves || self.focused == Some(index) || !self.reachable(index) {
+            return false;
+        }
+
+        self.focused = Some(index);
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, key_with, mouse};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Pane {
+        Files,
+        Editor,
+        Terminal,
+    }
+
+    fn placed(follows_mouse: bool) -> FocusManager<Pane> {
+        let focus = FocusManager::new([Pane::Files, Pane::Editor, Pane::Terminal]).follows_mouse(follows_mouse);
+
+        focus.place(&Pane::Files, Rect::new(0, 0, 10, 20));
+        focus.place(&Pane::Editor, Rect::new(10, 0, 30, 20));
+        focus.place(&Pane::Terminal, Rect::new(10, 14, 30, 6));
+
+        focus
+    }
+
+    #[test]
+    fn tab_cycles_through_the_components_in_order() {
+        let mut focus = placed(false);
+
+        assert_eq!(focus.focused(), Some(&Pane::Files));
+        assert!(focus.handle_key(&key(KeyCode::Tab)));
+        assert!(focus.handle_key(&key(KeyCode::Tab)));
+        assert!(focus.is_focused(&Pane::Terminal));
+        assert!(focus.handle_key(&key(KeyCode::Tab)));
+        assert!(focus.is_focused(&Pane::Files));
+        assert!(focus.handle_key(&key_with(KeyCode::Tab, KeyModifiers::SHIFT)));
+        assert!(focus.is_focused(&Pane::Terminal));
+        assert!(!focus.handle_key(&key(KeyCode::Enter)));
+    }
+
+    #[test]
+    fn clicks_focus_what_is_on_top() {
+        let mut focus = placed(false);
+
+        assert_eq!(focus.at(20, 15), Some(&Pane::Terminal));
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Moved, 20, 5)));
+        assert!(focus.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 20, 15)));
+        assert!(focus.is_focused(&Pane::Terminal));
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 50, 5)));
+    }
+
+    #[test]
+    fn the_focus_follows_the_pointer_once_it_is_well_inside() {
+        let mut focus = placed(true).hysteresis(2);
+
+        // Along the border between the files and the editor.
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Moved, 10, 5)));
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Moved, 11, 5)));
+        assert!(focus.is_focused(&Pane::Files));
+
+        assert!(focus.handle_mouse(&mouse(MouseEventKind::Moved, 12, 5)));
+        assert!(focus.is_focused(&Pane::Editor));
+
+        focus.set_follows_mouse(false);
+
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Moved, 5, 5)));
+        assert!(focus.is_focused(&Pane::Editor));
+    }
+
+    #[test]
+    fn small_components_take_the_focus_at_their_center() {
+        let mut focus = FocusManager::new([Pane::Files, Pane::Editor]).follows_mouse(true).hysteresis(5);
+
+        focus.place(&Pane::Editor, Rect::new(0, 0, 3, 1));
+
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Moved, 0, 0)));
+        assert!(focus.handle_mouse(&mouse(MouseEventKind::Moved, 1, 0)));
+        assert!(focus.is_focused(&Pane::Editor));
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Field {
+        Skip,
+        Search,
+        Results,
+        Name,
+        Ok,
+        Cancel,
+    }
+
+    fn dialog() -> FocusManager<Field> {
+        FocusManager::new([Field::Skip, Field::Search, Field::Results, Field::Name, Field::Ok, Field::Cancel])
+            .order([Field::Search, Field::Results, Field::Skip, Field::Name, Field::Ok, Field::Cancel])
+            .group("main", [Field::Search, Field::Results])
+            .group("dialog", [Field::Name, Field::Ok, Field::Cancel])
+    }
+
+    #[test]
+    fn tab_follows_the_declared_order() {
+        let mut focus = dialog().order([Field::Search, Field::Results, Field::Ok]);
+
+        assert_eq!(focus.tab_order(), [&Field::Search, &Field::Results, &Field::Ok]);
+
+        focus.focus_next();
+        focus.focus_next();
+
+        assert!(focus.is_focused(&Field::Results));
+
+        focus.focus_next();
+        focus.focus_next();
+
+        assert!(focus.is_focused(&Field::Search));
+
+        // Left out of the order, but not unfocusable.
+        assert!(focus.focus(&Field::Cancel));
+    }
+
+    #[test]
+    fn groups_are_jumped_between_like_skip_links() {
+        let mut focus = dialog();
+
+        assert_eq!(focus.focused_group(), None);
+        assert!(focus.handle_key(&key(KeyCode::F(6))));
+        assert!(focus.is_focused(&Field::Search));
+        assert_eq!(focus.focused_group(), Some("main"));
+
+        focus.handle_key(&key(KeyCode::F(6)));
+
+        assert!(focus.is_focused(&Field::Name));
+
+        focus.handle_key(&key_with(KeyCode::F(6), KeyModifiers::SHIFT));
+
+        assert!(focus.is_focused(&Field::Search));
+        assert!(focus.focus_group("dialog"));
+        assert!(!focus.focus_group("missing"));
+    }
+
+    #[test]
+    fn the_focus_stays_trapped_in_a_modal_until_released() {
+        let mut focus = dialog();
+
+        focus.focus(&Field::Results);
+
+        assert!(focus.trap("dialog"));
+        assert!(focus.is_trapped());
+        assert!(focus.is_focused(&Field::Name));
+        assert_eq!(focus.tab_order(), [&Field::Name, &Field::Ok, &Field::Cancel]);
+
+        for _ in 0..3 {
+            focus.handle_key(&key(KeyCode::Tab));
+            focus.handle_key(&key(KeyCode::BackTab));
+            focus.handle_key(&key(KeyCode::Tab));
+            focus.handle_key(&key(KeyCode::F(6)));
+
+            assert!(focus.focused_group() == Some("dialog"));
+        }
+
+        assert!(!focus.focus(&Field::Search));
+        assert!(!focus.focus_group("main"));
+
+        focus.place(&Field::Search, Rect::new(0, 0, 10, 1));
+
+        assert!(!focus.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 2, 0)));
+        assert_eq!(focus.focused_group(), Some("dialog"));
+
+        focus.release();
+
+        assert!(!focus.is_trapped());
+        assert!(focus.is_focused(&Field::Results));
+        assert!(focus.handle_mouse(&mouse(MouseEventKind::Down(MouseButton::Left), 2, 0)));
+    }
+
+    #[test]
+    fn traps_nest() {
+        let mut focus = dialog().group("confirm", [Field::Ok, Field::Cancel]);
+
+        focus.trap("dialog");
+        focus.focus(&Field::Cancel);
+        focus.trap("confirm");
+
+        assert!(focus.is_focused(&Field::Cancel));
+        assert!(!focus.focus(&Field::Name));
+
+        focus.release();
+
+        assert!(focus.focus(&Field::Name));
+
+        focus.release();
+
+        assert!(focus.is_focused(&Field::Skip));
+        assert!(!focus.trap("missing"));
+    }
+}
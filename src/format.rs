@@ -0,0 +1,134 @@
+use std::time;
+use std::sync::{PoisonError, RwLock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Locale {
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl Locale {
+    pub const ENGLISH: Self = Self { thousands_separator: Some(','), decimal_separator: '.' };
+    pub const GERMAN: Self = Self { thousands_separator: Some('.'), decimal_separator: ',' };
+    // French typography separates thousands with a narrow no-break space.
+    pub const FRENCH: Self = Self { thousands_separator: Some('\u{202F}'), decimal_separator: ',' };
+    pub const SWISS: Self = Self { thousands_separator: Some('\''), decimal_separator: '.' };
+    pub const PLAIN: Self = Self { thousands_separator: None, decimal_separator: '.' };
+
+    #[inline(always)]
+    pub fn current() -> Self {
+        *LOCALE.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // Applies to every widget in the process, the application sets it when it starts running.
+    #[inline(always)]
+    pub fn set(locale: Self) {
+        *LOCALE.write().unwrap_or_else(PoisonError::into_inner) = locale;
+    }
+}
+
+impl Default for Locale {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::ENGLISH
+    }
+}
+
+static LOCALE: RwLock<Locale> = RwLock::new(Locale::ENGLISH);
+
+fn group(digits: &str, separator: Option<char>) -> String {
+    let Some(separator) = separator else {
+        return digits.to_owned();
+    };
+
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(separator);
+        }
+
+        grouped.push(digit);
+    }
+
+    grouped
+}
+
+pub fn integer(value: i64) -> String {
+    let sign = if value < 0 { "-" } else { "" };
+
+    format!("{sign}{}", group(&value.unsigned_abs().to_string(), Locale::current().thousands_separator))
+}
+
+pub fn decimal(value: f64, precision: usize) -> String {
+    let locale = Locale::current();
+
+    let formatted = format!("{:.precision$}", value.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+
+    let sign = if value < 0. && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') { "-" } else { "" };
+
+    match fraction.is_empty() {
+        | true => format!("{sign}{}", group(whole, locale.thousands_separator)),
+        | false => format!("{sign}{}{}{fraction}", group(whole, locale.thousands_separator), locale.decimal_separator),
+    }
+}
+
+pub fn bytes(value: f64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut value = value;
+    let mut unit = 0;
+
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+
+    format!("{} {}", decimal(value, 1), UNITS[unit])
+}
+
+// Only the most significant unit is shown, e.g. "3m ago" rather than "3m 12s ago".
+pub fn relative(elapsed: time::Duration) -> String {
+    let seconds = elapsed.as_secs();
+
+    match seconds {
+        | 0 => String::from("just now"),
+        | 1..=59 => format!("{seconds}s ago"),
+        | 60..=3599 => format!("{}m ago", seconds / 60),
+        | 3600..=86399 => format!("{}h ago", seconds / 3600),
+        | _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The locale is shared by every test in the process, so only the default one is formatted with.
+    #[test]
+    fn numbers_are_grouped_by_thousands() {
+        assert_eq!(integer(0), "0");
+        assert_eq!(integer(-1234567), "-1,234,567");
+        assert_eq!(decimal(1234.5, 2), "1,234.50");
+        assert_eq!(decimal(-0.001, 2), "0.00");
+        assert_eq!(decimal(999.6, 0), "1,000");
+
+        assert_eq!(group("123456", Locale::FRENCH.thousands_separator), "123\u{202F}456");
+        assert_eq!(group("12345", Locale::SWISS.thousands_separator), "12'345");
+        assert_eq!(group("12345", Locale::PLAIN.thousands_separator), "12345");
+    }
+
+    #[test]
+    fn sizes_and_durations_are_shown_in_the_largest_unit() {
+        assert_eq!(bytes(512.), "512.0 B");
+        assert_eq!(bytes(1536.), "1.5 KiB");
+        assert_eq!(bytes(1024f64.powi(5) * 2.), "2,048.0 TiB");
+
+        assert_eq!(relative(time::Duration::from_millis(900)), "just now");
+        assert_eq!(relative(time::Duration::from_secs(59)), "59s ago");
+        assert_eq!(relative(time::Duration::from_secs(192)), "3m ago");
+        assert_eq!(relative(time::Duration::from_secs(7200)), "2h ago");
+        assert_eq!(relative(time::Duration::from_secs(86400 * 3)), "3d ago");
+    }
+}
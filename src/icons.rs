@@ -0,0 +1,171 @@
+use std::env;
+use std::path::Path;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Capability {
+    Ascii,
+    Unicode,
+    NerdFont,
+}
+
+impl Capability {
+    // Probed once, `RATATA_ICONS=ascii|unicode|nerd` overrides the detection.
+    pub fn detect() -> Self {
+        static DETECTED: OnceLock<Capability> = OnceLock::new();
+
+        *DETECTED.get_or_init(Self::probe)
+    }
+
+    fn probe() -> Self {
+        if let Ok(value) = env::var("RATATA_ICONS") {
+            match value.to_ascii_lowercase().as_str() {
+                | "ascii" => return Self::Ascii,
+                | "unicode" | "emoji" => return Self::Unicode,
+                | "nerd" | "nerdfont" => return Self::NerdFont,
+                | _ => (),
+            }
+        }
+
+        // The linux console and dumb terminals can't render anything beyond ASCII reliably.
+        if env::var("TERM").is_ok_and(|term| term == "linux" || term == "dumb") {
+            return Self::Ascii;
+        }
+
+        if env::var_os("WT_SESSION").is_some() {
+            return Self::Unicode;
+        }
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .into_iter()
+            .find_map(|name| env::var(name).ok().filter(|value| !value.is_empty()))
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match locale.contains("utf-8") || locale.contains("utf8") {
+            | true => Self::Unicode,
+            | false => Self::Ascii,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Icon {
+    Success,
+    Failure,
+    Warning,
+    Info,
+    Pending,
+    Running,
+    File,
+    Directory,
+    DirectoryOpen,
+    Symlink,
+    Executable,
+    Archive,
+    Image,
+    Rust,
+    Markdown,
+    Json,
+    Toml,
+    GitBranch,
+    GitCommit,
+    GitAdded,
+    GitModified,
+    GitDeleted,
+    GitRenamed,
+    GitUntracked,
+    GitConflict,
+}
+
+impl Icon {
+    #[inline(always)]
+    pub fn glyph(self) -> &'static str {
+        self.glyph_for(Capability::detect())
+    }
+
+    pub fn glyph_for(self, capability: Capability) -> &'static str {
+        let (ascii, unicode, nerd_font) = self.glyphs();
+
+        match capability {
+            | Capability::Ascii => ascii,
+            | Capability::Unicode => unicode,
+            | Capability::NerdFont => nerd_font,
+        }
+    }
+
+    fn glyphs(self) -> (&'static str, &'static str, &'static str) {
+        match self {
+            | Icon::Success => ("+", "✔", "\u{f00c}"),
+            | Icon::Failure => ("x", "✘", "\u{f00d}"),
+            | Icon::Warning => ("!", "⚠", "\u{f071}"),
+            | Icon::Info => ("i", "ℹ", "\u{f05a}"),
+            | Icon::Pending => (".", "…", "\u{f017}"),
+            | Icon::Running => ("~", "⟳", "\u{f021}"),
+            | Icon::File => ("-", "📄", "\u{f016}"),
+            | Icon::Directory => ("+", "📁", "\u{f07b}"),
+            | Icon::DirectoryOpen => ("-", "📂", "\u{f07c}"),
+            | Icon::Symlink => ("@", "🔗", "\u{f481}"),
+            | Icon::Executable => ("*", "⚙", "\u{f489}"),
+            | Icon::Archive => ("-", "📦", "\u{f1c6}"),
+            | Icon::Image => ("-", "🖼", "\u{f1c5}"),
+            | Icon::Rust => ("-", "🦀", "\u{e7a8}"),
+            | Icon::Markdown => ("-", "📝", "\u{e73e}"),
+            | Icon::Json => ("-", "📄", "\u{e60b}"),
+            | Icon::Toml => ("-", "📄", "\u{e615}"),
+            | Icon::GitBranch => ("*", "⎇", "\u{e725}"),
+            | Icon::GitCommit => ("o", "●", "\u{e729}"),
+            | Icon::GitAdded => ("A", "✚", "\u{f457}"),
+            | Icon::GitModified => ("M", "●", "\u{f459}"),
+            | Icon::GitDeleted => ("D", "✖", "\u{f458}"),
+            | Icon::GitRenamed => ("R", "➜", "\u{f45a}"),
+            | Icon::GitUntracked => ("?", "?", "\u{f128}"),
+            | Icon::GitConflict => ("U", "⚡", "\u{f421}"),
+        }
+    }
+
+    // Only looks at the name, the file system is never touched.
+    pub fn for_path<P: AsRef<Path>>(path: P) -> Self {
+        let extension = path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            | Some("rs") => Icon::Rust,
+            | Some("md" | "markdown") => Icon::Markdown,
+            | Some("json") => Icon::Json,
+            | Some("toml") => Icon::Toml,
+            | Some("png" | "jpg" | "jpeg" | "gif" | "bmp" | "svg" | "webp") => Icon::Image,
+            | Some("zip" | "tar" | "gz" | "xz" | "bz2" | "zst" | "7z" | "rar") => Icon::Archive,
+            | Some("sh" | "exe" | "bat" | "cmd") => Icon::Executable,
+            | _ => Icon::File,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_fall_back_to_what_the_terminal_can_show() {
+        assert_eq!(Icon::Success.glyph_for(Capability::Ascii), "+");
+        assert_eq!(Icon::Success.glyph_for(Capability::Unicode), "✔");
+        assert_eq!(Icon::Success.glyph_for(Capability::NerdFont), "\u{f00c}");
+
+        for icon in [Icon::Warning, Icon::Directory, Icon::GitConflict, Icon::Rust] {
+            assert!(icon.glyph_for(Capability::Ascii).is_ascii());
+        }
+    }
+
+    #[test]
+    fn paths_are_told_apart_by_their_extension() {
+        assert_eq!(Icon::for_path("src/main.rs"), Icon::Rust);
+        assert_eq!(Icon::for_path("README.MD"), Icon::Markdown);
+        assert_eq!(Icon::for_path("backup.tar.gz"), Icon::Archive);
+        assert_eq!(Icon::for_path("photo.JPEG"), Icon::Image);
+        assert_eq!(Icon::for_path("Makefile"), Icon::File);
+        assert_eq!(Icon::for_path(".gitignore"), Icon::File);
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::time;
+
+use crossterm::event::{KeyCode, KeyEventKind, KeyModifiers};
+
+use crate::message::Message;
+
+// Letters are held regardless of shift, a key pressed as 'a' may well be released as 'A'.
+#[inline(always)]
+fn normalize(code: KeyCode) -> KeyCode {
+    match code {
+        | KeyCode::Char(c) => KeyCode::Char(c.to_ascii_lowercase()),
+        | code => code,
+    }
+}
+
+// The keys currently held down, put together from presses and releases. Kept in the application's context,
+// see `Builder::input_state`, and only tracked with `Builder::game_mode`, which asks the terminal to report
+// releases. On terminals that can't, a key is let go of once it stops repeating.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputState {
+    // When each key was last pressed or repeated.
+    held: HashMap<KeyCode, time::Instant>,
+    modifiers: KeyModifiers,
+}
+
+impl Default for InputState {
+    fn default() -> Self {
+        Self { held: HashMap::new(), modifiers: KeyModifiers::NONE }
+    }
+}
+
+impl InputState {
+    #[inline(always)]
+    pub fn is_held(&self, code: KeyCode) -> bool {
+        self.held.contains_key(&normalize(code))
+    }
+
+    #[inline(always)]
+    pub fn held(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held.keys().copied()
+    }
+
+    // As of the last key event.
+    #[inline(always)]
+    pub fn modifiers(&self) -> KeyModifiers {
+        self.modifiers
+    }
+
+    // -1, 0 or 1, for movement along an axis. Holding both keys cancels out.
+    pub fn axis(&self, negative: KeyCode, positive: KeyCode) -> i32 {
+        self.is_held(positive) as i32 - self.is_held(negative) as i32
+    }
+
+    // Up to date with the message by the time a screen is updated with it.
+    pub(crate) fn apply(&mut self, message: &Message, now: time::Instant) {
+        match message {
+            | Message::Key(key) => {
+                match key.kind {
+                    | KeyEventKind::Press | KeyEventKind::Repeat => self.held.insert(normalize(key.code), now),
+                    | KeyEventKind::Release => self.held.remove(&normalize(key.code)),
+                };
+
+                self.modifiers = key.modifiers;
+            },
+            // Releases can't be seen while away.
+            | Message::FocusLost | Message::Resumed | Message::ExecFinished(_) => *self = Self::default(),
+            | _ => (),
+        }
+    }
+
+    // Without releases, a key is only known to be held for as long as the terminal keeps repeating it.
+    pub(crate) fn expire(&mut self, now: time::Instant, after: time::Duration) {
+        self.held.retain(|_, pressed| now.saturating_duration_since(*pressed) < after);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{self, KeyMsg};
+
+    fn key(c: char, kind: KeyEventKind) -> Message {
+        Message::Key(KeyMsg { kind, ..message::key(KeyCode::Char(c)) })
+    }
+
+    #[test]
+    fn keys_are_held_until_released() {
+        let (mut input, now) = (InputState::default(), time::Instant::now());
+
+        input.apply(&key('a', KeyEventKind::Press), now);
+        input.apply(&key('D', KeyEventKind::Press), now);
+
+        assert!(input.is_held(KeyCode::Char('A')));
+        assert_eq!(input.axis(KeyCode::Char('a'), KeyCode::Char('d')), 0);
+
+        input.apply(&key('a', KeyEventKind::Release), now);
+
+        assert!(!input.is_held(KeyCode::Char('a')));
+        assert_eq!(input.axis(KeyCode::Char('a'), KeyCode::Char('d')), 1);
+    }
+
+    #[test]
+    fn keys_that_stop_repeating_are_let_go_of() {
+        let (mut input, now) = (InputState::default(), time::Instant::now());
+        let later = |ms| now + time::Duration::from_millis(ms);
+
+        input.apply(&key('w', KeyEventKind::Press), now);
+        input.apply(&key('w', KeyEventKind::Repeat), later(500));
+        input.expire(later(900), time::Duration::from_millis(700));
+
+        assert!(input.is_held(KeyCode::Char('w')));
+
+        input.expire(later(1200), time::Duration::from_millis(700));
+
+        assert!(!input.is_held(KeyCode::Char('w')));
+    }
+
+    #[test]
+    fn everything_is_let_go_of_after_being_away() {
+        let (mut input, now) = (InputState::default(), time::Instant::now());
+        let exited = Message::ExecFinished(Err(std::io::ErrorKind::NotFound.into()));
+
+        for away in [Message::FocusLost, Message::Resumed, exited] {
+            input.apply(&key('a', KeyEventKind::Press), now);
+            input.apply(&away, now);
+
+            assert_eq!(input.held().count(), 0);
+        }
+    }
+}
@@ -0,0 +1,784 @@
+use std::{fmt, ptr};
+use std::any::TypeId;
+use std::cmp::Reverse;
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use thiserror::Error;
+
+use crate::message::{KeyKind, KeyMsg, KeyState, Message};
+
+#[derive(Debug, Error)]
+#[error("{0:?} is not a key binding, expected something like \"ctrl+s\" or \"shift+tab\"")]
+pub struct KeyBindingParseError(pub String);
+
+#[derive(Debug, Error)]
+#[error("{key} is bound to both {first} and {second} in the {context:?} context")]
+pub struct KeymapConflictError {
+    pub key: KeyBinding,
+    pub context: KeyContext,
+    pub first: String,
+    pub second: String,
+}
+
+// A key along with the modifiers it's pressed with. Everything is const, so default keymaps can be `static`
+// tables, e.g. `Binding::new(KeyBinding::ctrl('s'), Action::Save)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    #[inline(always)]
+    pub const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    #[inline(always)]
+    pub const fn key(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    #[inline(always)]
+    pub const fn char(c: char) -> Self {
+        Self::key(KeyCode::Char(c))
+    }
+
+    #[inline(always)]
+    pub const fn ctrl(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[inline(always)]
+    pub const fn alt(c: char) -> Self {
+        Self::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    #[inline(always)]
+    pub const fn with(self, modifiers: KeyModifiers) -> Self {
+        Self::new(self.code, self.modifiers.union(modifiers))
+    }
+
+    // Shift is part of the character for characters, `G` matches whether shift is reported along with it or not.
+    pub fn matches(&self, key: &KeyMsg) -> bool {
+        let ignored = match key.code {
+            | KeyCode::Char(_) => KeyModifiers::SHIFT,
+            | _ => KeyModifiers::NONE,
+        };
+
+        self.code == key.code && self.modifiers.difference(ignored) == key.modifiers.difference(ignored)
+    }
+}
+
+// The key press the binding stands for, e.g. to look up what it does in `KeyContexts::resolve`.
+impl From<KeyBinding> for KeyMsg {
+    fn from(binding: KeyBinding) -> Self {
+        KeyMsg { code: binding.code, modifiers: binding.modifiers, state: KeyState::NONE, kind: KeyKind::Press }
+    }
+}
+
+const MODIFIERS: [(&str, KeyModifiers); 3] = [
+    ("ctrl", KeyModifiers::CONTROL),
+    ("alt", KeyModifiers::ALT),
+    ("shift", KeyModifiers::SHIFT),
+];
+
+const NAMED: [(&str, KeyCode); 16] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("space", KeyCode::Char(' ')),
+];
+
+// The way they're written in configuration files, e.g. "ctrl+s", "alt+enter", "f5" or "G".
+impl FromStr for KeyBinding {
+    type Err = KeyBindingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || KeyBindingParseError(s.to_owned());
+
+        // The plus key itself, with or without modifiers.
+        let (modifiers, key) = match s.strip_suffix('+') {
+            | Some(modifiers) if modifiers.is_empty() || modifiers.ends_with('+') => (modifiers, "+"),
+            | _ => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let modifiers = modifiers.split('+').filter(|m| !m.is_empty()).try_fold(KeyModifiers::NONE, |all, m| {
+            MODIFIERS.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(m))
+                .map(|(_, modifier)| all | *modifier)
+        });
+
+        let mut chars = key.chars();
+
+        let code = match (chars.next(), chars.next()) {
+            | (Some(c), None) => Some(KeyCode::Char(c)),
+            | _ => NAMED.iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, code)| *code)
+                .or_else(|| key.strip_prefix(['f', 'F']).and_then(|n| n.parse().ok()).map(KeyCode::F)),
+        };
+
+        match (code, modifiers) {
+            | (Some(code), Some(modifiers)) => Ok(Self::new(code, modifiers)),
+            | _ => Err(error()),
+        }
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}+")?;
+            }
+        }
+
+        match NAMED.iter().find(|(_, code)| *code == self.code) {
+            | Some((name, _)) => f.write_str(name),
+            | None => match self.code {
+                | KeyCode::Char(c) => write!(f, "{c}"),
+                | KeyCode::F(n) => write!(f, "f{n}"),
+                | code => write!(f, "{code:?}"),
+            },
+        }
+    }
+}
+
+// A key bound to one of the application's actions, usually an enum of its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding<A> {
+    pub key: KeyBinding,
+    pub action: A,
+}
+
+impl<A> Binding<A> {
+    #[inline(always)]
+    pub const fn new(key: KeyBinding, action: A) -> Self {
+        Self { key, action }
+    }
+}
+
+// Looked up in the order the bindings were added, e.g. from a `static` table of defaults merged with the user's
+// overrides (see `KeyMap::merge`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap<A> {
+    bindings: Vec<Binding<A>>,
+}
+
+impl<A> Default for KeyMap<A> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> KeyMap<A> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self { bindings: Vec::new() }
+    }
+
+    pub fn bind(mut self, key: KeyBinding, action: A) -> Self {
+        self.insert(key, action);
+        self
+    }
+
+    #[inline(always)]
+    pub fn insert(&mut self, key: KeyBinding, action: A) {
+        self.bindings.push(Binding::new(key, action));
+    }
+
+    // An action bound in the overrides loses the keys it had, and a key bound in them loses the action it had.
+    pub fn merge<I>(&mut self, overrides: I)
+        where I: IntoIterator<Item = Binding<A>>, A: PartialEq,
+    {
+        let overrides = overrides.into_iter().collect::<Vec<_>>();
+
+        self.bindings.retain(|binding| {
+            !overrides.iter().any(|o| o.key == binding.key || o.action == binding.action)
+        });
+
+        self.bindings.extend(overrides);
+    }
+
+    pub fn action(&self, key: &KeyMsg) -> Option<&A> {
+        self.bindings.iter().find(|binding| binding.key.matches(key)).map(|binding| &binding.action)
+    }
+
+    // Every key the action is bound to, e.g. for showing it next to a menu entry.
+    pub fn keys<'a>(&'a self, action: &'a A) -> impl Iterator<Item = KeyBinding> + 'a
+        where A: PartialEq,
+    {
+        self.bindings.iter().filter(move |binding| binding.action == *action).map(|binding| binding.key)
+    }
+
+    #[inline(always)]
+    pub fn bindings(&self) -> &[Binding<A>] {
+        &self.bindings
+    }
+}
+
+impl<A: Clone> From<&[Binding<A>]> for KeyMap<A> {
+    fn from(bindings: &[Binding<A>]) -> Self {
+        Self { bindings: bindings.to_vec() }
+    }
+}
+
+impl<A> FromIterator<Binding<A>> for KeyMap<A> {
+    fn from_iter<I: IntoIterator<Item = Binding<A>>>(iter: I) -> Self {
+        Self { bindings: iter.into_iter().collect() }
+    }
+}
+
+// Where a keymap applies, from the least to the most specific.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyContext {
+    Global,
+    Screen,
+    Component,
+}
+
+// What a key does with the contexts as they are, and what it would have done without the context it's bound in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resolved<'a, A> {
+    pub key: KeyBinding,
+    pub context: KeyContext,
+    pub action: &'a A,
+    // Most specific first.
+    pub shadowed: Vec<(KeyContext, &'a A)>,
+}
+
+// The keymaps in effect, e.g. the application's, the active screen's and the focused component's. A key does
+// what the most specific context binds it to, of keymaps in the same context the one pushed last wins, and within
+// a keymap the binding added first does.
+#[derive(Debug)]
+pub struct KeyContexts<'a, A> {
+    keymaps: Vec<(KeyContext, &'a KeyMap<A>)>,
+}
+
+impl<A> Default for KeyContexts<'_, A> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, A> KeyContexts<'a, A> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self { keymaps: Vec::new() }
+    }
+
+    pub fn with(mut self, context: KeyContext, keymap: &'a KeyMap<A>) -> Self {
+        self.push(context, keymap);
+        self
+    }
+
+    #[inline(always)]
+    pub fn push(&mut self, context: KeyContext, keymap: &'a KeyMap<A>) {
+        self.keymaps.push((context, keymap));
+    }
+
+    #[inline(always)]
+    pub fn pop(&mut self) -> Option<(KeyContext, &'a KeyMap<A>)> {
+        self.keymaps.pop()
+    }
+
+    // Every binding, the ones that take precedence first.
+    fn by_precedence(&self) -> impl Iterator<Item = (KeyContext, &'a Binding<A>)> {
+        let mut keymaps = self.keymaps.iter().copied().enumerate().collect::<Vec<_>>();
+
+        keymaps.sort_by_key(|(pushed, (context, _))| Reverse((*context, *pushed)));
+
+        keymaps.into_iter().flat_map(|(_, (context, keymap))| keymap.bindings().iter().map(move |b| (context, b)))
+    }
+
+    #[inline(always)]
+    pub fn action(&self, key: &KeyMsg) -> Option<&'a A> {
+        self.by_precedence().find(|(_, binding)| binding.key.matches(key)).map(|(_, binding)| &binding.action)
+    }
+
+    pub fn resolve(&self, key: &KeyMsg) -> Option<Resolved<'a, A>> {
+        let mut bound = self.by_precedence().filter(|(_, binding)| binding.key.matches(key));
+        let (context, binding) = bound.next()?;
+
+        Some(Resolved {
+            key: binding.key,
+            context,
+            action: &binding.action,
+            shadowed: bound.map(|(context, binding)| (context, &binding.action)).collect(),
+        })
+    }
+
+    // Every key that does something, once, with what it does. E.g. for a help overlay.
+    pub fn effective(&self) -> Vec<Resolved<'a, A>> {
+        self.by_precedence()
+            .filter_map(|(_, binding)| {
+                self.resolve(&binding.key.into()).filter(|resolved| ptr::eq(resolved.action, &binding.action))
+            })
+            .collect()
+    }
+}
+
+// Keys typed one after the other, written separated by spaces, e.g. "g g" or "space f s".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KeySequence(pub Vec<KeyBinding>);
+
+impl From<KeyBinding> for KeySequence {
+    #[inline(always)]
+    fn from(key: KeyBinding) -> Self {
+        Self(vec![key])
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = KeyBindingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let keys = s.split_whitespace().map(str::parse).collect::<Result<Vec<_>, _>>()?;
+
+        match keys.is_empty() {
+            | true => Err(KeyBindingParseError(s.to_owned())),
+            | false => Ok(Self(keys)),
+        }
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, key) in self.0.iter().enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+
+            write!(f, "{key}")?;
+        }
+
+        Ok(())
+    }
+}
+
+// What a key did to the sequence typed so far, see `KeySequences::feed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<'a, A> {
+    Action(&'a A),
+    // The key starts or continues a longer sequence, the keys that can follow are `KeySequences::hints`.
+    Pending,
+    // Esc while a sequence was pending.
+    Cancelled,
+    // Nothing starts with the key. The sequence typed so far is dropped, the key is the screen's to handle.
+    Unbound,
+}
+
+// A key that can follow the keys typed so far.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint<'a, A> {
+    pub key: KeyBinding,
+    // `None` for keys that only lead to longer sequences.
+    pub action: Option<&'a A>,
+    // How many sequences go on past the key, none if it does an action since the longer ones can't be typed.
+    pub continuations: usize,
+}
+
+// Actions bound to sequences of keys, like a `KeyMap` for vim style bindings. Keys are fed in as they're pressed,
+// a sequence that's also the start of a longer one does its action right away and the longer one is never reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeySequences<A> {
+    bindings: Vec<(KeySequence, A)>,
+    pending: Vec<KeyBinding>,
+}
+
+impl<A> Default for KeySequences<A> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> KeySequences<A> {
+    #[inline(always)]
+    pub const fn new() -> Self {
+        Self { bindings: Vec::new(), pending: Vec::new() }
+    }
+
+    pub fn bind<S: Into<KeySequence>>(mut self, keys: S, action: A) -> Self {
+        self.insert(keys, action);
+        self
+    }
+
+    #[inline(always)]
+    pub fn insert<S: Into<KeySequence>>(&mut self, keys: S, action: A) {
+        self.bindings.push((keys.into(), action));
+    }
+
+    // The keys typed so far, empty unless a sequence is pending.
+    #[inline(always)]
+    pub fn pending(&self) -> &[KeyBinding] {
+        &self.pending
+    }
+
+    #[inline(always)]
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    // The sequences going on past the keys typed so far, by their index. One that starts with a shorter sequence
+    // can't be typed, the shorter one does its action first.
+    fn continuing(&self) -> impl Iterator<Item = (usize, &KeySequence)> + Clone {
+        let typed = self.pending.len();
+
+        let shadowed = |keys: &KeySequence| {
+            self.bindings.iter().any(|(other, _)| other.0.len() < keys.0.len() && keys.0.starts_with(&other.0))
+        };
+
+        self.bindings.iter()
+            .enumerate()
+            .filter(move |(_, (keys, _))| keys.0.len() > typed && keys.0.starts_with(&self.pending))
+            .filter(move |(_, (keys, _))| !shadowed(keys))
+            .map(|(index, (keys, _))| (index, keys))
+    }
+
+    pub fn feed(&mut self, key: &KeyMsg) -> Step<'_, A> {
+        let typed = self.pending.len();
+
+        if typed > 0 && key.code == KeyCode::Esc {
+            self.cancel();
+            return Step::Cancelled;
+        }
+
+        let (complete, longer) = {
+            let mut continuing = self.continuing().filter(|(_, keys)| keys.0[typed].matches(key));
+
+            (
+                continuing.clone().find(|(_, keys)| keys.0.len() == typed + 1).map(|(index, _)| index),
+                continuing.find(|(_, keys)| keys.0.len() > typed + 1).map(|(_, keys)| keys.0[typed]),
+            )
+        };
+
+        match (complete, longer) {
+            | (Some(index), _) => {
+                self.cancel();
+                Step::Action(&self.bindings[index].1)
+            },
+            | (None, Some(next)) => {
+                self.pending.push(next);
+                Step::Pending
+            },
+            | (None, None) => {
+                self.cancel();
+                Step::Unbound
+            },
+        }
+    }
+
+    // What can be typed next, in the order the sequences were bound. Nothing unless a sequence is pending.
+    pub fn hints(&self) -> Vec<Hint<'_, A>> {
+        let typed = self.pending.len();
+        let mut hints = Vec::<Hint<'_, A>>::new();
+
+        if typed == 0 {
+            return hints;
+        }
+
+        for (index, keys) in self.continuing() {
+            let key = keys.0[typed];
+
+            let hint = match hints.iter().position(|hint| hint.key == key) {
+                | Some(hint) => hint,
+                | None => {
+                    hints.push(Hint { key, action: None, continuations: 0 });
+                    hints.len() - 1
+                },
+            };
+
+            match keys.0.len() == typed + 1 {
+                | true => {
+                    hints[hint].action.get_or_insert(&self.bindings[index].1);
+                },
+                | false => hints[hint].continuations += 1,
+            }
+        }
+
+        hints
+    }
+}
+
+// What a key does in a registered keymap, if anything.
+type Lookup = Box<dyn Fn(&KeyMsg) -> Option<Message>>;
+
+// A keymap registered on the builder, for a screen or for all of them. Its actions are told apart by how they're
+// debug formatted, the same action bound twice isn't a conflict.
+struct Registered {
+    screen: Option<TypeId>,
+    bindings: Vec<(KeyBinding, String)>,
+    action: Lookup,
+}
+
+// The keys they bind reach the screen as `Message::Custom` holding the action instead. A screen's own keymaps
+// shadow the global ones, of the ones in the same context the one registered last wins.
+#[derive(Default)]
+pub(crate) struct Keymaps(Vec<Registered>);
+
+impl Keymaps {
+    pub fn register<A>(&mut self, screen: Option<TypeId>, keymap: KeyMap<A>) -> Result<(), KeymapConflictError>
+        where A: fmt::Debug + Clone + Send + 'static,
+    {
+        let bindings = keymap.bindings().iter()
+            .map(|binding| (binding.key, format!("{:?}", binding.action)))
+            .collect::<Vec<_>>();
+
+        let registered = self.0.iter().filter(|registered| registered.screen == screen);
+
+        for (index, (key, action)) in bindings.iter().enumerate() {
+            let mut earlier = registered.clone().flat_map(|registered| &registered.bindings).chain(&bindings[..index]);
+
+            if let Some((_, first)) = earlier.find(|(other, first)| other.matches(&(*key).into()) && first != action) {
+                return Err(KeymapConflictError {
+                    key: *key,
+                    context: if screen.is_some() { KeyContext::Screen } else { KeyContext::Global },
+                    first: first.clone(),
+                    second: action.clone(),
+                });
+            }
+        }
+
+        self.0.push(Registered {
+            screen,
+            bindings,
+            action: Box::new(move |key| keymap.action(key).cloned().map(Message::custom)),
+        });
+
+        Ok(())
+    }
+
+    // Releases stay keys, only presses and repeats do something.
+    pub fn translate(&self, screen: TypeId, message: Message) -> Message {
+        let Message::Key(key) = &message else {
+            return message;
+        };
+
+        if key.kind == KeyKind::Release {
+            return message;
+        }
+
+        let screens = self.0.iter().rev().filter(|registered| registered.screen == Some(screen));
+        let global = self.0.iter().rev().filter(|registered| registered.screen.is_none());
+
+        screens.chain(global).find_map(|registered| (registered.action)(key)).unwrap_or(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::message::{key, key_with};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Action {
+        Save,
+        Quit,
+        Help,
+    }
+
+    static DEFAULTS: &[Binding<Action>] = &[
+        Binding::new(KeyBinding::ctrl('s'), Action::Save),
+        Binding::new(KeyBinding::char('q'), Action::Quit),
+        Binding::new(KeyBinding::key(KeyCode::F(1)), Action::Help),
+        Binding::new(KeyBinding::char('?'), Action::Help),
+    ];
+
+    #[test]
+    fn static_defaults_are_looked_up() {
+        let keymap = KeyMap::from(DEFAULTS);
+
+        assert_eq!(keymap.action(&key_with(KeyCode::Char('s'), KeyModifiers::CONTROL)), Some(&Action::Save));
+        assert_eq!(keymap.action(&key(KeyCode::Char('s'))), None);
+        assert_eq!(keymap.keys(&Action::Help).collect::<Vec<_>>(), [DEFAULTS[2].key, DEFAULTS[3].key]);
+    }
+
+    #[test]
+    fn overrides_replace_the_keys_and_actions_they_bind() {
+        let mut keymap = KeyMap::from(DEFAULTS);
+
+        keymap.merge([
+            Binding::new("ctrl+q".parse().unwrap(), Action::Quit),
+            Binding::new(KeyBinding::char('?'), Action::Save),
+        ]);
+
+        assert_eq!(keymap.action(&key(KeyCode::Char('q'))), None);
+        assert_eq!(keymap.action(&key_with(KeyCode::Char('q'), KeyModifiers::CONTROL)), Some(&Action::Quit));
+        assert_eq!(keymap.action(&key_with(KeyCode::Char('s'), KeyModifiers::CONTROL)), None);
+        assert_eq!(keymap.action(&key(KeyCode::Char('?'))), Some(&Action::Save));
+        assert_eq!(keymap.keys(&Action::Help).collect::<Vec<_>>(), [KeyBinding::key(KeyCode::F(1))]);
+    }
+
+    #[test]
+    fn shifted_characters_match_with_or_without_shift() {
+        let binding = KeyBinding::char('G');
+
+        assert!(binding.matches(&key(KeyCode::Char('G'))));
+        assert!(binding.matches(&key_with(KeyCode::Char('G'), KeyModifiers::SHIFT)));
+        assert!(!binding.matches(&key_with(KeyCode::Char('G'), KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn bindings_read_back_the_way_they_are_written() {
+        for written in ["ctrl+s", "alt+shift+enter", "f5", "G", "space", "shift+tab", "+", "ctrl++", "left"] {
+            let binding = written.parse::<KeyBinding>().unwrap();
+
+            assert_eq!(binding.to_string(), written);
+        }
+
+        assert_eq!("Ctrl+S".parse::<KeyBinding>().unwrap(), KeyBinding::ctrl('S'));
+        assert!("hyper+s".parse::<KeyBinding>().is_err());
+        assert!("return".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn the_most_specific_context_wins() {
+        let global = KeyMap::from(DEFAULTS);
+        let screen = KeyMap::new().bind(KeyBinding::char('q'), Action::Help);
+        let component = KeyMap::new().bind(KeyBinding::char('q'), Action::Save);
+
+        let contexts = KeyContexts::new()
+            .with(KeyContext::Component, &component)
+            .with(KeyContext::Global, &global)
+            .with(KeyContext::Screen, &screen);
+
+        let resolved = contexts.resolve(&key(KeyCode::Char('q'))).unwrap();
+
+        assert_eq!(resolved.context, KeyContext::Component);
+        assert_eq!(resolved.action, &Action::Save);
+        assert_eq!(resolved.shadowed, [(KeyContext::Screen, &Action::Help), (KeyContext::Global, &Action::Quit)]);
+        assert_eq!(contexts.action(&key(KeyCode::F(1))), Some(&Action::Help));
+    }
+
+    #[test]
+    fn keymaps_pushed_later_win_within_a_context() {
+        let first = KeyMap::new().bind(KeyBinding::char('x'), Action::Save);
+        let second = KeyMap::new().bind(KeyBinding::char('x'), Action::Quit);
+
+        let mut contexts = KeyContexts::new().with(KeyContext::Screen, &first).with(KeyContext::Screen, &second);
+
+        assert_eq!(contexts.action(&key(KeyCode::Char('x'))), Some(&Action::Quit));
+
+        contexts.pop();
+
+        assert_eq!(contexts.action(&key(KeyCode::Char('x'))), Some(&Action::Save));
+    }
+
+    #[test]
+    fn effective_bindings_leave_out_what_is_shadowed() {
+        let global = KeyMap::from(DEFAULTS);
+        let screen = KeyMap::new().bind(KeyBinding::char('q'), Action::Help).bind(KeyBinding::char('q'), Action::Save);
+
+        let contexts = KeyContexts::new().with(KeyContext::Global, &global).with(KeyContext::Screen, &screen);
+
+        let effective = contexts.effective()
+            .into_iter()
+            .map(|resolved| (resolved.key.to_string(), resolved.context, *resolved.action))
+            .collect::<Vec<_>>();
+
+        assert_eq!(effective, [
+            (String::from("q"), KeyContext::Screen, Action::Help),
+            (String::from("ctrl+s"), KeyContext::Global, Action::Save),
+            (String::from("f1"), KeyContext::Global, Action::Help),
+            (String::from("?"), KeyContext::Global, Action::Help),
+        ]);
+    }
+
+    #[test]
+    fn conflicts_within_a_context_are_refused() {
+        let mut keymaps = Keymaps::default();
+
+        keymaps.register(None, KeyMap::from(DEFAULTS)).unwrap();
+        keymaps.register(Some(TypeId::of::<u8>()), KeyMap::new().bind(KeyBinding::char('q'), Action::Save))
+            .unwrap();
+        keymaps.register(None, KeyMap::new().bind(KeyBinding::char('?'), Action::Help)).unwrap();
+
+        let error = keymaps.register(None, KeyMap::new().bind(KeyBinding::char('q'), Action::Save)).unwrap_err();
+
+        assert_eq!(error.to_string(), "q is bound to both Quit and Save in the Global context");
+
+        let same = KeyMap::new().bind(KeyBinding::char('x'), Action::Save).bind(KeyBinding::char('x'), Action::Help);
+
+        assert!(keymaps.register(None, same).is_err());
+    }
+
+    #[test]
+    fn sequences_do_their_action_once_typed_in_full() {
+        let mut sequences = KeySequences::new()
+            .bind("g g".parse::<KeySequence>().unwrap(), Action::Help)
+            .bind("g s".parse::<KeySequence>().unwrap(), Action::Save)
+            .bind(KeyBinding::char('q'), Action::Quit);
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char('g'))), Step::Pending);
+        assert_eq!(sequences.pending(), [KeyBinding::char('g')]);
+        assert_eq!(sequences.feed(&key(KeyCode::Char('s'))), Step::Action(&Action::Save));
+        assert!(sequences.pending().is_empty());
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char('q'))), Step::Action(&Action::Quit));
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char('g'))), Step::Pending);
+        assert_eq!(sequences.feed(&key(KeyCode::Char('x'))), Step::Unbound);
+        assert!(sequences.pending().is_empty());
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char('g'))), Step::Pending);
+        assert_eq!(sequences.feed(&key(KeyCode::Esc)), Step::Cancelled);
+        assert_eq!(sequences.feed(&key(KeyCode::Esc)), Step::Unbound);
+    }
+
+    #[test]
+    fn hints_list_what_can_follow_the_pending_keys() {
+        let mut sequences = KeySequences::new()
+            .bind("space f".parse::<KeySequence>().unwrap(), Action::Help)
+            .bind("space f s".parse::<KeySequence>().unwrap(), Action::Save)
+            .bind("space f q".parse::<KeySequence>().unwrap(), Action::Quit)
+            .bind("space s".parse::<KeySequence>().unwrap(), Action::Save);
+
+        assert!(sequences.hints().is_empty());
+        assert_eq!(sequences.feed(&key(KeyCode::Char(' '))), Step::Pending);
+
+        assert_eq!(sequences.hints(), [
+            Hint { key: KeyBinding::char('f'), action: Some(&Action::Help), continuations: 0 },
+            Hint { key: KeyBinding::char('s'), action: Some(&Action::Save), continuations: 0 },
+        ]);
+
+        assert_eq!("space f  s".parse::<KeySequence>().unwrap().to_string(), "space f s");
+        assert!("".parse::<KeySequence>().is_err());
+    }
+
+    #[test]
+    fn sequences_starting_with_a_bound_one_are_not_hinted_at() {
+        let mut sequences = KeySequences::new()
+            .bind("space f".parse::<KeySequence>().unwrap(), Action::Help)
+            .bind("space f s".parse::<KeySequence>().unwrap(), Action::Save)
+            .bind("space q x".parse::<KeySequence>().unwrap(), Action::Quit)
+            .bind("space q x y".parse::<KeySequence>().unwrap(), Action::Save);
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char(' '))), Step::Pending);
+
+        assert_eq!(sequences.hints(), [
+            Hint { key: KeyBinding::char('f'), action: Some(&Action::Help), continuations: 0 },
+            Hint { key: KeyBinding::char('q'), action: None, continuations: 1 },
+        ]);
+
+        assert_eq!(sequences.feed(&key(KeyCode::Char('f'))), Step::Action(&Action::Help));
+        assert_eq!(sequences.feed(&key(KeyCode::Char('s'))), Step::Unbound);
+    }
+}
@@ -0,0 +1,382 @@
+use std::ops::Index;
+
+use cassowary::strength::{REQUIRED, STRONG, WEAK};
+use cassowary::WeightedRelation::{EQ, GE, LE};
+use cassowary::{Expression, Solver, Variable};
+
+use ratatui::layout::{Constraint, Direction, Rect};
+
+use thiserror::Error;
+
+// Regions fill what they're split from unless their sizes make that impossible, above any preferred size.
+const EDGES: f64 = STRONG * 10.0;
+
+// Exact lengths are kept over percentages and ratios of the parent.
+const LENGTH: f64 = STRONG * 2.0;
+
+#[derive(Debug, Error)]
+#[error("the layout's minimum and maximum sizes can't all be met")]
+pub struct LayoutError;
+
+// A part of a `ConstraintLayout`, to constrain and to look up once solved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Region(usize);
+
+impl Region {
+    // The whole area the layout is solved for.
+    pub const AREA: Self = Self(0);
+}
+
+#[derive(Debug, Clone)]
+enum Rule {
+    Split { parent: Region, regions: Vec<Region>, direction: Direction },
+    Centered { parent: Region, region: Region },
+    Size { region: Region, direction: Direction, constraint: Constraint },
+    Same { first: Region, second: Region, direction: Direction },
+}
+
+// Layouts with constraints across regions, which ratatui's `Layout` can't express, solved like it is with
+// cassowary. E.g. two sidebars of the same width on either side of the content, and a dialog centered in it all
+// that's 60% of the width but at least 40 columns wide:
+//
+//     let mut layout = ConstraintLayout::new();
+//     let [left, content, right, dialog] = layout.regions();
+//
+//     layout.row(Region::AREA, [left, content, right])
+//         .same_width(left, right)
+//         .width(left, Constraint::Percentage(20))
+//         .centered(Region::AREA, dialog)
+//         .width(dialog, Constraint::Percentage(60))
+//         .width(dialog, Constraint::Min(40))
+//         .height(dialog, Constraint::Length(10));
+//
+//     let solved = layout.solve(area)?;
+//
+// Minimum and maximum sizes always hold. Lengths, percentages, ratios, equal sizes and centering are preferences
+// that give way to them, and to regions filling what they're split from, lengths win over the rest. Percentages
+// and ratios are of the region a region was split from or centered in, `Fill` grows a region as much as the rest
+// lets it.
+#[derive(Debug, Clone)]
+pub struct ConstraintLayout {
+    // Including the area.
+    regions: usize,
+    parents: Vec<Region>,
+    rules: Vec<Rule>,
+    spacing: u16,
+}
+
+impl Default for ConstraintLayout {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The variables a region is solved for.
+#[derive(Debug, Clone, Copy)]
+struct Edges {
+    x: Variable,
+    y: Variable,
+    width: Variable,
+    height: Variable,
+}
+
+impl Edges {
+    #[inline(always)]
+    fn position(&self, direction: &Direction) -> Variable {
+        match direction {
+            | Direction::Horizontal => self.x,
+            | Direction::Vertical => self.y,
+        }
+    }
+
+    #[inline(always)]
+    fn size(&self, direction: &Direction) -> Variable {
+        match direction {
+            | Direction::Horizontal => self.width,
+            | Direction::Vertical => self.height,
+        }
+    }
+}
+
+// Solved, indexed by the regions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solved(Vec<Rect>);
+
+impl Index<Region> for Solved {
+    type Output = Rect;
+
+    #[inline(always)]
+    fn index(&self, region: Region) -> &Rect {
+        &self.0[region.0]
+    }
+}
+
+impl ConstraintLayout {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self { regions: 1, parents: vec![Region::AREA], rules: Vec::new(), spacing: 0 }
+    }
+
+    // Between the regions of every row and column.
+    pub fn spacing(mut self, spacing: u16) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    // A new region, which covers the whole area until it's placed in a row, a column or centered.
+    pub fn region(&mut self) -> Region {
+        self.regions += 1;
+        self.parents.push(Region::AREA);
+
+        Region(self.regions - 1)
+    }
+
+    pub fn regions<const N: usize>(&mut self) -> [Region; N] {
+        [(); N].map(|_| self.region())
+    }
+
+    fn split<I>(&mut self, parent: Region, regions: I, direction: Direction) -> &mut Self
+        where I: IntoIterator<Item = Region>,
+    {
+        let regions = regions.into_iter().collect::<Vec<_>>();
+
+        for region in &regions {
+            self.parents[region.0] = parent;
+        }
+
+        self.rules.push(Rule::Split { parent, regions, direction });
+        self
+    }
+
+    // Side by side from left to right, filling the parent. As wide as each other unless constrained.
+    #[inline(always)]
+    pub fn row<I>(&mut self, parent: Region, regions: I) -> &mut Self
+        where I: IntoIterator<Item = Region>,
+    {
+        self.split(parent, regions, Direction::Horizontal)
+    }
+
+    // From top to bottom, filling the parent. As tall as each other unless constrained.
+    #[inline(always)]
+    pub fn column<I>(&mut self, parent: Region, regions: I) -> &mut Self
+        where I: IntoIterator<Item = Region>,
+    {
+        self.split(parent, regions, Direction::Vertical)
+    }
+
+    pub fn centered(&mut self, parent: Region, region: Region) -> &mut Self {
+        self.parents[region.0] = parent;
+        self.rules.push(Rule::Centered { parent, region });
+        self
+    }
+
+    pub fn width(&mut self, region: Region, constraint: Constraint) -> &mut Self {
+        self.rules.push(Rule::Size { region, direction: Direction::Horizontal, constraint });
+        self
+    }
+
+    pub fn height(&mut self, region: Region, constraint: Constraint) -> &mut Self {
+        self.rules.push(Rule::Size { region, direction: Direction::Vertical, constraint });
+        self
+    }
+
+    pub fn same_width(&mut self, first: Region, second: Region) -> &mut Self {
+        self.rules.push(Rule::Same { first, second, direction: Direction::Horizontal });
+        self
+    }
+
+    pub fn same_height(&mut self, first: Region, second: Region) -> &mut Self {
+        self.rules.push(Rule::Same { first, second, direction: Direction::Vertical });
+        self
+    }
+
+    fn constrain(&self, solver: &mut Solver, edges: &[Edges], rule: &Rule) -> Result<(), LayoutError> {
+        let error = |_| LayoutError;
+
+        match rule {
+            | Rule::Split { parent, regions, direction } => {
+                let parent = edges[parent.0];
+                let cross = match direction {
+                    | Direction::Horizontal => Direction::Vertical,
+                    | Direction::Vertical => Direction::Horizontal,
+                };
+
+                let mut start = Expression::from(parent.position(direction));
+
+                for (index, region) in regions.iter().enumerate() {
+                    let region = edges[region.0];
+                    let spacing = if index == 0 { 0.0 } else { f64::from(self.spacing) };
+
+                    solver.add_constraint(region.position(direction) | EQ(REQUIRED) | (start.clone() + spacing))
+                        .map_err(error)?;
+                    solver.add_constraint(region.position(&cross) | EQ(REQUIRED) | parent.position(&cross))
+                        .map_err(error)?;
+                    solver.add_constraint(region.size(&cross) | EQ(REQUIRED) | parent.size(&cross))
+                        .map_err(error)?;
+
+                    start = region.position(direction) + region.size(direction);
+                }
+
+                let end = parent.position(direction) + parent.size(direction);
+
+                solver.add_constraint(start | EQ(EDGES) | end).map_err(error)?;
+
+                // Evenly split unless something says otherwise.
+                for pair in regions.windows(2) {
+                    let (first, second) = (edges[pair[0].0], edges[pair[1].0]);
+
+                    solver.add_constraint(first.size(direction) | EQ(WEAK) | second.size(direction)).map_err(error)?;
+                }
+            },
+            | Rule::Centered { parent, region } => {
+                let (parent, region) = (edges[parent.0], edges[region.0]);
+
+                for direction in [Direction::Horizontal, Direction::Vertical] {
+                    let (start, size) = (region.position(&direction), region.size(&direction));
+                    let (parent_start, parent_size) = (parent.position(&direction), parent.size(&direction));
+
+                    // Twice the start plus the size is twice the center.
+                    let (center, parent_center) = (start * 2.0 + size, parent_start * 2.0 + parent_size);
+
+                    solver.add_constraint(center | EQ(STRONG) | parent_center).map_err(error)?;
+                    solver.add_constraint(start | GE(REQUIRED) | parent_start).map_err(error)?;
+                    solver.add_constraint(size | LE(EDGES) | parent_size).map_err(error)?;
+                }
+            },
+            | Rule::Size { region, direction, constraint } => {
+                let size = edges[region.0].size(direction);
+                let parent = edges[self.parents[region.0].0].size(direction);
+
+                let constraint = match *constraint {
+                    | Constraint::Min(min) => size | GE(REQUIRED) | f64::from(min),
+                    | Constraint::Max(max) => size | LE(REQUIRED) | f64::from(max),
+                    | Constraint::Length(length) => size | EQ(LENGTH) | f64::from(length),
+                    | Constraint::Percentage(percent) => size | EQ(STRONG) | (parent * (f64::from(percent) / 100.0)),
+                    | Constraint::Ratio(_, 0) => return Ok(()),
+                    | Constraint::Ratio(n, d) => size | EQ(STRONG) | (parent * (f64::from(n) / f64::from(d))),
+                    | Constraint::Fill(_) => size | EQ(WEAK) | parent,
+                };
+
+                solver.add_constraint(constraint).map_err(error)?;
+            },
+            | Rule::Same { first, second, direction } => {
+                let (first, second) = (edges[first.0].size(direction), edges[second.0].size(direction));
+
+                solver.add_constraint(first | EQ(STRONG) | second).map_err(error)?;
+            },
+        }
+
+        Ok(())
+    }
+
+    // Fails when minimum and maximum sizes contradict each other, regions that don't fit the area are cut off.
+    pub fn solve(&self, area: Rect) -> Result<Solved, LayoutError> {
+        let mut solver = Solver::new();
+
+        let edges = (0..self.regions).map(|_| Edges {
+            x: Variable::new(),
+            y: Variable::new(),
+            width: Variable::new(),
+            height: Variable::new(),
+        }).collect::<Vec<_>>();
+
+        let values = |edges: &Edges| {
+            [(edges.x, area.x), (edges.y, area.y), (edges.width, area.width), (edges.height, area.height)]
+        };
+
+        for (variable, value) in values(&edges[Region::AREA.0]) {
+            solver.add_constraint(variable | EQ(REQUIRED) | f64::from(value)).map_err(|_| LayoutError)?;
+        }
+
+        for region in &edges[1..] {
+            for variable in [region.width, region.height] {
+                solver.add_constraint(variable | GE(REQUIRED) | 0.0).map_err(|_| LayoutError)?;
+            }
+
+            // Regions that aren't placed anywhere cover the whole area.
+            for (variable, value) in values(region) {
+                solver.add_constraint(variable | EQ(WEAK) | f64::from(value)).map_err(|_| LayoutError)?;
+            }
+        }
+
+        for rule in &self.rules {
+            self.constrain(&mut solver, &edges, rule)?;
+        }
+
+        // Rounded by their edges, so neighbours neither overlap nor leave gaps.
+        let round = |value: f64| value.round().clamp(0.0, f64::from(u16::MAX)) as u16;
+
+        Ok(Solved(edges.iter().map(|edges| {
+            let (x, y) = (solver.get_value(edges.x), solver.get_value(edges.y));
+            let (left, top) = (round(x), round(y));
+            let (right, bottom) = (round(x + solver.get_value(edges.width)), round(y + solver.get_value(edges.height)));
+
+            Rect::new(left, top, right.saturating_sub(left), bottom.saturating_sub(top)).intersection(area)
+        }).collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sidebars_of_the_same_width_surround_the_content() {
+        let mut layout = ConstraintLayout::new().spacing(1);
+        let [left, content, right] = layout.regions();
+
+        layout.row(Region::AREA, [left, content, right]).same_width(left, right).width(left, Constraint::Length(12));
+
+        let solved = layout.solve(Rect::new(0, 0, 80, 24)).unwrap();
+
+        assert_eq!(solved[left], Rect::new(0, 0, 12, 24));
+        assert_eq!(solved[content], Rect::new(13, 0, 54, 24));
+        assert_eq!(solved[right], Rect::new(68, 0, 12, 24));
+    }
+
+    #[test]
+    fn minimum_sizes_win_over_percentages() {
+        let mut layout = ConstraintLayout::new();
+        let dialog = layout.region();
+
+        layout
+            .centered(Region::AREA, dialog)
+            .width(dialog, Constraint::Percentage(60))
+            .width(dialog, Constraint::Min(40))
+            .height(dialog, Constraint::Length(10));
+
+        assert_eq!(layout.solve(Rect::new(0, 0, 100, 30)).unwrap()[dialog], Rect::new(20, 10, 60, 10));
+        assert_eq!(layout.solve(Rect::new(0, 0, 50, 30)).unwrap()[dialog], Rect::new(5, 10, 40, 10));
+        assert_eq!(layout.solve(Rect::new(0, 0, 30, 5)).unwrap()[dialog], Rect::new(0, 0, 30, 5));
+    }
+
+    #[test]
+    fn regions_split_from_regions_are_relative_to_them() {
+        let mut layout = ConstraintLayout::new();
+        let [sidebar, main, header, body] = layout.regions();
+
+        layout
+            .row(Region::AREA, [sidebar, main])
+            .width(sidebar, Constraint::Percentage(25))
+            .column(main, [header, body])
+            .height(header, Constraint::Length(3))
+            .height(body, Constraint::Percentage(50));
+
+        let solved = layout.solve(Rect::new(0, 0, 40, 20)).unwrap();
+
+        assert_eq!(solved[sidebar], Rect::new(0, 0, 10, 20));
+        assert_eq!(solved[main], Rect::new(10, 0, 30, 20));
+        assert_eq!(solved[header], Rect::new(10, 0, 30, 3));
+        assert_eq!(solved[body], Rect::new(10, 3, 30, 17));
+    }
+
+    #[test]
+    fn contradicting_bounds_are_an_error() {
+        let mut layout = ConstraintLayout::new();
+        let region = layout.region();
+
+        layout.width(region, Constraint::Min(20)).width(region, Constraint::Max(10));
+
+        assert!(layout.solve(Rect::new(0, 0, 80, 24)).is_err());
+    }
+}
@@ -1,8 +1,41 @@
 pub mod screen;
 pub mod events;
+pub mod icons;
 pub mod command;
+pub mod components;
+pub mod component;
+pub mod damage;
+pub mod compositor;
 pub mod message;
+pub mod terminal;
 pub mod application;
+pub mod vt;
+pub mod testing;
+pub mod ready;
+pub mod onboarding;
+pub mod tooltip;
+pub mod format;
+pub mod scheduler;
+pub mod subscription;
+pub mod motion;
+pub mod input;
+pub mod bell;
+pub mod canvas;
+pub mod sprite;
+pub mod clock;
+pub mod keymap;
+pub mod layout;
+pub mod focus;
+#[cfg(feature = "update-check")]
+pub mod update;
+
+mod pane;
+mod crash;
+mod action_log;
+mod autosave;
+mod toast;
+mod panic_hook;
+mod workers;
 
 pub use ratatui;
 
@@ -13,7 +46,7 @@ pub mod prelude {
     pub use ratatui::backend::CrosstermBackend;
 
     pub use crate::application::Builder as Application;
-    pub use crate::message::{Message, KeyMsg, MouseMsg, KeyState};
+    pub use crate::message::{Message, KeyMsg, MouseMsg, KeyState, ProgressMsg};
     pub use crate::command::{self, Command};
-    pub use crate::screen::Screen;
+    pub use crate::screen::{Screen, ScreenError};
 }
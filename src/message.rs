@@ -1,15 +1,58 @@
-use crossterm::event::{Event, KeyCode, KeyEventState, KeyModifiers, MouseEvent};
+use std::io;
+use std::any::{Any, TypeId};
+use std::process::ExitStatus;
+use std::time::Duration;
+use std::sync::Arc;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyEventState, KeyModifiers, MouseEvent};
 
 pub type KeyState = KeyEventState;
+pub type KeyKind = KeyEventKind;
 
+#[derive(Debug, Clone)]
 pub struct KeyMsg {
     pub code: KeyCode,
     pub modifiers: KeyModifiers,
     pub state: KeyState,
+    // Always a press unless `Builder::key_event_filter` lets repeats or releases through.
+    pub kind: KeyKind,
 }
 
 pub type MouseMsg = MouseEvent;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TickMsg {
+    // Since the previous tick, which can be more or less than the tick rate. Zero for the first one.
+    pub elapsed: Duration,
+    // Counts up from zero, once per tick.
+    pub frame: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressMsg {
+    pub id: u64,
+    pub parent: Option<u64>,
+    pub label: String,
+    // `None` for operations of unknown length.
+    pub fraction: Option<f64>,
+    pub bytes_per_second: Option<f64>,
+}
+
+// Where a result of work in the background came from and who it's for, see `Command::correlated` and
+// `Command::route_to`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Routing {
+    // The screen that returned the command, `None` if it wasn't returned by a screen.
+    pub origin: Option<TypeId>,
+    pub target: Option<TypeId>,
+    // Picked by whoever returned the command, e.g. to tell which of its components asked.
+    pub correlation: Option<u64>,
+}
+
+// Shared between all subscribers of a topic, downcast to get the published value back.
+pub type Payload = Arc<dyn Any + Send + Sync>;
+
+#[derive(Debug)]
 pub enum Message {
     Key(KeyMsg),
     Mouse(MouseMsg),
@@ -18,8 +61,62 @@ pub enum Message {
     FocusLost,
     #[cfg(feature = "paste")]
     Paste(String),
+    // Keys that arrived too fast to be typed, see `events::PasteBursts::Confirm`.
+    PasteBurst(String),
+    Published(String, Payload),
+    Custom(Box<dyn Any + Send>),
+    // A result of work started by a correlated or routed command.
+    Routed(Routing, Box<Message>),
+    // Sent at startup to screens that left unsaved state behind, e.g. to offer restoring it.
+    AutosaveAvailable(Vec<u8>),
+    #[cfg(feature = "update-check")]
+    UpdateAvailable(String),
     Shutdown,
-    Tick,
+    // The exit status of `command::exec`, or why it couldn't be started.
+    ExecFinished(io::Result<ExitStatus>),
+    // Sent to every screen after continuing from `Command::Suspend`.
+    Resumed,
+    Tick(TickMsg),
+}
+
+impl Message {
+    #[inline(always)]
+    pub fn custom<T: Any + Send>(value: T) -> Self {
+        Self::Custom(Box::new(value))
+    }
+
+    // `None` for anything but a custom message holding a `T`, routed or not.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        match self {
+            | Self::Custom(value) => value.downcast_ref(),
+            | Self::Routed(_, message) => message.downcast_ref(),
+            | _ => None,
+        }
+    }
+
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        match self {
+            | Self::Custom(value) => value.downcast().map(|value| *value).map_err(Self::Custom),
+            | Self::Routed(routing, message) => message.downcast().map_err(|message| Self::Routed(routing, Box::new(message))),
+            | message => Err(message),
+        }
+    }
+
+    #[inline(always)]
+    pub fn routing(&self) -> Option<&Routing> {
+        match self {
+            | Self::Routed(routing, _) => Some(routing),
+            | _ => None,
+        }
+    }
+
+    // The message without its routing, if it has any.
+    pub fn unrouted(self) -> Self {
+        match self {
+            | Self::Routed(_, message) => message.unrouted(),
+            | message => message,
+        }
+    }
 }
 
 impl From<Event> for Message {
@@ -31,6 +128,7 @@ impl From<Event> for Message {
                 code: key.code,
                 modifiers: key.modifiers,
                 state: key.state,
+                kind: key.kind,
             }),
             Event::Mouse(mouse) => Message::Mouse(mouse),
             #[cfg(feature = "paste")]
@@ -38,4 +136,37 @@ impl From<Event> for Message {
             Event::Resize(x, y) => Message::Resize(x, y),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+pub(crate) fn key(code: KeyCode) -> KeyMsg {
+    key_with(code, KeyModifiers::NONE)
+}
+
+#[cfg(test)]
+pub(crate) fn key_with(code: KeyCode, modifiers: KeyModifiers) -> KeyMsg {
+    KeyMsg { code, modifiers, state: KeyState::NONE, kind: KeyKind::Press }
+}
+
+#[cfg(test)]
+pub(crate) fn mouse(kind: crossterm::event::MouseEventKind, column: u16, row: u16) -> MouseMsg {
+    MouseMsg { kind, column, row, modifiers: KeyModifiers::NONE }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routed_messages_downcast_like_the_message_they_carry() {
+        let routing = Routing { origin: None, target: None, correlation: Some(3) };
+        let message = Message::Routed(routing, Box::new(Message::custom(5u8)));
+
+        assert_eq!(message.downcast_ref::<u8>(), Some(&5));
+        assert_eq!(message.routing(), Some(&routing));
+
+        let message = message.downcast::<String>().unwrap_err();
+
+        assert_eq!(message.routing(), Some(&routing));
+        assert!(matches!(message.unrouted(), Message::Custom(_)));
+    }
+}
@@ -0,0 +1,37 @@
+use std::env;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+const UNSET: u8 = 0;
+const REDUCED: u8 = 1;
+const FULL: u8 = 2;
+
+static OVERRIDE: AtomicU8 = AtomicU8::new(UNSET);
+
+// Built-in components skip transitions and slow down or freeze animations when this is set.
+pub fn reduced() -> bool {
+    static DETECTED: OnceLock<bool> = OnceLock::new();
+
+    match OVERRIDE.load(Ordering::Relaxed) {
+        | REDUCED => true,
+        | FULL => false,
+        | _ => *DETECTED.get_or_init(|| match env::var("REDUCE_MOTION") {
+            | Ok(value) => !matches!(value.to_ascii_lowercase().as_str(), "" | "0" | "false" | "no"),
+            | Err(_) => false,
+        }),
+    }
+}
+
+// Takes precedence over `REDUCE_MOTION`.
+#[inline(always)]
+pub fn set_reduced(reduced: bool) {
+    OVERRIDE.store(if reduced { REDUCED } else { FULL }, Ordering::Relaxed);
+}
+
+// For tests that change the setting, or depend on it, while others run alongside.
+#[cfg(test)]
+pub(crate) fn lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
@@ -0,0 +1,149 @@
+use std::{fs, io};
+use std::any::TypeId;
+use std::path::PathBuf;
+
+use crate::screen::Screen;
+
+// The state file only has to exist, a first run is one where it doesn't.
+pub struct Onboarding {
+    state_file: PathBuf,
+    steps: Vec<TypeId>,
+    position: Option<usize>,
+}
+
+impl Onboarding {
+    pub fn new<P: Into<PathBuf>>(state_file: P) -> Self {
+        Self {
+            state_file: state_file.into(),
+            steps: Vec::new(),
+            position: None,
+        }
+    }
+
+    // Steps are shown in the order they were added, the screens have to be registered on the builder.
+    pub fn step<S, Ctx>(mut self) -> Self
+        where S: Screen<Ctx> + 'static,
+    {
+        self.steps.push(TypeId::of::<S>());
+        self
+    }
+
+    #[inline(always)]
+    pub fn is_first_run(&self) -> bool {
+        !self.state_file.exists()
+    }
+
+    #[inline(always)]
+    pub fn in_progress(&self) -> bool {
+        self.position.is_some()
+    }
+
+    pub(crate) fn start(&mut self) -> Option<TypeId> {
+        let first = self.steps.first().copied();
+
+        self.position = first.map(|_| 0);
+
+        first
+    }
+
+    // Moves on to the next step, or returns `None` once the last one has been passed.
+    pub(crate) fn advance(&mut self) -> Option<TypeId> {
+        let next = self.position.map_or(0, |position| position + 1);
+
+        match self.steps.get(next) {
+            | Some(&step) => {
+                self.position = Some(next);
+                Some(step)
+            },
+            | None => {
+                self.position = None;
+                None
+            },
+        }
+    }
+
+    pub(crate) fn complete(&mut self) -> io::Result<()> {
+        self.position = None;
+
+        if let Some(parent) = self.state_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.state_file, "")
+    }
+
+    pub(crate) fn reset(&mut self) -> io::Result<()> {
+        self.position = None;
+
+        match fs::remove_file(&self.state_file) {
+            | Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            | _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+
+    use ratatui::Frame;
+
+    use crate::command::Command;
+    use crate::message::Message;
+    use crate::screen::ScreenError;
+
+    struct Welcome;
+
+    struct Tour;
+
+    impl Screen for Welcome {
+        fn render(&self, _: &mut Frame<'_>, _: &()) {}
+
+        fn update(&mut self, _: Message, _: &mut ()) -> Result<Option<Command>, ScreenError> {
+            Ok(None)
+        }
+    }
+
+    impl Screen for Tour {
+        fn render(&self, _: &mut Frame<'_>, _: &()) {}
+
+        fn update(&mut self, _: Message, _: &mut ()) -> Result<Option<Command>, ScreenError> {
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn steps_are_walked_through_in_order() {
+        let mut onboarding = Onboarding::new("unused").step::<Welcome, ()>().step::<Tour, ()>();
+
+        assert!(!onboarding.in_progress());
+        assert_eq!(onboarding.start(), Some(TypeId::of::<Welcome>()));
+        assert_eq!(onboarding.advance(), Some(TypeId::of::<Tour>()));
+        assert!(onboarding.in_progress());
+        assert_eq!(onboarding.advance(), None);
+        assert!(!onboarding.in_progress());
+    }
+
+    #[test]
+    fn completing_it_is_remembered_until_reset() {
+        let directory = env::temp_dir().join(format!("ratata-onboarding-{}", std::process::id()));
+        let mut onboarding = Onboarding::new(directory.join("state").join("onboarded")).step::<Welcome, ()>();
+
+        assert!(onboarding.is_first_run());
+
+        onboarding.start();
+        onboarding.complete().unwrap();
+
+        assert!(!onboarding.in_progress());
+        assert!(!onboarding.is_first_run());
+
+        onboarding.reset().unwrap();
+        onboarding.reset().unwrap();
+
+        assert!(onboarding.is_first_run());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
@@ -0,0 +1,108 @@
+use ratatui::Terminal;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::backend::TestBackend;
+
+use crate::screen::Screen;
+
+pub(crate) struct PinnedPane<Ctx> {
+    pub(crate) screen: Box<dyn Screen<Ctx>>,
+    placement: fn(Rect) -> Rect,
+    // Screens render into a whole frame, so the pane gets a terminal of its own that's sized to fit.
+    offscreen: Terminal<TestBackend>,
+}
+
+impl<Ctx> PinnedPane<Ctx> {
+    pub(crate) fn new(screen: Box<dyn Screen<Ctx>>, placement: fn(Rect) -> Rect) -> Self {
+        Self {
+            screen,
+            placement,
+            offscreen: Terminal::new(TestBackend::new(0, 0)).unwrap(),
+        }
+    }
+
+    pub(crate) fn render(&mut self, buf: &mut Buffer, ctx: &Ctx) {
+        let area = (self.placement)(buf.area).intersection(buf.area);
+
+        if area.is_empty() {
+            return;
+        }
+
+        // The terminal picks up the new backend size on its next draw.
+        let current = self.offscreen.backend().buffer().area;
+
+        if (current.width, current.height) != (area.width, area.height) {
+            self.offscreen.backend_mut().resize(area.width, area.height);
+        }
+
+        let screen = &self.screen;
+
+        let _ = self.offscreen.draw(|f| screen.render(f, ctx));
+
+        let rendered = self.offscreen.backend().buffer();
+
+        for y in 0..area.height {
+            for x in 0..area.width {
+                *buf.get_mut(area.x + x, area.y + y) = rendered.get(x, y).clone();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ratatui::Frame;
+    use ratatui::widgets::{Block, Borders};
+
+    use crate::command::Command;
+    use crate::message::Message;
+    use crate::screen::ScreenError;
+
+    struct Boxed;
+
+    impl Screen<()> for Boxed {
+        fn render(&self, f: &mut Frame<'_>, _: &()) {
+            f.render_widget(Block::default().borders(Borders::ALL), f.size());
+        }
+
+        fn update(&mut self, _: Message, _: &mut ()) -> Result<Option<Command>, ScreenError> {
+            Ok(None)
+        }
+    }
+
+    fn top_right(area: Rect) -> Rect {
+        Rect::new(area.right().saturating_sub(4), area.y, 4, 3)
+    }
+
+    fn lines(buf: &Buffer) -> Vec<String> {
+        (0..buf.area.height)
+            .map(|y| (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn the_screen_fills_only_the_pane() {
+        let mut pane = PinnedPane::new(Box::new(Boxed), top_right);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+
+        pane.render(&mut buf, &());
+
+        assert_eq!(lines(&buf), ["    ┌──┐", "    │  │", "    └──┘", "        "]);
+    }
+
+    #[test]
+    fn the_screen_is_laid_out_again_when_the_pane_is_clipped() {
+        let mut pane = PinnedPane::new(Box::new(Boxed), top_right);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 4));
+
+        pane.render(&mut buf, &());
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 2));
+
+        pane.render(&mut buf, &());
+
+        assert_eq!(lines(&buf), ["  ┌──┐", "  └──┘"]);
+    }
+}
@@ -0,0 +1,73 @@
+use std::panic::{self, PanicHookInfo};
+use std::sync::{Arc, Mutex, PoisonError};
+
+type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+// Runs in front of the hook that was installed before it, until it's uninstalled again.
+pub(crate) struct PanicHook(Arc<Mutex<Option<Hook>>>);
+
+impl PanicHook {
+    pub(crate) fn install<F>(hook: F) -> Self
+        where F: Fn(&PanicHookInfo<'_>) + Send + Sync + 'static,
+    {
+        let previous = Arc::new(Mutex::new(Some(panic::take_hook())));
+        let chained = Arc::clone(&previous);
+
+        panic::set_hook(Box::new(move |info| {
+            hook(info);
+
+            if let Some(previous) = &*chained.lock().unwrap_or_else(PoisonError::into_inner) {
+                previous(info);
+            }
+        }));
+
+        Self(previous)
+    }
+
+    // Puts the previous hook back, hooks installed on top of this one since are dropped along with it. So
+    // hooks are uninstalled in the reverse order they were installed in.
+    pub(crate) fn uninstall(self) {
+        if let Some(previous) = self.0.lock().unwrap_or_else(PoisonError::into_inner).take() {
+            panic::set_hook(previous);
+        }
+    }
+}
+
+// For tests that install hooks while others run alongside.
+#[cfg(test)]
+pub(crate) fn lock() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    LOCK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn runs_until_uninstalled() {
+        let _lock = lock();
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let (counted, test) = (Arc::clone(&calls), thread::current().id());
+
+        // Only counts its own panics, other tests may panic alongside.
+        let hook = PanicHook::install(move |_| {
+            if thread::current().id() == test {
+                counted.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+
+        let _ = panic::catch_unwind(|| panic!("first"));
+
+        hook.uninstall();
+
+        let _ = panic::catch_unwind(|| panic!("second"));
+
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+}
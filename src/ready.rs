@@ -0,0 +1,112 @@
+use std::{env, io};
+use std::io::Write;
+
+// Lets PTY driven end to end tests (expectrl, rexpect, ...) wait until the first frame is on screen
+// instead of sleeping and hoping, the signal is sent once per run and only if asked for.
+
+// The value is echoed back inside an OSC sequence right after the first frame, terminals ignore it.
+pub const MARKER_VAR: &str = "RATATA_READY";
+
+// "ready\n" is written to the file descriptor, e.g. a pipe set up by the test harness (unix only).
+pub const FD_VAR: &str = "RATATA_READY_FD";
+
+// What to expect on the PTY output for a given `RATATA_READY` value. Control characters are left out of the
+// marker, they could end the sequence early and have the rest interpreted by the terminal.
+pub fn sequence(marker: &str) -> String {
+    let marker = marker.chars().filter(|c| !c.is_control()).collect::<String>();
+
+    format!("\x1B]9999;ratata-ready={marker}\x07")
+}
+
+// Best effort, a harness that went away mustn't take the application down with it. Whatever failed is
+// passed to `failed`.
+pub(crate) fn signal<F: FnMut(io::Error)>(sink: &mut dyn Write, failed: F) {
+    let fd = env::var(FD_VAR).ok().and_then(|fd| fd.parse::<u32>().ok());
+
+    send(env::var(MARKER_VAR).ok().as_deref(), fd, sink, failed);
+}
+
+fn send<F: FnMut(io::Error)>(marker: Option<&str>, fd: Option<u32>, sink: &mut dyn Write, mut failed: F) {
+    if let Some(marker) = marker {
+        if let Err(error) = sink.write_all(sequence(marker).as_bytes()).and_then(|_| sink.flush()) {
+            failed(error);
+        }
+    }
+
+    #[cfg(unix)]
+    if let Some(fd) = fd {
+        // Opening the descriptor through /dev/fd duplicates it, the original stays open.
+        let result = std::fs::OpenOptions::new().write(true).open(format!("/dev/fd/{fd}"))
+            .and_then(|mut file| file.write_all(b"ready\n"));
+
+        if let Err(error) = result {
+            failed(error);
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = fd;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn control_characters_are_left_out_of_the_marker() {
+        assert_eq!(sequence("a\x07b\x1B]c\n"), "\x1B]9999;ratata-ready=ab]c\x07");
+    }
+
+    #[test]
+    fn writes_the_marker_to_the_sink() {
+        let mut sink = Vec::new();
+
+        send(Some("42"), None, &mut sink, |error| panic!("{error}"));
+
+        assert_eq!(sink, sequence("42").as_bytes());
+    }
+
+    #[test]
+    fn nothing_is_sent_unless_asked_for() {
+        let mut sink = Vec::new();
+
+        send(None, None, &mut sink, |error| panic!("{error}"));
+
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn failures_are_reported_and_dont_stop_the_rest() {
+        let mut failures = Vec::new();
+
+        send(Some("42"), Some(u32::MAX), &mut Broken, |error| failures.push(error.kind()));
+
+        assert_eq!(failures.first(), Some(&io::ErrorKind::BrokenPipe));
+        assert_eq!(failures.len(), if cfg!(unix) { 2 } else { 1 });
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_ready_to_the_descriptor() {
+        let path = env::temp_dir().join(format!("ratata-ready-{}", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+
+        send(None, Some(std::os::fd::AsRawFd::as_raw_fd(&file) as u32), &mut Vec::new(), |error| panic!("{error}"));
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "ready\n");
+
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,317 @@
+use std::{fmt, time};
+use std::str::FromStr;
+use std::ops::RangeInclusive;
+
+use thiserror::Error;
+
+use crate::command::Command;
+
+#[derive(Debug, Error)]
+#[error("invalid cron expression {0:?}")]
+pub struct CronError(String);
+
+// Standard five field expressions (minute, hour, day of month, month, day of week), evaluated in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    // Like cron, a day matches either field when both of them are restricted.
+    any_day: bool,
+}
+
+fn parse_field(field: &str, range: RangeInclusive<u32>) -> Option<(u64, bool)> {
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (values, step) = match part.split_once('/') {
+            | Some((values, step)) => (values, step.parse::<u32>().ok().filter(|s| *s > 0)?),
+            | None => (part, 1),
+        };
+
+        let (start, end) = match values.split_once('-') {
+            | _ if values == "*" => (*range.start(), *range.end()),
+            | Some((start, end)) => (start.parse().ok()?, end.parse().ok()?),
+            // A single value with a step runs up to the end of the range, like `5/15`.
+            | None if step > 1 => (values.parse().ok()?, *range.end()),
+            | None => {
+                let value = values.parse().ok()?;
+                (value, value)
+            },
+        };
+
+        if !range.contains(&start) || !range.contains(&end) || start > end {
+            return None;
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Some((bits, field != "*"))
+}
+
+impl FromStr for Cron {
+    type Err = CronError;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let error = || CronError(expression.to_owned());
+
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(error());
+        };
+
+        let (minutes, _) = parse_field(minutes, 0..=59).ok_or_else(error)?;
+        let (hours, _) = parse_field(hours, 0..=23).ok_or_else(error)?;
+        let (days, days_restricted) = parse_field(days, 1..=31).ok_or_else(error)?;
+        let (months, _) = parse_field(months, 1..=12).ok_or_else(error)?;
+        let (weekdays, weekdays_restricted) = parse_field(weekdays, 0..=7).ok_or_else(error)?;
+
+        Ok(Self {
+            minutes,
+            hours: hours as u32,
+            days: days as u32,
+            months: months as u16,
+            // Both 0 and 7 are sunday.
+            weekdays: (weekdays as u8 | (weekdays >> 7) as u8) & 0x7F,
+            any_day: days_restricted && weekdays_restricted,
+        })
+    }
+}
+
+// Days since the epoch to a (year, month, day) triple, see http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn civil(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    (year, month as u32, day as u32)
+}
+
+impl Cron {
+    fn matches_day(&self, days: i64) -> bool {
+        let (_, month, day) = civil(days);
+        // The epoch was a thursday.
+        let weekday = (days + 4).rem_euclid(7);
+
+        let day_matches = self.days & (1 << day) != 0;
+        let weekday_matches = self.weekdays & (1 << weekday) != 0;
+
+        self.months & (1 << month) != 0 && match self.any_day {
+            | true => day_matches || weekday_matches,
+            | false => day_matches && weekday_matches,
+        }
+    }
+
+    // The first matching minute after `time`, or `None` for expressions that never match (like february 30th).
+    pub fn next_after(&self, time: time::SystemTime) -> Option<time::SystemTime> {
+        let since_epoch = time.duration_since(time::UNIX_EPOCH).ok()?;
+
+        let start = since_epoch.as_secs() as i64 / 60 + 1;
+        let mut minute = start;
+
+        // Every valid expression matches at least once within a leap year cycle.
+        while minute < start + 4 * 366 * 1440 {
+            let days = minute.div_euclid(1440);
+
+            if !self.matches_day(days) {
+                minute = (days + 1) * 1440;
+                continue;
+            }
+
+            if self.hours & (1 << (minute % 1440 / 60)) == 0 {
+                minute = (minute / 60 + 1) * 60;
+                continue;
+            }
+
+            if self.minutes & (1 << (minute % 60)) == 0 {
+                minute += 1;
+                continue;
+            }
+
+            return Some(time::UNIX_EPOCH + time::Duration::from_secs(minute as u64 * 60));
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Every(time::Duration),
+    Cron(Cron),
+}
+
+impl Schedule {
+    pub fn cron(expression: &str) -> Result<Self, CronError> {
+        Ok(Self::Cron(expression.parse()?))
+    }
+
+    fn next_after(&self, time: time::SystemTime) -> Option<time::SystemTime> {
+        match self {
+            | Self::Every(interval) if interval.is_zero() => None,
+            | Self::Every(interval) => Some(time + *interval),
+            | Self::Cron(cron) => cron.next_after(time),
+        }
+    }
+}
+
+#[inline(always)]
+pub fn every(interval: time::Duration) -> Schedule {
+    Schedule::Every(interval)
+}
+
+// What happens to runs that were missed, e.g. while the machine was suspended.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CatchUp {
+    // Runs that piled up are skipped entirely, the job waits for its next scheduled time.
+    Skip,
+    // Runs that piled up are collapsed into a single one.
+    #[default]
+    Once,
+    // Every missed run is made up for, up to a limit.
+    All,
+}
+
+struct Job {
+    schedule: Schedule,
+    catch_up: CatchUp,
+    command: fn() -> Command,
+    next: Option<time::SystemTime>,
+}
+
+impl fmt::Debug for Job {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Job")
+            .field("schedule", &self.schedule)
+            .field("catch_up", &self.catch_up)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    jobs: Vec<Job>,
+    started: bool,
+}
+
+impl Scheduler {
+    const MAX_CATCH_UP: usize = 100;
+
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    pub fn job(self, schedule: Schedule, command: fn() -> Command) -> Self {
+        self.job_with(schedule, CatchUp::default(), command)
+    }
+
+    pub fn job_with(mut self, schedule: Schedule, catch_up: CatchUp, command: fn() -> Command) -> Self {
+        self.jobs.push(Job { schedule, catch_up, command, next: None });
+        self
+    }
+
+    // The wall clock is used so time spent suspended counts, unlike with `Instant`.
+    pub(crate) fn due(&mut self, now: time::SystemTime) -> Vec<Command> {
+        if !self.started {
+            self.started = true;
+
+            for job in &mut self.jobs {
+                job.next = job.schedule.next_after(now);
+            }
+        }
+
+        let mut commands = Vec::new();
+
+        for job in &mut self.jobs {
+            let mut runs = 0;
+
+            while let Some(next) = job.next.filter(|next| *next <= now) {
+                runs += 1;
+                job.next = job.schedule.next_after(next);
+
+                if runs == Self::MAX_CATCH_UP {
+                    job.next = job.schedule.next_after(now);
+                }
+            }
+
+            let runs = match job.catch_up {
+                | CatchUp::Skip if runs > 1 => 0,
+                | CatchUp::Once => runs.min(1),
+                | _ => runs,
+            };
+
+            commands.extend((0..runs).map(|_| (job.command)()));
+        }
+
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Monday, 2024-01-01 00:00 UTC.
+    const MONDAY: u64 = 1704067200;
+
+    fn at(minutes: u64) -> time::SystemTime {
+        time::UNIX_EPOCH + time::Duration::from_secs(MONDAY + minutes * 60)
+    }
+
+    fn next(expression: &str, after: u64) -> Option<time::SystemTime> {
+        expression.parse::<Cron>().unwrap().next_after(at(after))
+    }
+
+    #[test]
+    fn cron_expressions_match_the_next_minute_they_allow() {
+        const DAY: u64 = 1440;
+
+        assert_eq!(next("30 9 * * 1-5", 0), Some(at(9 * 60 + 30)));
+        assert_eq!(next("30 9 * * 1-5", 4 * DAY + 10 * 60), Some(at(7 * DAY + 9 * 60 + 30)));
+        assert_eq!(next("5/15 * * * *", 50), Some(at(65)));
+        assert_eq!(next("0 12 * * 7", 0), Some(at(6 * DAY + 12 * 60)));
+        // Either the 13th or a friday, whichever comes first.
+        assert_eq!(next("0 0 13 * 5", 0), Some(at(4 * DAY)));
+        assert_eq!(next("0 0 30 2 *", 0), None);
+    }
+
+    #[test]
+    fn malformed_cron_expressions_are_rejected() {
+        for expression in ["* * *", "60 * * * *", "*/0 * * * *", "5-1 * * * *", "* * 0 * *", "a * * * *"] {
+            assert!(expression.parse::<Cron>().is_err(), "{expression}");
+        }
+    }
+
+    #[test]
+    fn missed_runs_are_caught_up_on_as_configured() {
+        let job = || Command::Quit;
+        let minute = every(time::Duration::from_secs(60));
+
+        let mut scheduler = Scheduler::new()
+            .job_with(minute, CatchUp::Skip, job)
+            .job_with(minute, CatchUp::Once, job)
+            .job_with(minute, CatchUp::All, job);
+
+        let mut due = |minutes| scheduler.due(at(minutes)).len();
+
+        assert_eq!(due(0), 0);
+        assert_eq!(due(5), 6);
+        assert_eq!(due(6), 3);
+    }
+}
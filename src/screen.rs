@@ -1,9 +1,108 @@
+use std::error::Error;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time;
+
 use ratatui::Frame;
 
-use crate::{message::Message, command::Command};
+use crate::{message::Message, command::Command, subscription::Subscription};
+
+pub type ScreenError = Box<dyn Error + Send + Sync>;
+
+// `Ctx` is the application's context, shared by every screen (see `Builder::with_context`). An error returned
+// from `update` stops the application and is returned from `Application::run` as `RuntimeError::Screen`.
+pub trait Screen<Ctx = ()> {
+    fn render(&self, f: &mut Frame<'_>, ctx: &Ctx);
+
+    fn update(&mut self, message: Message, ctx: &mut Ctx) -> Result<Option<Command>, ScreenError>;
+
+    // Called once, the first time the screen becomes active.
+    fn init(&mut self, _ctx: &mut Ctx) -> Option<Command> {
+        None
+    }
+
+    // Called every time the screen becomes active, after `init`.
+    fn on_enter(&mut self, _ctx: &mut Ctx) -> Option<Command> {
+        None
+    }
+
+    // Called every time another screen takes over, whether this one is kept around to go back to or not.
+    fn on_exit(&mut self, _ctx: &mut Ctx) -> Option<Command> {
+        None
+    }
+
+    // Whether `Message::Published` should be delivered for the topic, active or not.
+    fn subscribed(&self, _topic: &str) -> bool {
+        false
+    }
+
+    // Polled periodically when autosave is enabled, returns the state to persist if it changed since the last call.
+    fn autosave(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    // Asked for again every time the screen becomes active, they're dropped once it isn't anymore.
+    fn subscriptions(&self, _ctx: &Ctx) -> Vec<Subscription> {
+        Vec::new()
+    }
+
+    // Asked for after every frame, when what was drawn changes by itself next, e.g. `LiveText::next_change`.
+    // The screen is drawn again then, without anything else having to happen.
+    fn next_change(&self, _ctx: &Ctx) -> Option<time::Instant> {
+        None
+    }
+}
+
+// Screens with heavy work of their own to do while inactive, done on worker threads alongside the screen that's
+// active (see `Builder::background_screen`). Without the context, which may not be shareable across threads.
+pub trait BackgroundUpdate: Send {
+    // Once per tick while the screen isn't active or an overlay, finished before the next frame is drawn.
+    fn background_update(&mut self, elapsed: time::Duration);
+}
+
+// A screen shared with the threads updating it in the background, everything else waits for them.
+pub(crate) struct Shared<S>(pub Arc<Mutex<S>>);
+
+impl<S> Shared<S> {
+    #[inline(always)]
+    fn lock(&self) -> MutexGuard<'_, S> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<Ctx, S: Screen<Ctx>> Screen<Ctx> for Shared<S> {
+    fn render(&self, f: &mut Frame<'_>, ctx: &Ctx) {
+        self.lock().render(f, ctx)
+    }
+
+    fn update(&mut self, message: Message, ctx: &mut Ctx) -> Result<Option<Command>, ScreenError> {
+        self.lock().update(message, ctx)
+    }
+
+    fn init(&mut self, ctx: &mut Ctx) -> Option<Command> {
+        self.lock().init(ctx)
+    }
+
+    fn on_enter(&mut self, ctx: &mut Ctx) -> Option<Command> {
+        self.lock().on_enter(ctx)
+    }
+
+    fn on_exit(&mut self, ctx: &mut Ctx) -> Option<Command> {
+        self.lock().on_exit(ctx)
+    }
+
+    fn subscribed(&self, topic: &str) -> bool {
+        self.lock().subscribed(topic)
+    }
+
+    fn autosave(&mut self) -> Option<Vec<u8>> {
+        self.lock().autosave()
+    }
 
-pub trait Screen {
-    fn render(&self, f: &mut Frame<'_>);
+    fn subscriptions(&self, ctx: &Ctx) -> Vec<Subscription> {
+        self.lock().subscriptions(ctx)
+    }
 
-    fn update(&mut self, message: Message) -> Option<Command>;
+    fn next_change(&self, ctx: &Ctx) -> Option<time::Instant> {
+        self.lock().next_change(ctx)
+    }
 }
@@ -0,0 +1,398 @@
+use std::time::Duration;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
+use ratatui::widgets::Widget;
+
+use crate::vt;
+use crate::motion;
+use crate::command::Command;
+use crate::message::TickMsg;
+
+// A single frame, cell by cell. `None` is transparent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Image {
+    rows: Vec<Vec<Option<(char, Style)>>>,
+}
+
+impl Image {
+    // Spaces are transparent.
+    pub fn ascii(text: &str) -> Self {
+        Self {
+            rows: text.lines().map(|line| {
+                line.chars().map(|c| (c != ' ').then_some((c, Style::default()))).collect()
+            }).collect(),
+        }
+    }
+
+    // Colored with escape sequences, spaces are only transparent without a background color.
+    pub fn ansi<T: AsRef<[u8]>>(input: T) -> Self {
+        let opaque = |c: char, style: Style| c != ' ' || style.bg.is_some_and(|bg| bg != Color::Reset);
+
+        Self {
+            rows: vt::parse(input).into_iter().map(|line| {
+                line.spans.iter()
+                    .flat_map(|span| {
+                        let style = line.style.patch(span.style);
+                        span.content.chars().map(move |c| opaque(c, style).then_some((c, style)))
+                    })
+                    .collect()
+            }).collect(),
+        }
+    }
+
+    // Patched under every cell's own style.
+    pub fn style(mut self, style: Style) -> Self {
+        for (_, cell) in self.rows.iter_mut().flatten().flatten() {
+            *cell = style.patch(*cell);
+        }
+
+        self
+    }
+
+    #[inline(always)]
+    pub fn width(&self) -> u16 {
+        self.rows.iter().map(Vec::len).max().unwrap_or(0) as u16
+    }
+
+    #[inline(always)]
+    pub fn height(&self) -> u16 {
+        self.rows.len() as u16
+    }
+
+    #[inline(always)]
+    fn get(&self, column: usize, row: usize) -> Option<(char, Style)> {
+        self.rows.get(row).and_then(|row| row.get(column).copied().flatten())
+    }
+}
+
+// One or more images shown one after the other, driven by `SpriteLayer::tick`.
+#[derive(Debug, Clone)]
+pub struct Sprite {
+    frames: Vec<Image>,
+    frame_duration: Duration,
+    looping: bool,
+}
+
+impl Sprite {
+    pub fn new<I: IntoIterator<Item = Image>>(frames: I) -> Self {
+        let frames = frames.into_iter().collect::<Vec<_>>();
+
+        Self {
+            frames: if frames.is_empty() { vec![Image::default()] } else { frames },
+            frame_duration: Duration::from_millis(100),
+            looping: true,
+        }
+    }
+
+    #[inline(always)]
+    pub fn still(image: Image) -> Self {
+        Self::new([image])
+    }
+
+    pub fn frame_duration(mut self, duration: Duration) -> Self {
+        self.frame_duration = duration;
+        self
+    }
+
+    // Stops on the last frame otherwise.
+    pub fn looping(mut self, looping: bool) -> Self {
+        self.looping = looping;
+        self
+    }
+
+    #[inline(always)]
+    pub fn frames(&self) -> usize {
+        self.frames.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpriteId(u64);
+
+#[derive(Debug)]
+struct Placed {
+    id: SpriteId,
+    sprite: Sprite,
+    position: (i32, i32),
+    z: i32,
+    visible: bool,
+    frame: usize,
+    elapsed: Duration,
+}
+
+impl Placed {
+    #[inline(always)]
+    fn image(&self) -> &Image {
+        &self.sprite.frames[self.frame]
+    }
+
+    #[inline(always)]
+    fn is_animating(&self) -> bool {
+        self.sprite.frames.len() > 1 && (self.sprite.looping || self.frame + 1 < self.sprite.frames.len())
+    }
+
+    // Returns whether the frame changed.
+    fn advance(&mut self, elapsed: Duration) -> bool {
+        if !self.is_animating() || self.sprite.frame_duration.is_zero() {
+            return false;
+        }
+
+        let (previous, count) = (self.frame, self.sprite.frames.len() as u128);
+        let duration = self.sprite.frame_duration.as_nanos();
+
+        let elapsed = self.elapsed.saturating_add(elapsed).as_nanos();
+        let steps = elapsed / duration;
+
+        // Computed rather than stepped through, a long pause with a short frame duration costs the same.
+        self.elapsed = Duration::from_nanos((elapsed % duration) as u64);
+        self.frame = match self.sprite.looping {
+            | true => ((self.frame as u128 + steps) % count) as usize,
+            | false => (self.frame as u128).saturating_add(steps).min(count - 1) as usize,
+        };
+
+        self.frame != previous
+    }
+}
+
+// Sprites placed in cells relative to the area the layer is rendered into, drawn over what's already there
+// from the lowest z to the highest. Sprites on the same z are drawn in the order they were added. Animations
+// are frozen with reduced motion.
+#[derive(Debug, Default)]
+pub struct SpriteLayer {
+    sprites: Vec<Placed>,
+    next_id: u64,
+}
+
+impl SpriteLayer {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, sprite: Sprite, position: (i32, i32), z: i32) -> SpriteId {
+        let id = SpriteId(self.next_id);
+
+        self.next_id += 1;
+
+        self.sprites.push(Placed { id, sprite, position, z, visible: true, frame: 0, elapsed: Duration::ZERO });
+        self.sort();
+
+        id
+    }
+
+    pub fn remove(&mut self, id: SpriteId) -> Option<Sprite> {
+        let index = self.sprites.iter().position(|placed| placed.id == id)?;
+
+        Some(self.sprites.remove(index).sprite)
+    }
+
+    pub fn clear(&mut self) {
+        self.sprites.clear();
+    }
+
+    #[inline(always)]
+    fn sort(&mut self) {
+        self.sprites.sort_by_key(|placed| placed.z);
+    }
+
+    #[inline(always)]
+    fn get_mut(&mut self, id: SpriteId) -> Option<&mut Placed> {
+        self.sprites.iter_mut().find(|placed| placed.id == id)
+    }
+
+    pub fn position(&self, id: SpriteId) -> Option<(i32, i32)> {
+        self.sprites.iter().find(|placed| placed.id == id).map(|placed| placed.position)
+    }
+
+    pub fn move_to(&mut self, id: SpriteId, position: (i32, i32)) {
+        if let Some(placed) = self.get_mut(id) {
+            placed.position = position;
+        }
+    }
+
+    pub fn move_by(&mut self, id: SpriteId, (dx, dy): (i32, i32)) {
+        if let Some(placed) = self.get_mut(id) {
+            placed.position = (placed.position.0 + dx, placed.position.1 + dy);
+        }
+    }
+
+    pub fn set_z(&mut self, id: SpriteId, z: i32) {
+        if let Some(placed) = self.get_mut(id) {
+            placed.z = z;
+            self.sort();
+        }
+    }
+
+    pub fn set_visible(&mut self, id: SpriteId, visible: bool) {
+        if let Some(placed) = self.get_mut(id) {
+            placed.visible = visible;
+        }
+    }
+
+    // Starts the animation over from there.
+    pub fn set_frame(&mut self, id: SpriteId, frame: usize) {
+        if let Some(placed) = self.get_mut(id) {
+            placed.frame = frame.min(placed.sprite.frames.len() - 1);
+            placed.elapsed = Duration::ZERO;
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_animating(&self) -> bool {
+        !motion::reduced() && self.sprites.iter().any(Placed::is_animating)
+    }
+
+    // Moves the animations along, returns the command to show the new frames if any of them changed.
+    pub fn tick(&mut self, tick: &TickMsg) -> Option<Command> {
+        if motion::reduced() {
+            return None;
+        }
+
+        let changed = self.sprites.iter_mut().fold(false, |changed, placed| placed.advance(tick.elapsed) | changed);
+
+        changed.then_some(Command::Render)
+    }
+
+    // The topmost visible sprite with a cell that isn't transparent at the position.
+    pub fn sprite_at(&self, (x, y): (i32, i32)) -> Option<SpriteId> {
+        self.sprites.iter().rev()
+            .filter(|placed| placed.visible)
+            .find(|placed| {
+                let (column, row) = (x - placed.position.0, y - placed.position.1);
+
+                column >= 0 && row >= 0 && placed.image().get(column as usize, row as usize).is_some()
+            })
+            .map(|placed| placed.id)
+    }
+}
+
+impl Widget for &SpriteLayer {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for placed in self.sprites.iter().filter(|placed| placed.visible) {
+            let (left, top) = placed.position;
+
+            for (row, cells) in placed.image().rows.iter().enumerate() {
+                let y = top + row as i32;
+
+                if y < 0 || y >= area.height as i32 {
+                    continue;
+                }
+
+                for (column, cell) in cells.iter().enumerate() {
+                    let x = left + column as i32;
+
+                    let Some((c, style)) = cell.filter(|_| x >= 0 && x < area.width as i32) else {
+                        continue;
+                    };
+
+                    buf.get_mut(area.x + x as u16, area.y + y as u16).set_char(c).set_style(style);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(count: usize) -> Vec<Image> {
+        (0..count).map(|i| Image::ascii(&i.to_string())).collect()
+    }
+
+    fn tick(elapsed: Duration) -> TickMsg {
+        TickMsg { elapsed, frame: 0 }
+    }
+
+    fn frame(layer: &SpriteLayer) -> String {
+        let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+
+        layer.render(buf.area, &mut buf);
+
+        buf.get(0, 0).symbol().to_string()
+    }
+
+    #[test]
+    fn parses_transparent_cells() {
+        let image = Image::ascii("a b\n c");
+
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.get(1, 0), None);
+        assert_eq!(image.get(1, 1).map(|(c, _)| c), Some('c'));
+    }
+
+    #[test]
+    fn loops_through_the_frames() {
+        let _lock = motion::lock();
+        let mut layer = SpriteLayer::new();
+
+        motion::set_reduced(false);
+        layer.add(Sprite::new(frames(3)), (0, 0), 0);
+
+        assert!(layer.tick(&tick(Duration::from_millis(50))).is_none());
+        assert!(layer.tick(&tick(Duration::from_millis(50))).is_some());
+        assert_eq!(frame(&layer), "1");
+
+        layer.tick(&tick(Duration::from_millis(250)));
+        assert_eq!(frame(&layer), "0");
+    }
+
+    #[test]
+    fn stops_on_the_last_frame_without_looping() {
+        let _lock = motion::lock();
+        let mut layer = SpriteLayer::new();
+
+        motion::set_reduced(false);
+        layer.add(Sprite::new(frames(3)).looping(false), (0, 0), 0);
+        layer.tick(&tick(Duration::from_secs(10)));
+
+        assert_eq!(frame(&layer), "2");
+        assert!(!layer.is_animating());
+    }
+
+    #[test]
+    fn long_pauses_dont_take_long() {
+        let _lock = motion::lock();
+        let mut layer = SpriteLayer::new();
+
+        motion::set_reduced(false);
+        layer.add(Sprite::new(frames(2)).frame_duration(Duration::from_nanos(1)), (0, 0), 0);
+        layer.tick(&tick(Duration::from_secs(u64::MAX)));
+        layer.tick(&tick(Duration::from_secs(u64::MAX)));
+
+        assert!(layer.is_animating());
+    }
+
+    #[test]
+    fn reduced_motion_freezes_the_animations() {
+        let _lock = motion::lock();
+        let mut layer = SpriteLayer::new();
+
+        motion::set_reduced(true);
+        layer.add(Sprite::new(frames(2)), (0, 0), 0);
+
+        let command = layer.tick(&tick(Duration::from_secs(1)));
+
+        motion::set_reduced(false);
+
+        assert!(command.is_none());
+        assert_eq!(frame(&layer), "0");
+    }
+
+    #[test]
+    fn finds_the_topmost_sprite() {
+        let mut layer = SpriteLayer::new();
+
+        let low = layer.add(Sprite::still(Image::ascii("ab")), (0, 0), 0);
+        let high = layer.add(Sprite::still(Image::ascii(" c")), (0, 0), 1);
+
+        assert_eq!(layer.sprite_at((0, 0)), Some(low));
+        assert_eq!(layer.sprite_at((1, 0)), Some(high));
+        assert_eq!(layer.sprite_at((2, 0)), None);
+
+        layer.set_visible(high, false);
+        assert_eq!(layer.sprite_at((1, 0)), Some(low));
+    }
+}
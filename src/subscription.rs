@@ -0,0 +1,159 @@
+use std::{fmt, fs, time};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+
+use crate::message::Message;
+
+type Poll = Box<dyn FnMut(time::Instant, &mut Vec<Message>)>;
+
+// A recurring source of messages, polled once per tick on the event loop instead of on a thread of its own.
+pub struct Subscription {
+    poll: Poll,
+}
+
+impl Subscription {
+    pub fn new<F>(mut poll: F) -> Self
+        where F: FnMut() -> Option<Message> + 'static,
+    {
+        Self {
+            poll: Box::new(move |_, messages| messages.extend(poll())),
+        }
+    }
+
+    // The first message is produced one interval after the subscription started.
+    pub fn every(interval: time::Duration, message: fn() -> Message) -> Self {
+        let mut next = None;
+
+        Self {
+            poll: Box::new(move |now, messages| {
+                let due = *next.get_or_insert(now + interval);
+
+                if now < due {
+                    return;
+                }
+
+                messages.push(message());
+
+                // Keeps the cadence, but skips intervals that were missed rather than bursting to catch up.
+                next = Some(match due + interval {
+                    | next if next <= now => now + interval,
+                    | next => next,
+                });
+            }),
+        }
+    }
+
+    pub fn receiver<T: 'static>(receiver: Receiver<T>, message: fn(T) -> Message) -> Self {
+        Self {
+            poll: Box::new(move |_, messages| messages.extend(receiver.try_iter().map(message))),
+        }
+    }
+
+    // Checks the modification time every `interval`, a file that appears or disappears counts as a change too.
+    pub fn watch<P: Into<PathBuf>>(path: P, interval: time::Duration, message: fn(&PathBuf) -> Message) -> Self {
+        let path = path.into();
+
+        let modified = |path: &PathBuf| fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        let mut last = modified(&path);
+        let mut next = None;
+
+        Self {
+            poll: Box::new(move |now, messages| {
+                if now < *next.get_or_insert(now) {
+                    return;
+                }
+
+                next = Some(now + interval);
+
+                let current = modified(&path);
+
+                if current != last {
+                    last = current;
+                    messages.push(message(&path));
+                }
+            }),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn poll(&mut self, now: time::Instant, messages: &mut Vec<Message>) {
+        (self.poll)(now, messages)
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::env;
+    use std::sync::mpsc;
+
+    fn poll(subscription: &mut Subscription, now: time::Instant) -> Vec<Message> {
+        let mut messages = Vec::new();
+
+        subscription.poll(now, &mut messages);
+
+        messages
+    }
+
+    #[test]
+    fn intervals_keep_their_cadence_without_bursting() {
+        let second = time::Duration::from_secs(1);
+        let start = time::Instant::now();
+        let mut ticks = Subscription::every(second, || Message::FocusGained);
+
+        let mut count = |secs: f64| poll(&mut ticks, start + second.mul_f64(secs)).len();
+
+        assert_eq!(count(0.), 0);
+        assert_eq!(count(0.9), 0);
+        assert_eq!(count(1.5), 1);
+        assert_eq!(count(1.9), 0);
+        assert_eq!(count(2.), 1);
+        // Missing a few only makes up for one of them, the next is an interval later.
+        assert_eq!(count(6.5), 1);
+        assert_eq!(count(7.), 0);
+        assert_eq!(count(7.5), 1);
+    }
+
+    #[test]
+    fn everything_received_since_the_last_poll_is_delivered() {
+        let (sender, receiver) = mpsc::channel();
+        let mut received = Subscription::receiver(receiver, |(width, height)| Message::Resize(width, height));
+
+        sender.send((1, 2)).unwrap();
+        sender.send((3, 4)).unwrap();
+
+        let messages = poll(&mut received, time::Instant::now());
+
+        assert!(matches!(messages[..], [Message::Resize(1, 2), Message::Resize(3, 4)]));
+        assert!(poll(&mut received, time::Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn files_appearing_count_as_changes() {
+        let path = env::temp_dir().join(format!("ratata-watch-{}", std::process::id()));
+        let second = time::Duration::from_secs(1);
+        let start = time::Instant::now();
+
+        let _ = fs::remove_file(&path);
+
+        let mut watch = Subscription::watch(&path, second, |_| Message::FocusGained);
+
+        assert!(poll(&mut watch, start).is_empty());
+
+        fs::write(&path, "").unwrap();
+
+        assert!(poll(&mut watch, start + second / 2).is_empty(), "not checked again before the interval");
+        assert_eq!(poll(&mut watch, start + second).len(), 1);
+        assert!(poll(&mut watch, start + second * 2).is_empty());
+
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,522 @@
+use std::{fmt, io, thread};
+use std::io::Write;
+use std::any::{Any, TypeId};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, TryLockError, Weak};
+
+use crossterm::{cursor, event, terminal};
+use crossterm::event::KeyboardEnhancementFlags;
+
+use crate::command::ObjectSafeCrosstermCommand;
+use crate::panic_hook::PanicHook;
+
+pub(crate) type Sink = Box<dyn Write + Send>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TerminalState {
+    pub raw_mode: bool,
+    pub alternate_screen: bool,
+    pub mouse_capture: bool,
+    pub focus_change: bool,
+    pub bracketed_paste: bool,
+    pub cursor_hidden: bool,
+    // Every set of flags pushed and not popped yet, the last one is in effect.
+    pub keyboard_enhancement_flags: Vec<KeyboardEnhancementFlags>,
+    // The last title set, if any was.
+    pub title: Option<String>,
+}
+
+impl TerminalState {
+    // Only raw mode can actually be queried, everything else is assumed to be in its default state.
+    pub fn detect() -> io::Result<Self> {
+        Ok(Self {
+            raw_mode: terminal::is_raw_mode_enabled()?,
+            ..Self::default()
+        })
+    }
+}
+
+// XTWINOPS title stack, there is no portable way to read the title back.
+struct PushTitle;
+
+impl crossterm::Command for PushTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B[22;0t")
+    }
+}
+
+struct PopTitle;
+
+impl crossterm::Command for PopTitle {
+    fn write_ansi(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        f.write_str("\x1B[23;0t")
+    }
+}
+
+// The state before the run and the one it's in now.
+#[derive(Debug, Default)]
+struct Modes {
+    initial: TerminalState,
+    current: TerminalState,
+    title_saved: bool,
+}
+
+impl Modes {
+    fn reset(&mut self, state: TerminalState) {
+        self.initial = state.clone();
+        self.current = state;
+        self.title_saved = false;
+    }
+}
+
+// What the panic hook needs of a guard, see `restore_on_panic`.
+pub(crate) struct SharedGuard {
+    sink: Weak<Mutex<Sink>>,
+    modes: Weak<Mutex<Modes>>,
+}
+
+pub struct TerminalGuard {
+    // Both shared with the panic hook.
+    sink: Arc<Mutex<Sink>>,
+    modes: Arc<Mutex<Modes>>,
+}
+
+impl TerminalGuard {
+    pub fn new<W: Write + Send + 'static>(sink: W) -> io::Result<Self> {
+        Ok(Self::with_state(sink, TerminalState::detect()?))
+    }
+
+    pub fn with_state<W: Write + Send + 'static>(sink: W, state: TerminalState) -> Self {
+        let mut modes = Modes::default();
+
+        modes.reset(state);
+
+        Self {
+            sink: Arc::new(Mutex::new(Box::new(sink))),
+            modes: Arc::new(Mutex::new(modes)),
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn reset(&mut self, state: TerminalState) {
+        self.modes().reset(state);
+    }
+
+    #[inline(always)]
+    pub(crate) fn sink(&self) -> MutexGuard<'_, Sink> {
+        self.sink.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline(always)]
+    fn modes(&self) -> MutexGuard<'_, Modes> {
+        self.modes.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    #[inline(always)]
+    pub(crate) fn shared(&self) -> SharedGuard {
+        SharedGuard { sink: Arc::downgrade(&self.sink), modes: Arc::downgrade(&self.modes) }
+    }
+
+    // Forgets about every change, so nothing is restored.
+    pub(crate) fn disarm(&mut self) {
+        let current = self.state();
+        self.reset(current);
+    }
+
+    #[inline(always)]
+    pub fn state(&self) -> TerminalState {
+        self.modes().current.clone()
+    }
+
+    #[inline(always)]
+    pub fn initial_state(&self) -> TerminalState {
+        self.modes().initial.clone()
+    }
+
+    pub fn enable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        self.modes().current.raw_mode = true;
+        Ok(())
+    }
+
+    pub fn disable_raw_mode(&mut self) -> io::Result<()> {
+        terminal::disable_raw_mode()?;
+        self.modes().current.raw_mode = false;
+        Ok(())
+    }
+
+    pub fn execute<C>(&mut self, command: C) -> io::Result<()>
+        where C: crossterm::Command + 'static,
+    {
+        self.execute_tracked(&command, &command)
+    }
+
+    pub(crate) fn execute_erased(&mut self, command: ObjectSafeCrosstermCommand) -> io::Result<()> {
+        self.execute_tracked(&command, command.as_any())
+    }
+
+    // `any` is the command itself, for telling what it does.
+    fn execute_tracked(&mut self, command: impl crossterm::Command, any: &dyn Any) -> io::Result<()> {
+        let title = title(any);
+
+        // Locked once, the macros name the writer more than once.
+        let mut sink = self.sink.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut modes = self.modes();
+
+        if title.is_some() && !modes.title_saved {
+            crossterm::execute!(sink, PushTitle)?;
+            modes.title_saved = true;
+        }
+
+        crossterm::execute!(sink, command)?;
+
+        drop(sink);
+
+        let state = &mut modes.current;
+
+        if title.is_some() {
+            state.title = title;
+        }
+
+        match any.type_id() {
+            | ty if ty == TypeId::of::<terminal::EnterAlternateScreen>() => state.alternate_screen = true,
+            | ty if ty == TypeId::of::<terminal::LeaveAlternateScreen>() => state.alternate_screen = false,
+            | ty if ty == TypeId::of::<event::EnableMouseCapture>() => state.mouse_capture = true,
+            | ty if ty == TypeId::of::<event::DisableMouseCapture>() => state.mouse_capture = false,
+            | ty if ty == TypeId::of::<event::EnableFocusChange>() => state.focus_change = true,
+            | ty if ty == TypeId::of::<event::DisableFocusChange>() => state.focus_change = false,
+            #[cfg(feature = "paste")]
+            | ty if ty == TypeId::of::<event::EnableBracketedPaste>() => state.bracketed_paste = true,
+            #[cfg(feature = "paste")]
+            | ty if ty == TypeId::of::<event::DisableBracketedPaste>() => state.bracketed_paste = false,
+            | ty if ty == TypeId::of::<cursor::Hide>() => state.cursor_hidden = true,
+            | ty if ty == TypeId::of::<cursor::Show>() => state.cursor_hidden = false,
+            | ty if ty == TypeId::of::<event::PushKeyboardEnhancementFlags>() => {
+                if let Some(event::PushKeyboardEnhancementFlags(flags)) = any.downcast_ref() {
+                    state.keyboard_enhancement_flags.push(*flags);
+                }
+            },
+            | ty if ty == TypeId::of::<event::PopKeyboardEnhancementFlags>() => {
+                state.keyboard_enhancement_flags.pop();
+            },
+            | _ => (),
+        }
+
+        Ok(())
+    }
+
+    // Turns the modes of `state` back on after a `restore`, like when continuing after a suspend, and pushes the
+    // keyboard enhancement flags and sets the title it had again.
+    pub(crate) fn reenter(&mut self, state: TerminalState) -> io::Result<()> {
+        let current = self.state();
+
+        if state.raw_mode && !current.raw_mode {
+            self.enable_raw_mode()?;
+        }
+
+        if state.alternate_screen && !current.alternate_screen {
+            self.execute(terminal::EnterAlternateScreen)?;
+        }
+
+        // On the screen they're popped before leaving again.
+        let pushed = current.keyboard_enhancement_flags.len();
+
+        for flags in state.keyboard_enhancement_flags.into_iter().skip(pushed) {
+            self.execute(event::PushKeyboardEnhancementFlags(flags))?;
+        }
+
+        if state.mouse_capture && !current.mouse_capture {
+            self.execute(event::EnableMouseCapture)?;
+        }
+
+        if state.focus_change && !current.focus_change {
+            self.execute(event::EnableFocusChange)?;
+        }
+
+        #[cfg(feature = "paste")]
+        if state.bracketed_paste && !current.bracketed_paste {
+            self.execute(event::EnableBracketedPaste)?;
+        }
+
+        if state.cursor_hidden && !current.cursor_hidden {
+            self.execute(cursor::Hide)?;
+        }
+
+        match state.title {
+            | Some(title) if current.title.as_ref() != Some(&title) => self.execute(terminal::SetTitle(title)),
+            | _ => Ok(()),
+        }
+    }
+
+    pub fn restore(&mut self) -> io::Result<()> {
+        let (mut sink, mut modes) = (self.sink(), self.modes());
+
+        restore(&mut sink, &modes)?;
+
+        let initial = modes.initial.clone();
+        modes.reset(initial);
+
+        Ok(())
+    }
+}
+
+// Back to the state before the run, only changing what's different from it.
+fn restore(sink: &mut Sink, modes: &Modes) -> io::Result<()> {
+    let (initial, current) = (&modes.initial, &modes.current);
+
+    // The flags pushed by us are popped before leaving the screen they were pushed on.
+    for _ in initial.keyboard_enhancement_flags.len()..current.keyboard_enhancement_flags.len() {
+        crossterm::queue!(sink, event::PopKeyboardEnhancementFlags)?;
+    }
+
+    if initial.mouse_capture != current.mouse_capture {
+        match initial.mouse_capture {
+            | true => crossterm::queue!(sink, event::EnableMouseCapture)?,
+            | false => crossterm::queue!(sink, event::DisableMouseCapture)?,
+        }
+    }
+
+    if initial.focus_change != current.focus_change {
+        match initial.focus_change {
+            | true => crossterm::queue!(sink, event::EnableFocusChange)?,
+            | false => crossterm::queue!(sink, event::DisableFocusChange)?,
+        }
+    }
+
+    #[cfg(feature = "paste")]
+    if initial.bracketed_paste != current.bracketed_paste {
+        match initial.bracketed_paste {
+            | true => crossterm::queue!(sink, event::EnableBracketedPaste)?,
+            | false => crossterm::queue!(sink, event::DisableBracketedPaste)?,
+        }
+    }
+
+    if initial.alternate_screen != current.alternate_screen {
+        match initial.alternate_screen {
+            | true => crossterm::queue!(sink, terminal::EnterAlternateScreen)?,
+            | false => crossterm::queue!(sink, terminal::LeaveAlternateScreen)?,
+        }
+    }
+
+    if initial.cursor_hidden != current.cursor_hidden {
+        match initial.cursor_hidden {
+            | true => crossterm::queue!(sink, cursor::Hide)?,
+            | false => crossterm::queue!(sink, cursor::Show)?,
+        }
+    }
+
+    if modes.title_saved {
+        crossterm::queue!(sink, PopTitle)?;
+    }
+
+    sink.flush()?;
+
+    if initial.raw_mode != current.raw_mode {
+        match initial.raw_mode {
+            | true => terminal::enable_raw_mode()?,
+            | false => terminal::disable_raw_mode()?,
+        }
+    }
+
+    Ok(())
+}
+
+fn title(command: &dyn Any) -> Option<String> {
+    match command.downcast_ref::<terminal::SetTitle<&str>>() {
+        | Some(terminal::SetTitle(title)) => Some(String::from(*title)),
+        | None => command.downcast_ref::<terminal::SetTitle<String>>().map(|terminal::SetTitle(title)| title.clone()),
+    }
+}
+
+// The guard restores the terminal while unwinding, but the panic message is printed before that (into the
+// alternate screen, mangled by raw mode) and nothing unwinds at all with `panic = "abort"`. So the guard's state
+// from before the run is restored right away, for panics on the thread the application runs on while it's around.
+pub(crate) fn restore_on_panic(guard: SharedGuard) -> PanicHook {
+    let running = thread::current().id();
+
+    PanicHook::install(move |_| {
+        let (Some(sink), Some(modes)) = (guard.sink.upgrade(), guard.modes.upgrade()) else {
+            return;
+        };
+
+        if thread::current().id() != running {
+            return;
+        }
+
+        // Unless the panic happened halfway through writing to it or recording a change.
+        let (Some(mut sink), Some(mut modes)) = (try_lock(&sink), try_lock(&modes)) else {
+            return;
+        };
+
+        // Restored once, the guard has nothing left to do while unwinding.
+        if restore(&mut sink, &modes).is_ok() {
+            let initial = modes.initial.clone();
+            modes.reset(initial);
+        }
+    })
+}
+
+fn try_lock<T>(mutex: &Mutex<T>) -> Option<MutexGuard<'_, T>> {
+    match mutex.try_lock() {
+        | Ok(guard) => Some(guard),
+        | Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        | Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+
+    use super::*;
+
+    use crate::panic_hook;
+
+    // What was written to the sink, read back by the test.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn panics_on_the_running_thread_restore_through_the_sink() {
+        let _lock = panic_hook::lock();
+
+        let written = Shared::default();
+        let mut guard = TerminalGuard::with_state(written.clone(), TerminalState::default());
+
+        guard.execute(terminal::EnterAlternateScreen).unwrap();
+        written.0.lock().unwrap().clear();
+
+        let hook = restore_on_panic(guard.shared());
+
+        let _ = thread::spawn(|| panic!("elsewhere")).join();
+
+        assert!(written.0.lock().unwrap().is_empty());
+
+        let _ = panic::catch_unwind(|| panic!("here"));
+
+        hook.uninstall();
+
+        let leave = ansi(terminal::LeaveAlternateScreen);
+
+        assert!(String::from_utf8_lossy(&written.0.lock().unwrap()).contains(&leave));
+
+        guard.disarm();
+    }
+
+    #[test]
+    fn panics_restore_the_state_from_before_the_run() {
+        let _lock = panic_hook::lock();
+
+        let written = Shared::default();
+        let before = TerminalState { alternate_screen: true, cursor_hidden: true, ..TerminalState::default() };
+        let mut guard = TerminalGuard::with_state(written.clone(), before);
+
+        guard.execute(event::EnableMouseCapture).unwrap();
+        guard.execute(cursor::Show).unwrap();
+        written.0.lock().unwrap().clear();
+
+        let hook = restore_on_panic(guard.shared());
+
+        let _ = panic::catch_unwind(|| panic!("here"));
+
+        hook.uninstall();
+
+        // Still on the alternate screen it was on before.
+        let expected = [ansi(event::DisableMouseCapture), ansi(cursor::Hide)];
+
+        assert_eq!(String::from_utf8_lossy(&written.0.lock().unwrap()), expected.concat());
+
+        guard.disarm();
+    }
+
+    #[test]
+    fn the_guard_does_not_restore_again_after_the_panic_hook() {
+        let _lock = panic_hook::lock();
+
+        let written = Shared::default();
+        let mut guard = TerminalGuard::with_state(written.clone(), TerminalState::default());
+
+        guard.execute(terminal::EnterAlternateScreen).unwrap();
+
+        let hook = restore_on_panic(guard.shared());
+
+        let _ = panic::catch_unwind(|| panic!("here"));
+
+        hook.uninstall();
+
+        let restored = written.0.lock().unwrap().len();
+
+        assert_eq!(guard.state(), TerminalState::default());
+
+        drop(guard);
+
+        assert_eq!(written.0.lock().unwrap().len(), restored);
+    }
+
+    #[test]
+    fn commands_are_written_to_the_sink() {
+        let written = Shared::default();
+        let mut guard = TerminalGuard::with_state(written.clone(), TerminalState::default());
+
+        guard.execute(terminal::SetTitle("ratata")).unwrap();
+        guard.execute(terminal::EnterAlternateScreen).unwrap();
+
+        let expected = [ansi(PushTitle), ansi(terminal::SetTitle("ratata")), ansi(terminal::EnterAlternateScreen)];
+
+        assert_eq!(String::from_utf8_lossy(&written.0.lock().unwrap()), expected.concat());
+        assert!(guard.state().alternate_screen);
+
+        guard.disarm();
+    }
+
+    #[test]
+    fn flags_and_title_are_back_after_reentering() {
+        let written = Shared::default();
+        let mut guard = TerminalGuard::with_state(written.clone(), TerminalState::default());
+
+        let flags = KeyboardEnhancementFlags::REPORT_EVENT_TYPES;
+
+        guard.execute(event::PushKeyboardEnhancementFlags(flags)).unwrap();
+        guard.execute(terminal::SetTitle("ratata")).unwrap();
+
+        let state = guard.state();
+
+        guard.restore().unwrap();
+
+        assert_eq!(guard.state(), TerminalState::default());
+
+        written.0.lock().unwrap().clear();
+
+        guard.reenter(state.clone()).unwrap();
+
+        let written = String::from_utf8_lossy(&written.0.lock().unwrap()).into_owned();
+
+        assert!(written.contains(&ansi(event::PushKeyboardEnhancementFlags(flags))));
+        assert!(written.contains(&ansi(PushTitle)));
+        assert!(written.contains(&ansi(terminal::SetTitle("ratata"))));
+        assert_eq!(guard.state(), state);
+
+        guard.disarm();
+    }
+
+    fn ansi(command: impl crossterm::Command) -> String {
+        let mut ansi = String::new();
+        let _ = command.write_ansi(&mut ansi);
+        ansi
+    }
+}
@@ -0,0 +1,361 @@
+use std::io;
+use std::time::{Duration, Instant};
+use std::any::TypeId;
+
+use crossterm::event::{Event, KeyCode, KeyEventKind, KeyEventState, KeyModifiers};
+
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+
+use crate::application::{Application, Builder, InitialScreen, RuntimeError};
+use crate::clock::Clock;
+use crate::message::{KeyMsg, Message};
+use crate::screen::Screen;
+
+// Runs an application against a `TestBackend`, every message is processed and rendered synchronously.
+// Commands that would touch a real terminal are only logged. Time only passes when it ticks, see `clock`.
+pub struct TestApplication<Ctx = ()> {
+    application: Application<TestBackend, Ctx>,
+}
+
+impl<Ctx> TestApplication<Ctx> {
+    pub fn new<S: Screen<Ctx> + 'static>(builder: Builder<Ctx>, width: u16, height: u16) -> Result<Self, RuntimeError> {
+        Self::start(builder, TypeId::of::<S>(), width, height)
+    }
+
+    // Starts on the builder's initial screen.
+    #[inline(always)]
+    pub fn initial<S: Screen<Ctx> + 'static>(builder: Builder<Ctx, InitialScreen<S>>, width: u16, height: u16) -> Result<Self, RuntimeError> {
+        Self::start(builder, TypeId::of::<S>(), width, height)
+    }
+
+    fn start<I>(builder: Builder<Ctx, I>, screen: TypeId, width: u16, height: u16) -> Result<Self, RuntimeError> {
+        let backend = TestBackend::new(width, height);
+        let mut application = builder.manual_clock().build(io::sink(), backend).map_err(RuntimeError::Terminal)?;
+
+        application.start_headless(screen)?;
+
+        Ok(Self { application })
+    }
+
+    // Goes through the same steps as a time around the real loop, with the message as the input.
+    pub fn send(&mut self, message: Message) -> Result<&mut Self, RuntimeError> {
+        self.application.step(Some(message), None)?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    pub fn event(&mut self, event: Event) -> Result<&mut Self, RuntimeError> {
+        self.send(Message::from(event))
+    }
+
+    #[inline(always)]
+    pub fn key(&mut self, code: KeyCode) -> Result<&mut Self, RuntimeError> {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    pub fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Result<&mut Self, RuntimeError> {
+        self.send(Message::Key(KeyMsg { code, modifiers, state: KeyEventState::NONE, kind: KeyEventKind::Press }))
+    }
+
+    // Exactly one tick rate passes, so anything driven by the elapsed time advances deterministically.
+    #[inline(always)]
+    pub fn tick(&mut self) -> Result<&mut Self, RuntimeError> {
+        self.tick_by(self.application.tick_rate())
+    }
+
+    // The clock is advanced by `elapsed` first. In game mode, as many fixed steps as fit in it.
+    pub fn tick_by(&mut self, elapsed: Duration) -> Result<&mut Self, RuntimeError> {
+        self.application.clock().advance(elapsed);
+        self.application.step(None, Some(elapsed))?;
+        Ok(self)
+    }
+
+    // Waits up to `timeout` for the work started in the background, then delivers what it produced without a
+    // tick passing.
+    pub fn settle(&mut self, timeout: Duration) -> Result<&mut Self, RuntimeError> {
+        self.application.wait_for_workers(Instant::now() + timeout);
+        self.application.step(None, None)?;
+        Ok(self)
+    }
+
+    // Types the text one key at a time.
+    pub fn type_text(&mut self, text: &str) -> Result<&mut Self, RuntimeError> {
+        text.chars().try_for_each(|c| self.key(KeyCode::Char(c)).map(drop))?;
+        Ok(self)
+    }
+
+    pub fn script<I>(&mut self, messages: I) -> Result<&mut Self, RuntimeError>
+        where I: IntoIterator<Item = Message>,
+    {
+        messages.into_iter().try_for_each(|message| self.send(message).map(drop))?;
+        Ok(self)
+    }
+
+    #[inline(always)]
+    pub fn buffer(&self) -> &Buffer {
+        self.application.terminal().backend().buffer()
+    }
+
+    // The rendered rows as plain text, handy for snapshot style assertions.
+    pub fn lines(&self) -> Vec<String> {
+        let buffer = self.buffer();
+        let area = buffer.area;
+
+        (area.top()..area.bottom())
+            .map(|y| (area.left()..area.right()).map(|x| buffer.get(x, y).symbol()).collect())
+            .collect()
+    }
+
+    // The dry run trace, every command handled so far (batches are flattened) in the order they ran.
+    #[inline(always)]
+    pub fn commands(&self) -> &[String] {
+        self.application.trace()
+    }
+
+    // Always a manual one, the builder's if it was given one. Hand it to components that go by the time, e.g.
+    // `LiveText::clock`.
+    #[inline(always)]
+    pub fn clock(&self) -> &Clock {
+        self.application.clock()
+    }
+
+    #[inline(always)]
+    pub fn context(&self) -> &Ctx {
+        self.application.context()
+    }
+
+    // How many frames were drawn, the ones skipped because nothing changed aren't counted.
+    #[inline(always)]
+    pub fn frames(&mut self) -> usize {
+        self.application.frames()
+    }
+
+    #[inline(always)]
+    pub fn is_exiting(&self) -> bool {
+        self.application.is_exiting()
+    }
+
+    // Delivers `Message::Shutdown` to every screen and runs the shutdown callback.
+    pub fn shutdown(mut self) -> Result<(), RuntimeError> {
+        self.application.stop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use ratatui::Frame;
+    use ratatui::layout::Rect;
+    use ratatui::widgets::Paragraph;
+
+    use super::*;
+
+    use crate::command::Command;
+    use crate::input::InputState;
+    use crate::scheduler::{self, Scheduler};
+    use crate::screen::ScreenError;
+    use crate::tooltip::Tooltip;
+
+    // Counts the keys it got, and notes every tick and focus change in the context.
+    #[derive(Default)]
+    struct Counter {
+        count: usize,
+    }
+
+    impl Screen<Vec<String>> for Counter {
+        fn render(&self, f: &mut Frame<'_>, _: &Vec<String>) {
+            f.render_widget(Paragraph::new(format!("count: {}", self.count)), f.size());
+        }
+
+        fn update(&mut self, message: Message, ticks: &mut Vec<String>) -> Result<Option<Command>, ScreenError> {
+            Ok(match message {
+                | Message::Key(key) if key.code == KeyCode::Char('q') => Some(Command::Quit),
+                | Message::Key(key) if key.code == KeyCode::Char('t') => {
+                    let tooltip = Tooltip::new(Rect::new(0, 0, 1, 1), "hint").delay(Duration::from_secs(1));
+
+                    Some(Command::ShowTooltip(tooltip))
+                },
+                | Message::Key(key) if key.code == KeyCode::Char('n') => Some(Command::Toast(String::from("note"))),
+                | Message::Key(_) => {
+                    self.count += 1;
+                    None
+                },
+                | Message::Tick(tick) => {
+                    ticks.push(format!("{:?} #{}", tick.elapsed, tick.frame));
+                    None
+                },
+                | Message::FocusGained => {
+                    ticks.push(String::from("focus"));
+                    None
+                },
+                | _ => None,
+            })
+        }
+
+        fn autosave(&mut self) -> Option<Vec<u8>> {
+            Some(vec![self.count as u8])
+        }
+    }
+
+    fn application(builder: Builder<Vec<String>>) -> TestApplication<Vec<String>> {
+        TestApplication::new::<Counter>(builder.screen(Counter::default()), 12, 4).unwrap()
+    }
+
+    fn shows(app: &TestApplication<Vec<String>>, text: &str) -> bool {
+        app.lines().iter().any(|line| line.contains(text))
+    }
+
+    #[test]
+    fn keys_update_and_redraw_the_screen() {
+        let mut app = application(Builder::with_context(Vec::new()));
+
+        app.type_text("ab").unwrap();
+
+        assert_eq!(app.lines()[0], "count: 2    ");
+    }
+
+    #[test]
+    fn starts_on_the_initial_screen_with_what_was_set_before() {
+        let builder = Builder::with_context(Vec::new()).tick_rate(Duration::from_millis(100));
+        let mut app = TestApplication::initial(builder.initial_screen(Counter::default()), 12, 4).unwrap();
+
+        app.key(KeyCode::Char('a')).unwrap().tick().unwrap();
+
+        assert_eq!(app.lines()[0], "count: 1    ");
+        assert_eq!(app.context(), &["100ms #0"]);
+    }
+
+    #[test]
+    fn ticks_carry_the_tick_rate() {
+        let mut app = application(Builder::with_context(Vec::new()).tick_rate(Duration::from_millis(100)));
+
+        app.tick().unwrap().tick().unwrap();
+
+        assert_eq!(app.context(), &["100ms #0", "100ms #1"]);
+    }
+
+    #[test]
+    fn game_mode_ticks_in_fixed_steps() {
+        let builder = Builder::with_context(Vec::new()).tick_rate(Duration::from_millis(10)).game_mode();
+        let mut app = application(builder);
+
+        app.tick_by(Duration::from_millis(35)).unwrap();
+
+        assert_eq!(app.context(), &["10ms #0", "10ms #1", "10ms #2"]);
+    }
+
+    // Notes whether 'a' is held whenever a key or the end of a child process is seen.
+    struct Player;
+
+    type Game = (InputState, Vec<String>);
+
+    impl Screen<Game> for Player {
+        fn render(&self, _: &mut Frame<'_>, _: &Game) {}
+
+        fn update(&mut self, message: Message, (input, log): &mut Game) -> Result<Option<Command>, ScreenError> {
+            if let Message::Key(_) | Message::ExecFinished(_) = message {
+                log.push(input.is_held(KeyCode::Char('a')).to_string());
+            }
+
+            Ok(None)
+        }
+    }
+
+    #[test]
+    fn game_mode_keeps_the_held_keys_in_the_context() {
+        let builder = Builder::with_context((InputState::default(), Vec::new()))
+            .game_mode()
+            .input_state(|(input, _)| input)
+            .screen(Player);
+        let mut app = TestApplication::new::<Player>(builder, 4, 1).unwrap();
+
+        let release = KeyMsg { kind: KeyEventKind::Release, ..crate::message::key(KeyCode::Char('a')) };
+
+        app.key(KeyCode::Char('a')).unwrap();
+        app.send(Message::ExecFinished(Err(io::ErrorKind::NotFound.into()))).unwrap();
+        app.key(KeyCode::Char('a')).unwrap().send(Message::Key(release)).unwrap();
+
+        assert_eq!(app.context().1, ["true", "false", "true", "false"]);
+    }
+
+    #[test]
+    fn commands_are_traced_instead_of_run() {
+        let mut app = application(Builder::with_context(Vec::new()));
+
+        app.key(KeyCode::Char('q')).unwrap();
+
+        assert_eq!(app.commands(), ["quit"]);
+        assert!(app.is_exiting());
+    }
+
+    #[test]
+    fn nothing_is_autosaved() {
+        let directory = env::temp_dir().join(format!("ratata-testing-{}", std::process::id()));
+        let mut app = application(Builder::with_context(Vec::new()).autosave(&directory, Duration::ZERO));
+
+        app.key(KeyCode::Char('a')).unwrap().tick().unwrap();
+        app.shutdown().unwrap();
+
+        assert!(!directory.exists());
+
+        let _ = fs::remove_dir_all(&directory);
+    }
+
+    #[test]
+    fn scheduled_jobs_go_by_the_ticks() {
+        let job = || Command::send_to::<Counter, _>(Message::FocusGained);
+        let scheduler = Scheduler::new().job(scheduler::every(Duration::from_secs(60)), job);
+        let mut app = application(Builder::with_context(Vec::new()).scheduler(scheduler));
+
+        let focused = |app: &TestApplication<Vec<String>>| app.context().iter().filter(|e| *e == "focus").count();
+
+        app.tick().unwrap().tick_by(Duration::from_secs(59)).unwrap();
+
+        assert_eq!(focused(&app), 0);
+
+        app.tick_by(Duration::from_secs(1)).unwrap();
+
+        assert_eq!(focused(&app), 1);
+    }
+
+    #[test]
+    fn tooltips_show_up_once_their_delay_passed() {
+        let mut app = application(Builder::with_context(Vec::new()));
+
+        app.key(KeyCode::Char('t')).unwrap();
+
+        assert!(!shows(&app, "hint"));
+
+        app.tick_by(Duration::from_secs(1)).unwrap();
+
+        assert!(shows(&app, "hint"));
+    }
+
+    #[test]
+    fn toasts_go_away_by_themselves() {
+        let mut app = application(Builder::with_context(Vec::new()));
+
+        app.key(KeyCode::Char('n')).unwrap();
+
+        assert!(shows(&app, "note"));
+
+        app.tick_by(Duration::from_secs(4)).unwrap();
+
+        assert!(!shows(&app, "note"));
+    }
+
+    #[test]
+    fn resizes_settle_after_the_debounce() {
+        let mut app = application(Builder::with_context(Vec::new()).resize_debounce(Duration::from_millis(100)));
+
+        app.send(Message::Resize(12, 4)).unwrap();
+
+        assert!(!shows(&app, "count"));
+
+        app.tick_by(Duration::from_millis(100)).unwrap();
+
+        assert!(shows(&app, "count"));
+    }
+}
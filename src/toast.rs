@@ -0,0 +1,95 @@
+use std::time;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use unicode_width::UnicodeWidthStr;
+
+// How long a toast stays up.
+const DURATION: time::Duration = time::Duration::from_secs(4);
+
+// A short notice in the bottom right corner that goes away by itself, a newer one replaces it.
+#[derive(Debug, Default)]
+pub(crate) struct Toasts {
+    current: Option<(time::Instant, String)>,
+}
+
+impl Toasts {
+    #[inline(always)]
+    pub(crate) fn show(&mut self, text: String, now: time::Instant) {
+        self.current = Some((now + DURATION, text));
+    }
+
+    // Until the frame after it expired, which takes it off the screen again.
+    #[inline(always)]
+    pub(crate) fn is_pending(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub(crate) fn render(&mut self, buf: &mut Buffer, now: time::Instant) {
+        let Some((until, text)) = &self.current else {
+            return;
+        };
+
+        if *until <= now {
+            self.current = None;
+            return;
+        }
+
+        let area = placement(buf.area, text);
+
+        Clear.render(area, buf);
+
+        Paragraph::new(text.as_str()).block(Block::default().borders(Borders::ALL)).render(area, buf);
+    }
+}
+
+fn placement(area: Rect, text: &str) -> Rect {
+    let width = u16::try_from(text.width()).unwrap_or(u16::MAX).saturating_add(2).min(area.width);
+    let height = 3.min(area.height);
+
+    Rect::new(area.right() - width, area.bottom() - height, width, height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(buf: &Buffer, y: u16) -> String {
+        (0..buf.area.width).map(|x| buf.get(x, y).symbol()).collect()
+    }
+
+    #[test]
+    fn shown_in_the_bottom_right_corner() {
+        let mut toasts = Toasts::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 4));
+
+        let now = time::Instant::now();
+
+        toasts.show(String::from("hello"), now);
+        toasts.render(&mut buf, now);
+
+        assert_eq!(line(&buf, 0), " ".repeat(12));
+        assert_eq!(line(&buf, 2), "     │hello│");
+    }
+
+    #[test]
+    fn fits_small_areas() {
+        assert_eq!(placement(Rect::new(0, 0, 4, 2), "hello"), Rect::new(0, 0, 4, 2));
+    }
+
+    #[test]
+    fn goes_away_once_expired() {
+        let mut toasts = Toasts::default();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 12, 4));
+
+        let now = time::Instant::now();
+
+        toasts.show(String::from("hello"), now);
+        toasts.render(&mut buf, now + DURATION);
+
+        assert!(!toasts.is_pending());
+        assert_eq!(line(&buf, 2), " ".repeat(12));
+    }
+}
@@ -0,0 +1,178 @@
+use std::time;
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Widget};
+
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone)]
+pub struct Tooltip {
+    anchor: Rect,
+    text: String,
+    style: Style,
+    delay: time::Duration,
+}
+
+impl Tooltip {
+    // Shown right away unless a delay is set, e.g. the hover delay for tooltips requested on mouse moves.
+    pub fn new<T: Into<String>>(anchor: Rect, text: T) -> Self {
+        Self {
+            anchor,
+            text: text.into(),
+            style: Style::default(),
+            delay: time::Duration::ZERO,
+        }
+    }
+
+    pub fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn delay(mut self, delay: time::Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    #[inline(always)]
+    pub fn anchor(&self) -> Rect {
+        self.anchor
+    }
+
+    // Prefers the space below the anchor, then above it, and is shifted left to stay on screen.
+    pub fn placement(&self, area: Rect) -> Rect {
+        // In columns, wide characters like CJK and most emoji take two.
+        let width = self.text.lines().map(UnicodeWidthStr::width).max().unwrap_or(0);
+
+        let width = u16::try_from(width).unwrap_or(u16::MAX).saturating_add(2);
+        let height = u16::try_from(self.text.lines().count()).unwrap_or(u16::MAX).saturating_add(2);
+
+        let (width, height) = (width.min(area.width), height.min(area.height));
+
+        let y = match (self.anchor.bottom().saturating_add(height) <= area.bottom(), self.anchor.y >= area.y + height) {
+            | (true, _) => self.anchor.bottom(),
+            | (false, true) => self.anchor.y - height,
+            | (false, false) => area.bottom() - height,
+        };
+
+        let x = self.anchor.x.clamp(area.x, area.right() - width);
+
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl Widget for &Tooltip {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = self.placement(area);
+
+        Clear.render(area, buf);
+
+        Paragraph::new(self.text.as_str())
+            .style(self.style)
+            .block(Block::default().borders(Borders::ALL).style(self.style))
+            .render(area, buf);
+    }
+}
+
+// The tooltip currently requested, which only shows up once its delay has passed.
+#[derive(Debug, Default)]
+pub(crate) struct Tooltips {
+    pending: Option<(time::Instant, Tooltip)>,
+    // Whether the pending tooltip made it into a frame yet.
+    shown: bool,
+}
+
+impl Tooltips {
+    #[inline(always)]
+    pub(crate) fn show(&mut self, tooltip: Tooltip, now: time::Instant) {
+        self.pending = Some((now, tooltip));
+        self.shown = false;
+    }
+
+    #[inline(always)]
+    pub(crate) fn hide(&mut self) {
+        self.pending = None;
+    }
+
+    // When a frame has to be drawn for a requested tooltip to show up, which may have passed already if the
+    // last one was drawn just before.
+    pub(crate) fn shows_at(&self) -> Option<time::Instant> {
+        match (&self.pending, self.shown) {
+            | (Some((requested, tooltip)), false) => Some(*requested + tooltip.delay),
+            | _ => None,
+        }
+    }
+
+    pub(crate) fn render(&mut self, buf: &mut Buffer, now: time::Instant) {
+        if let Some((requested, tooltip)) = &self.pending {
+            if now.saturating_duration_since(*requested) >= tooltip.delay {
+                tooltip.render(buf.area, buf);
+                self.shown = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA: Rect = Rect { x: 0, y: 0, width: 40, height: 10 };
+
+    #[test]
+    fn wide_characters_take_two_columns() {
+        let tooltip = Tooltip::new(Rect::new(0, 0, 1, 1), "日本語 🎉");
+
+        assert_eq!(tooltip.placement(AREA).width, 11);
+    }
+
+    #[test]
+    fn goes_below_the_anchor_when_there_is_room() {
+        let tooltip = Tooltip::new(Rect::new(5, 2, 4, 1), "hint");
+
+        assert_eq!(tooltip.placement(AREA), Rect::new(5, 3, 6, 3));
+    }
+
+    #[test]
+    fn goes_above_the_anchor_at_the_bottom() {
+        let tooltip = Tooltip::new(Rect::new(5, 9, 4, 1), "hint");
+
+        assert_eq!(tooltip.placement(AREA), Rect::new(5, 6, 6, 3));
+    }
+
+    #[test]
+    fn is_shifted_left_to_stay_on_screen() {
+        let tooltip = Tooltip::new(Rect::new(38, 2, 1, 1), "hint");
+
+        assert_eq!(tooltip.placement(AREA).right(), AREA.right());
+    }
+
+    #[test]
+    fn waits_for_its_delay() {
+        let mut tooltips = Tooltips::default();
+        let mut buf = Buffer::empty(AREA);
+        let now = time::Instant::now();
+
+        tooltips.show(Tooltip::new(Rect::new(0, 0, 1, 1), "hint").delay(time::Duration::from_secs(60)), now);
+        tooltips.render(&mut buf, now);
+
+        assert_eq!(tooltips.shows_at(), Some(now + time::Duration::from_secs(60)));
+        assert_eq!(buf, Buffer::empty(AREA));
+    }
+
+    #[test]
+    fn is_due_until_a_frame_shows_it() {
+        let mut tooltips = Tooltips::default();
+        let now = time::Instant::now();
+
+        tooltips.show(Tooltip::new(Rect::new(0, 0, 1, 1), "hint"), now);
+
+        assert_eq!(tooltips.shows_at(), Some(now));
+
+        tooltips.render(&mut Buffer::empty(AREA), now);
+
+        assert_eq!(tooltips.shows_at(), None);
+    }
+}
@@ -0,0 +1,59 @@
+use std::{io, time};
+
+// Compares dotted numeric versions, a leading `v` and anything after a `-` or `+` are ignored.
+pub fn is_newer(current: &str, latest: &str) -> bool {
+    let parse = |version: &str| -> Vec<u64> {
+        version.trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+
+    let (current, latest) = (parse(current), parse(latest));
+
+    for index in 0..current.len().max(latest.len()) {
+        let (c, l) = (current.get(index).copied().unwrap_or(0), latest.get(index).copied().unwrap_or(0));
+
+        if c != l {
+            return l > c;
+        }
+    }
+
+    false
+}
+
+// The endpoint is expected to respond with the latest version on its first line.
+pub(crate) fn check(url: &str, current: &str) -> io::Result<Option<String>> {
+    let agent = ureq::AgentBuilder::new().timeout(time::Duration::from_secs(10)).build();
+
+    let body = agent.get(url).call().map_err(io::Error::other)?.into_string()?;
+
+    let latest = body.lines().next().unwrap_or_default().trim();
+
+    Ok(is_newer(current, latest).then(|| latest.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_dotted_versions() {
+        assert!(is_newer("0.1.1", "0.1.2"));
+        assert!(is_newer("0.9.0", "0.10.0"));
+        assert!(is_newer("1.2", "v1.2.1"));
+        assert!(!is_newer("1.2.0", "1.2"));
+        assert!(!is_newer("1.3.0", "1.2.9"));
+    }
+
+    #[test]
+    fn ignores_pre_releases_and_build_metadata() {
+        assert!(!is_newer("1.2.0", "1.2.0-rc.1"));
+        assert!(!is_newer("1.2.0", "1.2.0+build.5"));
+        assert!(is_newer("1.2.0", "1.3.0-beta"));
+    }
+}
@@ -0,0 +1,361 @@
+use std::{mem, str};
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum State {
+    #[default]
+    Ground,
+    Escape,
+    // After intermediate bytes like the `(` of `ESC ( B`, until the final byte.
+    EscapeIntermediate,
+    Csi,
+    Osc,
+    OscEscape,
+    // DCS, SOS, PM and APC strings, which only end on ST.
+    Dcs,
+    DcsEscape,
+}
+
+enum Token {
+    Print(char),
+    Control(char),
+    Sgr,
+}
+
+#[derive(Debug, Default, Clone)]
+struct Tokenizer {
+    state: State,
+    params: String,
+}
+
+impl Tokenizer {
+    fn next(&mut self, c: char) -> Option<Token> {
+        match (self.state, c) {
+            | (State::Ground, '\x1B') => self.state = State::Escape,
+            | (State::Ground, c) if c.is_control() => return Some(Token::Control(c)),
+            | (State::Ground, c) => return Some(Token::Print(c)),
+            | (State::Escape, '[') => {
+                self.params.clear();
+                self.state = State::Csi;
+            },
+            | (State::Escape, ']') => self.state = State::Osc,
+            | (State::Escape, 'P' | 'X' | '^' | '_') => self.state = State::Dcs,
+            | (State::Escape, '\x20'..='\x2F') => self.state = State::EscapeIntermediate,
+            | (State::Escape, _) => self.state = State::Ground,
+            | (State::EscapeIntermediate, '\x20'..='\x2F') => (),
+            | (State::EscapeIntermediate, _) => self.state = State::Ground,
+            | (State::Csi, '\x40'..='\x7E') => {
+                self.state = State::Ground;
+
+                // Everything but SGR moves the cursor or edits the screen, which has no meaning here.
+                if c == 'm' {
+                    return Some(Token::Sgr);
+                }
+            },
+            | (State::Csi, c) => self.params.push(c),
+            | (State::Osc, '\x07') => self.state = State::Ground,
+            | (State::Osc, '\x1B') => self.state = State::OscEscape,
+            | (State::Osc, _) => (),
+            | (State::OscEscape, '\\') => self.state = State::Ground,
+            | (State::OscEscape, _) => self.state = State::Osc,
+            | (State::Dcs, '\x1B') => self.state = State::DcsEscape,
+            | (State::Dcs, _) => (),
+            | (State::DcsEscape, '\\') => self.state = State::Ground,
+            | (State::DcsEscape, _) => self.state = State::Dcs,
+        }
+
+        None
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Parser {
+    tokenizer: Tokenizer,
+    style: Style,
+    carriage_return: bool,
+    // Trailing bytes of a UTF-8 sequence that was split across two chunks.
+    pending: Vec<u8>,
+    lines: Vec<Line<'static>>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            tokenizer: Tokenizer::default(),
+            style: Style::default(),
+            carriage_return: false,
+            pending: Vec::new(),
+            lines: vec![Line::default()],
+        }
+    }
+}
+
+impl Parser {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Input may be split at arbitrary points, including in the middle of escape sequences.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+
+        let pending = mem::take(&mut self.pending);
+        let mut rest = &pending[..];
+
+        loop {
+            match str::from_utf8(rest) {
+                | Ok(valid) => break self.feed_str(valid),
+                | Err(error) => {
+                    let (valid, invalid) = rest.split_at(error.valid_up_to());
+
+                    self.feed_str(str::from_utf8(valid).unwrap());
+
+                    match error.error_len() {
+                        | Some(len) => {
+                            self.feed_char(char::REPLACEMENT_CHARACTER);
+                            rest = &invalid[len..];
+                        },
+                        | None => break self.pending = invalid.to_vec(),
+                    }
+                },
+            }
+        }
+    }
+
+    pub fn feed_str(&mut self, input: &str) {
+        input.chars().for_each(|c| self.feed_char(c));
+    }
+
+    fn feed_char(&mut self, c: char) {
+        match self.tokenizer.next(c) {
+            | Some(Token::Print(c)) => self.print(c),
+            | Some(Token::Control('\n')) => {
+                self.carriage_return = false;
+                self.lines.push(Line::default());
+            },
+            | Some(Token::Control('\r')) => self.carriage_return = true,
+            | Some(Token::Control('\t')) => {
+                let width = self.line().width();
+                (0..8 - width % 8).for_each(|_| self.print(' '));
+            },
+            | Some(Token::Control('\x08')) => self.backspace(),
+            | Some(Token::Sgr) => self.style = apply_sgr(self.style, &self.tokenizer.params),
+            | Some(Token::Control(_)) | None => (),
+        }
+    }
+
+    // The last line is the one currently being written to.
+    #[inline(always)]
+    pub fn lines(&self) -> &[Line<'static>] {
+        &self.lines
+    }
+
+    pub fn into_lines(mut self) -> Vec<Line<'static>> {
+        if self.lines.len() > 1 && self.lines.last().is_some_and(|line| line.spans.is_empty()) {
+            self.lines.pop();
+        }
+
+        self.lines
+    }
+
+    fn line(&mut self) -> &mut Line<'static> {
+        self.lines.last_mut().unwrap()
+    }
+
+    fn print(&mut self, c: char) {
+        // A lone carriage return overwrites the line, like progress output expects.
+        if mem::take(&mut self.carriage_return) {
+            self.line().spans.clear();
+        }
+
+        let style = self.style;
+        let line = self.line();
+
+        match line.spans.last_mut() {
+            | Some(span) if span.style == style => span.content.to_mut().push(c),
+            | _ => line.spans.push(Span::styled(String::from(c), style)),
+        }
+    }
+
+    fn backspace(&mut self) {
+        let line = self.line();
+
+        if let Some(span) = line.spans.last_mut() {
+            span.content.to_mut().pop();
+
+            if span.content.is_empty() {
+                line.spans.pop();
+            }
+        }
+    }
+}
+
+fn ansi_color(index: u16) -> Color {
+    match index {
+        | 0 => Color::Black,
+        | 1 => Color::Red,
+        | 2 => Color::Green,
+        | 3 => Color::Yellow,
+        | 4 => Color::Blue,
+        | 5 => Color::Magenta,
+        | 6 => Color::Cyan,
+        | 7 => Color::Gray,
+        | 8 => Color::DarkGray,
+        | 9 => Color::LightRed,
+        | 10 => Color::LightGreen,
+        | 11 => Color::LightYellow,
+        | 12 => Color::LightBlue,
+        | 13 => Color::LightMagenta,
+        | 14 => Color::LightCyan,
+        | _ => Color::White,
+    }
+}
+
+// Out of range values are ignored like unknown parameters, after their parameters are skipped over.
+fn extended_color(params: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    match params.next()? {
+        | 5 => u8::try_from(params.next()?).ok().map(Color::Indexed),
+        | 2 => {
+            let (r, g, b) = (params.next()?, params.next()?, params.next()?);
+            Some(Color::Rgb(u8::try_from(r).ok()?, u8::try_from(g).ok()?, u8::try_from(b).ok()?))
+        },
+        | _ => None,
+    }
+}
+
+fn apply_sgr(mut style: Style, params: &str) -> Style {
+    // Colon separated sub-parameters (`38:2:r:g:b`) are treated like regular ones.
+    let mut params = params.split([';', ':']).map(|param| param.parse::<u16>().unwrap_or(0));
+
+    while let Some(param) = params.next() {
+        match param {
+            | 0 => style = Style::default(),
+            | 1 => style.add_modifier.insert(Modifier::BOLD),
+            | 2 => style.add_modifier.insert(Modifier::DIM),
+            | 3 => style.add_modifier.insert(Modifier::ITALIC),
+            | 4 | 21 => style.add_modifier.insert(Modifier::UNDERLINED),
+            | 5 => style.add_modifier.insert(Modifier::SLOW_BLINK),
+            | 6 => style.add_modifier.insert(Modifier::RAPID_BLINK),
+            | 7 => style.add_modifier.insert(Modifier::REVERSED),
+            | 8 => style.add_modifier.insert(Modifier::HIDDEN),
+            | 9 => style.add_modifier.insert(Modifier::CROSSED_OUT),
+            | 22 => style.add_modifier.remove(Modifier::BOLD | Modifier::DIM),
+            | 23 => style.add_modifier.remove(Modifier::ITALIC),
+            | 24 => style.add_modifier.remove(Modifier::UNDERLINED),
+            | 25 => style.add_modifier.remove(Modifier::SLOW_BLINK | Modifier::RAPID_BLINK),
+            | 27 => style.add_modifier.remove(Modifier::REVERSED),
+            | 28 => style.add_modifier.remove(Modifier::HIDDEN),
+            | 29 => style.add_modifier.remove(Modifier::CROSSED_OUT),
+            | 30..=37 => style.fg = Some(ansi_color(param - 30)),
+            | 38 => style.fg = extended_color(&mut params).or(style.fg),
+            | 39 => style.fg = None,
+            | 40..=47 => style.bg = Some(ansi_color(param - 40)),
+            | 48 => style.bg = extended_color(&mut params).or(style.bg),
+            | 49 => style.bg = None,
+            // Underline colors are parsed only to skip over their parameters.
+            | 58 => { extended_color(&mut params); },
+            | 90..=97 => style.fg = Some(ansi_color(param - 90 + 8)),
+            | 100..=107 => style.bg = Some(ansi_color(param - 100 + 8)),
+            | _ => (),
+        }
+    }
+
+    style
+}
+
+pub fn parse<T: AsRef<[u8]>>(input: T) -> Vec<Line<'static>> {
+    let mut parser = Parser::new();
+    parser.feed(input.as_ref());
+    parser.into_lines()
+}
+
+// Removes escape sequences but keeps the text and control characters (newlines, tabs) as-is.
+pub fn strip(input: &str) -> String {
+    let mut tokenizer = Tokenizer::default();
+
+    input.chars()
+        .filter_map(|c| match tokenizer.next(c) {
+            | Some(Token::Print(c) | Token::Control(c)) => Some(c),
+            | Some(Token::Sgr) | None => None,
+        })
+        .collect()
+}
+
+// The display width of the widest line and the number of lines, as they would be rendered.
+pub fn measure(input: &str) -> (usize, usize) {
+    let lines = parse(input);
+
+    (lines.iter().map(Line::width).max().unwrap_or(0), lines.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn styles_apply_until_they_are_reset() {
+        let lines = parse("plain \x1B[1;31mbold red\x1B[22m red\x1B[0m\n\x1B[38;2;1;2;3mrgb\x1B[48;5;200m on indexed");
+
+        let spans = lines.iter()
+            .map(|line| line.spans.iter().map(|span| (span.content.as_ref(), span.style)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let red = Style::default().fg(Color::Red);
+        let rgb = Style::default().fg(Color::Rgb(1, 2, 3));
+
+        assert_eq!(spans, [
+            vec![
+                ("plain ", Style::default()),
+                ("bold red", red.add_modifier(Modifier::BOLD)),
+                (" red", red),
+            ],
+            vec![("rgb", rgb), (" on indexed", rgb.bg(Color::Indexed(200)))],
+        ]);
+    }
+
+    #[test]
+    fn input_split_anywhere_parses_the_same() {
+        let input = "\x1B[32mgrün\x1B]0;title\x07 ✔\x1B[0m".as_bytes();
+
+        for split in 0..input.len() {
+            let mut parser = Parser::new();
+
+            parser.feed(&input[..split]);
+            parser.feed(&input[split..]);
+
+            assert_eq!(parser.into_lines(), parse(input), "split at {split}");
+        }
+    }
+
+    #[test]
+    fn carriage_returns_overwrite_and_tabs_align() {
+        let lines = parse("50%\r100%\na\tb\x08c");
+
+        let text = lines.iter().map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect::<String>());
+
+        assert_eq!(text.collect::<Vec<_>>(), ["100%", "a       c"]);
+    }
+
+    #[test]
+    fn stripping_and_measuring_ignore_escapes() {
+        assert_eq!(strip("\x1B[1mbold\x1B[0m\tand \x1B]8;;https://example.com\x1B\\link"), "bold\tand link");
+        assert_eq!(measure("\x1B[31mwide ✔\x1B[0m\nx\n"), (6, 2));
+    }
+
+    #[test]
+    fn escapes_with_intermediates_and_device_control_strings_are_skipped() {
+        assert_eq!(strip("a\x1B(Bb\x1B#8c"), "abc");
+        assert_eq!(strip("a\x1BPq#0;2;0;0;0#0!7~\x1B\\b\x1B_apc\x07still apc\x1B\\c"), "abc");
+    }
+
+    #[test]
+    fn out_of_range_colors_are_ignored() {
+        let style = |input| parse(input)[0].spans[0].style;
+
+        assert_eq!(style("\x1B[31;38;5;300mx"), Style::default().fg(Color::Red));
+        assert_eq!(style("\x1B[38;2;1;256;3;1mx"), Style::default().add_modifier(Modifier::BOLD));
+    }
+}
@@ -0,0 +1,164 @@
+use std::sync::Arc;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use std::sync::Weak;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::{thread, time};
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use std::future::Future;
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+use futures::future::{self, AbortHandle};
+
+// Work started in the background, waited for on shutdown. Threads are joined, tasks running on a pool or a runtime
+// hold on to a token until they're done (or dropped).
+#[derive(Debug, Default)]
+pub(crate) struct Workers {
+    threads: Vec<thread::JoinHandle<()>>,
+    token: Arc<()>,
+}
+
+impl Workers {
+    pub fn spawn<F>(&mut self, f: F)
+        where F: FnOnce() + Send + 'static,
+    {
+        self.threads.retain(|thread| !thread.is_finished());
+        self.threads.push(thread::spawn(f));
+    }
+
+    #[cfg(any(feature = "futures", feature = "tokio", test))]
+    #[inline(always)]
+    pub fn token(&self) -> Arc<()> {
+        Arc::clone(&self.token)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.threads.iter().all(thread::JoinHandle::is_finished) && Arc::strong_count(&self.token) == 1
+    }
+
+    // Whether everything finished before the deadline, the threads are only joined if so.
+    pub fn join(&mut self, deadline: time::Instant) -> bool {
+        while !self.is_finished() {
+            if time::Instant::now() >= deadline {
+                return false;
+            }
+
+            thread::sleep(time::Duration::from_millis(1));
+        }
+
+        self.threads.drain(..).for_each(|thread| {
+            let _ = thread.join();
+        });
+
+        true
+    }
+}
+
+// Work started by a screen, cancelled once the screen is popped or replaced. Threads can't be stopped, what they
+// produce is dropped instead, tasks are aborted at their next await.
+#[derive(Debug, Default)]
+pub(crate) struct Scope {
+    cancelled: Arc<AtomicBool>,
+    // Dropped along with the task, so finished ones can be let go of.
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    tasks: Vec<(AbortHandle, Weak<()>)>,
+}
+
+impl Scope {
+    #[inline(always)]
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled(Arc::clone(&self.cancelled))
+    }
+
+    #[cfg(any(feature = "futures", feature = "tokio"))]
+    pub fn abortable<F>(&mut self, task: F) -> impl Future<Output = Option<F::Output>>
+        where F: Future,
+    {
+        let (task, handle) = future::abortable(task);
+        let alive = Arc::new(());
+
+        self.tasks.retain(|(_, alive)| alive.strong_count() > 0);
+        self.tasks.push((handle, Arc::downgrade(&alive)));
+
+        async move {
+            let _alive = alive;
+            task.await.ok()
+        }
+    }
+
+    pub fn cancel(self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+
+        #[cfg(any(feature = "futures", feature = "tokio"))]
+        self.tasks.iter().for_each(|(task, _)| task.abort());
+    }
+}
+
+// Whether the scope a result was produced in was cancelled, checked again once it arrives.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Cancelled(Arc<AtomicBool>);
+
+impl Cancelled {
+    #[inline(always)]
+    pub fn is_set(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    #[test]
+    fn threads_and_tokens_are_waited_for() {
+        let mut workers = Workers::default();
+
+        let (release, released) = mpsc::channel::<()>();
+
+        workers.spawn(move || {
+            let _ = released.recv();
+        });
+
+        let token = workers.token();
+
+        assert!(!workers.join(time::Instant::now() + time::Duration::from_millis(10)));
+
+        drop(release);
+
+        assert!(!workers.join(time::Instant::now() + time::Duration::from_millis(10)));
+
+        drop(token);
+
+        assert!(workers.join(time::Instant::now() + time::Duration::from_secs(5)));
+        assert!(workers.threads.is_empty());
+    }
+
+    #[test]
+    fn cancelling_a_scope_is_seen_by_what_was_started_in_it() {
+        let scope = Scope::default();
+        let cancelled = scope.cancelled();
+
+        assert!(!cancelled.is_set());
+
+        scope.cancel();
+
+        assert!(cancelled.is_set());
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn tasks_of_a_cancelled_scope_are_aborted() {
+        let mut scope = Scope::default();
+
+        let finished = futures::executor::block_on(scope.abortable(async { 1 }));
+        let pending = scope.abortable(future::pending::<u8>());
+
+        assert_eq!(finished, Some(1));
+        assert_eq!(scope.tasks.len(), 1);
+
+        scope.cancel();
+
+        assert_eq!(futures::executor::block_on(pending), None);
+    }
+}